@@ -0,0 +1,75 @@
+// A minimal arbitrary-precision unsigned integer, just big enough to hold
+// the result of `factorial_big`.
+// It relies on a few features (vectors, loops, traits) that we haven't covered yet:
+// you don't need to modify this module, come back to it later in the course!
+use std::fmt;
+
+// Each limb stores 9 decimal digits, least significant limb first.
+const BASE: u64 = 1_000_000_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn one() -> Self {
+        Self { limbs: vec![1] }
+    }
+
+    /// Multiply `self` by a `u32`, returning the (exact) product.
+    pub fn mul_u32(&self, factor: u32) -> Self {
+        if factor == 0 {
+            return Self { limbs: vec![0] };
+        }
+        let mut limbs = Vec::with_capacity(self.limbs.len() + 2);
+        let mut carry: u64 = 0;
+        for &limb in &self.limbs {
+            let product = limb as u64 * factor as u64 + carry;
+            limbs.push((product % BASE) as u32);
+            carry = product / BASE;
+        }
+        while carry > 0 {
+            limbs.push((carry % BASE) as u32);
+            carry /= BASE;
+        }
+        Self { limbs }
+    }
+}
+
+impl From<u32> for BigUint {
+    fn from(value: u32) -> Self {
+        Self::one().mul_u32(value)
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut limbs = self.limbs.iter().rev();
+        // The most significant limb is printed without padding,
+        // all the others must be zero-padded to 9 digits.
+        if let Some(most_significant) = limbs.next() {
+            write!(f, "{most_significant}")?;
+        }
+        for limb in limbs {
+            write!(f, "{limb:09}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BigUint;
+
+    #[test]
+    fn display_pads_inner_limbs() {
+        let n = BigUint::from(1_000_000).mul_u32(1_000);
+        assert_eq!(n.to_string(), "1000000000");
+    }
+
+    #[test]
+    fn multiply_by_zero() {
+        assert_eq!(BigUint::from(42).mul_u32(0).to_string(), "0");
+    }
+}
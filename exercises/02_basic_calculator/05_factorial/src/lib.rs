@@ -9,6 +9,9 @@
 // `factorial(2)` to return `2`, and so on.
 //
 // Use only what you learned! No loops yet, so you'll have to use recursion!
+mod big;
+
+use big::BigUint;
 
 fn factorial(k:u32)->u32{
     if k ==0 || k==1{
@@ -19,9 +22,20 @@ fn factorial(k:u32)->u32{
     }
 }
 
+// `u32` overflows as soon as we reach `13!`.
+// `factorial_big` computes the same value using `BigUint`, an integer type
+// that grows as needed (see `big.rs`), so it works for much larger inputs.
+fn factorial_big(n: u32) -> BigUint {
+    if n == 0 || n == 1 {
+        BigUint::one()
+    } else {
+        factorial_big(n - 1).mul_u32(n)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::factorial;
+    use crate::{factorial, factorial_big};
 
     #[test]
     fn first() {
@@ -42,4 +56,30 @@ mod tests {
     fn fifth() {
         assert_eq!(factorial(5), 120);
     }
+
+    #[test]
+    fn big_matches_small() {
+        assert_eq!(factorial_big(12).to_string(), factorial(12).to_string());
+    }
+
+    #[test]
+    fn big_twenty() {
+        assert_eq!(factorial_big(20).to_string(), "2432902008176640000");
+    }
+
+    #[test]
+    fn big_fifty() {
+        assert_eq!(
+            factorial_big(50).to_string(),
+            "30414093201713378043612608166064768844377641568960512000000000000"
+        );
+    }
+
+    #[test]
+    fn big_hundred() {
+        assert_eq!(
+            factorial_big(100).to_string(),
+            "93326215443944152681699238856266700490715968264381621468592963895217599993229915608941463976156518286253697920827223758251185210916864000000000000000000000000"
+        );
+    }
 }
@@ -0,0 +1,104 @@
+// Define two functions computing the `n`-th Fibonacci number, returning `None`
+// if the result doesn't fit into a `u64`.
+//
+// The Fibonacci sequence starts with `0` and `1`; every other number is the sum
+// of the two preceding ones: `0, 1, 1, 2, 3, 5, 8, 13, ...`.
+// We expect `fib_checked(0)` to return `Some(0)`, `fib_checked(1)` to return `Some(1)`,
+// `fib_checked(2)` to return `Some(1)`, and so on.
+//
+// - `fib_checked` should use a loop, relying on `checked_add` to detect overflows.
+// - `fib_memoized` should use recursion, remembering the values it has already
+//   computed in a `HashMap` so that each of them is only computed once.
+//   We'll cover `HashMap` in depth later in the course: for now, all you need
+//   are its `get` and `insert` methods.
+//   Each recursive call takes up some stack space: make sure a large `n` returns
+//   `None` instead of recursing deep enough to overflow the stack.
+use std::collections::HashMap;
+
+// F(93) is the biggest Fibonacci number that fits into a `u64`.
+const LARGEST_THAT_FITS: u32 = 93;
+
+fn fib_checked(n: u32) -> Option<u64> {
+    let mut previous: u64 = 0;
+    let mut current: u64 = 1;
+    if n == 0 {
+        return Some(previous);
+    }
+    for _ in 1..n {
+        let next = previous.checked_add(current)?;
+        previous = current;
+        current = next;
+    }
+    Some(current)
+}
+
+fn fib_memoized(n: u32) -> Option<u64> {
+    if n > LARGEST_THAT_FITS {
+        return None;
+    }
+    let mut cache = HashMap::new();
+    fib_with_cache(n, &mut cache)
+}
+
+fn fib_with_cache(n: u32, cache: &mut HashMap<u32, u64>) -> Option<u64> {
+    if n == 0 || n == 1 {
+        return Some(n as u64);
+    }
+    if let Some(value) = cache.get(&n) {
+        return Some(*value);
+    }
+    let value = fib_with_cache(n - 1, cache)?.checked_add(fib_with_cache(n - 2, cache)?)?;
+    cache.insert(n, value);
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{fib_checked, fib_memoized};
+
+    #[test]
+    fn first() {
+        assert_eq!(fib_checked(0), Some(0));
+        assert_eq!(fib_memoized(0), Some(0));
+    }
+
+    #[test]
+    fn second() {
+        assert_eq!(fib_checked(1), Some(1));
+        assert_eq!(fib_memoized(1), Some(1));
+    }
+
+    #[test]
+    fn tenth() {
+        assert_eq!(fib_checked(10), Some(55));
+        assert_eq!(fib_memoized(10), Some(55));
+    }
+
+    #[test]
+    fn largest_that_fits() {
+        // F(93) is the biggest Fibonacci number that fits into a `u64`.
+        assert_eq!(fib_checked(93), Some(12_200_160_415_121_876_738));
+        assert_eq!(fib_memoized(93), Some(12_200_160_415_121_876_738));
+    }
+
+    #[test]
+    fn overflow() {
+        assert_eq!(fib_checked(94), None);
+        assert_eq!(fib_memoized(94), None);
+        assert_eq!(fib_checked(200), None);
+        assert_eq!(fib_memoized(200), None);
+    }
+
+    #[test]
+    fn huge() {
+        assert_eq!(fib_checked(u32::MAX), None);
+        assert_eq!(fib_memoized(u32::MAX), None);
+    }
+
+    #[test]
+    fn both_agree() {
+        for n in 0..=100 {
+            assert_eq!(fib_checked(n), fib_memoized(n), "Mismatch for n = {n}");
+        }
+    }
+}
@@ -0,0 +1,167 @@
+// TODO: Implement `parse_ticket` and `parse_ticket_dyn`.
+//  Both parse a ticket from its textual representation—three lines holding, in order,
+//  the title, the description and the status—but they report failures differently:
+//  - `parse_ticket` returns `ParseTicketError`, a concrete error enum.
+//  - `parse_ticket_dyn` returns a boxed trait object, `Box<dyn Error + Send + Sync>`.
+//  Compare the two in the tests: which one would you rather call? Which one would you rather write?
+use std::error::Error;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Ticket {
+    pub title: String,
+    pub description: String,
+    pub status: Status,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TicketNewError {
+    #[error("Title cannot be empty")]
+    TitleCannotBeEmpty,
+    #[error("Title cannot be longer than 50 bytes")]
+    TitleTooLong,
+    #[error("Description cannot be empty")]
+    DescriptionCannotBeEmpty,
+    #[error("Description cannot be longer than 500 bytes")]
+    DescriptionTooLong,
+}
+
+impl Ticket {
+    pub fn new(title: &str, description: &str, status: Status) -> Result<Self, TicketNewError> {
+        if title.is_empty() {
+            return Err(TicketNewError::TitleCannotBeEmpty);
+        }
+        if title.len() > 50 {
+            return Err(TicketNewError::TitleTooLong);
+        }
+        if description.is_empty() {
+            return Err(TicketNewError::DescriptionCannotBeEmpty);
+        }
+        if description.len() > 500 {
+            return Err(TicketNewError::DescriptionTooLong);
+        }
+        Ok(Ticket {
+            title: title.into(),
+            description: description.into(),
+            status,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Status {
+    ToDo,
+    InProgress,
+    Done,
+}
+
+impl TryFrom<&str> for Status {
+    type Error = ParseStatusError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "todo" => Ok(Status::ToDo),
+            "inprogress" => Ok(Status::InProgress),
+            "done" => Ok(Status::Done),
+            _ => Err(ParseStatusError {
+                invalid_status: value.to_string(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("`{invalid_status}` is not a valid status. Use one of: ToDo, InProgress, Done")]
+pub struct ParseStatusError {
+    invalid_status: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ParseTicketError {
+    #[error("The `{0}` field is missing")]
+    MissingField(&'static str),
+    #[error("Invalid ticket")]
+    InvalidTicket(#[from] TicketNewError),
+    #[error("Invalid status")]
+    InvalidStatus(#[from] ParseStatusError),
+}
+
+fn split_fields(input: &str) -> Result<(&str, &str, &str), &'static str> {
+    let mut lines = input.lines();
+    let title = lines.next().ok_or("title")?;
+    let description = lines.next().ok_or("description")?;
+    let status = lines.next().ok_or("status")?;
+    Ok((title, description, status))
+}
+
+pub fn parse_ticket(input: &str) -> Result<Ticket, ParseTicketError> {
+    let (title, description, status) =
+        split_fields(input).map_err(ParseTicketError::MissingField)?;
+    Ok(Ticket::new(title, description, status.try_into()?)?)
+}
+
+pub fn parse_ticket_dyn(input: &str) -> Result<Ticket, Box<dyn Error + Send + Sync>> {
+    // `?` converts any error type implementing `Error + Send + Sync` into the boxed
+    // trait object for us, so there's no need for a dedicated enum.
+    // `&str` and `String` can be boxed too, handy for one-off error messages.
+    let (title, description, status) =
+        split_fields(input).map_err(|field| format!("The `{field}` field is missing"))?;
+    Ok(Ticket::new(title, description, status.try_into()?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &str = "A title\nA description\nToDo";
+
+    #[test]
+    fn both_parse_valid_input() {
+        let ticket = parse_ticket(VALID).unwrap();
+        assert_eq!(ticket.status, Status::ToDo);
+        assert_eq!(ticket, parse_ticket_dyn(VALID).unwrap());
+    }
+
+    #[test]
+    fn enum_can_be_matched() {
+        let err = parse_ticket("\nA description\nToDo").unwrap_err();
+        assert!(matches!(
+            err,
+            ParseTicketError::InvalidTicket(TicketNewError::TitleCannotBeEmpty)
+        ));
+
+        let err = parse_ticket("A title\nA description").unwrap_err();
+        assert!(matches!(err, ParseTicketError::MissingField("status")));
+    }
+
+    #[test]
+    fn boxed_error_can_be_downcast() {
+        let err = parse_ticket_dyn("\nA description\nToDo").unwrap_err();
+        let ticket_error = err.downcast_ref::<TicketNewError>().unwrap();
+        assert!(matches!(ticket_error, TicketNewError::TitleCannotBeEmpty));
+        // The concrete type is the only thing we get: downcasting to the wrong type fails.
+        assert!(err.downcast_ref::<ParseStatusError>().is_none());
+
+        let err = parse_ticket_dyn("A title\nA description\nBlocked").unwrap_err();
+        let status_error = err.downcast::<ParseStatusError>().unwrap();
+        assert_eq!(status_error.invalid_status, "Blocked");
+    }
+
+    #[test]
+    fn boxed_message_keeps_its_text() {
+        let err = parse_ticket_dyn("A title").unwrap_err();
+        assert_eq!(err.to_string(), "The `description` field is missing");
+        // There is no dedicated type to downcast to: the message is all we get.
+        assert!(err.downcast_ref::<ParseTicketError>().is_none());
+    }
+
+    #[test]
+    fn enum_exposes_the_source() {
+        let err = parse_ticket("A title\nA description\nBlocked").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid status");
+        let source = err.source().unwrap();
+        assert_eq!(
+            source.to_string(),
+            "`Blocked` is not a valid status. Use one of: ToDo, InProgress, Done"
+        );
+    }
+}
@@ -0,0 +1,107 @@
+// TODO: Implement `mean` and the methods on `Histogram` to pass the tests.
+//  Both rely on **const generics**: the size of the array is a parameter of the
+//  function (or type) itself, known at compile-time.
+
+/// The arithmetic mean of a fixed-size array of samples.
+/// It returns `NaN` for an empty array.
+pub fn mean<const N: usize>(xs: [f64; N]) -> f64 {
+    if N == 0 {
+        return f64::NAN;
+    }
+    xs.iter().sum::<f64>() / N as f64
+}
+
+/// A histogram with `BUCKETS` equally-sized buckets covering the `[min, max)` range.
+/// Samples below `min` are counted in the first bucket, samples at or above `max`
+/// in the last one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram<const BUCKETS: usize> {
+    min: f64,
+    max: f64,
+    counts: [u64; BUCKETS],
+}
+
+impl<const BUCKETS: usize> Histogram<BUCKETS> {
+    /// # Panics
+    ///
+    /// Panics if `BUCKETS` is zero or if `min` is not smaller than `max`.
+    pub fn new(min: f64, max: f64) -> Self {
+        assert!(BUCKETS > 0, "A histogram needs at least one bucket");
+        assert!(min < max, "`min` must be smaller than `max`");
+        Self {
+            min,
+            max,
+            counts: [0; BUCKETS],
+        }
+    }
+
+    pub fn record(&mut self, sample: f64) {
+        let width = (self.max - self.min) / BUCKETS as f64;
+        let index = ((sample - self.min) / width).floor();
+        // `as` saturates when going from floats to integers, so negative
+        // values (and `NaN`) become `0`.
+        let index = (index as usize).min(BUCKETS - 1);
+        self.counts[index] += 1;
+    }
+
+    pub fn counts(&self) -> [u64; BUCKETS] {
+        self.counts
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mean_of_different_sizes() {
+        assert_eq!(mean([1.0]), 1.0);
+        assert_eq!(mean([1.0, 2.0, 3.0]), 2.0);
+        assert_eq!(mean([2.5; 8]), 2.5);
+    }
+
+    #[test]
+    fn mean_of_empty_array() {
+        assert!(mean([]).is_nan());
+    }
+
+    #[test]
+    fn histogram_with_four_buckets() {
+        let mut histogram = Histogram::<4>::new(0.0, 4.0);
+        for sample in [0.0, 0.5, 1.0, 2.9, 3.5, 3.99] {
+            histogram.record(sample);
+        }
+        assert_eq!(histogram.counts(), [2, 1, 1, 2]);
+        assert_eq!(histogram.total(), 6);
+    }
+
+    #[test]
+    fn histogram_with_a_single_bucket() {
+        let mut histogram: Histogram<1> = Histogram::new(-1.0, 1.0);
+        histogram.record(0.0);
+        histogram.record(0.5);
+        assert_eq!(histogram.counts(), [2]);
+    }
+
+    #[test]
+    fn out_of_range_samples_are_clamped() {
+        let mut histogram = Histogram::<10>::new(0.0, 100.0);
+        histogram.record(-5.0);
+        histogram.record(100.0);
+        histogram.record(1_000.0);
+        let counts = histogram.counts();
+        assert_eq!(counts[0], 1);
+        assert_eq!(counts[9], 2);
+        assert_eq!(histogram.total(), 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_buckets() {
+        Histogram::<0>::new(0.0, 1.0);
+    }
+}
@@ -0,0 +1,179 @@
+// TODO: Implement `TicketStore::get_ref`, returning a `TicketRef` that borrows its fields
+//  from the ticket stored in `TicketStore`, instead of cloning them.
+//  `TicketRef` can't outlive the store it was obtained from: the doc-tests on `TicketRef`
+//  show the code that the compiler must reject.
+
+use ticket_fields::{TicketDescription, TicketTitle};
+
+#[derive(Clone, Default)]
+pub struct TicketStore {
+    tickets: Vec<Ticket>,
+    counter: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TicketId(u64);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ticket {
+    pub id: TicketId,
+    pub title: TicketTitle,
+    pub description: TicketDescription,
+    pub status: Status,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TicketDraft {
+    pub title: TicketTitle,
+    pub description: TicketDescription,
+}
+
+#[derive(Clone, Debug, Copy, PartialEq)]
+pub enum Status {
+    ToDo,
+    InProgress,
+    Done,
+}
+
+/// A read-only view of a ticket, borrowed from the `TicketStore` that holds it.
+///
+/// The view can't outlive the store:
+///
+/// ```compile_fail
+/// # use borrowed_views::{TicketDraft, TicketStore};
+/// # use ticket_fields::test_helpers::{ticket_description, ticket_title};
+/// let mut store = TicketStore::new();
+/// let id = store.add_ticket(TicketDraft { title: ticket_title(), description: ticket_description() });
+/// let view = store.get_ref(id).unwrap();
+/// drop(store); // error[E0505]: cannot move out of `store` because it is borrowed
+/// println!("{}", view.title);
+/// ```
+///
+/// Nor can the store be modified while a view is alive, since that could move
+/// (or drop) the ticket it points to:
+///
+/// ```compile_fail
+/// # use borrowed_views::{TicketDraft, TicketStore};
+/// # use ticket_fields::test_helpers::{ticket_description, ticket_title};
+/// let mut store = TicketStore::new();
+/// let draft = TicketDraft { title: ticket_title(), description: ticket_description() };
+/// let id = store.add_ticket(draft.clone());
+/// let view = store.get_ref(id).unwrap();
+/// store.add_ticket(draft); // error[E0502]: cannot borrow `store` as mutable because it is also borrowed as immutable
+/// println!("{}", view.title);
+/// ```
+///
+/// Once the view isn't used anymore, the store is free again:
+///
+/// ```
+/// # use borrowed_views::{TicketDraft, TicketStore};
+/// # use ticket_fields::test_helpers::{ticket_description, ticket_title};
+/// let mut store = TicketStore::new();
+/// let draft = TicketDraft { title: ticket_title(), description: ticket_description() };
+/// let id = store.add_ticket(draft.clone());
+/// let title = store.get_ref(id).unwrap().title.to_owned();
+/// store.add_ticket(draft);
+/// println!("{title}");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TicketRef<'a> {
+    pub id: TicketId,
+    pub title: &'a str,
+    pub description: &'a str,
+    pub status: Status,
+}
+
+impl TicketStore {
+    pub fn new() -> Self {
+        Self {
+            tickets: Vec::new(),
+            counter: 0,
+        }
+    }
+
+    pub fn add_ticket(&mut self, ticket: TicketDraft) -> TicketId {
+        let id = TicketId(self.counter);
+        self.counter += 1;
+        let ticket = Ticket {
+            id,
+            title: ticket.title,
+            description: ticket.description,
+            status: Status::ToDo,
+        };
+        self.tickets.push(ticket);
+        id
+    }
+
+    pub fn get(&self, id: TicketId) -> Option<&Ticket> {
+        self.tickets.iter().find(|&t| t.id == id)
+    }
+
+    pub fn get_ref(&self, id: TicketId) -> Option<TicketRef<'_>> {
+        self.get(id).map(|t| TicketRef {
+            id: t.id,
+            title: t.title.as_ref(),
+            description: t.description.as_ref(),
+            status: t.status,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Status, TicketDraft, TicketId, TicketRef, TicketStore};
+    use ticket_fields::test_helpers::{ticket_description, ticket_title};
+
+    #[test]
+    fn views_match_the_ticket() {
+        let mut store = TicketStore::new();
+        let draft = TicketDraft {
+            title: ticket_title(),
+            description: ticket_description(),
+        };
+        let id = store.add_ticket(draft.clone());
+
+        let view = store.get_ref(id).unwrap();
+        assert_eq!(
+            view,
+            TicketRef {
+                id,
+                title: draft.title.as_ref(),
+                description: draft.description.as_ref(),
+                status: Status::ToDo,
+            }
+        );
+        assert!(store.get_ref(TicketId(42)).is_none());
+    }
+
+    #[test]
+    fn views_borrow_instead_of_cloning() {
+        let mut store = TicketStore::new();
+        let id = store.add_ticket(TicketDraft {
+            title: ticket_title(),
+            description: ticket_description(),
+        });
+
+        let ticket = store.get(id).unwrap();
+        let view = store.get_ref(id).unwrap();
+        // Same bytes, same place in memory.
+        assert!(std::ptr::eq(view.title, ticket.title.as_ref()));
+        assert!(std::ptr::eq(view.description, ticket.description.as_ref()));
+    }
+
+    #[test]
+    fn many_views_at_once() {
+        let mut store = TicketStore::new();
+        let ids: Vec<_> = (0..3)
+            .map(|_| {
+                store.add_ticket(TicketDraft {
+                    title: ticket_title(),
+                    description: ticket_description(),
+                })
+            })
+            .collect();
+        // Shared borrows can coexist: no view prevents taking another one.
+        let views: Vec<TicketRef> = ids.iter().map(|&id| store.get_ref(id).unwrap()).collect();
+        assert_eq!(views.len(), 3);
+        assert!(views.iter().zip(&ids).all(|(view, &id)| view.id == id));
+    }
+}
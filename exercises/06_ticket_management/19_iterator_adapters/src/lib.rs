@@ -0,0 +1,146 @@
+// TODO: Implement `Iterator` for `FilterByStatus` by hand—no `filter` and no closures!—and
+//  the `TicketIteratorExt` extension trait, so that `.by_status(status)` can be called on any
+//  iterator over `&Ticket`s.
+
+use ticket_fields::{TicketDescription, TicketTitle};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ticket {
+    pub title: TicketTitle,
+    pub description: TicketDescription,
+    pub status: Status,
+}
+
+#[derive(Clone, Debug, Copy, PartialEq)]
+pub enum Status {
+    ToDo,
+    InProgress,
+    Done,
+}
+
+/// An iterator adapter that only yields the tickets with a given status.
+/// Create one with [`TicketIteratorExt::by_status`].
+pub struct FilterByStatus<I> {
+    inner: I,
+    status: Status,
+}
+
+impl<'a, I> Iterator for FilterByStatus<I>
+where
+    I: Iterator<Item = &'a Ticket>,
+{
+    type Item = &'a Ticket;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let ticket = self.inner.next()?;
+            if ticket.status == self.status {
+                return Some(ticket);
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Any of the remaining tickets could be filtered out.
+        let (_, upper) = self.inner.size_hint();
+        (0, upper)
+    }
+}
+
+/// Extra methods for every iterator over tickets.
+pub trait TicketIteratorExt<'a>: Iterator<Item = &'a Ticket> + Sized {
+    fn by_status(self, status: Status) -> FilterByStatus<Self> {
+        FilterByStatus {
+            inner: self,
+            status,
+        }
+    }
+}
+
+impl<'a, I> TicketIteratorExt<'a> for I where I: Iterator<Item = &'a Ticket> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ticket_fields::test_helpers::{ticket_description, ticket_title};
+
+    fn ticket(status: Status) -> Ticket {
+        Ticket {
+            title: ticket_title(),
+            description: ticket_description(),
+            status,
+        }
+    }
+
+    fn tickets() -> Vec<Ticket> {
+        [
+            Status::ToDo,
+            Status::Done,
+            Status::InProgress,
+            Status::ToDo,
+            Status::Done,
+            Status::ToDo,
+        ]
+        .into_iter()
+        .map(ticket)
+        .collect()
+    }
+
+    #[test]
+    fn keeps_the_matching_tickets() {
+        let tickets = tickets();
+        assert_eq!(tickets.iter().by_status(Status::ToDo).count(), 3);
+        assert_eq!(tickets.iter().by_status(Status::InProgress).count(), 1);
+        assert!(tickets
+            .iter()
+            .by_status(Status::Done)
+            .all(|t| t.status == Status::Done));
+    }
+
+    #[test]
+    fn empty_and_exhausted() {
+        let mut none = [].iter().by_status(Status::ToDo);
+        assert!(none.next().is_none());
+
+        let tickets = [ticket(Status::Done)];
+        let mut iter = tickets.iter().by_status(Status::ToDo);
+        assert!(iter.next().is_none());
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn chains_with_other_adapters() {
+        let tickets = tickets();
+        // Skipping the first ticket, which is to do.
+        assert_eq!(tickets.iter().skip(1).by_status(Status::ToDo).count(), 2);
+
+        let first_two_done: Vec<&Ticket> = tickets.iter().by_status(Status::Done).take(2).collect();
+        assert_eq!(first_two_done.len(), 2);
+
+        // Adapters can be stacked: nothing is both to do and done.
+        assert_eq!(
+            tickets
+                .iter()
+                .by_status(Status::ToDo)
+                .by_status(Status::Done)
+                .count(),
+            0
+        );
+
+        let titles: Vec<&str> = tickets
+            .iter()
+            .rev()
+            .by_status(Status::InProgress)
+            .chain(tickets.iter().by_status(Status::Done))
+            .map(|t| t.title.as_ref())
+            .collect();
+        assert_eq!(titles.len(), 3);
+    }
+
+    #[test]
+    fn size_hint_is_an_upper_bound() {
+        let tickets = tickets();
+        let iter = tickets.iter().by_status(Status::Done);
+        assert_eq!(iter.size_hint(), (0, Some(6)));
+    }
+}
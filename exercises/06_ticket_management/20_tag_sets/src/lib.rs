@@ -0,0 +1,214 @@
+// TODO: Implement `Tag::try_from`, `Ticket::add_tags` and the two set queries on `TicketStore`.
+//  Tags are normalized when they're created: `"  Good   First Issue "` and `"good first issue"`
+//  are the same tag. Since a ticket's tags are a `HashSet`, adding a tag twice has no effect.
+//  Look at the methods `HashSet` offers for comparing two sets!
+
+use std::collections::{HashMap, HashSet};
+use ticket_fields::{TicketDescription, TicketTitle};
+
+/// A tag: lowercase words separated by single spaces.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Tag(String);
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct EmptyTag;
+
+impl TryFrom<&str> for Tag {
+    type Error = EmptyTag;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let words: Vec<_> = value.split_whitespace().collect();
+        if words.is_empty() {
+            return Err(EmptyTag);
+        }
+        Ok(Self(words.join(" ").to_lowercase()))
+    }
+}
+
+impl AsRef<str> for Tag {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct TicketStore {
+    tickets: HashMap<TicketId, Ticket>,
+    counter: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TicketId(u64);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ticket {
+    pub id: TicketId,
+    pub title: TicketTitle,
+    pub description: TicketDescription,
+    pub status: Status,
+    pub tags: HashSet<Tag>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TicketDraft {
+    pub title: TicketTitle,
+    pub description: TicketDescription,
+}
+
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub enum Status {
+    ToDo,
+    InProgress,
+    Done,
+}
+
+impl Ticket {
+    /// Returns how many of the tags the ticket didn't have yet.
+    pub fn add_tags(&mut self, tags: impl IntoIterator<Item = Tag>) -> usize {
+        tags.into_iter()
+            .filter(|tag| self.tags.insert(tag.clone()))
+            .count()
+    }
+}
+
+impl TicketStore {
+    pub fn new() -> Self {
+        Self {
+            tickets: HashMap::new(),
+            counter: 0,
+        }
+    }
+
+    pub fn add_ticket(&mut self, ticket: TicketDraft) -> TicketId {
+        let id = TicketId(self.counter);
+        self.counter += 1;
+        let ticket = Ticket {
+            id,
+            title: ticket.title,
+            description: ticket.description,
+            status: Status::ToDo,
+            tags: HashSet::new(),
+        };
+        self.tickets.insert(id, ticket);
+        id
+    }
+
+    pub fn get(&self, id: TicketId) -> Option<&Ticket> {
+        self.tickets.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: TicketId) -> Option<&mut Ticket> {
+        self.tickets.get_mut(&id)
+    }
+
+    /// The tickets that have every one of `tags` (and possibly others), ordered by id.
+    /// Every ticket has all the tags of an empty set.
+    pub fn tickets_with_all_tags(&self, tags: &HashSet<Tag>) -> Vec<TicketId> {
+        self.ids_where(|ticket| tags.is_subset(&ticket.tags))
+    }
+
+    /// The tickets that have at least one of `tags`, ordered by id.
+    pub fn tickets_with_any_tag(&self, tags: &HashSet<Tag>) -> Vec<TicketId> {
+        self.ids_where(|ticket| !tags.is_disjoint(&ticket.tags))
+    }
+
+    fn ids_where(&self, predicate: impl Fn(&Ticket) -> bool) -> Vec<TicketId> {
+        let mut ids: Vec<_> = self
+            .tickets
+            .values()
+            .filter(|ticket| predicate(ticket))
+            .map(|ticket| ticket.id)
+            .collect();
+        // A `HashMap` iterates in an arbitrary order.
+        ids.sort();
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ticket_fields::test_helpers::{ticket_description, ticket_title};
+
+    fn tag(name: &str) -> Tag {
+        Tag::try_from(name).unwrap()
+    }
+
+    fn tags(names: &[&str]) -> HashSet<Tag> {
+        names.iter().map(|name| tag(name)).collect()
+    }
+
+    /// Tickets #0 to #3, tagged with:
+    /// - #0: bug, ui
+    /// - #1: bug, backend
+    /// - #2: docs
+    /// - #3: nothing
+    fn store() -> TicketStore {
+        let mut store = TicketStore::new();
+        for names in [&["bug", "ui"][..], &["bug", "backend"], &["docs"], &[]] {
+            let id = store.add_ticket(TicketDraft {
+                title: ticket_title(),
+                description: ticket_description(),
+            });
+            store.get_mut(id).unwrap().add_tags(tags(names));
+        }
+        store
+    }
+
+    #[test]
+    fn tags_are_normalized() {
+        assert_eq!(tag("  Good   First\tIssue "), tag("good first issue"));
+        assert_eq!(tag("BUG").as_ref(), "bug");
+        assert_eq!(Tag::try_from(""), Err(EmptyTag));
+        assert_eq!(Tag::try_from(" \n "), Err(EmptyTag));
+    }
+
+    #[test]
+    fn adding_tags_deduplicates() {
+        let mut store = store();
+        let ticket = store.get_mut(TicketId(0)).unwrap();
+        let added = ticket.add_tags([tag("Bug"), tag("urgent"), tag(" urgent ")]);
+        assert_eq!(added, 1);
+        assert_eq!(ticket.tags, tags(&["bug", "ui", "urgent"]));
+    }
+
+    #[test]
+    fn overlapping_tag_sets() {
+        let store = store();
+        let bug_ui = tags(&["bug", "ui"]);
+        assert_eq!(store.tickets_with_all_tags(&bug_ui), [TicketId(0)]);
+        assert_eq!(
+            store.tickets_with_any_tag(&bug_ui),
+            [TicketId(0), TicketId(1)]
+        );
+
+        let bug = tags(&["bug"]);
+        assert_eq!(
+            store.tickets_with_all_tags(&bug),
+            store.tickets_with_any_tag(&bug)
+        );
+    }
+
+    #[test]
+    fn disjoint_tag_sets() {
+        let store = store();
+        let ui_and_backend = tags(&["ui", "backend"]);
+        assert!(store.tickets_with_all_tags(&ui_and_backend).is_empty());
+        assert_eq!(
+            store.tickets_with_any_tag(&ui_and_backend),
+            [TicketId(0), TicketId(1)]
+        );
+
+        let unused = tags(&["wontfix"]);
+        assert!(store.tickets_with_all_tags(&unused).is_empty());
+        assert!(store.tickets_with_any_tag(&unused).is_empty());
+    }
+
+    #[test]
+    fn empty_tag_set() {
+        let store = store();
+        let none = HashSet::new();
+        assert_eq!(store.tickets_with_all_tags(&none).len(), 4);
+        assert!(store.tickets_with_any_tag(&none).is_empty());
+    }
+}
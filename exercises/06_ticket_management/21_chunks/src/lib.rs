@@ -0,0 +1,132 @@
+// TODO: Implement `Iterator` for `Chunks` and `IntoChunks`, without calling `slice::chunks`
+//  (or any of its siblings): the point is to write them yourself!
+//  Every chunk holds `size` items, except the last one, which gets whatever remains.
+//  A size of zero would yield empty chunks forever: `chunked` and `into_chunks` panic instead.
+//  `slice::split_at` and `Iterator::by_ref` will come in handy.
+
+use std::vec;
+
+/// The chunks of `items`, `size` items at a time, as slices of it.
+///
+/// # Panics
+///
+/// If `size` is zero.
+pub fn chunked<T>(items: &[T], size: usize) -> Chunks<'_, T> {
+    assert_ne!(size, 0, "Chunks must hold at least one item");
+    Chunks { rest: items, size }
+}
+
+/// The chunks of `items`, `size` items at a time, each one a `Vec` of its own.
+/// Nothing is copied: the items are moved out of `items`.
+///
+/// # Panics
+///
+/// If `size` is zero.
+pub fn into_chunks<T>(items: Vec<T>, size: usize) -> IntoChunks<T> {
+    assert_ne!(size, 0, "Chunks must hold at least one item");
+    IntoChunks {
+        items: items.into_iter(),
+        size,
+    }
+}
+
+pub struct Chunks<'a, T> {
+    /// What hasn't been handed out yet.
+    rest: &'a [T],
+    size: usize,
+}
+
+impl<'a, T> Iterator for Chunks<'a, T> {
+    type Item = &'a [T];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        let (chunk, rest) = self.rest.split_at(self.size.min(self.rest.len()));
+        self.rest = rest;
+        Some(chunk)
+    }
+}
+
+pub struct IntoChunks<T> {
+    items: vec::IntoIter<T>,
+    size: usize,
+}
+
+impl<T> Iterator for IntoChunks<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // `by_ref` keeps `take` from consuming the whole iterator.
+        let chunk: Vec<T> = self.items.by_ref().take(self.size).collect();
+        if chunk.is_empty() {
+            None
+        } else {
+            Some(chunk)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ticket_fields::test_helpers::{ticket_description, ticket_title};
+    use ticket_fields::{TicketDescription, TicketTitle};
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct TicketDraft {
+        title: TicketTitle,
+        description: TicketDescription,
+    }
+
+    #[test]
+    fn the_last_chunk_holds_the_remainder() {
+        let items = [1, 2, 3, 4, 5, 6, 7];
+        let chunks: Vec<_> = chunked(&items, 3).collect();
+        assert_eq!(chunks, [&[1, 2, 3][..], &[4, 5, 6], &[7]]);
+
+        let chunks: Vec<_> = into_chunks(items.to_vec(), 3).collect();
+        assert_eq!(chunks, [vec![1, 2, 3], vec![4, 5, 6], vec![7]]);
+    }
+
+    #[test]
+    fn even_splits_have_no_remainder() {
+        let items: Vec<_> = (0..6).collect();
+        let lengths: Vec<_> = chunked(&items, 2).map(<[_]>::len).collect();
+        assert_eq!(lengths, [2, 2, 2]);
+        assert_eq!(into_chunks(items, 3).count(), 2);
+    }
+
+    #[test]
+    fn short_and_empty_inputs() {
+        let items = [1, 2];
+        assert_eq!(chunked(&items, 5).collect::<Vec<_>>(), [&items[..]]);
+        assert_eq!(chunked::<u8>(&[], 5).next(), None);
+        assert_eq!(into_chunks(Vec::<u8>::new(), 5).next(), None);
+    }
+
+    #[test]
+    fn owned_chunks_move_their_items() {
+        // Drafts aren't `Copy`: each one ends up in exactly one chunk.
+        let draft = TicketDraft {
+            title: ticket_title(),
+            description: ticket_description(),
+        };
+        let batches: Vec<Vec<TicketDraft>> = into_chunks(vec![draft.clone(); 5], 2).collect();
+        assert_eq!(batches.len(), 3);
+        assert_eq!(batches[2], [draft]);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one item")]
+    fn borrowed_chunks_cannot_be_empty() {
+        chunked(&[1, 2, 3], 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one item")]
+    fn owned_chunks_cannot_be_empty() {
+        into_chunks(vec![1, 2, 3], 0);
+    }
+}
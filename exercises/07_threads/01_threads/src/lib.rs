@@ -12,6 +12,7 @@
 // slices of the vector directly. You'll need to allocate new
 // vectors for each half of the original vector. We'll see why
 // this is necessary in the next exercise.
+use std::sync::Arc;
 use std::thread;
 
 pub fn sum(v: Vec<i32>) -> i32 {
@@ -29,6 +30,16 @@ pub fn sum(v: Vec<i32>) -> i32 {
     });
     left_thread.join().unwrap() + right_thread.join().unwrap()
 }
+
+/// Like `sum`, but the halves aren't copied: both threads share the same vector,
+/// and each one only reads its own half.
+pub fn sum_shared(v: Arc<Vec<i32>>) -> i32 {
+    let mid = v.len() / 2;
+    let left = Arc::clone(&v);
+    let left_thread = thread::spawn(move || -> i32 { left[..mid].iter().sum() });
+    let right_thread = thread::spawn(move || -> i32 { v[mid..].iter().sum() });
+    left_thread.join().unwrap() + right_thread.join().unwrap()
+}
 /*
 use std::thread;
 
@@ -96,4 +107,38 @@ mod tests {
     fn ten() {
         assert_eq!(sum(vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10]), 55);
     }
+
+    #[test]
+    fn shared() {
+        assert_eq!(sum_shared(Arc::new(vec![])), 0);
+        assert_eq!(sum_shared(Arc::new(vec![1])), 1);
+        assert_eq!(sum_shared(Arc::new(vec![1, 2, 3, 4, 5, 6, 7, 8, 9])), 45);
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // Small values, so that the sum can't overflow regardless of the overflow settings.
+    fn vector(sizes: std::ops::RangeInclusive<usize>) -> impl Strategy<Value = Vec<i32>> {
+        prop::collection::vec(-1_000..1_000, sizes)
+    }
+
+    proptest! {
+        #[test]
+        fn matches_sequential_sum(v in vector(0..=1_000)) {
+            let expected: i32 = v.iter().sum();
+            prop_assert_eq!(sum_shared(Arc::new(v.clone())), expected);
+            prop_assert_eq!(sum(v), expected);
+        }
+
+        // Splitting in halves is where off-by-one errors hide: hammer the smallest sizes.
+        #[test]
+        fn matches_sequential_sum_for_tiny_vectors(v in vector(0..=3)) {
+            let expected: i32 = v.iter().sum();
+            prop_assert_eq!(sum(v), expected);
+        }
+    }
 }
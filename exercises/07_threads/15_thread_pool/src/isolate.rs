@@ -0,0 +1,86 @@
+use std::any::Any;
+use std::fmt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// The value a closure panicked with, as captured by `run_isolated`.
+pub struct PanicPayload(Box<dyn Any + Send + 'static>);
+
+impl PanicPayload {
+    /// The panic message, if the closure panicked with a string
+    /// (e.g. via `panic!("...")`, `unwrap` or `expect`).
+    pub fn message(&self) -> Option<&str> {
+        if let Some(message) = self.0.downcast_ref::<&'static str>() {
+            Some(message)
+        } else {
+            self.0.downcast_ref::<String>().map(String::as_str)
+        }
+    }
+
+    /// Get back the raw payload, e.g. to resume unwinding with `std::panic::resume_unwind`.
+    pub fn into_inner(self) -> Box<dyn Any + Send + 'static> {
+        self.0
+    }
+}
+
+impl fmt::Debug for PanicPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PanicPayload").field(&self.message()).finish()
+    }
+}
+
+impl fmt::Display for PanicPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.message() {
+            Some(message) => write!(f, "The job panicked: {message}"),
+            None => write!(f, "The job panicked"),
+        }
+    }
+}
+
+impl std::error::Error for PanicPayload {}
+
+/// Run `f`, turning a panic into an `Err` instead of unwinding into the caller.
+///
+/// `catch_unwind` requires the closure to be `UnwindSafe`, to warn you that
+/// a panic might leave the data it captured in an inconsistent state.
+/// We assert it on the caller's behalf: jobs own what they capture, so nobody
+/// gets to observe that state once the job has panicked.
+///
+/// Panics that abort the process (e.g. with `panic = "abort"`) can't be caught.
+pub fn run_isolated<F, T>(f: F) -> Result<T, PanicPayload>
+where
+    F: FnOnce() -> T,
+{
+    catch_unwind(AssertUnwindSafe(f)).map_err(PanicPayload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ok() {
+        assert_eq!(run_isolated(|| 1 + 1).unwrap(), 2);
+    }
+
+    #[test]
+    fn static_str_message() {
+        let payload = run_isolated(|| panic!("boom")).unwrap_err();
+        assert_eq!(payload.message(), Some("boom"));
+    }
+
+    #[test]
+    fn formatted_message() {
+        let n = 42;
+        let payload = run_isolated(|| panic!("boom {n}")).unwrap_err();
+        assert_eq!(payload.message(), Some("boom 42"));
+        assert_eq!(payload.to_string(), "The job panicked: boom 42");
+    }
+
+    #[test]
+    fn non_string_payload() {
+        let payload = run_isolated(|| std::panic::panic_any(7u8)).unwrap_err();
+        assert_eq!(payload.message(), None);
+        assert_eq!(*payload.into_inner().downcast::<u8>().unwrap(), 7);
+    }
+}
@@ -0,0 +1,137 @@
+// TODO: Implement a fixed-size thread pool.
+//  `ThreadPool::new` spawns the workers, `execute` hands a job over to them and
+//  returns a handle to retrieve its result.
+//  A panicking job must not take down the worker running it: use `run_isolated`
+//  (in `isolate.rs`) to catch the panic and report it back through the handle.
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+pub use isolate::{run_isolated, PanicPayload};
+
+mod isolate;
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+pub struct ThreadPool {
+    // `None` once the pool starts shutting down.
+    sender: Option<Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+/// A handle to the result of a job submitted to a `ThreadPool`.
+pub struct JobHandle<T> {
+    receiver: Receiver<Result<T, PanicPayload>>,
+}
+
+impl<T> JobHandle<T> {
+    /// Block until the job has run, returning its output or the payload it panicked with.
+    pub fn join(self) -> Result<T, PanicPayload> {
+        self.receiver
+            .recv()
+            .expect("The worker dropped the job without running it")
+    }
+}
+
+impl ThreadPool {
+    /// # Panics
+    ///
+    /// Panics if `n_workers` is zero.
+    pub fn new(n_workers: usize) -> Self {
+        assert!(n_workers > 0, "A thread pool needs at least one worker");
+        let (sender, receiver) = channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..n_workers)
+            .map(|_| {
+                let receiver = Arc::clone(&receiver);
+                std::thread::spawn(move || loop {
+                    // The guard is a temporary: the lock is released as soon as
+                    // we get a job, before running it.
+                    let job = receiver.lock().unwrap().recv();
+                    match job {
+                        Ok(job) => job(),
+                        // The pool has been dropped, no more jobs are coming.
+                        Err(_) => break,
+                    }
+                })
+            })
+            .collect();
+        Self {
+            sender: Some(sender),
+            workers,
+        }
+    }
+
+    pub fn execute<F, T>(&self, f: F) -> JobHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (result_sender, receiver) = channel();
+        let job = move || {
+            // The caller might have dropped the handle already, that's fine.
+            let _ = result_sender.send(run_isolated(f));
+        };
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(Box::new(job))
+            .expect("All workers have shut down");
+        JobHandle { receiver }
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        // Closing the channel makes every worker exit its loop once the queue is drained.
+        drop(self.sender.take());
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn runs_jobs() {
+        let pool = ThreadPool::new(4);
+        let handles: Vec<_> = (0..10).map(|i| pool.execute(move || i * 2)).collect();
+        let results: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert_eq!(results, (0..10).map(|i| i * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn panicking_job_does_not_kill_the_worker() {
+        // A single worker: if the panic killed it, the second job would never run.
+        let pool = ThreadPool::new(1);
+
+        let failing = pool.execute(|| -> (thread::ThreadId, u32) { panic!("boom") });
+        let healthy = pool.execute(|| (thread::current().id(), 42));
+
+        let payload = failing.join().unwrap_err();
+        assert_eq!(payload.message(), Some("boom"));
+
+        let (worker_id, value) = healthy.join().unwrap();
+        assert_eq!(value, 42);
+
+        // The same worker keeps serving jobs.
+        let (same_worker_id, _) = pool.execute(|| (thread::current().id(), 0)).join().unwrap();
+        assert_eq!(worker_id, same_worker_id);
+    }
+
+    #[test]
+    fn drop_waits_for_queued_jobs() {
+        let counter = Arc::new(Mutex::new(0));
+        let pool = ThreadPool::new(2);
+        for _ in 0..20 {
+            let counter = Arc::clone(&counter);
+            pool.execute(move || *counter.lock().unwrap() += 1);
+        }
+        drop(pool);
+        assert_eq!(*counter.lock().unwrap(), 20);
+    }
+}
@@ -0,0 +1,152 @@
+// TODO: Implement `DraftEditor`, `Editor` and `Preview` so that the tests pass.
+//  The editor and the preview share the same draft, through an `Rc<RefCell<TicketDraft>>`:
+//  changes made through the editor are immediately visible in the preview.
+//  `RefCell` checks the borrowing rules at runtime: find out which usage patterns panic,
+//  and how `Editor::try_edit` lets you avoid the panic.
+
+use std::cell::{BorrowMutError, Ref, RefCell};
+use std::rc::Rc;
+use ticket_fields::{TicketDescription, TicketDraft, TicketTitle};
+
+/// A draft being edited, with a live preview of the result.
+pub struct DraftEditor {
+    pub editor: Editor,
+    pub preview: Preview,
+}
+
+impl DraftEditor {
+    pub fn new(draft: TicketDraft) -> Self {
+        let draft = Rc::new(RefCell::new(draft));
+        Self {
+            editor: Editor {
+                draft: Rc::clone(&draft),
+            },
+            preview: Preview { draft },
+        }
+    }
+}
+
+/// The part of the UI that changes the draft.
+pub struct Editor {
+    draft: Rc<RefCell<TicketDraft>>,
+}
+
+impl Editor {
+    pub fn set_title(&self, title: TicketTitle) {
+        self.edit(|draft| draft.title = title);
+    }
+
+    pub fn set_description(&self, description: TicketDescription) {
+        self.edit(|draft| draft.description = description);
+    }
+
+    /// Change the draft in place.
+    ///
+    /// # Panics
+    ///
+    /// If the draft is borrowed elsewhere—e.g. through [`Preview::draft`]—while it's being
+    /// edited, or if `f` tries to look at the draft through the preview.
+    pub fn edit(&self, f: impl FnOnce(&mut TicketDraft)) {
+        f(&mut self.draft.borrow_mut());
+    }
+
+    /// Like [`Editor::edit`], but returns an error instead of panicking if the draft
+    /// is borrowed elsewhere. `f` isn't called in that case.
+    pub fn try_edit(&self, f: impl FnOnce(&mut TicketDraft)) -> Result<(), BorrowMutError> {
+        f(&mut *self.draft.try_borrow_mut()?);
+        Ok(())
+    }
+}
+
+/// The part of the UI that shows the draft.
+pub struct Preview {
+    draft: Rc<RefCell<TicketDraft>>,
+}
+
+impl Preview {
+    /// The draft as it'll be shown: its title, a blank line, then its description.
+    pub fn render(&self) -> String {
+        let draft = self.draft.borrow();
+        format!("{}\n\n{}", draft.title.as_ref(), draft.description.as_ref())
+    }
+
+    /// A view of the draft. The draft can't be edited for as long as the view is alive.
+    pub fn draft(&self) -> Ref<'_, TicketDraft> {
+        self.draft.borrow()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ticket_fields::test_helpers::{ticket_description, ticket_title};
+
+    fn editor() -> DraftEditor {
+        DraftEditor::new(TicketDraft {
+            title: ticket_title(),
+            description: ticket_description(),
+        })
+    }
+
+    #[test]
+    fn edits_show_up_in_the_preview() {
+        let DraftEditor { editor, preview } = editor();
+        editor.set_title("A new title".try_into().unwrap());
+        editor.set_description("Some details".try_into().unwrap());
+        assert_eq!(preview.render(), "A new title\n\nSome details");
+        assert_eq!(preview.draft().title.as_ref(), "A new title");
+    }
+
+    #[test]
+    fn views_can_coexist() {
+        let DraftEditor { preview, .. } = editor();
+        // Any number of shared borrows can be alive at the same time.
+        let view = preview.draft();
+        assert!(preview.render().starts_with(view.title.as_ref()));
+    }
+
+    #[test]
+    fn views_can_be_dropped_before_editing() {
+        let DraftEditor { editor, preview } = editor();
+        // The view is a temporary: the borrow ends with the statement.
+        let title = preview.draft().title.clone();
+        editor.set_title(title);
+
+        let view = preview.draft();
+        let description = view.description.clone();
+        drop(view);
+        editor.set_description(description);
+    }
+
+    #[test]
+    #[should_panic(expected = "already borrowed")]
+    fn editing_while_a_view_is_alive_panics() {
+        let DraftEditor { editor, preview } = editor();
+        let _view = preview.draft();
+        editor.set_title("A new title".try_into().unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "already mutably borrowed")]
+    fn rendering_while_editing_panics() {
+        let DraftEditor { editor, preview } = editor();
+        editor.edit(|_| {
+            preview.render();
+        });
+    }
+
+    #[test]
+    fn try_edit_reports_the_conflict() {
+        let DraftEditor { editor, preview } = editor();
+        let view = preview.draft();
+        let mut called = false;
+        assert!(editor.try_edit(|_| called = true).is_err());
+        assert!(!called);
+
+        drop(view);
+        editor
+            .try_edit(|draft| draft.title = "A new title".try_into().unwrap())
+            .unwrap();
+        assert_eq!(preview.draft().title.as_ref(), "A new title");
+    }
+}
@@ -0,0 +1,113 @@
+//! Who may do what.
+//!
+//! A server can be given a set of [`Users`] (see `ticket_core::auth`).
+//! Clients then have to open every connection with `Request::Authenticate`, and the
+//! server checks each following request against the role of the authenticated user.
+//! Servers started without users accept every request, as before.
+pub use ticket_core::auth::{AuthError, Role, User, Users};
+
+use crate::protocol::Request;
+
+/// The least privileged role allowed to send `request`.
+pub fn required_role(request: &Request) -> Role {
+    match request {
+        Request::Authenticate { .. }
+        | Request::Get { .. }
+        | Request::RenderDescription { .. }
+        | Request::List
+        | Request::ListTemplates
+        | Request::ListProjects
+        | Request::Metrics
+        | Request::Health
+        | Request::Ready => Role::Reader,
+        Request::Insert { .. }
+        | Request::InsertFromTemplate { .. }
+        | Request::Update { .. }
+        | Request::Delete { .. }
+        | Request::RegisterTemplate { .. } => Role::Writer,
+        Request::CreateProject { .. } | Request::ReloadConfig | Request::Shutdown => Role::Admin,
+    }
+}
+
+/// Who's on the other end of a connection.
+#[derive(Debug)]
+pub(crate) enum Session {
+    /// The server doesn't require authentication: anything goes.
+    Open,
+    /// The server requires authentication, and the client hasn't authenticated yet.
+    Anonymous,
+    Authenticated(User),
+}
+
+impl Session {
+    pub(crate) fn new(users: Option<&Users>) -> Self {
+        match users {
+            Some(_) => Session::Anonymous,
+            None => Session::Open,
+        }
+    }
+
+    /// Check whether this session may send `request`.
+    pub(crate) fn authorize(&self, request: &Request) -> Result<(), AuthError> {
+        match self {
+            Session::Open => Ok(()),
+            // Anonymous clients can only authenticate, or probe the server's health.
+            Session::Anonymous
+                if matches!(
+                    request,
+                    Request::Authenticate { .. } | Request::Health | Request::Ready
+                ) =>
+            {
+                Ok(())
+            }
+            Session::Anonymous => Err(AuthError::Unauthenticated),
+            Session::Authenticated(user) => user.authorize(required_role(request)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ticket_core::TicketPatch;
+
+    fn user(role: Role) -> Session {
+        Session::Authenticated(User {
+            name: "alice".into(),
+            role,
+        })
+    }
+
+    #[test]
+    fn role_boundaries() {
+        let read = Request::List;
+        let write = Request::Update {
+            patch: TicketPatch::new(0.into()),
+        };
+        let admin = Request::Shutdown;
+
+        let reader = user(Role::Reader);
+        assert!(reader.authorize(&read).is_ok());
+        assert!(reader.authorize(&write).is_err());
+
+        let writer = user(Role::Writer);
+        assert!(writer.authorize(&write).is_ok());
+        assert!(writer.authorize(&admin).is_err());
+
+        assert!(user(Role::Admin).authorize(&admin).is_ok());
+    }
+
+    #[test]
+    fn anonymous_clients_can_only_authenticate() {
+        let session = Session::new(Some(&Users::new()));
+        let authenticate = Request::Authenticate {
+            token: "secret".into(),
+        };
+        assert!(session.authorize(&authenticate).is_ok());
+        assert_eq!(
+            session.authorize(&Request::List),
+            Err(AuthError::Unauthenticated)
+        );
+        assert!(Session::new(None).authorize(&Request::Shutdown).is_ok());
+    }
+}
@@ -0,0 +1,221 @@
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpStream, ToSocketAddrs};
+
+use ticket_core::{
+    ProjectName, TemplateOverrides, Ticket, TicketDraft, TicketId, TicketPatch, TicketTemplate,
+};
+
+use crate::auth::{AuthError, User};
+use crate::protocol::{Envelope, Health, Metrics, Request, Response, SimilarTicket};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("Failed to talk to the server")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to encode or decode a message")]
+    Json(#[from] serde_json::Error),
+    #[error("The server closed the connection")]
+    Disconnected,
+    #[error("The server rejected the request: {0}")]
+    Server(String),
+    #[error("The request was denied")]
+    Denied(#[source] AuthError),
+    #[error("Unexpected response from the server: {0:?}")]
+    UnexpectedResponse(Response),
+}
+
+/// A connection to a ticket server.
+///
+/// Ticket requests go to the default project, unless another one is selected
+/// with [`Client::use_project`].
+pub struct Client {
+    reader: Lines<BufReader<OwnedReadHalf>>,
+    writer: OwnedWriteHalf,
+    project: Option<ProjectName>,
+}
+
+impl Client {
+    pub async fn connect(addr: impl ToSocketAddrs) -> Result<Self, ClientError> {
+        let (reader, writer) = TcpStream::connect(addr).await?.into_split();
+        Ok(Self {
+            reader: BufReader::new(reader).lines(),
+            writer,
+            project: None,
+        })
+    }
+
+    /// Identify ourselves to a server that requires authentication.
+    pub async fn authenticate(&mut self, token: impl Into<String>) -> Result<User, ClientError> {
+        let token = token.into();
+        match self.call(&Request::Authenticate { token }).await? {
+            Response::Authenticated { user } => Ok(user),
+            other => Err(ClientError::UnexpectedResponse(other)),
+        }
+    }
+
+    /// Send all subsequent ticket requests to `project`.
+    pub fn use_project(&mut self, project: ProjectName) {
+        self.project = Some(project);
+    }
+
+    pub async fn create_project(&mut self, name: ProjectName) -> Result<(), ClientError> {
+        match self.call(&Request::CreateProject { name }).await? {
+            Response::ProjectCreated => Ok(()),
+            other => Err(ClientError::UnexpectedResponse(other)),
+        }
+    }
+
+    /// Project names, in alphabetical order.
+    pub async fn projects(&mut self) -> Result<Vec<ProjectName>, ClientError> {
+        match self.call(&Request::ListProjects).await? {
+            Response::Projects { projects } => Ok(projects),
+            other => Err(ClientError::UnexpectedResponse(other)),
+        }
+    }
+
+    pub async fn insert(&mut self, draft: TicketDraft) -> Result<TicketId, ClientError> {
+        self.insert_reporting_similar(draft).await.map(|(id, _)| id)
+    }
+
+    /// Like [`Client::insert`], also returning the tickets with a similar title, if the
+    /// server looks for them.
+    pub async fn insert_reporting_similar(
+        &mut self,
+        draft: TicketDraft,
+    ) -> Result<(TicketId, Vec<SimilarTicket>), ClientError> {
+        match self.call(&Request::Insert { draft }).await? {
+            Response::Inserted { id, similar } => Ok((id, similar)),
+            other => Err(ClientError::UnexpectedResponse(other)),
+        }
+    }
+
+    /// Insert a ticket created from the project's template named `template`.
+    pub async fn insert_from_template(
+        &mut self,
+        template: impl Into<String>,
+        overrides: TemplateOverrides,
+    ) -> Result<TicketId, ClientError> {
+        let request = Request::InsertFromTemplate {
+            template: template.into(),
+            overrides,
+        };
+        match self.call(&request).await? {
+            Response::Inserted { id, .. } => Ok(id),
+            other => Err(ClientError::UnexpectedResponse(other)),
+        }
+    }
+
+    pub async fn get(&mut self, id: TicketId) -> Result<Option<Ticket>, ClientError> {
+        match self.call(&Request::Get { id }).await? {
+            Response::Ticket { ticket } => Ok(ticket),
+            other => Err(ClientError::UnexpectedResponse(other)),
+        }
+    }
+
+    /// The ticket's description as HTML, or `None` if there's no ticket with that id.
+    pub async fn render_description(
+        &mut self,
+        id: TicketId,
+    ) -> Result<Option<String>, ClientError> {
+        match self.call(&Request::RenderDescription { id }).await? {
+            Response::RenderedDescription { html } => Ok(html),
+            other => Err(ClientError::UnexpectedResponse(other)),
+        }
+    }
+
+    pub async fn update(&mut self, patch: TicketPatch) -> Result<(), ClientError> {
+        match self.call(&Request::Update { patch }).await? {
+            Response::Updated => Ok(()),
+            other => Err(ClientError::UnexpectedResponse(other)),
+        }
+    }
+
+    pub async fn list(&mut self) -> Result<Vec<Ticket>, ClientError> {
+        match self.call(&Request::List).await? {
+            Response::Tickets { tickets } => Ok(tickets),
+            other => Err(ClientError::UnexpectedResponse(other)),
+        }
+    }
+
+    pub async fn register_template(&mut self, template: TicketTemplate) -> Result<(), ClientError> {
+        match self.call(&Request::RegisterTemplate { template }).await? {
+            Response::TemplateRegistered => Ok(()),
+            other => Err(ClientError::UnexpectedResponse(other)),
+        }
+    }
+
+    /// The project's templates, by name.
+    pub async fn templates(&mut self) -> Result<Vec<TicketTemplate>, ClientError> {
+        match self.call(&Request::ListTemplates).await? {
+            Response::Templates { templates } => Ok(templates),
+            other => Err(ClientError::UnexpectedResponse(other)),
+        }
+    }
+
+    /// Returns the deleted ticket, or `None` if there was no ticket with that id.
+    pub async fn delete(&mut self, id: TicketId) -> Result<Option<Ticket>, ClientError> {
+        match self.call(&Request::Delete { id }).await? {
+            Response::Deleted { ticket } => Ok(ticket),
+            other => Err(ClientError::UnexpectedResponse(other)),
+        }
+    }
+
+    pub async fn metrics(&mut self) -> Result<Metrics, ClientError> {
+        match self.call(&Request::Metrics).await? {
+            Response::Metrics { metrics } => Ok(metrics),
+            other => Err(ClientError::UnexpectedResponse(other)),
+        }
+    }
+
+    pub async fn health(&mut self) -> Result<Health, ClientError> {
+        match self.call(&Request::Health).await? {
+            Response::Health { health } => Ok(health),
+            other => Err(ClientError::UnexpectedResponse(other)),
+        }
+    }
+
+    /// Whether the server is ready, and why.
+    pub async fn ready(&mut self) -> Result<(bool, Health), ClientError> {
+        match self.call(&Request::Ready).await? {
+            Response::Ready { ready, health } => Ok((ready, health)),
+            other => Err(ClientError::UnexpectedResponse(other)),
+        }
+    }
+
+    pub async fn reload_config(&mut self) -> Result<(), ClientError> {
+        match self.call(&Request::ReloadConfig).await? {
+            Response::ConfigReloaded => Ok(()),
+            other => Err(ClientError::UnexpectedResponse(other)),
+        }
+    }
+
+    pub async fn shutdown(&mut self) -> Result<(), ClientError> {
+        match self.call(&Request::Shutdown).await? {
+            Response::ShuttingDown => Ok(()),
+            other => Err(ClientError::UnexpectedResponse(other)),
+        }
+    }
+
+    /// Send a request to the selected project and wait for the matching response.
+    /// Error responses are turned into `ClientError::Server`, denials into `ClientError::Denied`.
+    pub async fn call(&mut self, request: &Request) -> Result<Response, ClientError> {
+        let envelope = Envelope {
+            project: self.project.clone(),
+            request: request.clone(),
+        };
+        let mut encoded = serde_json::to_vec(&envelope)?;
+        encoded.push(b'\n');
+        self.writer.write_all(&encoded).await?;
+        let line = self
+            .reader
+            .next_line()
+            .await?
+            .ok_or(ClientError::Disconnected)?;
+        match serde_json::from_str(&line)? {
+            Response::Error { message } => Err(ClientError::Server(message)),
+            Response::Denied { error } => Err(ClientError::Denied(error)),
+            response => Ok(response),
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! A length-prefixed framing for the protocol's messages, as an alternative to one per line.
+//!
+//! Each frame is a 4-byte big-endian length, followed by that many bytes of JSON.
+//! Unlike newlines, the length tells the receiver upfront how much to expect: an oversized frame
+//! is rejected as soon as its header arrives, before any of it is buffered.
+//!
+//! Nothing here does any I/O: [`FrameDecoder`] is fed whatever bytes the socket returned,
+//! so it works the same on top of a blocking `std::net::TcpStream` or a tokio one.
+use serde::Serialize;
+
+/// The largest frame [`FrameDecoder::new`] accepts, in bytes, not counting the header.
+pub const MAX_FRAME_LEN: usize = 1024 * 1024;
+
+const HEADER_LEN: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum FrameError {
+    /// There's no telling where the next frame starts: the connection should be closed.
+    #[error("The frame is {len} bytes long, more than the limit of {max} bytes")]
+    TooLarge { len: usize, max: usize },
+}
+
+/// Encode `message`—a `Request`, an `Envelope` or a `Response`—as a single frame.
+pub fn encode_frame(message: &impl Serialize) -> Vec<u8> {
+    let mut frame = vec![0; HEADER_LEN];
+    serde_json::to_writer(&mut frame, message).expect("Protocol messages can always be encoded");
+    let len = u32::try_from(frame.len() - HEADER_LEN).expect("Frames are smaller than 4 GiB");
+    frame[..HEADER_LEN].copy_from_slice(&len.to_be_bytes());
+    frame
+}
+
+/// Splits a stream of bytes, received in arbitrary chunks, back into frames.
+#[derive(Debug)]
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+    /// How many bytes at the start of `buffer` belong to frames that were already returned.
+    consumed: usize,
+    max_len: usize,
+}
+
+impl Default for FrameDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameDecoder {
+    pub fn new() -> Self {
+        Self::with_max_len(MAX_FRAME_LEN)
+    }
+
+    pub fn with_max_len(max_len: usize) -> Self {
+        Self {
+            buffer: Vec::new(),
+            consumed: 0,
+            max_len,
+        }
+    }
+
+    /// Add bytes received from the peer.
+    pub fn extend(&mut self, bytes: &[u8]) {
+        // The frames returned so far can't be borrowed anymore: their space can be reused.
+        self.buffer.drain(..self.consumed);
+        self.consumed = 0;
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// The payload of the next frame, or `None` if it hasn't been fully received yet.
+    ///
+    /// Once an error is returned, every later call returns it too.
+    pub fn next_frame(&mut self) -> Result<Option<&[u8]>, FrameError> {
+        let available = &self.buffer[self.consumed..];
+        let Some(header) = available.first_chunk::<HEADER_LEN>() else {
+            return Ok(None);
+        };
+        let len = u32::from_be_bytes(*header) as usize;
+        if len > self.max_len {
+            return Err(FrameError::TooLarge {
+                len,
+                max: self.max_len,
+            });
+        }
+        if available.len() < HEADER_LEN + len {
+            return Ok(None);
+        }
+        let start = self.consumed + HEADER_LEN;
+        self.consumed = start + len;
+        Ok(Some(&self.buffer[start..self.consumed]))
+    }
+
+    /// How many bytes have been received but not returned as part of a frame yet.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len() - self.consumed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{decode_request, Request};
+    use std::io::Read;
+
+    fn requests() -> Vec<Request> {
+        vec![
+            Request::Get { id: 1.into() },
+            Request::List,
+            Request::Authenticate {
+                token: "a token\nwith a newline".into(),
+            },
+        ]
+    }
+
+    fn encoded() -> Vec<u8> {
+        requests().iter().flat_map(encode_frame).collect()
+    }
+
+    /// Decode every complete frame, e.g. right after feeding the decoder.
+    fn drain(decoder: &mut FrameDecoder) -> Vec<Request> {
+        let mut requests = Vec::new();
+        while let Some(frame) = decoder.next_frame().unwrap() {
+            requests.push(decode_request(frame).unwrap().request);
+        }
+        requests
+    }
+
+    #[test]
+    fn frame_layout() {
+        let frame = encode_frame(&Request::List);
+        assert_eq!(&frame[..4], &18u32.to_be_bytes());
+        assert_eq!(&frame[4..], br#"{"command":"list"}"#);
+    }
+
+    #[test]
+    fn whole_frames() {
+        let mut decoder = FrameDecoder::new();
+        decoder.extend(&encoded());
+        assert_eq!(drain(&mut decoder), requests());
+        assert_eq!(decoder.buffered(), 0);
+    }
+
+    #[test]
+    fn byte_at_a_time() {
+        let mut decoder = FrameDecoder::new();
+        let mut decoded = Vec::new();
+        for byte in encoded() {
+            decoder.extend(&[byte]);
+            decoded.extend(drain(&mut decoder));
+        }
+        assert_eq!(decoded, requests());
+        assert_eq!(decoder.buffered(), 0);
+    }
+
+    #[test]
+    fn partial_frames_are_kept() {
+        let encoded = encoded();
+        let mut decoder = FrameDecoder::new();
+        // The first frame, and the first two bytes of the second one's header.
+        let first = encode_frame(&requests()[0]).len();
+        decoder.extend(&encoded[..first + 2]);
+        assert_eq!(drain(&mut decoder), &requests()[..1]);
+        assert_eq!(decoder.buffered(), 2);
+
+        decoder.extend(&encoded[first + 2..]);
+        assert_eq!(drain(&mut decoder), &requests()[1..]);
+    }
+
+    #[test]
+    fn oversized_frames_are_rejected_from_their_header() {
+        let mut decoder = FrameDecoder::with_max_len(16);
+        // Only the header has arrived.
+        decoder.extend(&17u32.to_be_bytes());
+        let error = FrameError::TooLarge { len: 17, max: 16 };
+        assert_eq!(decoder.next_frame(), Err(error.clone()));
+        assert_eq!(decoder.next_frame(), Err(error));
+
+        let mut decoder = FrameDecoder::new();
+        decoder.extend(&[0xff; 4]);
+        assert!(matches!(
+            decoder.next_frame(),
+            Err(FrameError::TooLarge { len, .. }) if len == u32::MAX as usize
+        ));
+    }
+
+    #[test]
+    fn corrupted_lengths_garble_the_payload() {
+        let mut frame = encode_frame(&Request::List);
+        // One byte short: the frame stops before the closing brace...
+        frame[3] -= 1;
+        let mut decoder = FrameDecoder::new();
+        decoder.extend(&frame);
+        assert!(decode_request(decoder.next_frame().unwrap().unwrap()).is_err());
+        // ...which is then read as the start of the next header.
+        assert_eq!(decoder.buffered(), 1);
+        assert_eq!(decoder.next_frame(), Ok(None));
+
+        // A header announcing more than was sent leaves the decoder waiting.
+        let mut frame = encode_frame(&Request::List);
+        frame[3] += 10;
+        let mut decoder = FrameDecoder::new();
+        decoder.extend(&frame);
+        assert_eq!(decoder.next_frame(), Ok(None));
+    }
+
+    #[test]
+    fn over_a_blocking_reader() {
+        let mut reader = std::io::Cursor::new(encoded());
+        let mut decoder = FrameDecoder::new();
+        let mut decoded = Vec::new();
+        let mut chunk = [0; 5];
+        loop {
+            let read = reader.read(&mut chunk).unwrap();
+            if read == 0 {
+                break;
+            }
+            decoder.extend(&chunk[..read]);
+            decoded.extend(drain(&mut decoder));
+        }
+        assert_eq!(decoded, requests());
+    }
+
+    #[tokio::test]
+    async fn over_a_tokio_stream() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let (mut client, mut server) = tokio::io::duplex(7);
+        tokio::spawn(async move {
+            client.write_all(&encoded()).await.unwrap();
+        });
+        let mut decoder = FrameDecoder::new();
+        let mut decoded = Vec::new();
+        let mut chunk = [0; 64];
+        loop {
+            let read = server.read(&mut chunk).await.unwrap();
+            if read == 0 {
+                break;
+            }
+            decoder.extend(&chunk[..read]);
+            decoded.extend(drain(&mut decoder));
+        }
+        assert_eq!(decoded, requests());
+    }
+}
@@ -8,3 +8,21 @@
 //
 // Use Rust's package registry, crates.io, to find the dependencies you need
 // (if any) to build this system.
+//
+// Our take: rather than HTTP, the server speaks a tiny JSON protocol over TCP—one
+// JSON request per line, answered by one JSON response per line (see `protocol.rs`).
+// `codec.rs` offers a length-prefixed framing for the same messages.
+// The ticket model itself comes from the `ticket_core` crate, the canonical version
+// of the types you built in the previous chapters.
+pub mod auth;
+pub mod client;
+pub mod codec;
+mod persistence;
+pub mod protocol;
+pub mod server;
+pub mod webhook;
+
+pub use auth::{AuthError, Role, User, Users};
+pub use client::{Client, ClientError};
+pub use server::{serve, serve_persistent, ticker, Server};
+pub use webhook::{Retry, WebhookEvent, WebhookUrl};
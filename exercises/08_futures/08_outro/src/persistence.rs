@@ -0,0 +1,145 @@
+//! Saving the workspace, off the request path.
+//!
+//! Writers hand every new version of the workspace to a background task, in the order they
+//! made their changes, and wait for it to be on disk before answering. The task saves on a
+//! blocking thread: neither the runtime nor the store's lock waits for the disk.
+//! Versions queued while a save is in progress are saved together: only the latest one is
+//! written, for everyone waiting on it.
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::{mpsc, oneshot};
+
+use ticket_core::{Clock, Encoding, Timestamp, Workspace};
+use ticket_fields::render_chain;
+
+/// The queue of the persistence task. The task stops once it's dropped, after saving what's
+/// left in it.
+pub(crate) struct Persistence {
+    queue: mpsc::UnboundedSender<Pending>,
+}
+
+struct Pending {
+    workspace: Workspace,
+    saved: oneshot::Sender<Result<(), String>>,
+}
+
+/// A version of the workspace on its way to the disk.
+pub(crate) struct Saved(oneshot::Receiver<Result<(), String>>);
+
+impl Saved {
+    /// Wait for the version to be saved. Returns why it couldn't be, if it wasn't.
+    pub(crate) async fn wait(self) -> Result<(), String> {
+        self.0
+            .await
+            .unwrap_or_else(|_| Err("The server stopped before saving the change".into()))
+    }
+}
+
+impl Persistence {
+    /// Spawn a task saving the workspace to `path`.
+    /// It keeps `unsaved_since` up to date: when saving first failed, if it hasn't succeeded since.
+    pub(crate) fn start(
+        path: PathBuf,
+        encoding: Encoding,
+        clock: Arc<dyn Clock>,
+        unsaved_since: Arc<Mutex<Option<Timestamp>>>,
+    ) -> Self {
+        let (queue, pending) = mpsc::unbounded_channel();
+        tokio::spawn(save_all(path, encoding, clock, unsaved_since, pending));
+        Self { queue }
+    }
+
+    /// Queue `workspace` to be saved.
+    pub(crate) fn save(&self, workspace: &Workspace) -> Saved {
+        let (saved, receiver) = oneshot::channel();
+        let pending = Pending {
+            workspace: workspace.clone(),
+            saved,
+        };
+        // If the task is gone, `receiver` reports it.
+        let _ = self.queue.send(pending);
+        Saved(receiver)
+    }
+}
+
+async fn save_all(
+    path: PathBuf,
+    encoding: Encoding,
+    clock: Arc<dyn Clock>,
+    unsaved_since: Arc<Mutex<Option<Timestamp>>>,
+    mut queue: mpsc::UnboundedReceiver<Pending>,
+) {
+    while let Some(Pending {
+        mut workspace,
+        saved,
+    }) = queue.recv().await
+    {
+        let mut waiting = vec![saved];
+        while let Ok(next) = queue.try_recv() {
+            workspace = next.workspace;
+            waiting.push(next.saved);
+        }
+        let (path, encoding) = (path.clone(), encoding.clone());
+        let result = tokio::task::spawn_blocking(move || {
+            workspace
+                .save_with(&path, &encoding)
+                .map_err(|e| render_chain(&e))
+        })
+        .await
+        .unwrap_or_else(|e| Err(format!("Saving the workspace panicked: {e}")));
+
+        let mut unsaved_since = unsaved_since.lock().unwrap();
+        match result {
+            Ok(()) => *unsaved_since = None,
+            Err(_) => {
+                unsaved_since.get_or_insert_with(|| clock.now());
+            }
+        }
+        drop(unsaved_since);
+        for saved in waiting {
+            let _ = saved.send(result.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ticket_core::{ProjectName, TestClock};
+    use ticket_fields::test_helpers::ticket_draft;
+
+    #[tokio::test]
+    async fn every_version_is_acknowledged_and_the_latest_is_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tickets.json");
+        let clock = TestClock::new();
+        let unsaved_since = Arc::default();
+        let persistence = Persistence::start(
+            path.clone(),
+            Encoding::default(),
+            Arc::new(clock.clone()),
+            Arc::clone(&unsaved_since),
+        );
+
+        let mut workspace = Workspace::new();
+        let mut saved = Vec::new();
+        for _ in 0..3 {
+            workspace
+                .add_ticket(ProjectName::DEFAULT, ticket_draft())
+                .unwrap();
+            saved.push(persistence.save(&workspace));
+        }
+        for saved in saved {
+            saved.wait().await.unwrap();
+        }
+        let loaded = Workspace::load_or_default_with(&path, &Encoding::default()).unwrap();
+        assert_eq!(loaded.project(ProjectName::DEFAULT).unwrap().len(), 3);
+
+        // Failures are reported to the writer, and remembered until the next save succeeds.
+        drop(dir);
+        let failed = persistence.save(&workspace).wait().await;
+        assert!(failed.is_err());
+        assert_eq!(*unsaved_since.lock().unwrap(), Some(clock.now()));
+    }
+}
@@ -0,0 +1,359 @@
+use std::borrow::Cow;
+
+use crate::auth::{AuthError, User};
+use serde::{Deserialize, Deserializer, Serialize};
+use ticket_core::{
+    ProjectName, TemplateOverrides, Ticket, TicketDraft, TicketId, TicketPatch, TicketTemplate,
+};
+
+/// A request, together with the project it targets.
+/// Requests that don't name a project go to the default one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Envelope {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<ProjectName>,
+    #[serde(flatten)]
+    pub request: Request,
+}
+
+/// An [`Envelope`] as the server decodes it, borrowing from the frame it was read from.
+///
+/// The project name is only looked up, never stored, so it isn't copied out of the frame
+/// (unless it contains JSON escapes). The request itself is already owned: the only strings
+/// it holds are the ones that end up stored—drafts, patches, new project names—and a token,
+/// sent once per connection.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct EnvelopeRef<'a> {
+    #[serde(default, borrow, deserialize_with = "borrowed_project")]
+    pub project: Option<Cow<'a, str>>,
+    #[serde(flatten)]
+    pub request: Request,
+}
+
+impl EnvelopeRef<'_> {
+    pub fn into_owned(self) -> Envelope {
+        Envelope {
+            project: self.project.map(|name| {
+                ProjectName::try_from(name.into_owned()).expect("Validated while decoding")
+            }),
+            request: self.request,
+        }
+    }
+}
+
+/// Project names are validated like `ProjectName`'s, without allocating one.
+fn borrowed_project<'de: 'a, 'a, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Cow<'a, str>>, D::Error> {
+    /// `Cow` only borrows with `#[serde(borrow)]`, which doesn't reach inside the `Option`.
+    #[derive(Deserialize)]
+    struct Borrowed<'a>(#[serde(borrow)] Cow<'a, str>);
+
+    let Some(Borrowed(name)) = Option::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    ProjectName::validate(&name).map_err(serde::de::Error::custom)?;
+    Ok(Some(name))
+}
+
+/// A command sent by a client, encoded as a single line of JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Request {
+    /// Identify the client, on servers that require it.
+    /// It must be the first request sent on the connection.
+    Authenticate {
+        token: String,
+    },
+    Insert {
+        draft: TicketDraft,
+    },
+    /// Insert a ticket created from one of the project's templates.
+    InsertFromTemplate {
+        template: String,
+        #[serde(default)]
+        overrides: TemplateOverrides,
+    },
+    Get {
+        id: TicketId,
+    },
+    /// Get a ticket's description, rendered from Markdown to HTML.
+    RenderDescription {
+        id: TicketId,
+    },
+    Update {
+        patch: TicketPatch,
+    },
+    Delete {
+        id: TicketId,
+    },
+    List,
+    /// Add a template to the project, or replace the one with the same name.
+    RegisterTemplate {
+        template: TicketTemplate,
+    },
+    ListTemplates,
+    /// Projects are workspace-wide: these two ignore the envelope's project.
+    CreateProject {
+        name: ProjectName,
+    },
+    ListProjects,
+    /// Get a summary of what the server holds, across every project.
+    Metrics,
+    /// Check how the server is doing. Like `Ready`, it doesn't need authenticating.
+    Health,
+    /// Check whether the server is fit to take requests.
+    Ready,
+    /// Reload the limits, the connection cap and the rate limit from the server's config file.
+    /// If the file is invalid, the current settings stay in place.
+    ReloadConfig,
+    /// Stop accepting new connections and shut the server down.
+    Shutdown,
+}
+
+impl Request {
+    /// The `command` tag this request is encoded with.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Request::Authenticate { .. } => "authenticate",
+            Request::Insert { .. } => "insert",
+            Request::InsertFromTemplate { .. } => "insert_from_template",
+            Request::Get { .. } => "get",
+            Request::RenderDescription { .. } => "render_description",
+            Request::Update { .. } => "update",
+            Request::Delete { .. } => "delete",
+            Request::List => "list",
+            Request::RegisterTemplate { .. } => "register_template",
+            Request::ListTemplates => "list_templates",
+            Request::CreateProject { .. } => "create_project",
+            Request::ListProjects => "list_projects",
+            Request::Metrics => "metrics",
+            Request::Health => "health",
+            Request::Ready => "ready",
+            Request::ReloadConfig => "reload_config",
+            Request::Shutdown => "shutdown",
+        }
+    }
+}
+
+/// The server's answer to a `Request`, encoded as a single line of JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "response", rename_all = "snake_case")]
+pub enum Response {
+    Authenticated {
+        user: User,
+    },
+    Inserted {
+        id: TicketId,
+        /// The other tickets in the project with a similar title, most similar first,
+        /// if the server looks for them: see `Server::warn_on_similar_titles`.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        similar: Vec<SimilarTicket>,
+    },
+    Ticket {
+        ticket: Option<Ticket>,
+    },
+    /// `None` if there's no ticket with that id.
+    RenderedDescription {
+        html: Option<String>,
+    },
+    Updated,
+    /// The deleted ticket, or `None` if there was no ticket with that id.
+    Deleted {
+        ticket: Option<Ticket>,
+    },
+    Tickets {
+        tickets: Vec<Ticket>,
+    },
+    TemplateRegistered,
+    /// By name.
+    Templates {
+        templates: Vec<TicketTemplate>,
+    },
+    ProjectCreated,
+    Projects {
+        projects: Vec<ProjectName>,
+    },
+    Metrics {
+        metrics: Metrics,
+    },
+    Health {
+        health: Health,
+    },
+    /// The server is ready unless it's unhealthy.
+    Ready {
+        ready: bool,
+        health: Health,
+    },
+    ConfigReloaded,
+    ShuttingDown,
+    /// The client isn't allowed to send this request.
+    Denied {
+        error: AuthError,
+    },
+    /// The request was malformed or couldn't be fulfilled.
+    Error {
+        message: String,
+    },
+}
+
+/// A possible duplicate of an inserted ticket.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SimilarTicket {
+    pub id: TicketId,
+    /// From 0 to 1, see `ticket_core::query::title_similarity`.
+    pub similarity: f64,
+}
+
+/// What `Request::Metrics` reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metrics {
+    pub projects: usize,
+    pub tickets: usize,
+    /// Tickets inserted since the server started, in the last minute and in the last hour.
+    /// Counts are rounded to ten seconds, see `ticket_core::RateTracker`.
+    pub created_last_minute: u64,
+    pub created_last_hour: u64,
+}
+
+/// What `Request::Health` and `Request::Ready` report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Health {
+    pub status: HealthStatus,
+    /// Whether the store could be locked promptly. A write that holds it for too long—such as
+    /// saving a snapshot to a disk that hangs—blocks every other request.
+    pub store_accessible: bool,
+    /// How long changes have gone unsaved, in milliseconds, since saving them failed.
+    /// `None` if every change is saved (or the server isn't persistent).
+    pub persistence_lag_ms: Option<u64>,
+    /// Open client connections, including the one asking.
+    pub connections: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Healthy,
+    /// Requests are served, but changes aren't being saved.
+    Degraded,
+    /// The store is stuck, or changes have gone unsaved for too long.
+    Unhealthy,
+}
+
+/// Decode a single request frame, i.e. one line without its trailing newline.
+pub fn decode_request(frame: &[u8]) -> Result<EnvelopeRef<'_>, serde_json::Error> {
+    serde_json::from_slice(frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_encoding() {
+        let request = decode_request(
+            br#"{"command": "insert", "draft": {"title": "A title", "description": "A description"}}"#,
+        )
+        .unwrap();
+        assert!(matches!(request.request, Request::Insert { .. }));
+        assert_eq!(request.project, None);
+
+        let encoded = serde_json::to_string(&Request::Get { id: 3.into() }).unwrap();
+        assert_eq!(encoded, r#"{"command":"get","id":3}"#);
+        assert_eq!(
+            serde_json::to_string(&Request::List).unwrap(),
+            r#"{"command":"list"}"#
+        );
+    }
+
+    #[test]
+    fn names_match_the_encoding() {
+        let requests = [
+            Request::RenderDescription { id: 1.into() },
+            Request::ListProjects,
+            Request::Metrics,
+            Request::Health,
+            Request::Ready,
+            Request::ReloadConfig,
+            Request::Shutdown,
+        ];
+        for request in requests {
+            let encoded = serde_json::to_value(&request).unwrap();
+            assert_eq!(encoded["command"], request.name());
+        }
+    }
+
+    #[test]
+    fn project_selector() {
+        let envelope =
+            decode_request(br#"{"project": "backend", "command": "get", "id": 1}"#).unwrap();
+        assert_eq!(
+            envelope.project.as_ref().map(AsRef::as_ref),
+            Some("backend")
+        );
+        assert_eq!(envelope.request, Request::Get { id: 1.into() });
+        assert_eq!(
+            serde_json::to_string(&envelope.into_owned()).unwrap(),
+            r#"{"project":"backend","command":"get","id":1}"#
+        );
+        assert!(decode_request(br#"{"project": "Not valid!", "command": "list"}"#).is_err());
+        assert!(serde_json::from_str::<Envelope>(r#"{"project": "", "command": "list"}"#).is_err());
+    }
+
+    #[test]
+    fn project_names_borrow_from_the_frame() {
+        let envelope = decode_request(br#"{"project": "backend", "command": "list"}"#).unwrap();
+        assert!(matches!(envelope.project, Some(Cow::Borrowed("backend"))));
+        // Escapes have to be decoded into a buffer of their own.
+        let envelope =
+            decode_request(br#"{"project": "back\u0065nd", "command": "list"}"#).unwrap();
+        assert!(matches!(envelope.project, Some(Cow::Owned(name)) if name == "backend"));
+    }
+
+    #[test]
+    fn denial_encoding() {
+        let response = Response::Denied {
+            error: AuthError::Unauthenticated,
+        };
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"response":"denied","error":{"reason":"unauthenticated"}}"#
+        );
+    }
+
+    #[test]
+    fn invalid_drafts_are_rejected_while_decoding() {
+        let err = decode_request(
+            br#"{"command": "insert", "draft": {"title": "", "description": "A description"}}"#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("The title cannot be empty"));
+    }
+
+    #[test]
+    fn response_encoding() {
+        let response = Response::Inserted {
+            id: 0.into(),
+            similar: Vec::new(),
+        };
+        let encoded = serde_json::to_string(&response).unwrap();
+        assert_eq!(encoded, r#"{"response":"inserted","id":0}"#);
+
+        let response = Response::Inserted {
+            id: 2.into(),
+            similar: vec![SimilarTicket {
+                id: 0.into(),
+                similarity: 0.9,
+            }],
+        };
+        let encoded = serde_json::to_string(&response).unwrap();
+        assert_eq!(
+            encoded,
+            r#"{"response":"inserted","id":2,"similar":[{"id":0,"similarity":0.9}]}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Response>(&encoded).unwrap(),
+            response
+        );
+    }
+}
@@ -0,0 +1,938 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use arc_swap::ArcSwap;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, watch};
+use tracing::{Instrument, Span};
+
+use ticket_core::config::Limits;
+use ticket_core::{
+    AuditEntry, Clock, Compression, Config, Encoding, EncryptionKey, FromTemplate, ProjectName,
+    RateTracker, StaleTickets, SystemClock, TemplateError, TemplateOverrides, TicketDraft,
+    TicketId, TicketStore, Timestamp, Workspace,
+};
+use ticket_fields::render_chain;
+
+use crate::auth::{Session, Users};
+use crate::persistence::{Persistence, Saved};
+use crate::protocol::{
+    decode_request, EnvelopeRef, Health, HealthStatus, Metrics, Request, Response, SimilarTicket,
+};
+use crate::webhook::{EventKind, Retry, WebhookEvent, WebhookUrl, Webhooks};
+
+/// How long changes can go unsaved before the server reports itself unhealthy, by default.
+const MAX_PERSISTENCE_LAG: Duration = Duration::from_secs(30);
+/// How long a health check waits for the store, before deeming it inaccessible.
+const STORE_PATIENCE: Duration = Duration::from_millis(100);
+const SECOND: Duration = Duration::from_secs(1);
+
+/// Everything the connections share.
+struct State {
+    /// One store per project.
+    store: Store,
+    /// Where new versions of the workspace go to be saved, if the server is persistent.
+    persistence: Option<Persistence>,
+    /// The users allowed to connect, if the server requires authentication.
+    users: Option<Users>,
+    settings: ArcSwap<Settings>,
+    /// Where to reload the settings from.
+    config_file: Option<PathBuf>,
+    clock: Arc<dyn Clock>,
+    /// Tickets inserted in every project.
+    creations: Mutex<RateTracker>,
+    /// When saving the workspace first failed, if it hasn't succeeded since.
+    unsaved_since: Arc<Mutex<Option<Timestamp>>>,
+    max_persistence_lag: Duration,
+    connections: AtomicUsize,
+    webhooks: Webhooks,
+    /// How similar titles have to be for `Response::Inserted` to report them.
+    similarity_threshold: Option<f64>,
+}
+
+/// Where the workspace lives, depending on how the server was configured.
+enum Store {
+    /// Readers and writers share a lock.
+    /// We never hold it across an `.await`, so a blocking `RwLock` is fine.
+    Locked(RwLock<Workspace>),
+    /// Readers load the current version of the workspace without locking anything.
+    /// Writers take turns: each one copies the current version, changes the copy and
+    /// publishes it, while readers keep using the version they loaded.
+    Swapped {
+        current: ArcSwap<Workspace>,
+        writer: Mutex<()>,
+    },
+}
+
+impl Store {
+    fn new(workspace: Workspace, lock_free_reads: bool) -> Self {
+        if lock_free_reads {
+            Store::Swapped {
+                current: ArcSwap::from_pointee(workspace),
+                writer: Mutex::new(()),
+            }
+        } else {
+            Store::Locked(RwLock::new(workspace))
+        }
+    }
+
+    fn read<R>(&self, f: impl FnOnce(&Workspace) -> R) -> R {
+        match self {
+            Store::Locked(lock) => f(&lock.read().unwrap()),
+            Store::Swapped { current, .. } => f(&current.load()),
+        }
+    }
+
+    /// Whether the store can be locked within `patience`.
+    ///
+    /// It only ever tries to lock it, without blocking: while it waits, the runtime is free
+    /// to get on with other tasks.
+    async fn is_accessible(&self, patience: Duration) -> bool {
+        // A stuck writer holds the workspace (or the writer's turn) and never lets go.
+        let free = || match self {
+            Store::Locked(lock) => lock.try_read().is_ok(),
+            Store::Swapped { writer, .. } => writer.try_lock().is_ok(),
+        };
+        let unlocked = async {
+            while !free() {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        };
+        tokio::time::timeout(patience, unlocked).await.is_ok()
+    }
+
+    fn write<R>(&self, f: impl FnOnce(&mut Workspace) -> R) -> R {
+        match self {
+            Store::Locked(lock) => f(&mut lock.write().unwrap()),
+            Store::Swapped { current, writer } => {
+                let _turn = writer.lock().unwrap();
+                let mut next = Workspace::clone(&current.load());
+                let output = f(&mut next);
+                current.store(Arc::new(next));
+                output
+            }
+        }
+    }
+}
+
+/// Serve requests on `listener` until a client sends `Request::Shutdown`.
+/// Tickets are kept in memory: they're lost when the server stops.
+pub async fn serve(listener: TcpListener) -> std::io::Result<()> {
+    Server::new().serve(listener).await
+}
+
+/// Like [`serve`], but every project is persisted to a snapshot at `path`:
+/// it's loaded (if it exists) on startup, and rewritten after every change.
+pub async fn serve_persistent(
+    listener: TcpListener,
+    path: impl Into<PathBuf>,
+) -> std::io::Result<()> {
+    Server::new().persistent(path).serve(listener).await
+}
+
+/// A server configuration, for when [`serve`] and [`serve_persistent`] aren't enough.
+#[derive(Debug, Default)]
+pub struct Server {
+    snapshot: Option<PathBuf>,
+    encoding: Encoding,
+    users: Option<Users>,
+    settings: Settings,
+    config_file: Option<PathBuf>,
+    lock_free_reads: bool,
+    clock: Option<Arc<dyn Clock>>,
+    max_persistence_lag: Option<Duration>,
+    auto_close: Option<AutoClose>,
+    webhooks: Vec<WebhookUrl>,
+    webhook_retry: Retry,
+    similarity_threshold: Option<f64>,
+}
+
+/// The settings that can change while the server runs, see [`Server::reload_from`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Settings {
+    limits: Limits,
+    max_connections: Option<usize>,
+    max_requests_per_second: Option<u32>,
+}
+
+impl Settings {
+    fn from_config(config: &Config) -> Self {
+        Self {
+            limits: config.limits,
+            max_connections: config.server.max_connections,
+            max_requests_per_second: config.server.max_requests_per_second,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct AutoClose {
+    stale: StaleTickets,
+    ticks: mpsc::Receiver<()>,
+    audit: mpsc::UnboundedSender<Vec<(ProjectName, AuditEntry)>>,
+}
+
+impl Server {
+    /// An in-memory server, open to everyone.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Persist every project to a snapshot at `path`, like [`serve_persistent`].
+    pub fn persistent(mut self, path: impl Into<PathBuf>) -> Self {
+        self.snapshot = Some(path.into());
+        self
+    }
+
+    /// Compress the snapshot, if the server is persistent. It's read back either way.
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.encoding.compression = compression;
+        self
+    }
+
+    /// Encrypt the snapshot with `key`, if the server is persistent.
+    /// An unencrypted snapshot is refused on startup, see [`Server::migrate_unencrypted`].
+    pub fn encryption_key(mut self, key: EncryptionKey) -> Self {
+        self.encoding.key = Some(key);
+        self
+    }
+
+    /// With an encryption key, load an unencrypted snapshot anyway, to encrypt it on the
+    /// next write. Only meant for the first start after a key is set: otherwise, whoever can
+    /// write the snapshot could swap it for one of their own.
+    pub fn migrate_unencrypted(mut self) -> Self {
+        self.encoding.migrate_unencrypted = true;
+        self
+    }
+
+    /// Only accept requests from `users`, within the limits of their role.
+    pub fn authenticated(mut self, users: Users) -> Self {
+        self.users = Some(users);
+        self
+    }
+
+    /// Reject tickets whose fields exceed `limits`, on top of the usual validation.
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.settings.limits = limits;
+        self
+    }
+
+    /// Refuse new connections while `max` clients are connected.
+    pub fn max_connections(mut self, max: usize) -> Self {
+        self.settings.max_connections = Some(max);
+        self
+    }
+
+    /// Reject the requests a connection sends beyond `max` per second.
+    pub fn max_requests_per_second(mut self, max: u32) -> Self {
+        self.settings.max_requests_per_second = Some(max);
+        self
+    }
+
+    /// Apply the limits, the connection cap and the rate limit that `config` sets (or doesn't).
+    /// Its other settings are left to the caller.
+    pub fn settings(mut self, config: &Config) -> Self {
+        self.settings = Settings::from_config(config);
+        self
+    }
+
+    /// Reload the settings that [`Server::settings`] applies from the config file at `path`,
+    /// whenever an admin sends `Request::ReloadConfig` or, on Unix, the process gets a SIGHUP.
+    /// An invalid file is reported, and the current settings stay in place.
+    pub fn reload_from(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_file = Some(path.into());
+        self
+    }
+
+    /// Tell the time with `clock`: to record when tickets are created, to report how many
+    /// were inserted recently and how long changes have gone unsaved, and to rate limit.
+    /// The auto-close job tells the time with it too, whatever clock its `StaleTickets` had:
+    /// it compares against when tickets were last changed, which this clock records.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Report the server as unhealthy, and not ready, once changes have gone unsaved for `lag`.
+    /// Defaults to 30 seconds.
+    pub fn max_persistence_lag(mut self, lag: Duration) -> Self {
+        self.max_persistence_lag = Some(lag);
+        self
+    }
+
+    /// Mark the tickets that have been `InProgress`, unchanged, for a while as `Done`,
+    /// in every project: see `StaleTickets`. Tickets are scanned each time `ticks` yields
+    /// (see [`ticker`]), and what was closed is then sent to `audit`—even if that's nothing.
+    pub fn auto_close(
+        mut self,
+        stale: StaleTickets,
+        ticks: mpsc::Receiver<()>,
+        audit: mpsc::UnboundedSender<Vec<(ProjectName, AuditEntry)>>,
+    ) -> Self {
+        self.auto_close = Some(AutoClose {
+            stale,
+            ticks,
+            audit,
+        });
+        self
+    }
+
+    /// POST a [`WebhookEvent`] to `url` whenever a ticket is created or updated, in any project.
+    /// See [`crate::webhook`] for how deliveries are made, and retried.
+    pub fn webhook(mut self, url: WebhookUrl) -> Self {
+        self.webhooks.push(url);
+        self
+    }
+
+    /// How hard to try delivering each event to the webhooks. See [`Retry`] for the defaults.
+    pub fn webhook_retry(mut self, retry: Retry) -> Self {
+        self.webhook_retry = retry;
+        self
+    }
+
+    /// Report the tickets whose title is at least `threshold` similar to an inserted
+    /// ticket's, in `Response::Inserted`: see `ticket_core::query::title_similarity`.
+    /// The ticket is inserted either way.
+    pub fn warn_on_similar_titles(mut self, threshold: f64) -> Self {
+        self.similarity_threshold = Some(threshold);
+        self
+    }
+
+    /// Never make readers wait for writers, at the cost of copying the whole workspace on
+    /// every write. Worth it when reads vastly outnumber writes.
+    pub fn lock_free_reads(mut self) -> Self {
+        self.lock_free_reads = true;
+        self
+    }
+
+    /// Serve requests on `listener` until an authorized client sends `Request::Shutdown`.
+    pub async fn serve(self, listener: TcpListener) -> std::io::Result<()> {
+        let mut store = match &self.snapshot {
+            Some(path) => Workspace::load_or_default_with(path, &self.encoding)
+                .map_err(|e| std::io::Error::other(render_chain(&e)))?,
+            None => Workspace::new(),
+        };
+        let clock = self.clock.unwrap_or_else(|| Arc::new(SystemClock));
+        store.set_clock(Arc::clone(&clock));
+        let unsaved_since = Arc::new(Mutex::new(None));
+        let persistence = self.snapshot.map(|path| {
+            Persistence::start(
+                path,
+                self.encoding,
+                Arc::clone(&clock),
+                Arc::clone(&unsaved_since),
+            )
+        });
+        let state = Arc::new(State {
+            store: Store::new(store, self.lock_free_reads),
+            persistence,
+            users: self.users,
+            settings: ArcSwap::from_pointee(self.settings),
+            config_file: self.config_file,
+            creations: Mutex::new(RateTracker::last_hour(Arc::clone(&clock))),
+            clock,
+            unsaved_since,
+            max_persistence_lag: self.max_persistence_lag.unwrap_or(MAX_PERSISTENCE_LAG),
+            connections: AtomicUsize::new(0),
+            webhooks: Webhooks::start(self.webhooks, self.webhook_retry),
+            similarity_threshold: self.similarity_threshold,
+        });
+        let (shutdown_sender, mut shutdown_receiver) = watch::channel(false);
+        if let Some(auto_close) = self.auto_close {
+            let state = Arc::clone(&state);
+            tokio::spawn(close_stale_tickets(
+                state,
+                auto_close,
+                shutdown_receiver.clone(),
+            ));
+        }
+        let mut hangups = Hangups::new(state.config_file.is_some())?;
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => {
+                    let (socket, _) = accepted?;
+                    accept(socket, &state, &shutdown_sender);
+                }
+                () = hangups.next() => {
+                    if let Response::Error { message } = reload(&state) {
+                        tracing::warn!(error = %message, "failed to reload the config");
+                    }
+                }
+                _ = shutdown_receiver.changed() => return Ok(()),
+            }
+        }
+    }
+}
+
+/// Serve `socket` on a task of its own, unless there are too many connections already.
+fn accept(socket: TcpStream, state: &Arc<State>, shutdown: &watch::Sender<bool>) {
+    let max = state.settings.load().max_connections;
+    if let Some(max) = max.filter(|&max| state.connections.load(Ordering::Relaxed) >= max) {
+        tracing::warn!(max, "connection refused");
+        tokio::spawn(refuse(
+            socket,
+            format!("Too many connections: the server takes up to {max}"),
+        ));
+        return;
+    }
+    let state = Arc::clone(state);
+    let shutdown = shutdown.clone();
+    state.connections.fetch_add(1, Ordering::Relaxed);
+    tokio::spawn(async move {
+        // A broken connection only affects the client on the other end.
+        let _ = handle_connection(socket, &state, shutdown).await;
+        state.connections.fetch_sub(1, Ordering::Relaxed);
+    });
+}
+
+/// Answer whatever the client sends first with an error, and hang up.
+async fn refuse(mut socket: TcpStream, message: String) -> std::io::Result<()> {
+    let mut encoded = serde_json::to_vec(&Response::Error { message })?;
+    encoded.push(b'\n');
+    socket.write_all(&encoded).await?;
+    socket.shutdown().await?;
+    // Closing the socket with a request still unread would reset the connection, and the
+    // client could lose the error: wait for it to hang up first, within reason.
+    let mut sink = tokio::io::sink();
+    let drain = tokio::io::copy(&mut socket, &mut sink);
+    let _ = tokio::time::timeout(SECOND, drain).await;
+    Ok(())
+}
+
+/// Resolves on every SIGHUP, on Unix, if it's enabled. Otherwise, never.
+struct Hangups {
+    #[cfg(unix)]
+    signal: Option<tokio::signal::unix::Signal>,
+}
+
+impl Hangups {
+    fn new(enabled: bool) -> std::io::Result<Self> {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            let signal = enabled.then(|| signal(SignalKind::hangup())).transpose()?;
+            Ok(Self { signal })
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = enabled;
+            Ok(Self {})
+        }
+    }
+
+    async fn next(&mut self) {
+        #[cfg(unix)]
+        if let Some(signal) = &mut self.signal {
+            if signal.recv().await.is_some() {
+                return;
+            }
+        }
+        std::future::pending().await
+    }
+}
+
+/// Reload the settings from the config file, keeping the current ones if it's invalid.
+fn reload(state: &State) -> Response {
+    let Some(path) = &state.config_file else {
+        return Response::Error {
+            message: "This server wasn't given a config file to reload".into(),
+        };
+    };
+    match Config::load(path) {
+        Ok(config) => {
+            state
+                .settings
+                .store(Arc::new(Settings::from_config(&config)));
+            tracing::info!(path = %path.display(), "config reloaded");
+            Response::ConfigReloaded
+        }
+        Err(e) => Response::Error {
+            message: format!("Kept the current config. {}", render_chain(&e)),
+        },
+    }
+}
+
+/// A tick every `period`, for [`Server::auto_close`]. The first one comes after a full period.
+pub fn ticker(period: Duration) -> mpsc::Receiver<()> {
+    let (sender, receiver) = mpsc::channel(1);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        // An interval's first tick completes immediately.
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if sender.send(()).await.is_err() {
+                return;
+            }
+        }
+    });
+    receiver
+}
+
+/// The task behind [`Server::auto_close`]. It stops with the server, or when ticks run out.
+async fn close_stale_tickets(
+    state: Arc<State>,
+    mut auto_close: AutoClose,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let stale = auto_close.stale.with_clock(Arc::clone(&state.clock));
+    loop {
+        tokio::select! {
+            tick = auto_close.ticks.recv() => if tick.is_none() { return },
+            _ = shutdown.changed() => return,
+        }
+        let (closed, saved) = state.store.write(|store| {
+            let mut closed = Vec::new();
+            let names: Vec<_> = store.projects().cloned().collect();
+            for name in names {
+                let tickets = store
+                    .project_mut(&name)
+                    .expect("The project was just listed");
+                closed.extend(stale.close(tickets).into_iter().map(|e| (name.clone(), e)));
+            }
+            for (name, entry) in &closed {
+                state.announce(store, name, EventKind::Updated, entry.ticket);
+            }
+            let saved = if closed.is_empty() {
+                None
+            } else {
+                state.persist(store)
+            };
+            (closed, saved)
+        });
+        if let Some(saved) = saved {
+            if let Err(message) = saved.wait().await {
+                tracing::warn!(error = %message, "failed to save closed tickets");
+            }
+        }
+        let _ = auto_close.audit.send(closed);
+    }
+}
+
+async fn handle_connection(
+    socket: TcpStream,
+    state: &State,
+    shutdown: watch::Sender<bool>,
+) -> std::io::Result<()> {
+    let (reader, mut writer) = socket.into_split();
+    let mut reader = BufReader::new(reader);
+    let mut session = Session::new(state.users.as_ref());
+    let mut requests =
+        RateTracker::with_clock(SECOND, Duration::from_millis(100), Arc::clone(&state.clock));
+    // Reused for every frame: requests borrow from it instead of allocating their own strings.
+    let mut frame = Vec::new();
+    loop {
+        frame.clear();
+        if reader.read_until(b'\n', &mut frame).await? == 0 {
+            return Ok(());
+        }
+        let line = frame.strip_suffix(b"\n").unwrap_or(&frame);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let response = match decode_request(line) {
+            // The span is only entered while the request is being handled, not while the
+            // task waits for the change to be saved.
+            Ok(envelope) => {
+                let span = request_span(&envelope);
+                respond(envelope, &mut session, &mut requests, state, &shutdown)
+                    .instrument(span)
+                    .await
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "invalid request");
+                Response::Error {
+                    message: format!("Invalid request: {e}"),
+                }
+            }
+        };
+        let mut encoded = serde_json::to_vec(&response)?;
+        encoded.push(b'\n');
+        writer.write_all(&encoded).await?;
+    }
+}
+
+/// The span that every event about `envelope` is recorded in.
+fn request_span(envelope: &EnvelopeRef) -> Span {
+    let ticket_id = match &envelope.request {
+        Request::Get { id } | Request::RenderDescription { id } | Request::Delete { id } => {
+            Some(id.value())
+        }
+        Request::Update { patch } => Some(patch.id.value()),
+        // The id of an inserted ticket is recorded once it's known.
+        _ => None,
+    };
+    tracing::info_span!(
+        "request",
+        command = envelope.request.name(),
+        project = envelope.project.as_deref(),
+        ticket_id,
+    )
+}
+
+/// Answer a single request, and record how long it took.
+async fn respond(
+    envelope: EnvelopeRef<'_>,
+    session: &mut Session,
+    requests: &mut RateTracker,
+    state: &State,
+    shutdown: &watch::Sender<bool>,
+) -> Response {
+    let started = Instant::now();
+    let response = if let Err(message) = rate_limit(state, requests) {
+        Response::Error { message }
+    } else if let Err(error) = session.authorize(&envelope.request) {
+        Response::Denied { error }
+    } else {
+        match envelope.request {
+            Request::Authenticate { token } => authenticate(session, state.users.as_ref(), &token),
+            Request::Shutdown => {
+                let _ = shutdown.send(true);
+                Response::ShuttingDown
+            }
+            // Answered without going through the store, which may be stuck.
+            Request::Health => Response::Health {
+                health: health(state).await,
+            },
+            Request::Ready => {
+                let health = health(state).await;
+                Response::Ready {
+                    ready: health.status != HealthStatus::Unhealthy,
+                    health,
+                }
+            }
+            Request::ReloadConfig => reload(state),
+            _ => handle_request(envelope, state).await,
+        }
+    };
+    if let Response::Inserted { id, .. } = &response {
+        Span::current().record("ticket_id", id.value());
+    }
+    let latency_us = started.elapsed().as_micros() as u64;
+    match &response {
+        Response::Denied { error } => tracing::warn!(latency_us, %error, "request denied"),
+        Response::Error { message } => {
+            tracing::warn!(latency_us, error = %message, "request failed")
+        }
+        _ => tracing::info!(latency_us, "request handled"),
+    }
+    response
+}
+
+/// Count a request against the connection's rate limit.
+/// Returns the error to send back instead if it's over: rejected requests don't count.
+fn rate_limit(state: &State, requests: &mut RateTracker) -> Result<(), String> {
+    if let Some(max) = state.settings.load().max_requests_per_second {
+        if requests.count_last(SECOND) >= u64::from(max) {
+            return Err(format!("Too many requests: the limit is {max} per second"));
+        }
+    }
+    requests.record();
+    Ok(())
+}
+
+fn authenticate(session: &mut Session, users: Option<&Users>, token: &str) -> Response {
+    let Some(users) = users else {
+        return Response::Error {
+            message: "This server doesn't require authentication".into(),
+        };
+    };
+    match users.authenticate(token) {
+        Ok(user) => {
+            *session = Session::Authenticated(user.clone());
+            Response::Authenticated { user: user.clone() }
+        }
+        Err(error) => Response::Denied { error },
+    }
+}
+
+/// Answer a request that goes through the store. Changes are only acknowledged once saved.
+async fn handle_request(envelope: EnvelopeRef<'_>, state: &State) -> Response {
+    let limits = state.settings.load().limits;
+    let within_limits = match &envelope.request {
+        Request::Insert { draft } => limits.check_draft(draft),
+        Request::Update { patch } => limits.check_patch(patch),
+        _ => Ok(()),
+    };
+    if let Err(e) = within_limits {
+        return Response::Error {
+            message: render_chain(&e),
+        };
+    }
+
+    let store = &state.store;
+    let project = envelope.project.as_deref().unwrap_or(ProjectName::DEFAULT);
+    let mut saved = None;
+    let response = match envelope.request {
+        Request::Insert { draft } => store.write(|store| {
+            store.add_ticket(project, draft).map(|id| {
+                state.announce(store, project, EventKind::Created, id.id);
+                let similar = state.similar_to(store, project, id.id);
+                saved = state.persist(store);
+                Response::Inserted { id: id.id, similar }
+            })
+        }),
+        Request::InsertFromTemplate {
+            template,
+            overrides,
+        } => store.write(|store| {
+            let tickets = store.project_mut(project)?;
+            Ok(
+                match insert_from_template(tickets, &template, &overrides, &limits) {
+                    Ok(id) => {
+                        state.announce(store, project, EventKind::Created, id);
+                        let similar = state.similar_to(store, project, id);
+                        saved = state.persist(store);
+                        Response::Inserted { id, similar }
+                    }
+                    Err(message) => Response::Error { message },
+                },
+            )
+        }),
+        Request::Get { id } => store.read(|store| {
+            store.project(project).map(|tickets| Response::Ticket {
+                ticket: tickets.get(id).cloned(),
+            })
+        }),
+        Request::RenderDescription { id } => store.read(|store| {
+            store
+                .project(project)
+                .map(|tickets| Response::RenderedDescription {
+                    html: tickets.get(id).map(|t| t.description.render_html()),
+                })
+        }),
+        Request::Update { patch } => store.write(|store| {
+            let id = patch.id;
+            store.update(project, patch).map(|()| {
+                state.announce(store, project, EventKind::Updated, id);
+                saved = state.persist(store);
+                Response::Updated
+            })
+        }),
+        Request::Delete { id } => store.write(|store| {
+            store
+                .project_mut(project)
+                .map(|tickets| tickets.delete(id))
+                .map(|deleted| match deleted {
+                    Some(ticket) => {
+                        saved = state.persist(store);
+                        Response::Deleted {
+                            ticket: Some(ticket),
+                        }
+                    }
+                    None => Response::Deleted { ticket: None },
+                })
+        }),
+        Request::List => store.read(|store| {
+            store.project(project).map(|tickets| Response::Tickets {
+                tickets: tickets.iter().cloned().collect(),
+            })
+        }),
+        Request::RegisterTemplate { template } => store.write(|store| {
+            store.project_mut(project)?.register_template(template);
+            saved = state.persist(store);
+            Ok(Response::TemplateRegistered)
+        }),
+        Request::ListTemplates => store.read(|store| {
+            store.project(project).map(|tickets| Response::Templates {
+                templates: tickets.templates().cloned().collect(),
+            })
+        }),
+        Request::CreateProject { name } => store.write(|store| {
+            store.create_project(name).map(|()| {
+                saved = state.persist(store);
+                Response::ProjectCreated
+            })
+        }),
+        Request::ListProjects => Ok(Response::Projects {
+            projects: store.read(|store| store.projects().cloned().collect()),
+        }),
+        Request::Metrics => Ok(Response::Metrics {
+            metrics: metrics(store, &state.creations.lock().unwrap()),
+        }),
+        Request::Authenticate { .. }
+        | Request::Shutdown
+        | Request::Health
+        | Request::Ready
+        | Request::ReloadConfig => {
+            unreachable!("Handled by `respond`")
+        }
+    };
+    let mut response = response.unwrap_or_else(|e| Response::Error {
+        message: e.to_string(),
+    });
+    if let Some(saved) = saved {
+        if let Err(message) = saved.wait().await {
+            response = Response::Error { message };
+        }
+    }
+    if let Response::Inserted { .. } = response {
+        state.creations.lock().unwrap().record();
+    }
+    response
+}
+
+/// Like `TicketStore::add_from_template`, except that the ticket has to be within `limits` too.
+fn insert_from_template(
+    tickets: &mut TicketStore,
+    name: &str,
+    overrides: &TemplateOverrides,
+    limits: &Limits,
+) -> Result<TicketId, String> {
+    let template = tickets
+        .template(name)
+        .ok_or_else(|| TemplateError::UnknownTemplate(name.to_owned()).to_string())?;
+    let draft = TicketDraft::from_template(template, overrides).map_err(|e| render_chain(&e))?;
+    limits.check_draft(&draft).map_err(|e| render_chain(&e))?;
+    tickets
+        .add_from_template(name, overrides)
+        .map_err(|e| render_chain(&e))
+}
+
+fn metrics(store: &Store, creations: &RateTracker) -> Metrics {
+    let (projects, tickets) = store.read(|store| {
+        let tickets = store
+            .projects()
+            .filter_map(|name| store.project(name).ok())
+            .map(|tickets| tickets.len())
+            .sum();
+        (store.projects().count(), tickets)
+    });
+    Metrics {
+        projects,
+        tickets,
+        created_last_minute: creations.count_last(Duration::from_secs(60)),
+        created_last_hour: creations.count_last(Duration::from_secs(60 * 60)),
+    }
+}
+
+impl State {
+    /// The other tickets of `project` with a title similar to ticket `id`'s, if the server
+    /// looks for them.
+    fn similar_to(&self, store: &Workspace, project: &str, id: TicketId) -> Vec<SimilarTicket> {
+        let Some(threshold) = self.similarity_threshold else {
+            return Vec::new();
+        };
+        let Ok(tickets) = store.project(project) else {
+            return Vec::new();
+        };
+        let Some(ticket) = tickets.get(id) else {
+            return Vec::new();
+        };
+        tickets
+            .find_similar(ticket.title.as_ref(), threshold)
+            .into_iter()
+            .filter(|&(other, _)| other != id)
+            .map(|(id, similarity)| SimilarTicket { id, similarity })
+            .collect()
+    }
+
+    /// Tell the webhooks about a ticket that was just created or updated in `project`.
+    ///
+    /// Like [`State::persist`], it's called under the write lock: events are queued in the
+    /// same order as the changes they describe.
+    fn announce(
+        &self,
+        store: &Workspace,
+        project: &(impl AsRef<str> + ?Sized),
+        event: EventKind,
+        id: TicketId,
+    ) {
+        if self.webhooks.is_empty() {
+            return;
+        }
+        let project = project.as_ref();
+        let Some(ticket) = store.project(project).ok().and_then(|t| t.get(id)) else {
+            return;
+        };
+        self.webhooks.notify(&WebhookEvent {
+            event,
+            project: ProjectName::try_from(project.to_owned())
+                .expect("It's the name of an existing project"),
+            ticket: ticket.clone(),
+        });
+    }
+
+    /// Queue the workspace to be saved, if the server is persistent. The change mustn't be
+    /// acknowledged before the returned [`Saved`] resolves.
+    ///
+    /// The caller still holds the write lock (or the writer's turn): versions are queued in
+    /// the same order as the changes they capture. Only copying the workspace happens under
+    /// it, the saving itself happens on the persistence task.
+    fn persist(&self, store: &Workspace) -> Option<Saved> {
+        self.persistence.as_ref().map(|p| p.save(store))
+    }
+}
+
+async fn health(state: &State) -> Health {
+    let store_accessible = state.store.is_accessible(STORE_PATIENCE).await;
+    let persistence_lag = state
+        .unsaved_since
+        .lock()
+        .unwrap()
+        .map(|since| state.clock.now().saturating_duration_since(since));
+    let status = match persistence_lag {
+        _ if !store_accessible => HealthStatus::Unhealthy,
+        Some(lag) if lag >= state.max_persistence_lag => HealthStatus::Unhealthy,
+        Some(_) => HealthStatus::Degraded,
+        None => HealthStatus::Healthy,
+    };
+    Health {
+        status,
+        store_accessible,
+        persistence_lag_ms: persistence_lag.map(|lag| lag.as_millis() as u64),
+        connections: state.connections.load(Ordering::Relaxed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(lock_free_reads: bool) -> State {
+        State {
+            store: Store::new(Workspace::new(), lock_free_reads),
+            persistence: None,
+            users: None,
+            settings: ArcSwap::from_pointee(Settings::default()),
+            config_file: None,
+            clock: Arc::new(SystemClock),
+            creations: Mutex::new(RateTracker::default()),
+            unsaved_since: Arc::default(),
+            max_persistence_lag: MAX_PERSISTENCE_LAG,
+            connections: AtomicUsize::new(0),
+            webhooks: Webhooks::default(),
+            similarity_threshold: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn a_wedged_writer_makes_the_server_unhealthy() {
+        for lock_free_reads in [false, true] {
+            let state = Arc::new(state(lock_free_reads));
+            assert_eq!(health(&state).await.status, HealthStatus::Healthy);
+
+            // As if saving a snapshot hung, while holding the workspace.
+            let (wedged_sender, wedged) = std::sync::mpsc::channel();
+            let (release, released) = std::sync::mpsc::channel::<()>();
+            let writer = std::thread::spawn({
+                let state = Arc::clone(&state);
+                move || {
+                    state.store.write(|_| {
+                        wedged_sender.send(()).unwrap();
+                        released.recv().unwrap();
+                    })
+                }
+            });
+            wedged.recv().unwrap();
+            let wedged_health = health(&state).await;
+            assert!(!wedged_health.store_accessible);
+            assert_eq!(wedged_health.status, HealthStatus::Unhealthy);
+
+            release.send(()).unwrap();
+            writer.join().unwrap();
+            assert_eq!(health(&state).await.status, HealthStatus::Healthy);
+        }
+    }
+}
@@ -0,0 +1,248 @@
+//! Webhooks: HTTP endpoints that hear about every ticket the server creates or updates.
+//!
+//! Each webhook gets a JSON `POST` per [`WebhookEvent`], in the order the changes were made.
+//! Deliveries go out from a background task per webhook, so a slow or broken endpoint never
+//! holds requests up: a failed delivery is retried with exponential backoff, up to
+//! [`Retry::max_attempts`] times, and then dropped.
+//!
+//! Only plain `http://` URLs are supported: webhooks are meant for services running next to
+//! the server, not across the internet.
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+
+use ticket_core::{ProjectName, Ticket};
+
+/// How long an endpoint has to answer a delivery, before it counts as failed.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The body of every delivery, e.g.
+/// `{"event":"created","project":"default","ticket":{"id":0,...}}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    pub event: EventKind,
+    pub project: ProjectName,
+    /// The ticket as of the change.
+    pub ticket: Ticket,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Created,
+    /// Including the tickets that `Server::auto_close` closes.
+    Updated,
+}
+
+/// Where to deliver events: an `http://host[:port][/path]` URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookUrl {
+    host: String,
+    port: u16,
+    path: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WebhookUrlError {
+    #[error("`{0}` is not an `http://` URL")]
+    NotHttp(String),
+    #[error("`{0}` has no host")]
+    NoHost(String),
+    #[error("`{0}` has an invalid port")]
+    InvalidPort(String),
+}
+
+impl FromStr for WebhookUrl {
+    type Err = WebhookUrlError;
+
+    fn from_str(url: &str) -> Result<Self, Self::Err> {
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or_else(|| WebhookUrlError::NotHttp(url.into()))?;
+        let (authority, path) = match rest.find('/') {
+            Some(slash) => rest.split_at(slash),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => {
+                let port = port
+                    .parse()
+                    .map_err(|_| WebhookUrlError::InvalidPort(url.into()))?;
+                (host, port)
+            }
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(WebhookUrlError::NoHost(url.into()));
+        }
+        Ok(Self {
+            host: host.into(),
+            port,
+            path: path.into(),
+        })
+    }
+}
+
+impl fmt::Display for WebhookUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "http://{}:{}{}", self.host, self.port, self.path)
+    }
+}
+
+/// How hard to try delivering each event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retry {
+    /// Including the first one. Defaults to 5.
+    pub max_attempts: u32,
+    /// How long to wait after the first failure. Each failure doubles it. Defaults to 100ms.
+    pub initial_backoff: Duration,
+    /// The longest the wait can get. Defaults to 10 seconds.
+    pub max_backoff: Duration,
+}
+
+impl Default for Retry {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+/// The queues of the delivery tasks, one per webhook.
+/// The tasks stop once they're dropped, after delivering what's left in them.
+#[derive(Debug, Default)]
+pub(crate) struct Webhooks {
+    queues: Vec<mpsc::UnboundedSender<Arc<[u8]>>>,
+}
+
+impl Webhooks {
+    /// Spawn a delivery task for each of `urls`.
+    pub(crate) fn start(urls: Vec<WebhookUrl>, retry: Retry) -> Self {
+        let queues = urls
+            .into_iter()
+            .map(|url| {
+                let (sender, receiver) = mpsc::unbounded_channel();
+                tokio::spawn(deliver_all(url, retry, receiver));
+                sender
+            })
+            .collect();
+        Self { queues }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.queues.is_empty()
+    }
+
+    /// Queue `event` for every webhook. This never blocks.
+    pub(crate) fn notify(&self, event: &WebhookEvent) {
+        let body: Arc<[u8]> = serde_json::to_vec(event)
+            .expect("Events are always serializable")
+            .into();
+        for queue in &self.queues {
+            // The task only stops once its queue is dropped.
+            let _ = queue.send(Arc::clone(&body));
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+enum DeliveryError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("the endpoint answered with status {0}")]
+    Status(u16),
+    #[error("the endpoint didn't answer with an HTTP status line")]
+    Malformed,
+    #[error("the endpoint didn't answer within {DELIVERY_TIMEOUT:?}")]
+    TimedOut,
+}
+
+/// The task behind each webhook: deliver events one at a time, in order.
+async fn deliver_all(url: WebhookUrl, retry: Retry, mut queue: mpsc::UnboundedReceiver<Arc<[u8]>>) {
+    while let Some(body) = queue.recv().await {
+        deliver(&url, &body, retry).await;
+    }
+}
+
+async fn deliver(url: &WebhookUrl, body: &[u8], retry: Retry) {
+    let mut backoff = retry.initial_backoff;
+    for attempt in 1..=retry.max_attempts {
+        let error = match tokio::time::timeout(DELIVERY_TIMEOUT, post(url, body)).await {
+            Ok(Ok(())) => return,
+            Ok(Err(e)) => e,
+            Err(_) => DeliveryError::TimedOut,
+        };
+        tracing::warn!(%url, attempt, %error, "webhook delivery failed");
+        if attempt < retry.max_attempts {
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(retry.max_backoff);
+        }
+    }
+    tracing::error!(%url, attempts = retry.max_attempts, "webhook event dropped");
+}
+
+/// Send `body` to `url`, over a connection of its own. Any 2xx status counts as delivered.
+async fn post(url: &WebhookUrl, body: &[u8]) -> Result<(), DeliveryError> {
+    let mut socket = TcpStream::connect((url.host.as_str(), url.port)).await?;
+    let head = format!(
+        "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n",
+        url.path,
+        url.host,
+        url.port,
+        body.len()
+    );
+    socket.write_all(head.as_bytes()).await?;
+    socket.write_all(body).await?;
+    // The status line is all we need: e.g. `HTTP/1.1 204 No Content`.
+    let mut status_line = String::new();
+    BufReader::new(socket).read_line(&mut status_line).await?;
+    let status: u16 = status_line
+        .strip_prefix("HTTP/1.")
+        .and_then(|rest| rest.split(' ').nth(1))
+        .and_then(|status| status.parse().ok())
+        .ok_or(DeliveryError::Malformed)?;
+    if (200..300).contains(&status) {
+        Ok(())
+    } else {
+        Err(DeliveryError::Status(status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_urls() {
+        let url: WebhookUrl = "http://localhost:8080/hooks/tickets".parse().unwrap();
+        assert_eq!(url.host, "localhost");
+        assert_eq!(url.port, 8080);
+        assert_eq!(url.path, "/hooks/tickets");
+        assert_eq!(url.to_string(), "http://localhost:8080/hooks/tickets");
+
+        let url: WebhookUrl = "http://10.0.0.1".parse().unwrap();
+        assert_eq!(url.to_string(), "http://10.0.0.1:80/");
+
+        assert!(matches!(
+            "https://example.com".parse::<WebhookUrl>(),
+            Err(WebhookUrlError::NotHttp(_))
+        ));
+        assert!(matches!(
+            "http://:80/".parse::<WebhookUrl>(),
+            Err(WebhookUrlError::NoHost(_))
+        ));
+        assert!(matches!(
+            "http://localhost:http/".parse::<WebhookUrl>(),
+            Err(WebhookUrlError::InvalidPort(_))
+        ));
+    }
+}
@@ -0,0 +1,133 @@
+use outro_08::{AuthError, Client, ClientError, Role, Server, User, Users};
+use test_support::{spawn_test_server_with, ServerGuard};
+use ticket_core::{ProjectName, Status, TicketPatch};
+use ticket_fields::test_helpers::ticket_draft;
+
+fn users() -> Users {
+    let mut users = Users::new();
+    for (token, name, role) in [
+        ("reader-token", "rita", Role::Reader),
+        ("writer-token", "will", Role::Writer),
+        ("admin-token", "ada", Role::Admin),
+    ] {
+        users.insert(
+            token,
+            User {
+                name: name.into(),
+                role,
+            },
+        );
+    }
+    users
+}
+
+/// The client isn't authenticated.
+async fn start() -> (Client, ServerGuard) {
+    spawn_test_server_with(Server::new().authenticated(users())).await
+}
+
+async fn login(server: &ServerGuard, token: &str) -> Client {
+    let mut client = server.client().await;
+    client.authenticate(token).await.unwrap();
+    client
+}
+
+fn assert_forbidden(result: Result<impl std::fmt::Debug, ClientError>, required: Role) {
+    match result {
+        Err(ClientError::Denied(AuthError::Forbidden { required: r, .. })) => {
+            assert_eq!(r, required)
+        }
+        other => panic!("Expected a denial requiring {required}, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn requests_before_authenticating_are_denied() {
+    let (mut client, _server) = start().await;
+    assert!(matches!(
+        client.list().await,
+        Err(ClientError::Denied(AuthError::Unauthenticated))
+    ));
+    assert!(matches!(
+        client.authenticate("made-up").await,
+        Err(ClientError::Denied(AuthError::InvalidToken))
+    ));
+    // A failed attempt doesn't lock the connection out.
+    let user = client.authenticate("reader-token").await.unwrap();
+    assert_eq!(user.name, "rita");
+    assert!(client.list().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn readers_cannot_mutate() {
+    let (_, server) = start().await;
+    let mut writer = login(&server, "writer-token").await;
+    let id = writer.insert(ticket_draft()).await.unwrap();
+
+    let mut reader = login(&server, "reader-token").await;
+    assert!(reader.get(id).await.unwrap().is_some());
+    assert_eq!(reader.list().await.unwrap().len(), 1);
+    assert!(reader.projects().await.is_ok());
+    assert_forbidden(reader.insert(ticket_draft()).await, Role::Writer);
+    assert_forbidden(
+        reader
+            .update(TicketPatch {
+                status: Some(Status::Done),
+                ..TicketPatch::new(id)
+            })
+            .await,
+        Role::Writer,
+    );
+    assert_forbidden(reader.delete(id).await, Role::Writer);
+
+    // Nothing changed.
+    assert_eq!(reader.get(id).await.unwrap().unwrap().status, Status::ToDo);
+}
+
+#[tokio::test]
+async fn writers_cannot_administer() {
+    let (_, server) = start().await;
+    let mut writer = login(&server, "writer-token").await;
+    let id = writer.insert(ticket_draft()).await.unwrap();
+    writer
+        .update(TicketPatch {
+            status: Some(Status::Done),
+            ..TicketPatch::new(id)
+        })
+        .await
+        .unwrap();
+    assert!(writer.delete(id).await.unwrap().is_some());
+
+    let backend = ProjectName::try_from("backend").unwrap();
+    assert_forbidden(writer.create_project(backend).await, Role::Admin);
+    assert_forbidden(writer.shutdown().await, Role::Admin);
+    // The server is still up.
+    assert!(writer.list().await.is_ok());
+}
+
+#[tokio::test]
+async fn admins_can_do_everything() {
+    let (_, server) = start().await;
+    let mut admin = login(&server, "admin-token").await;
+    admin
+        .create_project(ProjectName::try_from("backend").unwrap())
+        .await
+        .unwrap();
+    admin.insert(ticket_draft()).await.unwrap();
+    admin.shutdown().await.unwrap();
+    server.stopped().await.unwrap();
+}
+
+#[tokio::test]
+async fn health_probes_do_not_need_authenticating() {
+    let (mut probe, _server) = start().await;
+    assert_eq!(
+        probe.health().await.unwrap().status,
+        outro_08::protocol::HealthStatus::Healthy
+    );
+    assert!(probe.ready().await.unwrap().0);
+    assert!(matches!(
+        probe.metrics().await,
+        Err(ClientError::Denied(AuthError::Unauthenticated))
+    ));
+}
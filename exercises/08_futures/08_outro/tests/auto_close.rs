@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use outro_08::Server;
+use test_support::{spawn_test_server_with, within_async, TIMEOUT};
+use ticket_core::{AuditEntry, ProjectName, StaleTickets, Status, TestClock, TicketPatch};
+use ticket_fields::test_helpers::ticket_draft;
+use tokio::sync::mpsc;
+
+const HOUR: Duration = Duration::from_secs(60 * 60);
+
+fn in_progress(id: ticket_core::TicketId) -> TicketPatch {
+    TicketPatch {
+        status: Some(Status::InProgress),
+        ..TicketPatch::new(id)
+    }
+}
+
+#[tokio::test]
+async fn stale_tickets_are_closed_in_every_project() {
+    let clock = TestClock::new();
+    let (tick, ticks) = mpsc::channel(1);
+    let (audit, mut audit_log) = mpsc::unbounded_channel();
+    let stale = StaleTickets::new(HOUR);
+    let (mut client, server) = spawn_test_server_with(
+        Server::new()
+            .clock(clock.clone())
+            .auto_close(stale, ticks, audit),
+    )
+    .await;
+
+    let backend = ProjectName::try_from("backend").unwrap();
+    client.create_project(backend.clone()).await.unwrap();
+    let mut ids = Vec::new();
+    for project in [ProjectName::default_project(), backend.clone()] {
+        client.use_project(project);
+        let id = client.insert(ticket_draft()).await.unwrap();
+        client.update(in_progress(id)).await.unwrap();
+        ids.push(id);
+    }
+    // Both projects number their tickets from 0.
+    assert_eq!(ids[0], ids[1]);
+
+    tick.send(()).await.unwrap();
+    assert!(within_async(TIMEOUT, audit_log.recv())
+        .await
+        .unwrap()
+        .is_empty());
+    clock.advance(HOUR / 2);
+    client
+        .update(TicketPatch {
+            assignee: Some(Some("Alice".into())),
+            ..TicketPatch::new(ids[1])
+        })
+        .await
+        .unwrap();
+    tick.send(()).await.unwrap();
+    assert!(within_async(TIMEOUT, audit_log.recv())
+        .await
+        .unwrap()
+        .is_empty());
+
+    clock.advance(HOUR / 2);
+    tick.send(()).await.unwrap();
+    let closed = within_async(TIMEOUT, audit_log.recv()).await.unwrap();
+    let entry = AuditEntry {
+        ticket: ids[0],
+        idle_for: HOUR,
+    };
+    assert_eq!(closed, [(ProjectName::default_project(), entry)]);
+
+    assert_eq!(
+        client.get(ids[1]).await.unwrap().unwrap().status,
+        Status::InProgress
+    );
+    client.use_project(ProjectName::default_project());
+    assert_eq!(
+        client.get(ids[0]).await.unwrap().unwrap().status,
+        Status::Done
+    );
+
+    server.shutdown().await;
+    // The task stops with the server.
+    assert!(within_async(TIMEOUT, audit_log.recv()).await.is_none());
+}
@@ -0,0 +1,49 @@
+use outro_08::{Client, Server};
+use test_support::spawn_test_server_with;
+use ticket_core::{Status, TicketPatch};
+use ticket_fields::test_helpers::ticket_draft;
+
+const TICKETS: usize = 200;
+const READERS: usize = 4;
+
+// Readers hammer the server while a writer inserts tickets and then closes them, in order.
+// Every version of the workspace they load must be one the writer actually published.
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn readers_see_every_write_in_order() {
+    let (mut writer, server) = spawn_test_server_with(Server::new().lock_free_reads()).await;
+    let addr = server.addr();
+
+    let readers: Vec<_> = (0..READERS)
+        .map(|_| {
+            tokio::spawn(async move {
+                let mut client = Client::connect(addr).await.unwrap();
+                let (mut inserted, mut done) = (0, 0);
+                while done < TICKETS {
+                    let tickets = client.list().await.unwrap();
+                    let now_done = tickets.iter().filter(|t| t.status == Status::Done).count();
+                    // Nothing is ever deleted, and tickets are closed in the order they were added.
+                    assert!(tickets.len() >= inserted && now_done >= done);
+                    assert!(tickets[..now_done].iter().all(|t| t.status == Status::Done));
+                    (inserted, done) = (tickets.len(), now_done);
+                }
+            })
+        })
+        .collect();
+
+    let mut ids = Vec::with_capacity(TICKETS);
+    for _ in 0..TICKETS {
+        ids.push(writer.insert(ticket_draft()).await.unwrap());
+    }
+    for id in ids {
+        let patch = TicketPatch {
+            status: Some(Status::Done),
+            ..TicketPatch::new(id)
+        };
+        writer.update(patch).await.unwrap();
+    }
+
+    for reader in readers {
+        reader.await.unwrap();
+    }
+    server.shutdown().await;
+}
@@ -0,0 +1,329 @@
+use outro_08::protocol::{Request, Response};
+use outro_08::{ClientError, Server};
+use std::time::Duration;
+use test_support::{
+    assert_ticket_eq_ignoring_timestamps, spawn_test_server, spawn_test_server_with,
+};
+use ticket_core::{
+    Clock, CustomFields, ProjectName, Status, TemplateOverrides, TestClock, TicketDraft,
+    TicketPatch, TicketStore, TicketTemplate,
+};
+use ticket_fields::test_helpers::{ticket_description, ticket_draft, ticket_title};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+#[tokio::test]
+async fn create_retrieve_and_patch() {
+    let (mut client, server) = spawn_test_server().await;
+
+    let id = client.insert(ticket_draft()).await.unwrap();
+    let ticket = client.get(id).await.unwrap().unwrap();
+    // The same ticket as a local store's, but for when it was created.
+    let mut local = TicketStore::new();
+    let local_id = local.add_ticket(ticket_draft());
+    assert_ticket_eq_ignoring_timestamps(&ticket, &local[local_id]);
+
+    client
+        .update(TicketPatch {
+            status: Some(Status::InProgress),
+            ..TicketPatch::new(id)
+        })
+        .await
+        .unwrap();
+    let ticket = client.get(id).await.unwrap().unwrap();
+    assert_eq!(ticket.status, Status::InProgress);
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn connections_share_the_store() {
+    let (mut first, server) = spawn_test_server().await;
+    let mut second = server.client().await;
+
+    let id = first.insert(ticket_draft()).await.unwrap();
+    assert!(second.get(id).await.unwrap().is_some());
+    assert_eq!(second.list().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn delete_removes_the_ticket() {
+    let (mut client, _server) = spawn_test_server().await;
+
+    let id = client.insert(ticket_draft()).await.unwrap();
+    let deleted = client.delete(id).await.unwrap().unwrap();
+    assert_eq!(deleted.id, id);
+    assert!(client.get(id).await.unwrap().is_none());
+    assert!(client.delete(id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn errors_are_reported_to_the_client() {
+    let (mut client, _server) = spawn_test_server().await;
+
+    let missing = TicketPatch::new(99.into());
+    let err = client.update(missing).await.unwrap_err();
+    assert!(
+        matches!(err, ClientError::Server(message) if message == "There is no ticket with id 99")
+    );
+
+    // The connection is still usable after an error.
+    let response = client.call(&Request::List).await.unwrap();
+    assert_eq!(response, Response::Tickets { tickets: vec![] });
+}
+
+#[tokio::test]
+async fn malformed_requests_are_rejected() {
+    let (_client, server) = spawn_test_server().await;
+    let (reader, mut writer) = TcpStream::connect(server.addr())
+        .await
+        .unwrap()
+        .into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    writer.write_all(b"not json\n").await.unwrap();
+    let line = lines.next_line().await.unwrap().unwrap();
+    let response: Response = serde_json::from_str(&line).unwrap();
+    assert!(
+        matches!(response, Response::Error { message } if message.starts_with("Invalid request"))
+    );
+}
+
+#[tokio::test]
+async fn projects_are_isolated() {
+    let (mut default, server) = spawn_test_server().await;
+    let backend = ProjectName::try_from("backend").unwrap();
+    let mut scoped = server.client().await;
+    scoped.create_project(backend.clone()).await.unwrap();
+    scoped.use_project(backend.clone());
+
+    // Each project has its own id sequence: both tickets get the same id...
+    let in_default = default.insert(ticket_draft()).await.unwrap();
+    let in_backend = scoped.insert(ticket_draft()).await.unwrap();
+    assert_eq!(in_default, in_backend);
+
+    // ...but they are different tickets, and changes don't cross over.
+    scoped
+        .update(TicketPatch {
+            status: Some(Status::Done),
+            ..TicketPatch::new(in_backend)
+        })
+        .await
+        .unwrap();
+    let ticket = default.get(in_default).await.unwrap().unwrap();
+    assert_eq!(ticket.status, Status::ToDo);
+    scoped.delete(in_backend).await.unwrap();
+    assert_eq!(default.list().await.unwrap().len(), 1);
+    assert!(scoped.list().await.unwrap().is_empty());
+
+    let names: Vec<_> = default.projects().await.unwrap();
+    assert_eq!(names, [backend, ProjectName::default_project()]);
+}
+
+#[tokio::test]
+async fn unknown_projects_are_rejected() {
+    let (mut client, _server) = spawn_test_server().await;
+    client.use_project(ProjectName::try_from("mobile").unwrap());
+    let err = client.insert(ticket_draft()).await.unwrap_err();
+    assert!(
+        matches!(&err, ClientError::Server(message) if message.contains("no project named `mobile`")),
+        "{err}"
+    );
+
+    let err = client
+        .create_project(ProjectName::default_project())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ClientError::Server(_)));
+}
+
+#[tokio::test]
+async fn descriptions_are_rendered_to_html() {
+    let (mut client, _server) = spawn_test_server().await;
+    let id = client
+        .insert(TicketDraft {
+            title: ticket_title(),
+            description: "Fix the **login** page:\n- <b>escape</b> input\n- add `tests`"
+                .try_into()
+                .unwrap(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        client.render_description(id).await.unwrap().unwrap(),
+        "<p>Fix the <strong>login</strong> page:</p>\n\
+         <ul>\n<li>&lt;b&gt;escape&lt;/b&gt; input</li>\n<li>add <code>tests</code></li>\n</ul>\n"
+    );
+    assert_eq!(client.render_description(99.into()).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn configured_limits_are_enforced() {
+    let limits = ticket_core::config::Limits {
+        max_title_len: 5,
+        ..Default::default()
+    };
+    let (mut client, _server) = spawn_test_server_with(Server::new().limits(limits)).await;
+
+    let err = client.insert(ticket_draft()).await.unwrap_err();
+    assert!(
+        matches!(&err, ClientError::Server(message)
+            if message == "Invalid ticket title: The title cannot be longer than 5 bytes"),
+        "{err}"
+    );
+    let id = client
+        .insert(TicketDraft {
+            title: "Short".try_into().unwrap(),
+            description: ticket_description(),
+        })
+        .await
+        .unwrap();
+    let rename = TicketPatch {
+        title: Some("Longer".try_into().unwrap()),
+        ..TicketPatch::new(id)
+    };
+    assert!(client.update(rename).await.is_err());
+}
+
+#[tokio::test]
+async fn tickets_from_templates() {
+    let limits = ticket_core::config::Limits {
+        max_title_len: 20,
+        ..Default::default()
+    };
+    let (mut client, _server) = spawn_test_server_with(Server::new().limits(limits)).await;
+    let bug = TicketTemplate {
+        name: "bug".into(),
+        title_prefix: "[Bug] ".into(),
+        description: "Steps to reproduce:".try_into().unwrap(),
+        tags: vec!["bug".into()],
+        custom_fields: CustomFields::new(),
+    };
+    client.register_template(bug.clone()).await.unwrap();
+    assert_eq!(client.templates().await.unwrap(), [bug]);
+
+    let overrides = |title: &str| TemplateOverrides {
+        title: Some(title.into()),
+        ..TemplateOverrides::default()
+    };
+    let id = client
+        .insert_from_template("bug", overrides("Crash"))
+        .await
+        .unwrap();
+    let ticket = client.get(id).await.unwrap().unwrap();
+    assert_eq!(ticket.title.as_ref(), "[Bug] Crash");
+    assert_eq!(ticket.description.as_ref(), "Steps to reproduce:");
+
+    // The prefix counts towards the limits.
+    let err = client
+        .insert_from_template("bug", overrides("Crash on startup"))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("longer than 20 bytes"), "{err}");
+    let err = client
+        .insert_from_template("feature", overrides("Dark mode"))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, ClientError::Server(message)
+            if message == "There is no template named `feature`"),
+        "{err}"
+    );
+    // Templates belong to a project.
+    let backend = ProjectName::try_from("backend").unwrap();
+    client.create_project(backend.clone()).await.unwrap();
+    client.use_project(backend);
+    assert!(client.templates().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn insertions_report_similar_titles() {
+    let (mut client, _server) =
+        spawn_test_server_with(Server::new().warn_on_similar_titles(0.8)).await;
+    let titled = |title: &str| TicketDraft::new(title.into(), "A description".into()).unwrap();
+
+    let (first, similar) = client
+        .insert_reporting_similar(titled("Crash on startup"))
+        .await
+        .unwrap();
+    // The ticket isn't similar to itself.
+    assert!(similar.is_empty());
+    let (_, similar) = client
+        .insert_reporting_similar(titled("Dark mode"))
+        .await
+        .unwrap();
+    assert!(similar.is_empty());
+
+    let (_, similar) = client
+        .insert_reporting_similar(titled("crash on startup"))
+        .await
+        .unwrap();
+    assert_eq!(similar.len(), 1);
+    assert_eq!(similar[0].id, first);
+    assert_eq!(similar[0].similarity, 1.0);
+    let (_, similar) = client
+        .insert_reporting_similar(titled("Crashes on startup"))
+        .await
+        .unwrap();
+    assert_eq!(similar.len(), 2);
+    // Near duplicates are inserted all the same.
+    assert_eq!(client.list().await.unwrap().len(), 4);
+
+    // Servers that don't look for them report none.
+    let (mut client, _server) = spawn_test_server().await;
+    client.insert(titled("Crash on startup")).await.unwrap();
+    let (_, similar) = client
+        .insert_reporting_similar(titled("Crash on startup"))
+        .await
+        .unwrap();
+    assert!(similar.is_empty());
+}
+
+#[tokio::test]
+async fn metrics_count_recent_insertions() {
+    let clock = TestClock::new();
+    let (mut client, server) = spawn_test_server_with(Server::new().clock(clock.clone())).await;
+
+    client.insert(ticket_draft()).await.unwrap();
+    clock.advance(Duration::from_secs(30 * 60));
+    let backend = ProjectName::try_from("backend").unwrap();
+    client.create_project(backend.clone()).await.unwrap();
+    client.use_project(backend);
+    client.insert(ticket_draft()).await.unwrap();
+    client.insert(ticket_draft()).await.unwrap();
+    // Failed insertions don't count.
+    let mut lost = server.client().await;
+    lost.use_project(ProjectName::try_from("missing").unwrap());
+    assert!(lost.insert(ticket_draft()).await.is_err());
+
+    let metrics = client.metrics().await.unwrap();
+    assert_eq!((metrics.projects, metrics.tickets), (2, 3));
+    assert_eq!(metrics.created_last_minute, 2);
+    assert_eq!(metrics.created_last_hour, 3);
+
+    clock.advance(Duration::from_secs(31 * 60));
+    let metrics = client.metrics().await.unwrap();
+    assert_eq!(metrics.created_last_minute, 0);
+    assert_eq!(metrics.created_last_hour, 2);
+    assert_eq!(metrics.tickets, 3);
+}
+
+#[tokio::test]
+async fn tickets_are_timestamped_by_the_server_clock() {
+    let clock = TestClock::new();
+    let (mut client, _server) = spawn_test_server_with(Server::new().clock(clock.clone())).await;
+
+    let start = clock.now();
+    let first = client.insert(ticket_draft()).await.unwrap();
+    clock.advance(Duration::from_secs(90));
+    let backend = ProjectName::try_from("backend").unwrap();
+    client.create_project(backend.clone()).await.unwrap();
+    client.use_project(backend);
+    let second = client.insert(ticket_draft()).await.unwrap();
+
+    let created_at = client.get(second).await.unwrap().unwrap().created_at;
+    assert_eq!(created_at, Some(start + Duration::from_secs(90)));
+    client.use_project(ProjectName::default_project());
+    let created_at = client.get(first).await.unwrap().unwrap().created_at;
+    assert_eq!(created_at, Some(start));
+}
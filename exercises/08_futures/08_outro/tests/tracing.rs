@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+use test_support::spawn_test_server;
+use ticket_core::{TicketDraft, TicketPatch};
+use ticket_fields::test_helpers::{ticket_description, ticket_title};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Record};
+use tracing::{Event, Id, Subscriber};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+type Fields = BTreeMap<&'static str, String>;
+
+/// An event, together with the span it was emitted in.
+#[derive(Debug)]
+struct Recorded {
+    span: Option<(&'static str, Fields)>,
+    message: String,
+    fields: Fields,
+}
+
+/// A layer that keeps every event it sees, for the test to inspect.
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<Recorded>>>);
+
+struct FieldVisitor<'a>(&'a mut Fields);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.insert(field.name(), format!("{value:?}"));
+    }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Recorder {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = Fields::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        ctx.span(id).unwrap().extensions_mut().insert(fields);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let span = ctx.span(id).unwrap();
+        let mut extensions = span.extensions_mut();
+        values.record(&mut FieldVisitor(extensions.get_mut::<Fields>().unwrap()));
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = Fields::new();
+        event.record(&mut FieldVisitor(&mut fields));
+        let message = fields.remove("message").unwrap_or_default();
+        let span = ctx.event_span(event).map(|span| {
+            let fields = span.extensions().get::<Fields>().cloned().unwrap();
+            (span.name(), fields)
+        });
+        self.0.lock().unwrap().push(Recorded {
+            span,
+            message,
+            fields,
+        });
+    }
+}
+
+impl Recorder {
+    /// The events emitted in `request` spans for `command`.
+    fn requests(&self, command: &str) -> Vec<(Fields, Recorded)> {
+        let mut recorded = self.0.lock().unwrap();
+        recorded
+            .drain(..)
+            .filter_map(|event| match event.span.clone() {
+                Some(("request", span)) if span["command"] == command => Some((span, event)),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+// `#[tokio::test]` runs everything on the current thread, server included,
+// so the subscriber set for this thread sees every event.
+#[tokio::test]
+async fn requests_are_traced() {
+    let recorder = Recorder::default();
+    let _guard =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(recorder.clone()));
+
+    let (mut client, server) = spawn_test_server().await;
+
+    let draft = TicketDraft {
+        title: ticket_title(),
+        description: ticket_description(),
+    };
+    let id = client.insert(draft).await.unwrap();
+    let [(span, event)] = <[_; 1]>::try_from(recorder.requests("insert")).unwrap();
+    assert_eq!(span["ticket_id"], id.to_string());
+    assert_eq!(event.message, "request handled");
+    assert!(event.fields["latency_us"].parse::<u64>().is_ok());
+
+    client.get(id).await.unwrap();
+    let [(span, _)] = <[_; 1]>::try_from(recorder.requests("get")).unwrap();
+    assert_eq!(span["ticket_id"], id.to_string());
+    assert!(!span.contains_key("project"));
+
+    // Failures are reported at a higher level, with the reason.
+    let missing = TicketPatch {
+        title: Some(ticket_title()),
+        ..TicketPatch::new(42.into())
+    };
+    assert!(client.update(missing).await.is_err());
+    let [(span, event)] = <[_; 1]>::try_from(recorder.requests("update")).unwrap();
+    assert_eq!(span["ticket_id"], "42");
+    assert_eq!(event.message, "request failed");
+    assert!(event.fields.contains_key("error"));
+
+    server.shutdown().await;
+}
@@ -0,0 +1,144 @@
+use outro_08::webhook::EventKind;
+use outro_08::{Retry, Server, WebhookEvent, WebhookUrl};
+use std::time::Duration;
+use test_support::{spawn_test_server_with, within_async, TIMEOUT};
+use ticket_core::{Status, TicketPatch};
+use ticket_fields::test_helpers::ticket_draft;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+
+/// A request received by an [`endpoint`], and whether it said it was delivered.
+#[derive(Debug)]
+struct Delivery {
+    event: WebhookEvent,
+    accepted: bool,
+}
+
+/// A local HTTP endpoint that fails the first `failures` requests it gets, with a 500.
+async fn endpoint(failures: usize) -> (WebhookUrl, mpsc::UnboundedReceiver<Delivery>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hooks", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        for request in 0.. {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut socket = BufReader::new(socket);
+            let mut length = 0;
+            let mut line = String::new();
+            loop {
+                line.clear();
+                socket.read_line(&mut line).await.unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                if let Some(value) = line.strip_prefix("Content-Length: ") {
+                    length = value.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; length];
+            socket.read_exact(&mut body).await.unwrap();
+
+            let accepted = request >= failures;
+            let status = if accepted {
+                "204 No Content"
+            } else {
+                "500 Internal Server Error"
+            };
+            let response = format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\n\r\n");
+            socket.write_all(response.as_bytes()).await.unwrap();
+            let event = serde_json::from_slice(&body).unwrap();
+            if sender.send(Delivery { event, accepted }).is_err() {
+                return;
+            }
+        }
+    });
+    (url.parse().unwrap(), receiver)
+}
+
+async fn next(deliveries: &mut mpsc::UnboundedReceiver<Delivery>) -> Delivery {
+    within_async(TIMEOUT, deliveries.recv()).await.unwrap()
+}
+
+#[tokio::test]
+async fn every_webhook_hears_about_created_and_updated_tickets() {
+    let (first_url, mut first) = endpoint(0).await;
+    let (second_url, mut second) = endpoint(0).await;
+    let (mut client, _server) =
+        spawn_test_server_with(Server::new().webhook(first_url).webhook(second_url)).await;
+
+    let id = client.insert(ticket_draft()).await.unwrap();
+    client
+        .update(TicketPatch {
+            status: Some(Status::InProgress),
+            ..TicketPatch::new(id)
+        })
+        .await
+        .unwrap();
+    // Reads aren't events.
+    client.get(id).await.unwrap();
+    let other = client.insert(ticket_draft()).await.unwrap();
+
+    for deliveries in [&mut first, &mut second] {
+        let created = next(deliveries).await;
+        assert!(created.accepted);
+        assert_eq!(created.event.event, EventKind::Created);
+        assert_eq!(created.event.project.as_ref(), "default");
+        assert_eq!(created.event.ticket.id, id);
+        assert_eq!(created.event.ticket.status, Status::ToDo);
+
+        let updated = next(deliveries).await.event;
+        assert_eq!(updated.event, EventKind::Updated);
+        assert_eq!(updated.ticket.id, id);
+        assert_eq!(updated.ticket.status, Status::InProgress);
+
+        assert_eq!(next(deliveries).await.event.ticket.id, other);
+    }
+}
+
+#[tokio::test]
+async fn failed_deliveries_are_retried_until_they_give_up() {
+    // Enough failures to use up every attempt at the first event, and one at the second.
+    let (url, mut deliveries) = endpoint(4).await;
+    let retry = Retry {
+        max_attempts: 3,
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(20),
+    };
+    let (mut client, _server) =
+        spawn_test_server_with(Server::new().webhook(url).webhook_retry(retry)).await;
+
+    let dropped = client.insert(ticket_draft()).await.unwrap();
+    let retried = client.insert(ticket_draft()).await.unwrap();
+
+    for _ in 0..retry.max_attempts {
+        let attempt = next(&mut deliveries).await;
+        assert!(!attempt.accepted);
+        assert_eq!(attempt.event.ticket.id, dropped);
+    }
+    let failed = next(&mut deliveries).await;
+    assert!(!failed.accepted);
+    assert_eq!(failed.event.ticket.id, retried);
+    let delivered = next(&mut deliveries).await;
+    assert!(delivered.accepted);
+    assert_eq!(delivered.event.ticket.id, retried);
+}
+
+#[tokio::test]
+async fn an_unreachable_webhook_does_not_hold_requests_up() {
+    // Nothing listens there anymore.
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url: WebhookUrl = format!("http://{}/", listener.local_addr().unwrap())
+        .parse()
+        .unwrap();
+    drop(listener);
+    let (mut client, _server) = spawn_test_server_with(Server::new().webhook(url)).await;
+
+    let answered = tokio::time::timeout(Duration::from_secs(1), async {
+        for _ in 0..10 {
+            client.insert(ticket_draft()).await.unwrap();
+        }
+    });
+    answered.await.expect("Requests waited on the webhook");
+    assert_eq!(client.list().await.unwrap().len(), 10);
+}
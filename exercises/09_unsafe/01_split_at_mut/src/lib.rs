@@ -0,0 +1,78 @@
+// TODO: Implement `split_at_mut` without using the one provided by `std`.
+//  Given a mutable slice and an index, return two non-overlapping mutable slices:
+//  one covering `[0, mid)` and the other covering `[mid, len)`.
+//  Panic if `mid > slice.len()`.
+//
+// Hint: the borrow checker won't let you take two mutable borrows of the same slice,
+// even if they don't overlap. You'll need raw pointers and
+// `std::slice::from_raw_parts_mut`.
+use std::slice;
+
+pub fn split_at_mut<T>(slice: &mut [T], mid: usize) -> (&mut [T], &mut [T]) {
+    let len = slice.len();
+    assert!(mid <= len, "mid > len");
+    let ptr = slice.as_mut_ptr();
+    // SAFETY:
+    // - `ptr` is valid for `len` elements, since it comes from a slice of length `len`;
+    // - `mid <= len`, so `ptr.add(mid)` stays within (or one past the end of) the allocation;
+    // - the two slices don't overlap, so handing out two `&mut` doesn't alias;
+    // - both borrow from `slice`, so they can't outlive it.
+    unsafe {
+        (
+            slice::from_raw_parts_mut(ptr, mid),
+            slice::from_raw_parts_mut(ptr.add(mid), len - mid),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_in_the_middle() {
+        let mut v = vec![1, 2, 3, 4, 5];
+        let (left, right) = split_at_mut(&mut v, 2);
+        assert_eq!(left, &[1, 2]);
+        assert_eq!(right, &[3, 4, 5]);
+    }
+
+    #[test]
+    fn both_halves_are_writable() {
+        let mut v = vec![1, 2, 3, 4];
+        let (left, right) = split_at_mut(&mut v, 2);
+        left[0] = 10;
+        right[1] = 40;
+        // Writing through one half, then the other, then the first again:
+        // Miri would flag this if the two halves aliased.
+        left[1] += right[0];
+        assert_eq!(v, [10, 5, 3, 40]);
+    }
+
+    #[test]
+    fn split_at_the_edges() {
+        let mut v = vec![1, 2, 3];
+        let (left, right) = split_at_mut(&mut v, 0);
+        assert!(left.is_empty());
+        assert_eq!(right, &[1, 2, 3]);
+
+        let (left, right) = split_at_mut(&mut v, 3);
+        assert_eq!(left, &[1, 2, 3]);
+        assert!(right.is_empty());
+    }
+
+    #[test]
+    fn empty_slice() {
+        let mut v: Vec<String> = vec![];
+        let (left, right) = split_at_mut(&mut v, 0);
+        assert!(left.is_empty());
+        assert!(right.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "mid > len")]
+    fn out_of_bounds() {
+        let mut v = vec![1, 2, 3];
+        split_at_mut(&mut v, 4);
+    }
+}
@@ -0,0 +1,186 @@
+// TODO: Implement `RawBuffer`, a growable buffer built on top of raw pointers.
+//  It's a tiny `Vec`: `push` appends an element (growing the allocation when needed),
+//  `pop` removes the last one, `get` returns a reference if the index is in bounds.
+//  Dropping the buffer must drop all of its elements and free the allocation.
+//
+// Hint: check out `std::alloc::{alloc, realloc, dealloc}` and `Layout::array`.
+// Zero-sized types need special care: you can reject them with an assertion.
+use std::alloc::{self, Layout};
+use std::ptr::{self, NonNull};
+
+pub struct RawBuffer<T> {
+    // Points to an allocation of `capacity` elements, the first `len` of which are initialized.
+    // When `capacity` is zero, nothing has been allocated and `ptr` is dangling.
+    ptr: NonNull<T>,
+    capacity: usize,
+    len: usize,
+}
+
+impl<T> RawBuffer<T> {
+    pub fn new() -> Self {
+        assert!(
+            std::mem::size_of::<T>() != 0,
+            "zero-sized types are not supported"
+        );
+        Self {
+            ptr: NonNull::dangling(),
+            capacity: 0,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn push(&mut self, value: T) {
+        if self.len == self.capacity {
+            self.grow();
+        }
+        // SAFETY: `len < capacity`, so the slot is inside the allocation and uninitialized.
+        unsafe { ptr::write(self.ptr.as_ptr().add(self.len), value) };
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        // SAFETY: the slot at `len` was initialized, and decrementing `len` first
+        // guarantees we'll never read (or drop) it again.
+        Some(unsafe { ptr::read(self.ptr.as_ptr().add(self.len)) })
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        // SAFETY: `index < len`, so the slot is initialized.
+        // The reference borrows `self`, so the buffer can't be mutated while it's alive.
+        Some(unsafe { &*self.ptr.as_ptr().add(index) })
+    }
+
+    fn grow(&mut self) {
+        let new_capacity = if self.capacity == 0 {
+            4
+        } else {
+            self.capacity * 2
+        };
+        let new_layout = Layout::array::<T>(new_capacity).expect("capacity overflow");
+        let new_ptr = if self.capacity == 0 {
+            // SAFETY: `T` isn't zero-sized, so the layout has a non-zero size.
+            unsafe { alloc::alloc(new_layout) }
+        } else {
+            let old_layout = Layout::array::<T>(self.capacity).unwrap();
+            // SAFETY: `ptr` was allocated with `old_layout` by this same allocator,
+            // and the new size is non-zero and doesn't overflow `isize` (checked by `Layout::array`).
+            unsafe { alloc::realloc(self.ptr.as_ptr() as *mut u8, old_layout, new_layout.size()) }
+        };
+        self.ptr = match NonNull::new(new_ptr as *mut T) {
+            Some(ptr) => ptr,
+            None => alloc::handle_alloc_error(new_layout),
+        };
+        self.capacity = new_capacity;
+    }
+}
+
+impl<T> Default for RawBuffer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for RawBuffer<T> {
+    fn drop(&mut self) {
+        if self.capacity == 0 {
+            return;
+        }
+        // SAFETY: the first `len` elements are initialized and we won't touch them again.
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.ptr.as_ptr(), self.len));
+        }
+        let layout = Layout::array::<T>(self.capacity).unwrap();
+        // SAFETY: `ptr` was allocated with this very layout.
+        unsafe { alloc::dealloc(self.ptr.as_ptr() as *mut u8, layout) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn push_and_get() {
+        let mut buffer = RawBuffer::new();
+        assert!(buffer.is_empty());
+        for i in 0..10 {
+            buffer.push(i);
+        }
+        assert_eq!(buffer.len(), 10);
+        assert!(buffer.capacity() >= 10);
+        for i in 0..10 {
+            assert_eq!(buffer.get(i), Some(&i));
+        }
+        assert_eq!(buffer.get(10), None);
+    }
+
+    #[test]
+    fn pop_in_reverse_order() {
+        let mut buffer = RawBuffer::new();
+        buffer.push("a".to_string());
+        buffer.push("b".to_string());
+        assert_eq!(buffer.pop().as_deref(), Some("b"));
+        assert_eq!(buffer.pop().as_deref(), Some("a"));
+        assert_eq!(buffer.pop(), None);
+    }
+
+    #[test]
+    fn growing_preserves_heap_allocated_elements() {
+        // `String`s own heap memory: if `realloc` or the copy went wrong,
+        // Miri would report a use-after-free or an invalid read here.
+        let mut buffer = RawBuffer::new();
+        for i in 0..100 {
+            buffer.push(i.to_string());
+        }
+        for i in 0..100 {
+            assert_eq!(buffer.get(i), Some(&i.to_string()));
+        }
+    }
+
+    #[test]
+    fn drop_releases_every_element_exactly_once() {
+        let counter = Rc::new(());
+        {
+            let mut buffer = RawBuffer::new();
+            for _ in 0..5 {
+                buffer.push(Rc::clone(&counter));
+            }
+            // An element we took out is dropped by us, not by the buffer.
+            drop(buffer.pop());
+            assert_eq!(Rc::strong_count(&counter), 5);
+        }
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn empty_buffer_does_not_allocate() {
+        let buffer: RawBuffer<u64> = RawBuffer::new();
+        assert_eq!(buffer.capacity(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "zero-sized types are not supported")]
+    fn zero_sized_types_are_rejected() {
+        let _ = RawBuffer::<()>::new();
+    }
+}
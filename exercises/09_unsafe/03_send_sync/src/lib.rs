@@ -0,0 +1,116 @@
+// TODO: Implement `SpinLock`, a minimal lock that busy-waits instead of parking the thread.
+//  `lock` spins until it manages to flip `locked` from `false` to `true`, then returns a
+//  guard that gives access to the data and releases the lock when dropped.
+//  Then tell the compiler when `SpinLock<T>` can be shared across threads.
+//
+// Hint: `UnsafeCell` isn't `Sync`, so neither is `SpinLock` by default.
+// Think carefully about the bound on `T` for your `unsafe impl`:
+// compare with the ones on `std::sync::Mutex`.
+use std::cell::UnsafeCell;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub struct SpinLock<T> {
+    locked: AtomicBool,
+    data: UnsafeCell<T>,
+}
+
+// SAFETY: the lock guarantees that at most one thread at a time gets access to `data`,
+// so sharing a `SpinLock<T>` only ever moves access to `T` from one thread to another.
+// That's sound as long as `T` can be sent across threads: `T: Send`, no need for `T: Sync`.
+unsafe impl<T: Send> Sync for SpinLock<T> {}
+
+impl<T> SpinLock<T> {
+    pub fn new(data: T) -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    pub fn lock(&self) -> SpinLockGuard<'_, T> {
+        // `Acquire` pairs with the `Release` store in the guard's `Drop`:
+        // everything the previous holder wrote is visible to us.
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            std::hint::spin_loop();
+        }
+        SpinLockGuard { lock: self }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+pub struct SpinLockGuard<'a, T> {
+    lock: &'a SpinLock<T>,
+}
+
+impl<T> Deref for SpinLockGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: the guard exists, so we hold the lock.
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T> DerefMut for SpinLockGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: the guard exists, so we hold the lock, and `&mut self` ensures
+        // that this is the only reference handed out by the guard.
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T> Drop for SpinLockGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.locked.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn assert_sync<T: Sync>() {}
+
+    #[test]
+    fn is_sync_when_data_is_send() {
+        assert_sync::<SpinLock<u32>>();
+        // `Cell` is `Send` but not `Sync`: the lock makes it shareable.
+        assert_sync::<SpinLock<std::cell::Cell<u32>>>();
+    }
+
+    #[test]
+    fn lock_and_mutate() {
+        let lock = SpinLock::new(vec![1]);
+        lock.lock().push(2);
+        assert_eq!(*lock.lock(), vec![1, 2]);
+        assert_eq!(lock.into_inner(), vec![1, 2]);
+    }
+
+    #[test]
+    fn concurrent_increments() {
+        // Kept small so that the test runs in a reasonable time under Miri,
+        // which would detect a data race if the lock didn't synchronize access.
+        let n_threads = 4;
+        let n_increments = 100;
+        let lock = SpinLock::new(0);
+        thread::scope(|scope| {
+            for _ in 0..n_threads {
+                scope.spawn(|| {
+                    for _ in 0..n_increments {
+                        *lock.lock() += 1;
+                    }
+                });
+            }
+        });
+        assert_eq!(lock.into_inner(), n_threads * n_increments);
+    }
+}
@@ -0,0 +1,91 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use outro_08::codec::{encode_frame, FrameDecoder, FrameError};
+use outro_08::protocol::decode_request;
+
+/// Small enough for the fuzzer to stumble upon oversized frames.
+const MAX_LEN: usize = 1024;
+/// Frames start with their length, as a big-endian `u32`.
+const HEADER_LEN: usize = 4;
+
+/// The frames in `stream` when it's received all at once, up to the first oversized one.
+fn frames(mut stream: &[u8]) -> (Vec<&[u8]>, bool) {
+    let mut frames = Vec::new();
+    while let Some((header, rest)) = stream.split_first_chunk::<HEADER_LEN>() {
+        let len = u32::from_be_bytes(*header) as usize;
+        if len > MAX_LEN {
+            return (frames, true);
+        }
+        if rest.len() < len {
+            break;
+        }
+        let (frame, rest) = rest.split_at(len);
+        frames.push(frame);
+        stream = rest;
+    }
+    (frames, false)
+}
+
+fuzz_target!(|input: &[u8]| {
+    // The first byte picks where the stream is split: chunks of 1 to 256 bytes, cycling
+    // through the first few bytes of the stream itself to vary their sizes.
+    let Some((&seed, stream)) = input.split_first() else {
+        return;
+    };
+    let (expected, too_large) = frames(stream);
+
+    let mut decoder = FrameDecoder::with_max_len(MAX_LEN);
+    let mut received = Vec::new();
+    let mut failed = false;
+    let mut rest = stream;
+    let mut i = 0;
+    while !rest.is_empty() && !failed {
+        let size = 1 + usize::from(seed.wrapping_add(stream[i % stream.len()]));
+        let (chunk, tail) = rest.split_at(size.min(rest.len()));
+        decoder.extend(chunk);
+        rest = tail;
+        i += 1;
+        loop {
+            match decoder.next_frame() {
+                Ok(Some(frame)) => received.push(frame.to_vec()),
+                Ok(None) => break,
+                Err(FrameError::TooLarge { len, max }) => {
+                    assert!(len > max && max == MAX_LEN);
+                    failed = true;
+                    break;
+                }
+            }
+        }
+        if !failed {
+            // Whatever is left over is a single incomplete frame, which can't be oversized.
+            assert!(decoder.buffered() < HEADER_LEN + MAX_LEN);
+        }
+    }
+
+    // Splitting the stream doesn't change where frames start and end.
+    assert_eq!(failed, too_large);
+    assert_eq!(received, expected);
+    for frame in &received {
+        let Ok(request) = decode_request(frame) else {
+            continue;
+        };
+        // Whatever `decode_request` accepts comes back unchanged from a frame of its own.
+        let request = request.into_owned();
+        let frames = frames_of(&encode_frame(&request));
+        assert_eq!(frames.len(), 1);
+        assert_eq!(decode_request(&frames[0]).unwrap().into_owned(), request);
+    }
+});
+
+/// The frames `FrameDecoder::new` finds in `stream`, fed all at once.
+fn frames_of(stream: &[u8]) -> Vec<Vec<u8>> {
+    let mut decoder = FrameDecoder::new();
+    decoder.extend(stream);
+    let mut frames = Vec::new();
+    while let Ok(Some(frame)) = decoder.next_frame() {
+        frames.push(frame.to_vec());
+    }
+    assert_eq!(decoder.buffered(), 0);
+    frames
+}
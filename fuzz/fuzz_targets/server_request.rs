@@ -0,0 +1,21 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use outro_08::protocol::{decode_request, Envelope, Request};
+
+fuzz_target!(|frame: &[u8]| {
+    let Ok(request) = decode_request(frame) else {
+        return;
+    };
+    // The server's borrowing decoder must agree with the owned one.
+    let request = request.into_owned();
+    assert_eq!(serde_json::from_slice::<Envelope>(frame).unwrap(), request);
+    // Validation runs during deserialization: accepted drafts are always well-formed.
+    if let Request::Insert { draft } = &request.request {
+        assert!(String::from(draft.title.clone()).len() <= 50);
+        assert!(String::from(draft.description.clone()).len() <= 500);
+    }
+    // Anything we accept must survive a round-trip through the encoder.
+    let encoded = serde_json::to_vec(&request).unwrap();
+    assert_eq!(decode_request(&encoded).unwrap().into_owned(), request);
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ticket_core::Status;
+
+fuzz_target!(|input: &str| {
+    if let Ok(status) = Status::try_from(input) {
+        // Whatever we accept must be a case-insensitive match of a canonical name.
+        assert!(status.to_string().eq_ignore_ascii_case(input));
+    }
+});
@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ticket_fields::TicketDescription;
+
+fuzz_target!(|input: &str| {
+    let accepted = TicketDescription::try_from(input).is_ok();
+    assert_eq!(accepted, !input.is_empty() && input.len() <= 500);
+    assert_eq!(TicketDescription::try_from(input.to_owned()).is_ok(), accepted);
+});
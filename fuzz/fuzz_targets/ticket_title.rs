@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ticket_fields::TicketTitle;
+
+fuzz_target!(|input: &str| {
+    let accepted = TicketTitle::try_from(input).is_ok();
+    assert_eq!(accepted, !input.is_empty() && input.len() <= 50);
+    assert_eq!(TicketTitle::try_from(input.to_owned()).is_ok(), accepted);
+});
@@ -0,0 +1,98 @@
+use benches::draft;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use outro_08::{Client, Server};
+use std::net::SocketAddr;
+use ticket_core::TicketId;
+use tokio::net::TcpListener;
+use tokio::runtime::Runtime;
+
+const REQUESTS_PER_CLIENT: usize = 100;
+
+fn start(runtime: &Runtime, server: Server) -> SocketAddr {
+    runtime.block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(server.serve(listener));
+        addr
+    })
+}
+
+/// Requests per second served to a growing number of concurrent clients,
+/// each sending its requests one after the other over its own connection.
+fn throughput(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let addr = start(&runtime, Server::new());
+
+    let mut group = c.benchmark_group("async_server");
+    for clients in [1, 4, 16] {
+        group.throughput(Throughput::Elements((clients * REQUESTS_PER_CLIENT) as u64));
+        group.bench_function(BenchmarkId::new("insert", clients), |b| {
+            b.to_async(&runtime).iter(|| async move {
+                let tasks: Vec<_> = (0..clients)
+                    .map(|_| {
+                        tokio::spawn(async move {
+                            let mut client = Client::connect(addr).await.unwrap();
+                            for i in 0..REQUESTS_PER_CLIENT {
+                                client.insert(draft(i)).await.unwrap();
+                            }
+                        })
+                    })
+                    .collect();
+                for task in tasks {
+                    task.await.unwrap();
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Many clients reading the same tickets while one keeps writing, with readers either
+/// sharing a `RwLock` with the writer or loading snapshots that the writer swaps in.
+fn read_heavy(c: &mut Criterion) {
+    const READERS: usize = 16;
+    let runtime = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("async_server_reads");
+    group.throughput(Throughput::Elements((READERS * REQUESTS_PER_CLIENT) as u64));
+    for (mode, server) in [
+        ("rw_lock", Server::new()),
+        ("arc_swap", Server::new().lock_free_reads()),
+    ] {
+        let addr = start(&runtime, server);
+        runtime.block_on(async {
+            let mut client = Client::connect(addr).await.unwrap();
+            for i in 0..REQUESTS_PER_CLIENT {
+                client.insert(draft(i)).await.unwrap();
+            }
+        });
+        group.bench_function(mode, |b| {
+            b.to_async(&runtime).iter(|| async move {
+                let writer = tokio::spawn(async move {
+                    let mut client = Client::connect(addr).await.unwrap();
+                    for i in 0..REQUESTS_PER_CLIENT / 10 {
+                        client.insert(draft(i)).await.unwrap();
+                    }
+                });
+                let readers: Vec<_> = (0..READERS)
+                    .map(|_| {
+                        tokio::spawn(async move {
+                            let mut client = Client::connect(addr).await.unwrap();
+                            for i in 0..REQUESTS_PER_CLIENT {
+                                let id = TicketId::from(i as u64);
+                                client.get(id).await.unwrap();
+                            }
+                        })
+                    })
+                    .collect();
+                for reader in readers {
+                    reader.await.unwrap();
+                }
+                writer.await.unwrap();
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, throughput, read_heavy);
+criterion_main!(benches);
@@ -0,0 +1,45 @@
+use benches::draft;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use ticket_core::client::launch;
+
+/// Round-trip latency of a single command through the channel-based server thread.
+fn round_trip(c: &mut Criterion) {
+    let client = launch(16);
+    let id = client.insert(draft(0)).unwrap();
+
+    let mut group = c.benchmark_group("channel_server");
+    group.bench_function("insert", |b| b.iter(|| client.insert(draft(1)).unwrap()));
+    group.bench_function("get", |b| b.iter(|| client.get(black_box(id)).unwrap()));
+    group.finish();
+}
+
+/// Importing 10k tickets with one command each, or with a single batch.
+fn bulk_import(c: &mut Criterion) {
+    const TICKETS: usize = 10_000;
+    let drafts = || (0..TICKETS).map(draft).collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("channel_server_import");
+    group.sample_size(20);
+    group.bench_function("one_by_one", |b| {
+        b.iter_batched(
+            || (launch(16), drafts()),
+            |(client, drafts)| {
+                for draft in drafts {
+                    client.insert(draft).unwrap();
+                }
+            },
+            BatchSize::PerIteration,
+        )
+    });
+    group.bench_function("insert_batch", |b| {
+        b.iter_batched(
+            || (launch(16), drafts()),
+            |(client, drafts)| client.insert_batch(drafts).unwrap(),
+            BatchSize::PerIteration,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, round_trip, bulk_import);
+criterion_main!(benches);
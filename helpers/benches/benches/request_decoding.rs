@@ -0,0 +1,66 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use outro_08::protocol::{decode_request, Envelope};
+
+/// The system allocator, counting how many allocations are made.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+const FRAMES: [(&str, &[u8]); 3] = [
+    ("get", br#"{"project": "backend", "command": "get", "id": 42}"#),
+    ("list", br#"{"project": "frontend", "command": "list"}"#),
+    (
+        "insert",
+        br#"{"project": "backend", "command": "insert", "draft": {"title": "A title", "description": "A description"}}"#,
+    ),
+];
+
+/// The server's borrowing decoder against decoding straight into an owned `Envelope`.
+/// Criterion only measures time, so the allocations each one makes per request are printed first.
+fn decoding(c: &mut Criterion) {
+    let mut group = c.benchmark_group("request_decoding");
+    for (name, frame) in FRAMES {
+        let owned = allocations(|| {
+            black_box(serde_json::from_slice::<Envelope>(frame).unwrap());
+        });
+        let borrowed = allocations(|| {
+            black_box(decode_request(frame).unwrap());
+        });
+        println!("{name}: {owned} allocations owned, {borrowed} borrowed");
+
+        group.bench_with_input(BenchmarkId::new("owned", name), frame, |b, frame| {
+            b.iter(|| serde_json::from_slice::<Envelope>(frame).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("borrowed", name), frame, |b, frame| {
+            b.iter(|| decode_request(frame).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, decoding);
+criterion_main!(benches);
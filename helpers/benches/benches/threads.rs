@@ -0,0 +1,81 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use thread_pool::ThreadPool;
+
+const SIZES: [usize; 4] = [1_000, 10_000, 100_000, 1_000_000];
+
+fn input(size: usize) -> Vec<i32> {
+    (0..size as i32).map(|i| i % 100).collect()
+}
+
+/// The two-threads `sum` from the first threads exercise, against a plain iterator sum.
+/// Below a certain size, spawning threads costs more than it saves.
+fn sum_scaling(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sum");
+    for size in SIZES {
+        let v = input(size);
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::new("sequential", size), &v, |b, v| {
+            b.iter(|| v.iter().sum::<i32>())
+        });
+        group.bench_with_input(BenchmarkId::new("two_threads", size), &v, |b, v| {
+            b.iter_batched(|| v.clone(), threads::sum, criterion::BatchSize::LargeInput)
+        });
+    }
+    group.finish();
+}
+
+/// `parallel::sum_fast`, with one vectorized loop per core, against the two-threads `sum`
+/// and `parallel_sum`, which splits the input the same way but leaves vectorizing to the optimizer.
+fn fast_sum(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fast_sum");
+    for size in [100_000, 1_000_000, 10_000_000] {
+        let v = input(size);
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::new("two_threads", size), &v, |b, v| {
+            b.iter_batched(|| v.clone(), threads::sum, criterion::BatchSize::LargeInput)
+        });
+        group.bench_with_input(BenchmarkId::new("parallel_sum", size), &v, |b, v| {
+            b.iter(|| parallel::parallel_sum(v))
+        });
+        group.bench_with_input(BenchmarkId::new("sum_fast", size), &v, |b, v| {
+            b.iter(|| parallel::sum_fast(v))
+        });
+    }
+    group.finish();
+}
+
+/// Summing one chunk per worker on a `ThreadPool`, varying the number of workers.
+fn pool_scaling(c: &mut Criterion) {
+    let size = 1_000_000;
+    let v = input(size);
+    let mut group = c.benchmark_group("pool_sum");
+    group.throughput(Throughput::Elements(size as u64));
+    for workers in [1, 2, 4, 8] {
+        let pool = ThreadPool::new(workers);
+        let chunks: Vec<Vec<i32>> = v
+            .chunks(size.div_ceil(workers))
+            .map(|c| c.to_vec())
+            .collect();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(workers),
+            &chunks,
+            |b, chunks| {
+                b.iter_batched(
+                    || chunks.clone(),
+                    |chunks| {
+                        let handles: Vec<_> = chunks
+                            .into_iter()
+                            .map(|chunk| pool.execute(move || chunk.iter().sum::<i32>()))
+                            .collect();
+                        handles.into_iter().map(|h| h.join().unwrap()).sum::<i32>()
+                    },
+                    criterion::BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, sum_scaling, fast_sum, pool_scaling);
+criterion_main!(benches);
@@ -0,0 +1,117 @@
+use benches::{draft, store_with, SEED};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use ticket_core::{Encoding, Status, TicketId, TicketPatch, TicketStore};
+
+const SIZES: [usize; 3] = [100, 10_000, 100_000];
+
+fn insert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("store_insert");
+    for size in SIZES {
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            b.iter_batched(
+                || (store_with(size), draft(size)),
+                |(mut store, draft)| store.add_ticket(draft),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.bench_function("empty_store", |b| {
+        b.iter_batched(
+            || draft(0),
+            |draft| TicketStore::new().add_ticket(draft),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn get(c: &mut Criterion) {
+    let mut group = c.benchmark_group("store_get");
+    for size in SIZES {
+        let store = store_with(size);
+        let id = TicketId::from(size as u64 / 2);
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            b.iter(|| store.get(black_box(id)).is_some())
+        });
+    }
+    group.finish();
+}
+
+fn query(c: &mut Criterion) {
+    let mut group = c.benchmark_group("store_with_status");
+    for size in SIZES {
+        let mut store = store_with(size);
+        // Move one ticket in three to `Done`, so that the filter has something to skip.
+        for i in (0..size as u64).step_by(3) {
+            store
+                .update(TicketPatch {
+                    status: Some(Status::Done),
+                    ..TicketPatch::new(TicketId::from(i))
+                })
+                .unwrap();
+        }
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            b.iter(|| store.with_status(black_box(Status::Done)).count())
+        });
+    }
+    group.finish();
+}
+
+/// 100k tickets sharing a handful of tags: each tag name is stored once, and `with_tag`
+/// goes through the tag index instead of checking every ticket's tags.
+fn tags(c: &mut Criterion) {
+    const TAGS: [&str; 5] = ["bug", "feature", "docs", "good first issue", "wontfix"];
+    let size = 100_000;
+    let mut store = store_with(size);
+    for i in 0..size as u64 {
+        let id = TicketId::from(i);
+        store.add_tag(id, TAGS[i as usize % TAGS.len()]).unwrap();
+        // One ticket in seven gets a second tag, so that only a few have both.
+        if i % 7 == 0 {
+            store.add_tag(id, "urgent").unwrap();
+        }
+    }
+
+    let mut group = c.benchmark_group("store_tags");
+    group.bench_function("with_tag", |b| {
+        b.iter(|| store.with_tag(black_box("urgent")).count())
+    });
+    group.bench_function("scan", |b| {
+        b.iter(|| {
+            let tag = black_box("urgent");
+            store
+                .iter()
+                .filter(|t| store.tags(t.id).any(|t| t == tag))
+                .count()
+        })
+    });
+    group.bench_function("add_tag", |b| {
+        b.iter_batched(
+            || store.clone(),
+            |mut store| store.add_tag(TicketId::from(1), black_box("docs")),
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+/// Loading a 10k tickets snapshot, validating every title and description or trusting them.
+/// Validation is only a few length checks per ticket: most of the time goes into parsing JSON.
+/// The tickets are fully generated ones, with tags, assignees and priorities to parse too.
+fn snapshot_load(c: &mut Criterion) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("store.json");
+    ticket_fixtures::store(SEED, 10_000).save(&path).unwrap();
+
+    let mut group = c.benchmark_group("store_snapshot_load");
+    group.bench_function("validated", |b| {
+        b.iter(|| TicketStore::load(&path).unwrap())
+    });
+    group.bench_function("trusted", |b| {
+        b.iter(|| TicketStore::load_trusted(&path, &Encoding::default()).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, insert, get, query, tags, snapshot_load);
+criterion_main!(benches);
@@ -0,0 +1,32 @@
+//! Criterion benchmarks for the hot paths across the exercises.
+//!
+//! Each file under `benches/` covers one area: threaded sum scaling, `TicketStore`
+//! operations, the channel-based server, the async TCP server and how it decodes requests.
+//! Run them all with `cargo bench -p benches`, or a single one with
+//! `cargo bench -p benches --bench ticket_store`.
+//!
+//! This library only holds the fixtures shared by the benchmarks.
+use ticket_core::{TicketDraft, TicketStore};
+use ticket_fields::{TicketDescription, TicketTitle};
+use ticket_fixtures::Generator;
+
+/// What the generated tickets are derived from, see `ticket_fixtures`.
+pub const SEED: u64 = 42;
+
+/// A valid draft, with a title that's unique for each `n`.
+pub fn draft(n: usize) -> TicketDraft {
+    TicketDraft {
+        title: TicketTitle::try_from(format!("Ticket #{n}")).unwrap(),
+        description: TicketDescription::try_from("A description").unwrap(),
+    }
+}
+
+/// A store pre-filled with `n` generated drafts, all of them still to do and untagged:
+/// benchmarks set up whatever else they measure themselves.
+pub fn store_with(n: usize) -> TicketStore {
+    let mut store = TicketStore::new();
+    for draft in Generator::new(SEED).drafts().take(n) {
+        store.add_ticket(draft);
+    }
+    store
+}
@@ -0,0 +1,75 @@
+//! End-to-end tests for the capstone async server.
+//!
+//! The exercises' tests check each component in isolation. The tests in this crate
+//! boot the assembled server on a real socket and drive it through its client library,
+//! the way an actual user of the system would. Run them with `cargo test -p integration-tests`.
+//!
+//! This library only holds the harness shared by the tests under `tests/`.
+use std::io;
+use std::net::SocketAddr;
+use std::path::Path;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+use outro_08::{serve, serve_persistent, Client, Server};
+use ticket_core::TicketDraft;
+use ticket_fields::{TicketDescription, TicketTitle};
+
+/// A server running in the background on a random local port.
+pub struct TestServer {
+    pub addr: SocketAddr,
+    handle: JoinHandle<io::Result<()>>,
+}
+
+impl TestServer {
+    /// Start a server that keeps its tickets in memory.
+    pub async fn in_memory() -> Self {
+        let listener = bind().await;
+        let addr = listener.local_addr().unwrap();
+        let handle = tokio::spawn(serve(listener));
+        Self { addr, handle }
+    }
+
+    /// Start a server that persists its tickets to `snapshot`.
+    pub async fn persistent(snapshot: &Path) -> Self {
+        let listener = bind().await;
+        let addr = listener.local_addr().unwrap();
+        let handle = tokio::spawn(serve_persistent(listener, snapshot.to_owned()));
+        Self { addr, handle }
+    }
+
+    /// Start a server configured with `server`.
+    pub async fn start(server: Server) -> Self {
+        let listener = bind().await;
+        let addr = listener.local_addr().unwrap();
+        let handle = tokio::spawn(server.serve(listener));
+        Self { addr, handle }
+    }
+
+    pub async fn client(&self) -> Client {
+        Client::connect(self.addr).await.unwrap()
+    }
+
+    /// Ask the server to shut down and wait until it has stopped.
+    pub async fn shutdown(self) {
+        self.client().await.shutdown().await.unwrap();
+        self.stopped().await.unwrap();
+    }
+
+    /// Wait for the server to stop on its own, returning the error it stopped with (if any).
+    pub async fn stopped(self) -> io::Result<()> {
+        self.handle.await.expect("The server task panicked")
+    }
+}
+
+async fn bind() -> TcpListener {
+    TcpListener::bind("127.0.0.1:0").await.unwrap()
+}
+
+/// A valid draft, with a title that's unique for each `n`.
+pub fn draft(n: usize) -> TicketDraft {
+    TicketDraft {
+        title: TicketTitle::try_from(format!("Ticket #{n}")).unwrap(),
+        description: TicketDescription::try_from("A description").unwrap(),
+    }
+}
@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use integration_tests::{draft, TestServer};
+use outro_08::protocol::HealthStatus;
+use outro_08::Server;
+use ticket_core::TestClock;
+
+#[tokio::test]
+async fn readiness_flips_when_changes_go_unsaved() {
+    let dir = tempfile::tempdir().unwrap();
+    let data = dir.path().join("data");
+    std::fs::create_dir(&data).unwrap();
+    let clock = TestClock::new();
+    let server = Server::new()
+        .persistent(data.join("tickets.json"))
+        .clock(clock.clone())
+        .max_persistence_lag(Duration::from_secs(10));
+    let server = TestServer::start(server).await;
+
+    let mut client = server.client().await;
+    let mut probe = server.client().await;
+    client.insert(draft(0)).await.unwrap();
+    let (ready, health) = probe.ready().await.unwrap();
+    assert!(ready);
+    assert_eq!(health.status, HealthStatus::Healthy);
+    assert_eq!(health.persistence_lag_ms, None);
+    assert!(health.store_accessible);
+    assert_eq!(health.connections, 2);
+
+    // The disk goes away: changes are still applied, but not saved.
+    std::fs::remove_dir_all(&data).unwrap();
+    assert!(client.insert(draft(1)).await.is_err());
+    clock.advance(Duration::from_secs(5));
+    let (ready, health) = probe.ready().await.unwrap();
+    assert!(ready);
+    assert_eq!(health.status, HealthStatus::Degraded);
+    assert_eq!(health.persistence_lag_ms, Some(5_000));
+
+    // The lag is counted from the first failure, however many followed.
+    assert!(client.insert(draft(2)).await.is_err());
+    clock.advance(Duration::from_secs(5));
+    let (ready, health) = probe.ready().await.unwrap();
+    assert!(!ready);
+    assert_eq!(health.status, HealthStatus::Unhealthy);
+    assert_eq!(probe.health().await.unwrap(), health);
+
+    // The next successful save catches up with everything.
+    std::fs::create_dir(&data).unwrap();
+    client.insert(draft(3)).await.unwrap();
+    let (ready, health) = probe.ready().await.unwrap();
+    assert!(ready);
+    assert_eq!(health.persistence_lag_ms, None);
+    drop(client);
+    server.shutdown().await;
+
+    let server = TestServer::persistent(&data.join("tickets.json")).await;
+    assert_eq!(server.client().await.list().await.unwrap().len(), 4);
+    server.shutdown().await;
+}
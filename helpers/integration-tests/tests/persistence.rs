@@ -0,0 +1,99 @@
+use integration_tests::{draft, TestServer};
+use test_support::TempSnapshot;
+use ticket_core::{Status, TicketId, TicketPatch};
+use ticket_fixtures::Generator;
+
+#[tokio::test]
+async fn tickets_survive_a_restart() {
+    let snapshot = TempSnapshot::new();
+
+    let server = TestServer::persistent(snapshot.path()).await;
+    let mut client = server.client().await;
+    let first = client.insert(draft(0)).await.unwrap();
+    let second = client.insert(draft(1)).await.unwrap();
+    client
+        .update(TicketPatch {
+            status: Some(Status::Done),
+            ..TicketPatch::new(first)
+        })
+        .await
+        .unwrap();
+    let before = client.list().await.unwrap();
+    server.shutdown().await;
+
+    let server = TestServer::persistent(snapshot.path()).await;
+    let mut client = server.client().await;
+    assert_eq!(client.list().await.unwrap(), before);
+    assert_eq!(
+        client.get(first).await.unwrap().unwrap().status,
+        Status::Done
+    );
+
+    // Ids keep increasing across restarts.
+    let third = client.insert(draft(2)).await.unwrap();
+    assert!(third > second);
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn generated_tickets_survive_a_restart() {
+    let snapshot = TempSnapshot::new();
+
+    let server = TestServer::persistent(snapshot.path()).await;
+    let mut client = server.client().await;
+    let mut generator = Generator::new(170);
+    for _ in 0..50 {
+        let ticket = generator.ticket();
+        let id = client.insert(ticket.draft).await.unwrap();
+        client
+            .update(TicketPatch {
+                status: Some(ticket.status),
+                assignee: Some(ticket.assignee.map(Into::into)),
+                ..TicketPatch::new(id)
+            })
+            .await
+            .unwrap();
+    }
+    let before = client.list().await.unwrap();
+    server.shutdown().await;
+
+    // Unicode descriptions and assignees included.
+    let server = TestServer::persistent(snapshot.path()).await;
+    assert_eq!(server.client().await.list().await.unwrap(), before);
+    assert!(before.iter().any(|t| !t.description.as_ref().is_ascii()));
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn first_start_without_a_snapshot() {
+    let snapshot = TempSnapshot::new();
+
+    let server = TestServer::persistent(snapshot.path()).await;
+    let mut client = server.client().await;
+    assert!(client.list().await.unwrap().is_empty());
+    assert_eq!(client.insert(draft(0)).await.unwrap(), TicketId::from(0));
+    server.shutdown().await;
+
+    assert!(snapshot.exists());
+}
+
+#[tokio::test]
+async fn in_memory_server_forgets_everything() {
+    let server = TestServer::in_memory().await;
+    server.client().await.insert(draft(0)).await.unwrap();
+    server.shutdown().await;
+
+    let server = TestServer::in_memory().await;
+    assert!(server.client().await.list().await.unwrap().is_empty());
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn corrupted_snapshot_prevents_startup() {
+    let snapshot = TempSnapshot::new();
+    std::fs::write(snapshot.path(), "{ not json").unwrap();
+
+    let server = TestServer::persistent(snapshot.path()).await;
+    let err = server.stopped().await.unwrap_err();
+    assert!(err.to_string().starts_with("Invalid snapshot"));
+}
@@ -0,0 +1,76 @@
+use integration_tests::{draft, TestServer};
+use outro_08::ClientError;
+use ticket_core::{Status, TicketId, TicketPatch, TicketTitle};
+
+#[tokio::test]
+async fn full_protocol() {
+    let server = TestServer::in_memory().await;
+    let mut client = server.client().await;
+
+    let first = client.insert(draft(0)).await.unwrap();
+    let second = client.insert(draft(1)).await.unwrap();
+    assert_ne!(first, second);
+
+    let ticket = client.get(first).await.unwrap().unwrap();
+    assert_eq!(ticket.id, first);
+    assert_eq!(ticket.title, draft(0).title);
+    assert_eq!(ticket.status, Status::ToDo);
+    assert!(client.get(TicketId::from(42)).await.unwrap().is_none());
+
+    client
+        .update(TicketPatch {
+            title: Some(TicketTitle::try_from("Renamed").unwrap()),
+            status: Some(Status::InProgress),
+            ..TicketPatch::new(second)
+        })
+        .await
+        .unwrap();
+    let tickets = client.list().await.unwrap();
+    assert_eq!(tickets.len(), 2);
+    assert_eq!(tickets[1].title, TicketTitle::try_from("Renamed").unwrap());
+    assert_eq!(tickets[1].status, Status::InProgress);
+    assert_eq!(tickets[1].description, draft(1).description);
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn updating_a_missing_ticket_is_an_error() {
+    let server = TestServer::in_memory().await;
+    let mut client = server.client().await;
+
+    let err = client
+        .update(TicketPatch {
+            status: Some(Status::Done),
+            ..TicketPatch::new(TicketId::from(7))
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ClientError::Server(message) if message.contains('7')));
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn clients_share_the_same_store() {
+    let server = TestServer::in_memory().await;
+
+    let tasks: Vec<_> = (0..8)
+        .map(|n| {
+            let addr = server.addr;
+            tokio::spawn(async move {
+                let mut client = outro_08::Client::connect(addr).await.unwrap();
+                for i in 0..10 {
+                    client.insert(draft(n * 10 + i)).await.unwrap();
+                }
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    let tickets = server.client().await.list().await.unwrap();
+    assert_eq!(tickets.len(), 80);
+    server.shutdown().await;
+}
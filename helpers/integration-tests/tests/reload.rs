@@ -0,0 +1,94 @@
+use std::fs;
+use std::time::Duration;
+
+use integration_tests::{draft, TestServer};
+use outro_08::client::ClientError;
+use outro_08::Server;
+use ticket_core::{Config, TestClock};
+
+#[tokio::test]
+async fn limits_are_reloaded_from_the_config_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    fs::write(&path, "").unwrap();
+    let config = Config::load(&path).unwrap();
+    let server = Server::new().settings(&config).reload_from(&path);
+    let server = TestServer::start(server).await;
+
+    let mut client = server.client().await;
+    client.insert(draft(0)).await.unwrap();
+
+    fs::write(&path, "[limits]\nmax_title_len = 5\n").unwrap();
+    client.reload_config().await.unwrap();
+    assert!(matches!(
+        client.insert(draft(1)).await,
+        Err(ClientError::Server(_))
+    ));
+
+    // An invalid config is reported, and the current one stays in place.
+    fs::write(&path, "[limits]\nmax_title_len = 99\n").unwrap();
+    let Err(ClientError::Server(message)) = client.reload_config().await else {
+        panic!("The config should have been rejected");
+    };
+    assert!(message.starts_with("Kept the current config."), "{message}");
+    assert!(client.insert(draft(2)).await.is_err());
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn connections_beyond_the_cap_are_refused() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    fs::write(&path, "").unwrap();
+    let server = TestServer::start(Server::new().reload_from(&path)).await;
+
+    let mut admin = server.client().await;
+    fs::write(&path, "[server]\nmax_connections = 1\n").unwrap();
+    admin.reload_config().await.unwrap();
+
+    let mut extra = server.client().await;
+    let Err(ClientError::Server(message)) = extra.list().await else {
+        panic!("The connection should have been refused");
+    };
+    assert_eq!(message, "Too many connections: the server takes up to 1");
+    // The connections already open are left alone.
+    admin.list().await.unwrap();
+
+    drop(admin);
+    drop(extra);
+    // The server notices the hang-up in its own time.
+    let mut client = loop {
+        let mut client = server.client().await;
+        if client.list().await.is_ok() {
+            break client;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    };
+    // `TestServer::shutdown` would need a second connection.
+    client.shutdown().await.unwrap();
+    server.stopped().await.unwrap();
+}
+
+#[tokio::test]
+async fn requests_beyond_the_rate_limit_are_rejected() {
+    let clock = TestClock::new();
+    let server = Server::new()
+        .clock(clock.clone())
+        .max_requests_per_second(2);
+    let server = TestServer::start(server).await;
+
+    let mut client = server.client().await;
+    client.list().await.unwrap();
+    client.list().await.unwrap();
+    let Err(ClientError::Server(message)) = client.list().await else {
+        panic!("The request should have been rejected");
+    };
+    assert_eq!(message, "Too many requests: the limit is 2 per second");
+    // Rejected requests don't count: the client only has to wait out the ones that went through.
+    clock.advance(Duration::from_secs(1));
+    client.list().await.unwrap();
+    // The limit is per connection.
+    server.client().await.list().await.unwrap();
+    drop(client);
+    server.shutdown().await;
+}
@@ -0,0 +1,12 @@
+fn main() {
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        let config = cbindgen::Config::from_file(format!("{crate_dir}/cbindgen.toml")).unwrap();
+        cbindgen::generate_with_config(&crate_dir, config)
+            .expect("Failed to generate the C header")
+            .write_to_file(format!("{crate_dir}/include/parallel.h"));
+    }
+}
@@ -0,0 +1,32 @@
+#ifndef PARALLEL_H
+#define PARALLEL_H
+
+/* Generated by cbindgen from src/ffi.rs: don't edit it by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The sum of the `len` integers starting at `values`.
+ *
+ * # Safety
+ *
+ * `values` must point to `len` initialized `int32_t`s, which must not be modified
+ * during the call.
+ */
+int64_t parallel_sum(const int32_t *values, size_t len);
+
+/**
+ * Sort the `len` integers starting at `values` in place, in ascending order.
+ *
+ * # Safety
+ *
+ * `values` must point to `len` initialized `int32_t`s, which must not be read or
+ * modified by anyone else during the call.
+ */
+void parallel_sort(int32_t *values, size_t len);
+
+#endif  /* PARALLEL_H */
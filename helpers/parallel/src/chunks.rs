@@ -0,0 +1,117 @@
+//! Splitting work into chunks, as in the `chunks` exercise: [`chunked`] borrows them from a
+//! slice, [`IntoChunks`] moves them out of a `Vec`.
+//!
+//! Every chunk holds `size` items, except the last one, which holds whatever remains.
+//! Chunks of zero items would never make progress: asking for them panics.
+use std::vec;
+
+/// The chunks of `items`, `size` items at a time, as slices of it.
+///
+/// # Panics
+///
+/// If `size` is zero.
+pub fn chunked<T>(items: &[T], size: usize) -> impl ExactSizeIterator<Item = &[T]> {
+    assert_ne!(size, 0, "Chunks must hold at least one item");
+    Chunked { rest: items, size }
+}
+
+struct Chunked<'a, T> {
+    rest: &'a [T],
+    size: usize,
+}
+
+impl<'a, T> Iterator for Chunked<'a, T> {
+    type Item = &'a [T];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        let (chunk, rest) = self.rest.split_at(self.size.min(self.rest.len()));
+        self.rest = rest;
+        Some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.rest.len().div_ceil(self.size);
+        (len, Some(len))
+    }
+}
+
+impl<T> ExactSizeIterator for Chunked<'_, T> {}
+
+/// The chunks of a `Vec`, `size` items at a time, each one a `Vec` of its own.
+#[derive(Debug)]
+pub struct IntoChunks<T> {
+    items: vec::IntoIter<T>,
+    size: usize,
+}
+
+impl<T> IntoChunks<T> {
+    /// # Panics
+    ///
+    /// If `size` is zero.
+    pub fn new(items: Vec<T>, size: usize) -> Self {
+        assert_ne!(size, 0, "Chunks must hold at least one item");
+        Self {
+            items: items.into_iter(),
+            size,
+        }
+    }
+}
+
+impl<T> Iterator for IntoChunks<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk: Vec<T> = self.items.by_ref().take(self.size).collect();
+        (!chunk.is_empty()).then_some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.items.len().div_ceil(self.size);
+        (len, Some(len))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoChunks<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_last_chunk_holds_the_remainder() {
+        let items = [1, 2, 3, 4, 5];
+        let chunks: Vec<_> = chunked(&items, 2).collect();
+        assert_eq!(chunks, [&[1, 2][..], &[3, 4], &[5]]);
+        assert_eq!(chunked(&items, 2).len(), 3);
+
+        let chunks: Vec<_> = IntoChunks::new(items.to_vec(), 2).collect();
+        assert_eq!(chunks, [vec![1, 2], vec![3, 4], vec![5]]);
+    }
+
+    #[test]
+    fn even_splits_and_oversized_chunks() {
+        let items: Vec<_> = (0..6).collect();
+        assert!(chunked(&items, 3).all(|chunk| chunk.len() == 3));
+        assert_eq!(IntoChunks::new(items.clone(), 3).len(), 2);
+        // A single chunk, shorter than asked for.
+        assert_eq!(chunked(&items, 10).collect::<Vec<_>>(), [&items[..]]);
+        assert_eq!(IntoChunks::new(items.clone(), 10).next(), Some(items));
+        assert_eq!(chunked::<u8>(&[], 3).next(), None);
+        assert_eq!(IntoChunks::<u8>::new(Vec::new(), 3).len(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one item")]
+    fn borrowed_chunks_cannot_be_empty() {
+        let _ = chunked(&[1, 2, 3], 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one item")]
+    fn owned_chunks_cannot_be_empty() {
+        let _ = IntoChunks::new(vec![1, 2, 3], 0);
+    }
+}
@@ -0,0 +1,34 @@
+//! The C API. The header, `include/parallel.h`, is generated from this module by `cbindgen`.
+//!
+//! Both functions accept a null pointer, as long as `len` is zero.
+use std::slice;
+
+/// The sum of the `len` integers starting at `values`.
+///
+/// # Safety
+///
+/// `values` must point to `len` initialized `int32_t`s, which must not be modified
+/// during the call.
+#[no_mangle]
+pub unsafe extern "C" fn parallel_sum(values: *const i32, len: usize) -> i64 {
+    if len == 0 {
+        return 0;
+    }
+    // SAFETY: guaranteed by the caller.
+    crate::parallel_sum(unsafe { slice::from_raw_parts(values, len) })
+}
+
+/// Sort the `len` integers starting at `values` in place, in ascending order.
+///
+/// # Safety
+///
+/// `values` must point to `len` initialized `int32_t`s, which must not be read or
+/// modified by anyone else during the call.
+#[no_mangle]
+pub unsafe extern "C" fn parallel_sort(values: *mut i32, len: usize) {
+    if len == 0 {
+        return;
+    }
+    // SAFETY: guaranteed by the caller.
+    crate::parallel_sort(unsafe { slice::from_raw_parts_mut(values, len) })
+}
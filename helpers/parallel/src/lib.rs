@@ -0,0 +1,166 @@
+//! The data-parallel helpers from the threads chapter, finished: each one splits its input
+//! into chunks and processes them on scoped threads, one per core.
+//!
+//! With the `ffi` feature, they can be called from C too: see [`ffi`] and `include/parallel.h`.
+use std::num::NonZeroUsize;
+use std::thread;
+
+pub mod chunks;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+pub use chunks::{chunked, IntoChunks};
+
+/// Below this many elements per thread, spawning one costs more than it saves.
+const MIN_CHUNK_LEN: usize = 1024;
+
+/// How many threads to split `len` elements across.
+fn threads_for(len: usize) -> usize {
+    let cores = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    (len / MIN_CHUNK_LEN).clamp(1, cores)
+}
+
+/// The sum of `values`. It's computed as an `i64`, so it can't overflow for
+/// any slice that fits in memory.
+pub fn parallel_sum(values: &[i32]) -> i64 {
+    let chunk_len = values.len().div_ceil(threads_for(values.len())).max(1);
+    thread::scope(|scope| {
+        let handles: Vec<_> = chunked(values, chunk_len)
+            .map(|chunk| scope.spawn(|| chunk.iter().map(|&v| i64::from(v)).sum::<i64>()))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).sum()
+    })
+}
+
+/// Inputs shorter than this are summed on the calling thread.
+const FAST_SUM_THRESHOLD: usize = 64 * 1024;
+
+/// How many partial sums `sum_lanes` keeps: enough to fill a 512-bit register.
+const LANES: usize = 8;
+
+/// Like [`parallel_sum`], but each thread accumulates its chunk into [`LANES`] independent
+/// partial sums, which the compiler reliably turns into SIMD additions instead of having
+/// to spot the pattern on its own. Small inputs skip the threads altogether.
+pub fn sum_fast(values: &[i32]) -> i64 {
+    if values.len() < FAST_SUM_THRESHOLD {
+        return sum_lanes(values);
+    }
+    let chunk_len = values.len().div_ceil(threads_for(values.len()));
+    thread::scope(|scope| {
+        let handles: Vec<_> = chunked(values, chunk_len)
+            .map(|chunk| scope.spawn(|| sum_lanes(chunk)))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).sum()
+    })
+}
+
+/// Sum `values` sequentially, `LANES` elements at a time.
+fn sum_lanes(values: &[i32]) -> i64 {
+    let mut lanes = [0i64; LANES];
+    let mut chunks = values.chunks_exact(LANES);
+    for chunk in &mut chunks {
+        for (lane, &v) in lanes.iter_mut().zip(chunk) {
+            *lane += i64::from(v);
+        }
+    }
+    let tail: i64 = chunks.remainder().iter().map(|&v| i64::from(v)).sum();
+    lanes.iter().sum::<i64>() + tail
+}
+
+/// Sort `values` in place. The halves are sorted in parallel, then merged.
+pub fn parallel_sort<T: Ord + Copy + Send>(values: &mut [T]) {
+    sort(values, threads_for(values.len()));
+}
+
+fn sort<T: Ord + Copy + Send>(values: &mut [T], threads: usize) {
+    if threads <= 1 {
+        values.sort_unstable();
+        return;
+    }
+    let mid = values.len() / 2;
+    let (left, right) = values.split_at_mut(mid);
+    thread::scope(|scope| {
+        scope.spawn(|| sort(left, threads / 2));
+        sort(right, threads - threads / 2);
+    });
+    merge(values, mid);
+}
+
+/// Merge the sorted runs `values[..mid]` and `values[mid..]`.
+fn merge<T: Ord + Copy>(values: &mut [T], mid: usize) {
+    let mut merged = Vec::with_capacity(values.len());
+    let (mut left, mut right) = (
+        values[..mid].iter().peekable(),
+        values[mid..].iter().peekable(),
+    );
+    while let (Some(&&l), Some(&&r)) = (left.peek(), right.peek()) {
+        if l <= r {
+            merged.push(l);
+            left.next();
+        } else {
+            merged.push(r);
+            right.next();
+        }
+    }
+    merged.extend(left);
+    merged.extend(right);
+    values.copy_from_slice(&merged);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn input(len: usize) -> Vec<i32> {
+        // Deterministic, but far from sorted.
+        (0..len as i32)
+            .map(|i| i.wrapping_mul(7919) % 1000)
+            .collect()
+    }
+
+    #[test]
+    fn sum() {
+        assert_eq!(parallel_sum(&[]), 0);
+        assert_eq!(parallel_sum(&[1, 2, 3]), 6);
+        let v = input(100_000);
+        assert_eq!(
+            parallel_sum(&v),
+            v.iter().map(|&x| i64::from(x)).sum::<i64>()
+        );
+        assert_eq!(parallel_sum(&[i32::MAX; 4]), 4 * i64::from(i32::MAX));
+    }
+
+    #[test]
+    fn fast_sum_edge_cases() {
+        assert_eq!(sum_fast(&[]), 0);
+        // Shorter than a single lane group: only the tail is summed.
+        assert_eq!(sum_fast(&[1, 2, 3]), 6);
+        let v = vec![i32::MIN; FAST_SUM_THRESHOLD + 3];
+        assert_eq!(sum_fast(&v), v.len() as i64 * i64::from(i32::MIN));
+    }
+
+    proptest! {
+        // Most inputs are large enough to be split across threads, so keep the case count low.
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn fast_sum_matches_sequential_sum(
+            v in prop::collection::vec(any::<i32>(), 0..3 * FAST_SUM_THRESHOLD)
+        ) {
+            let expected: i64 = v.iter().map(|&x| i64::from(x)).sum();
+            prop_assert_eq!(sum_fast(&v), expected);
+        }
+    }
+
+    #[test]
+    fn sort() {
+        for len in [0, 1, 2, 1000, 100_001] {
+            let mut v = input(len);
+            let mut expected = v.clone();
+            expected.sort();
+            parallel_sort(&mut v);
+            assert_eq!(v, expected);
+        }
+    }
+}
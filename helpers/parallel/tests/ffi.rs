@@ -0,0 +1,27 @@
+//! Calls go through the C ABI, as they would from C: the functions are declared here
+//! from their C signatures, and resolved by the linker.
+#![cfg(feature = "ffi")]
+
+// Link the crate in, even though nothing refers to it by name.
+use parallel as _;
+
+extern "C" {
+    fn parallel_sum(values: *const i32, len: usize) -> i64;
+    fn parallel_sort(values: *mut i32, len: usize);
+}
+
+#[test]
+fn sum() {
+    let values: Vec<i32> = (1..=10_000).collect();
+    let sum = unsafe { parallel_sum(values.as_ptr(), values.len()) };
+    assert_eq!(sum, 50_005_000);
+    assert_eq!(unsafe { parallel_sum(std::ptr::null(), 0) }, 0);
+}
+
+#[test]
+fn sort() {
+    let mut values: Vec<i32> = (0..10_000).rev().collect();
+    unsafe { parallel_sort(values.as_mut_ptr(), values.len()) };
+    assert!(values.windows(2).all(|w| w[0] <= w[1]));
+    unsafe { parallel_sort(std::ptr::null_mut(), 0) };
+}
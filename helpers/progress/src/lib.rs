@@ -0,0 +1,218 @@
+use anyhow::{Context, Error};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// An exercise crate, located at `exercises/<chapter>/<name>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exercise {
+    pub chapter: String,
+    pub name: String,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExerciseResult {
+    pub name: String,
+    pub path: PathBuf,
+    pub passed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChapterReport {
+    pub name: String,
+    pub completed: usize,
+    pub total: usize,
+    pub exercises: Vec<ExerciseResult>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub completed: usize,
+    pub total: usize,
+    pub chapters: Vec<ChapterReport>,
+}
+
+/// Find all exercise crates under `exercises_dir`, sorted by chapter and exercise name.
+pub fn discover(exercises_dir: &Path) -> Result<Vec<Exercise>, Error> {
+    let mut exercises = Vec::new();
+    for chapter in sorted_subdirectories(exercises_dir)? {
+        for exercise in sorted_subdirectories(&chapter)? {
+            if !exercise.join("Cargo.toml").is_file() {
+                continue;
+            }
+            exercises.push(Exercise {
+                chapter: file_name(&chapter),
+                name: file_name(&exercise),
+                path: exercise,
+            });
+        }
+    }
+    Ok(exercises)
+}
+
+fn sorted_subdirectories(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let entries =
+        std::fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))?;
+    let mut dirs = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            dirs.push(path);
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().unwrap().to_string_lossy().into_owned()
+}
+
+/// Run `check` against every exercise and group the outcomes by chapter.
+pub fn collect<F>(exercises: &[Exercise], mut check: F) -> Report
+where
+    F: FnMut(&Exercise) -> bool,
+{
+    let mut chapters: BTreeMap<&str, Vec<ExerciseResult>> = BTreeMap::new();
+    for exercise in exercises {
+        let passed = check(exercise);
+        chapters
+            .entry(&exercise.chapter)
+            .or_default()
+            .push(ExerciseResult {
+                name: exercise.name.clone(),
+                path: exercise.path.clone(),
+                passed,
+            });
+    }
+    let chapters: Vec<_> = chapters
+        .into_iter()
+        .map(|(name, exercises)| ChapterReport {
+            name: name.to_owned(),
+            completed: exercises.iter().filter(|e| e.passed).count(),
+            total: exercises.len(),
+            exercises,
+        })
+        .collect();
+    Report {
+        completed: chapters.iter().map(|c| c.completed).sum(),
+        total: chapters.iter().map(|c| c.total).sum(),
+        chapters,
+    }
+}
+
+impl Report {
+    /// A human-readable, per-chapter completion table.
+    pub fn table(&self) -> String {
+        let width = self
+            .chapters
+            .iter()
+            .map(|c| c.name.len())
+            .chain(std::iter::once("Total".len()))
+            .max()
+            .unwrap();
+        let mut table = String::new();
+        writeln!(
+            table,
+            "{:<width$}  {:>4}  {:>5}  {:>8}",
+            "Chapter", "Done", "Total", "Progress"
+        )
+        .unwrap();
+        let rows = self
+            .chapters
+            .iter()
+            .map(|c| (c.name.as_str(), c.completed, c.total))
+            .chain(std::iter::once(("Total", self.completed, self.total)));
+        for (name, completed, total) in rows {
+            writeln!(
+                table,
+                "{name:<width$}  {completed:>4}  {total:>5}  {:>7}%",
+                percentage(completed, total)
+            )
+            .unwrap();
+        }
+        table
+    }
+}
+
+fn percentage(completed: usize, total: usize) -> usize {
+    (completed * 100).checked_div(total).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exercise(chapter: &str, name: &str) -> Exercise {
+        Exercise {
+            chapter: chapter.into(),
+            name: name.into(),
+            path: PathBuf::from(chapter).join(name),
+        }
+    }
+
+    #[test]
+    fn discover_finds_crates_in_order() {
+        let root = std::env::temp_dir().join(format!("progress-discover-{}", std::process::id()));
+        for dir in ["02_b/01_y", "02_b/00_x", "01_a/00_z", "01_a/01_not_a_crate"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for dir in ["02_b/01_y", "02_b/00_x", "01_a/00_z"] {
+            std::fs::write(root.join(dir).join("Cargo.toml"), "").unwrap();
+        }
+
+        let found = discover(&root).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let names: Vec<_> = found
+            .iter()
+            .map(|e| format!("{}/{}", e.chapter, e.name))
+            .collect();
+        assert_eq!(names, ["01_a/00_z", "02_b/00_x", "02_b/01_y"]);
+    }
+
+    #[test]
+    fn collect_groups_by_chapter() {
+        let exercises = [
+            exercise("01_intro", "00_welcome"),
+            exercise("01_intro", "01_syntax"),
+            exercise("02_basic_calculator", "00_intro"),
+        ];
+        let report = collect(&exercises, |e| e.name != "01_syntax");
+
+        assert_eq!((report.completed, report.total), (2, 3));
+        assert_eq!(report.chapters.len(), 2);
+        assert_eq!(report.chapters[0].name, "01_intro");
+        assert_eq!(
+            (report.chapters[0].completed, report.chapters[0].total),
+            (1, 2)
+        );
+        assert!(!report.chapters[0].exercises[1].passed);
+    }
+
+    #[test]
+    fn table_rendering() {
+        let exercises = [
+            exercise("01_intro", "00_welcome"),
+            exercise("01_intro", "01_syntax"),
+        ];
+        let report = collect(&exercises, |e| e.name == "00_welcome");
+        assert_eq!(
+            report.table(),
+            "Chapter   Done  Total  Progress\n\
+             01_intro     1      2       50%\n\
+             Total        1      2       50%\n"
+        );
+    }
+
+    #[test]
+    fn json_report() {
+        let report = collect(&[exercise("01_intro", "00_welcome")], |_| true);
+        let json: serde_json::Value = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["completed"], 1);
+        assert_eq!(json["chapters"][0]["exercises"][0]["name"], "00_welcome");
+        assert_eq!(json["chapters"][0]["exercises"][0]["passed"], true);
+    }
+}
@@ -0,0 +1,60 @@
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Error};
+use clap::Parser;
+
+use progress::{collect, discover, Exercise};
+
+/// Run the tests of every exercise and report how far through the course you are.
+#[derive(Parser)]
+struct Cli {
+    /// The root of the repository.
+    #[arg(long, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/../.."))]
+    root: PathBuf,
+    /// Only check the chapters whose name starts with this prefix (e.g. `07`).
+    #[arg(long)]
+    chapter: Option<String>,
+    /// Where to write the JSON report.
+    #[arg(long, default_value = "progress.json")]
+    report: PathBuf,
+}
+
+fn main() -> Result<(), Error> {
+    let cli = Cli::parse();
+    let root = cli
+        .root
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", cli.root.display()))?;
+    let mut exercises = discover(&root.join("exercises"))?;
+    if let Some(prefix) = &cli.chapter {
+        exercises.retain(|e| e.chapter.starts_with(prefix.as_str()));
+    }
+
+    let report = collect(&exercises, |exercise| {
+        eprint!("Testing {}/{}... ", exercise.chapter, exercise.name);
+        let passed = run_tests(exercise);
+        eprintln!("{}", if passed { "ok" } else { "FAILED" });
+        passed
+    });
+
+    print!("{}", report.table());
+    let json = serde_json::to_string_pretty(&report)?;
+    std::fs::write(&cli.report, json)
+        .with_context(|| format!("Failed to write {}", cli.report.display()))?;
+    println!("\nJSON report written to {}", cli.report.display());
+    Ok(())
+}
+
+/// An exercise is complete if it compiles and its tests pass.
+fn run_tests(exercise: &Exercise) -> bool {
+    Command::new(env!("CARGO"))
+        .arg("test")
+        .arg("--quiet")
+        .arg("--manifest-path")
+        .arg(exercise.path.join("Cargo.toml"))
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
@@ -0,0 +1,74 @@
+use ticket_core::Ticket;
+
+/// Like `assert_eq!`, but tickets created or changed at different times are still equal: their
+/// `created_at` and `updated_at` are left out of the comparison. Due dates are set on purpose, so they count.
+#[track_caller]
+pub fn assert_ticket_eq_ignoring_timestamps(left: &Ticket, right: &Ticket) {
+    assert_eq!(
+        without_timestamps(left),
+        without_timestamps(right),
+        "The tickets differ, even ignoring their timestamps"
+    );
+}
+
+/// [`assert_ticket_eq_ignoring_timestamps`], ticket by ticket. The order matters.
+#[track_caller]
+pub fn assert_tickets_eq_ignoring_timestamps(left: &[Ticket], right: &[Ticket]) {
+    let left: Vec<_> = left.iter().map(without_timestamps).collect();
+    let right: Vec<_> = right.iter().map(without_timestamps).collect();
+    assert_eq!(
+        left, right,
+        "The tickets differ, even ignoring their timestamps"
+    );
+}
+
+fn without_timestamps(ticket: &Ticket) -> Ticket {
+    Ticket {
+        created_at: None,
+        updated_at: None,
+        ..ticket.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ticket_core::{Status, TestClock, TicketDraft, TicketStore, Timestamp};
+
+    fn ticket(store: &mut TicketStore) -> Ticket {
+        let draft = TicketDraft::new("A title".into(), "A description".into()).unwrap();
+        let id = store.add_ticket(draft);
+        store[id].clone()
+    }
+
+    #[test]
+    fn creation_times_are_ignored() {
+        let untimed = ticket(&mut TicketStore::new());
+        let timed = ticket(&mut TicketStore::with_clock(TestClock::new()));
+        assert_ne!(untimed, timed);
+        assert_ticket_eq_ignoring_timestamps(&untimed, &timed);
+        assert_tickets_eq_ignoring_timestamps(&[untimed], &[timed]);
+    }
+
+    #[test]
+    #[should_panic(expected = "even ignoring their timestamps")]
+    fn other_fields_are_not() {
+        let ticket = ticket(&mut TicketStore::new());
+        let done = Ticket {
+            status: Status::Done,
+            ..ticket.clone()
+        };
+        assert_ticket_eq_ignoring_timestamps(&ticket, &done);
+    }
+
+    #[test]
+    #[should_panic(expected = "even ignoring their timestamps")]
+    fn neither_are_due_dates() {
+        let ticket = ticket(&mut TicketStore::new());
+        let due = Ticket {
+            due: Some(Timestamp::UNIX_EPOCH),
+            ..ticket.clone()
+        };
+        assert_tickets_eq_ignoring_timestamps(&[ticket], &[due]);
+    }
+}
@@ -0,0 +1,25 @@
+//! Helpers shared by the tests of the later chapters, and of the tools built on top of them.
+//!
+//! - [`assert_ticket_eq_ignoring_timestamps`], to compare tickets created at different times.
+//! - [`TempSnapshot`], somewhere to persist a store or a server to, cleaned up afterwards.
+//! - [`within`], to fail a test that's stuck waiting on a channel instead of hanging forever.
+//!
+//! With the `server` feature, for the capstone async server (`outro_08`):
+//! - [`spawn_test_server`], a server on a random local port with a client connected to it.
+//! - [`within_async`], like [`within`], for futures.
+//!
+//! It's meant for integration tests, under `tests/`: the crates it depends on can't use it in
+//! their own unit tests, which would see two copies of their types.
+mod assertions;
+#[cfg(feature = "server")]
+mod server;
+mod snapshot;
+mod timeout;
+
+pub use assertions::{assert_ticket_eq_ignoring_timestamps, assert_tickets_eq_ignoring_timestamps};
+#[cfg(feature = "server")]
+pub use server::{spawn_test_server, spawn_test_server_with, ServerGuard};
+pub use snapshot::TempSnapshot;
+#[cfg(feature = "server")]
+pub use timeout::within_async;
+pub use timeout::{within, TIMEOUT};
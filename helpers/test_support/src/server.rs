@@ -0,0 +1,91 @@
+use outro_08::{Client, Server};
+use std::io;
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+/// Start a server with the default settings in the background, on a random local port,
+/// and connect a client to it. The server runs until the guard is dropped.
+pub async fn spawn_test_server() -> (Client, ServerGuard) {
+    spawn_test_server_with(Server::new()).await
+}
+
+/// Like [`spawn_test_server`], for a server configured with `server`.
+pub async fn spawn_test_server_with(server: Server) -> (Client, ServerGuard) {
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind a local port");
+    let addr = listener.local_addr().unwrap();
+    let guard = ServerGuard {
+        addr,
+        handle: Some(tokio::spawn(server.serve(listener))),
+    };
+    (guard.client().await, guard)
+}
+
+/// Keeps a server started by [`spawn_test_server`] running.
+///
+/// Dropping the guard aborts the server, so that tests that fail halfway through don't leave
+/// it behind. [`ServerGuard::shutdown`] stops it the way clients would.
+#[derive(Debug)]
+pub struct ServerGuard {
+    addr: SocketAddr,
+    /// Only taken when waiting for the server to stop.
+    handle: Option<JoinHandle<io::Result<()>>>,
+}
+
+impl ServerGuard {
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Another client, connected to the same server.
+    pub async fn client(&self) -> Client {
+        Client::connect(self.addr)
+            .await
+            .expect("Failed to connect to the test server")
+    }
+
+    /// Ask the server to shut down, and wait until it has stopped.
+    /// Servers that require authentication only shut down for an admin: use
+    /// [`ServerGuard::stopped`] after sending them `Request::Shutdown` yourself.
+    pub async fn shutdown(self) {
+        self.client()
+            .await
+            .shutdown()
+            .await
+            .expect("The server refused to shut down");
+        self.stopped().await.expect("The server failed");
+    }
+
+    /// Wait for the server to stop on its own, returning the error it stopped with, if any.
+    pub async fn stopped(mut self) -> io::Result<()> {
+        let handle = self.handle.take().expect("Only taken here");
+        handle.await.expect("The server task panicked")
+    }
+}
+
+impl Drop for ServerGuard {
+    fn drop(&mut self) {
+        if let Some(handle) = &self.handle {
+            handle.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ticket_fields::test_helpers::ticket_draft;
+
+    #[tokio::test]
+    async fn clients_share_the_server() {
+        let (mut client, server) = spawn_test_server().await;
+        let id = client.insert(ticket_draft()).await.unwrap();
+        let mut other = server.client().await;
+        assert!(other.get(id).await.unwrap().is_some());
+        let addr = server.addr();
+        server.shutdown().await;
+        assert!(Client::connect(addr).await.is_err());
+    }
+}
@@ -0,0 +1,62 @@
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// A path to persist tickets to, in a temporary directory of its own.
+/// The directory, and everything in it, is deleted when this is dropped.
+///
+/// Nothing is written to the path until something saves there: it starts out missing,
+/// like a server's snapshot before its first start.
+#[derive(Debug)]
+pub struct TempSnapshot {
+    dir: TempDir,
+    path: PathBuf,
+}
+
+impl TempSnapshot {
+    pub fn new() -> Self {
+        let dir = tempfile::tempdir().expect("Failed to create a temporary directory");
+        let path = dir.path().join("tickets.json");
+        Self { dir, path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The directory the snapshot is in, for anything else that needs to live next to it:
+    /// backups, a write-ahead log, a config file...
+    pub fn dir(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Whether anything was saved to the path yet.
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+}
+
+impl Default for TempSnapshot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshots_are_cleaned_up() {
+        let snapshot = TempSnapshot::new();
+        assert!(!snapshot.exists());
+        assert_eq!(snapshot.path().parent(), Some(snapshot.dir()));
+        ticket_core::TicketStore::new()
+            .save(snapshot.path())
+            .unwrap();
+        assert!(snapshot.exists());
+
+        let dir = snapshot.dir().to_owned();
+        drop(snapshot);
+        assert!(!dir.exists());
+    }
+}
@@ -0,0 +1,71 @@
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+/// How long tests wait, by default, before giving up on something that should be instant.
+/// Generous, for slow CI machines.
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Run `test` on a thread of its own, and return what it returns—or panic, if it takes
+/// longer than `timeout`. If `test` panics, so does `within`, with the same payload.
+///
+/// A test that waits on a channel nobody sends to blocks forever: this fails it instead,
+/// pointing at what was left waiting. The stuck thread is left behind, it can't be stopped.
+#[track_caller]
+pub fn within<T: Send + 'static>(
+    timeout: Duration,
+    test: impl FnOnce() -> T + Send + 'static,
+) -> T {
+    let (sender, receiver) = mpsc::channel();
+    let handle = std::thread::spawn(move || {
+        let _ = sender.send(test());
+    });
+    match receiver.recv_timeout(timeout) {
+        Ok(value) => value,
+        Err(RecvTimeoutError::Timeout) => panic!(
+            "The test didn't finish within {timeout:?}: is it waiting for a message that never comes?"
+        ),
+        // The sender was dropped without sending: `test` panicked.
+        Err(RecvTimeoutError::Disconnected) => {
+            std::panic::resume_unwind(handle.join().expect_err("`test` returned without sending"))
+        }
+    }
+}
+
+/// Like [`within`], for a future: await it, or panic if it takes longer than `timeout`.
+#[cfg(feature = "server")]
+pub async fn within_async<F: std::future::IntoFuture>(timeout: Duration, future: F) -> F::Output {
+    match tokio::time::timeout(timeout, future).await {
+        Ok(output) => output,
+        Err(_) => panic!("The future didn't complete within {timeout:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn results_are_passed_through() {
+        assert_eq!(within(TIMEOUT, || 1 + 1), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "didn't finish within 100ms")]
+    fn hanging_tests_fail() {
+        let (_sender, receiver) = mpsc::channel::<()>();
+        within(Duration::from_millis(100), move || receiver.recv().unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "the test's own panic")]
+    fn panics_are_passed_through() {
+        within(TIMEOUT, || panic!("the test's own panic"));
+    }
+
+    #[cfg(feature = "server")]
+    #[tokio::test]
+    #[should_panic(expected = "didn't complete within 100ms")]
+    async fn hanging_futures_fail() {
+        within_async(Duration::from_millis(100), std::future::pending::<()>()).await;
+    }
+}
@@ -0,0 +1,63 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context, Error};
+use clap::Parser;
+
+use ticket_cli::{summary, timeline};
+use ticket_core::{EncryptionKey, History};
+use ticket_fields::render_chain;
+
+/// Inspect the write-ahead log of a `DurableStore`, and rebuild its tickets as of any record.
+#[derive(Parser)]
+struct Cli {
+    /// The directory holding the snapshot and the write-ahead log.
+    dir: PathBuf,
+    /// Stop at the record with this sequence number. Defaults to the last one.
+    #[arg(long)]
+    until: Option<u64>,
+    /// Save the tickets as of `--until` to this snapshot file, instead of listing them.
+    #[arg(long)]
+    output: Option<PathBuf>,
+    /// The environment variable holding the encryption key, if the store is encrypted.
+    #[arg(long)]
+    key_env: Option<String>,
+}
+
+fn main() -> Result<(), Error> {
+    let cli = Cli::parse();
+    let key = match &cli.key_env {
+        Some(var) => {
+            let value = std::env::var(var).with_context(|| format!("Failed to read `{var}`"))?;
+            let key: EncryptionKey = value
+                .parse()
+                .map_err(|e| anyhow!("`{var}` doesn't hold a valid key: {e}"))?;
+            Some(key)
+        }
+        None => None,
+    };
+    let history = History::read(&cli.dir, key).map_err(|e| anyhow!(render_chain(&e)))?;
+    let until = cli.until.unwrap_or(history.last_seq());
+    for line in timeline(&history, until) {
+        println!("{line}");
+    }
+
+    let store = history.rebuild(until)?;
+    println!();
+    match &cli.output {
+        Some(path) => {
+            store.save(path).map_err(|e| anyhow!(render_chain(&e)))?;
+            println!(
+                "Saved {} tickets, as of #{until}, to {}",
+                store.len(),
+                path.display()
+            );
+        }
+        None => {
+            println!("{} tickets as of #{until}:", store.len());
+            for ticket in store.iter() {
+                println!("{}", summary(ticket));
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,223 @@
+//! Manage tickets from the command line.
+//!
+//! Tickets live either in a local snapshot file, loaded and saved by the CLI itself,
+//! or in a running ticket server (`exercises/08_futures/08_outro`), reached over its protocol.
+//! [`Backend`] hides the difference from the subcommands.
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Error};
+use outro_08::Client;
+use ticket_core::{
+    Encoding, History, TemplateOverrides, Ticket, TicketDraft, TicketEvent, TicketId, TicketPatch,
+    TicketStore, TicketTemplate, WalRecord,
+};
+use ticket_fields::render_chain;
+
+pub enum Backend {
+    /// An in-process store, persisted to `path` after every change.
+    Local {
+        path: PathBuf,
+        /// Boxed: a store is much bigger than a client.
+        store: Box<TicketStore>,
+    },
+    Remote(Client),
+}
+
+impl Backend {
+    pub fn local(path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let path = path.into();
+        let store = TicketStore::load_or_default(&path).map_err(|e| anyhow!(render_chain(&e)))?;
+        Ok(Self::Local {
+            path,
+            store: Box::new(store),
+        })
+    }
+
+    pub async fn remote(addr: &str) -> Result<Self, Error> {
+        let client = Client::connect(addr)
+            .await
+            .map_err(|e| anyhow!("Failed to connect to {addr}: {e}"))?;
+        Ok(Self::Remote(client))
+    }
+
+    pub async fn add(&mut self, draft: TicketDraft) -> Result<TicketId, Error> {
+        match self {
+            Self::Local { path, store } => {
+                let id = store.add_ticket(draft);
+                save(store, path)?;
+                Ok(id)
+            }
+            Self::Remote(client) => Ok(client.insert(draft).await?),
+        }
+    }
+
+    /// Add a ticket created from the template named `template`.
+    pub async fn add_from_template(
+        &mut self,
+        template: &str,
+        overrides: TemplateOverrides,
+    ) -> Result<TicketId, Error> {
+        match self {
+            Self::Local { path, store } => {
+                let id = store
+                    .add_from_template(template, &overrides)
+                    .map_err(|e| anyhow!(render_chain(&e)))?;
+                save(store, path)?;
+                Ok(id)
+            }
+            Self::Remote(client) => Ok(client.insert_from_template(template, overrides).await?),
+        }
+    }
+
+    /// Add `template`, or replace the one with the same name.
+    pub async fn register_template(&mut self, template: TicketTemplate) -> Result<(), Error> {
+        match self {
+            Self::Local { path, store } => {
+                store.register_template(template);
+                save(store, path)
+            }
+            Self::Remote(client) => Ok(client.register_template(template).await?),
+        }
+    }
+
+    /// Every template, by name.
+    pub async fn templates(&mut self) -> Result<Vec<TicketTemplate>, Error> {
+        match self {
+            Self::Local { store, .. } => Ok(store.templates().cloned().collect()),
+            Self::Remote(client) => Ok(client.templates().await?),
+        }
+    }
+
+    pub async fn show(&mut self, id: TicketId) -> Result<Option<Ticket>, Error> {
+        match self {
+            Self::Local { store, .. } => Ok(store.get(id).cloned()),
+            Self::Remote(client) => Ok(client.get(id).await?),
+        }
+    }
+
+    /// All tickets, in the store's order (see `TicketStore`).
+    pub async fn list(&mut self) -> Result<Vec<Ticket>, Error> {
+        match self {
+            Self::Local { store, .. } => Ok(store.iter().cloned().collect()),
+            Self::Remote(client) => Ok(client.list().await?),
+        }
+    }
+
+    pub async fn update(&mut self, patch: TicketPatch) -> Result<(), Error> {
+        match self {
+            Self::Local { path, store } => {
+                store.update(patch)?;
+                save(store, path)
+            }
+            Self::Remote(client) => Ok(client.update(patch).await?),
+        }
+    }
+
+    /// Returns the deleted ticket, or `None` if there was no ticket with that id.
+    pub async fn delete(&mut self, id: TicketId) -> Result<Option<Ticket>, Error> {
+        match self {
+            Self::Local { path, store } => {
+                let deleted = store.delete(id);
+                if deleted.is_some() {
+                    save(store, path)?;
+                }
+                Ok(deleted)
+            }
+            Self::Remote(client) => Ok(client.delete(id).await?),
+        }
+    }
+
+    /// Back the tickets up to a new file in `dir`, and return its path.
+    /// Backups are plain JSON, like the store itself.
+    pub fn backup(&self, dir: &Path) -> Result<PathBuf, Error> {
+        match self {
+            Self::Local { store, .. } => store
+                .backup_to(dir, &Encoding::default())
+                .map_err(|e| anyhow!(render_chain(&e))),
+            Self::Remote(_) => bail!("Backups are only supported for local stores"),
+        }
+    }
+
+    /// Replace every ticket with the ones in the backup at `backup`.
+    /// Returns how many tickets it held.
+    pub fn restore(&mut self, backup: &Path) -> Result<usize, Error> {
+        match self {
+            Self::Local { path, store } => {
+                **store = TicketStore::restore_from(backup, &Encoding::default())
+                    .map_err(|e| anyhow!(render_chain(&e)))?;
+                save(store, path)?;
+                Ok(store.len())
+            }
+            Self::Remote(_) => bail!("Backups are only supported for local stores"),
+        }
+    }
+}
+
+fn save(store: &TicketStore, path: &Path) -> Result<(), Error> {
+    store.save(path).map_err(|e| anyhow!(render_chain(&e)))
+}
+
+/// A one-line summary of a ticket, for listings.
+pub fn summary(ticket: &Ticket) -> String {
+    format!(
+        "#{} [{}] {}",
+        ticket.id,
+        ticket.status,
+        ticket.title.as_ref()
+    )
+}
+
+/// A ticket with all its details.
+pub fn details(ticket: &Ticket) -> String {
+    format!("{}\n\n{}", summary(ticket), ticket.description.as_ref())
+}
+
+/// A line per entry of a `DurableStore`'s history, oldest first: the snapshot (if any),
+/// then every record of the write-ahead log up to `until`.
+pub fn timeline(history: &History, until: u64) -> Vec<String> {
+    let mut lines = Vec::new();
+    if history.snapshot_seq > 0 {
+        lines.push(format!("snapshot up to #{}", history.snapshot_seq));
+    }
+    for record in history.records.iter().take_while(|r| r.seq <= until) {
+        let mut line = format!("#{} {}", record.seq, describe(record));
+        if record.seq <= history.snapshot_seq {
+            line.push_str(" (already in the snapshot)");
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+fn describe(record: &WalRecord) -> String {
+    match &record.event {
+        TicketEvent::Created { id, draft, .. } => {
+            format!("created #{id} {:?}", draft.title.as_ref())
+        }
+        TicketEvent::Patched { patch, .. } => format!("patched #{}: {}", patch.id, changes(patch)),
+        TicketEvent::Deleted { id } => format!("deleted #{id}"),
+    }
+}
+
+fn changes(patch: &TicketPatch) -> String {
+    let mut changes = Vec::new();
+    if let Some(title) = &patch.title {
+        changes.push(format!("title {:?}", title.as_ref()));
+    }
+    if patch.description.is_some() {
+        changes.push("new description".to_string());
+    }
+    if let Some(status) = patch.status {
+        changes.push(format!("status {status}"));
+    }
+    match &patch.assignee {
+        Some(Some(assignee)) => changes.push(format!("assigned to {assignee}")),
+        Some(None) => changes.push("unassigned".to_string()),
+        None => {}
+    }
+    if changes.is_empty() {
+        "no changes".to_string()
+    } else {
+        changes.join(", ")
+    }
+}
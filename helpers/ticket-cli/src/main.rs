@@ -0,0 +1,253 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Error};
+use clap::{ArgGroup, Parser, Subcommand};
+
+use ticket_cli::{details, summary, Backend};
+use ticket_core::{
+    list_backups, CustomFields, Encoding, Status, TemplateOverrides, TicketDraft, TicketId,
+    TicketPatch, TicketTemplate,
+};
+use ticket_fields::render_chain;
+use ticket_fields::{TicketDescription, TicketTitle};
+
+/// Manage tickets, either in a local file or on a running ticket server.
+#[derive(Parser)]
+struct Cli {
+    /// The snapshot file holding the tickets.
+    #[arg(long, default_value = "tickets.json")]
+    store: PathBuf,
+    /// Talk to the ticket server at this address (e.g. `127.0.0.1:8000`) instead of using a local file.
+    #[arg(long, conflicts_with = "store")]
+    server: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a new ticket.
+    Add {
+        #[arg(long, value_parser = parse_title)]
+        title: TicketTitle,
+        #[arg(long, value_parser = parse_description)]
+        description: TicketDescription,
+    },
+    /// Show the details of a ticket.
+    Show {
+        #[arg(value_parser = parse_id)]
+        id: TicketId,
+    },
+    /// List all tickets.
+    List {
+        /// Only list the tickets with this status (`todo`, `inprogress` or `done`).
+        #[arg(long, value_parser = parse_status)]
+        status: Option<Status>,
+    },
+    /// Change the title, description or status of a ticket.
+    #[command(group(ArgGroup::new("changes").required(true).multiple(true)))]
+    Update {
+        #[arg(value_parser = parse_id)]
+        id: TicketId,
+        #[arg(long, value_parser = parse_title, group = "changes")]
+        title: Option<TicketTitle>,
+        #[arg(long, value_parser = parse_description, group = "changes")]
+        description: Option<TicketDescription>,
+        #[arg(long, value_parser = parse_status, group = "changes")]
+        status: Option<Status>,
+    },
+    /// Delete a ticket.
+    Delete {
+        #[arg(value_parser = parse_id)]
+        id: TicketId,
+    },
+    /// Manage ticket templates, and create tickets from them.
+    Template {
+        #[command(subcommand)]
+        command: TemplateCommand,
+    },
+    /// Back the tickets up to a new, timestamped file.
+    Backup {
+        /// The directory holding the backups.
+        #[arg(long, default_value = "backups")]
+        dir: PathBuf,
+        /// List the existing backups, oldest first, instead of taking a new one.
+        #[arg(long)]
+        list: bool,
+    },
+    /// Replace every ticket with the ones in a backup.
+    Restore {
+        /// The backup to restore. Defaults to the latest one in `--dir`.
+        backup: Option<PathBuf>,
+        /// The directory holding the backups.
+        #[arg(long, default_value = "backups")]
+        dir: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum TemplateCommand {
+    /// Add a template, or replace the one with the same name.
+    Add {
+        name: String,
+        /// Put in front of the title of every ticket created from the template.
+        #[arg(long, default_value = "")]
+        title_prefix: String,
+        /// The description of tickets created without one.
+        #[arg(long, value_parser = parse_description)]
+        description: TicketDescription,
+        /// Tag every ticket created from the template. Can be repeated.
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+    },
+    /// List the templates.
+    List,
+    /// Create a ticket from a template. Whatever is given overrides the template.
+    New {
+        template: String,
+        /// Comes after the template's title prefix.
+        #[arg(long)]
+        title: Option<String>,
+        #[arg(long)]
+        description: Option<String>,
+        /// Replace the template's tags. Can be repeated.
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let cli = Cli::parse();
+    let mut backend = match &cli.server {
+        Some(addr) => Backend::remote(addr).await?,
+        None => Backend::local(&cli.store)?,
+    };
+
+    match cli.command {
+        Command::Add { title, description } => {
+            let id = backend.add(TicketDraft { title, description }).await?;
+            println!("Created ticket #{id}");
+        }
+        Command::Show { id } => match backend.show(id).await? {
+            Some(ticket) => println!("{}", details(&ticket)),
+            None => bail!("There is no ticket with id {id}"),
+        },
+        Command::List { status } => {
+            for ticket in backend.list().await? {
+                if status.is_none_or(|s| s == ticket.status) {
+                    println!("{}", summary(&ticket));
+                }
+            }
+        }
+        Command::Update {
+            id,
+            title,
+            description,
+            status,
+        } => {
+            backend
+                .update(TicketPatch {
+                    title,
+                    description,
+                    status,
+                    ..TicketPatch::new(id)
+                })
+                .await?;
+            println!("Updated ticket #{id}");
+        }
+        Command::Delete { id } => match backend.delete(id).await? {
+            Some(_) => println!("Deleted ticket #{id}"),
+            None => bail!("There is no ticket with id {id}"),
+        },
+        Command::Template {
+            command:
+                TemplateCommand::Add {
+                    name,
+                    title_prefix,
+                    description,
+                    tags,
+                },
+        } => {
+            let template = TicketTemplate {
+                name,
+                title_prefix,
+                description,
+                tags,
+                custom_fields: CustomFields::new(),
+            };
+            let name = template.name.clone();
+            backend.register_template(template).await?;
+            println!("Registered template `{name}`");
+        }
+        Command::Template {
+            command: TemplateCommand::List,
+        } => {
+            for template in backend.templates().await? {
+                let tags = template.tags.join(", ");
+                println!("{} {:?} [{tags}]", template.name, template.title_prefix);
+            }
+        }
+        Command::Template {
+            command:
+                TemplateCommand::New {
+                    template,
+                    title,
+                    description,
+                    tags,
+                },
+        } => {
+            let overrides = TemplateOverrides {
+                title,
+                description,
+                tags: (!tags.is_empty()).then_some(tags),
+                custom_fields: CustomFields::new(),
+            };
+            let id = backend.add_from_template(&template, overrides).await?;
+            println!("Created ticket #{id}");
+        }
+        Command::Backup { dir, list: true } => {
+            for backup in
+                list_backups(&dir, &Encoding::default()).map_err(|e| anyhow!(render_chain(&e)))?
+            {
+                let tickets = backup.metadata.tickets;
+                println!("{} ({tickets} tickets)", backup.path.display());
+            }
+        }
+        Command::Backup { dir, list: false } => {
+            let path = backend.backup(&dir)?;
+            println!("Backed up to {}", path.display());
+        }
+        Command::Restore { backup, dir } => {
+            let backup = match backup {
+                Some(path) => path,
+                None => match list_backups(&dir, &Encoding::default())
+                    .map_err(|e| anyhow!(render_chain(&e)))?
+                    .pop()
+                {
+                    Some(latest) => latest.path,
+                    None => bail!("There are no backups in {}", dir.display()),
+                },
+            };
+            let tickets = backend.restore(&backup)?;
+            println!("Restored {tickets} tickets from {}", backup.display());
+        }
+    }
+    Ok(())
+}
+
+fn parse_id(s: &str) -> Result<TicketId, std::num::ParseIntError> {
+    s.parse::<u64>().map(TicketId::from)
+}
+
+fn parse_title(s: &str) -> Result<TicketTitle, ticket_fields::TicketTitleError> {
+    TicketTitle::try_from(s)
+}
+
+fn parse_description(s: &str) -> Result<TicketDescription, ticket_fields::TicketDescriptionError> {
+    TicketDescription::try_from(s)
+}
+
+fn parse_status(s: &str) -> Result<Status, ticket_core::ParseStatusError> {
+    Status::try_from(s)
+}
@@ -0,0 +1,120 @@
+use test_support::{spawn_test_server, TempSnapshot};
+use ticket_cli::{details, summary, Backend};
+use ticket_core::{CustomFields, Status, TemplateOverrides, TicketId, TicketPatch, TicketTemplate};
+use ticket_fields::test_helpers::{ticket_description, ticket_draft, ticket_title};
+
+fn done(id: TicketId) -> TicketPatch {
+    TicketPatch {
+        status: Some(Status::Done),
+        ..TicketPatch::new(id)
+    }
+}
+
+/// The same sequence of operations, whatever the backend.
+async fn exercise(backend: &mut Backend) {
+    let first = backend.add(ticket_draft()).await.unwrap();
+    let second = backend.add(ticket_draft()).await.unwrap();
+    backend.update(done(first)).await.unwrap();
+    assert!(backend.update(done(TicketId::from(42))).await.is_err());
+
+    let ticket = backend.show(first).await.unwrap().unwrap();
+    assert_eq!(ticket.status, Status::Done);
+    assert_eq!(backend.delete(second).await.unwrap().unwrap().id, second);
+    assert!(backend.delete(second).await.unwrap().is_none());
+    assert_eq!(backend.list().await.unwrap(), [ticket]);
+}
+
+/// Templates work the same, whatever the backend.
+async fn templates(backend: &mut Backend) {
+    let bug = TicketTemplate {
+        name: "bug".into(),
+        title_prefix: "[Bug] ".into(),
+        description: ticket_description(),
+        tags: vec!["bug".into()],
+        custom_fields: CustomFields::new(),
+    };
+    backend.register_template(bug.clone()).await.unwrap();
+    assert_eq!(backend.templates().await.unwrap(), [bug]);
+
+    let overrides = TemplateOverrides {
+        title: Some("Crash".into()),
+        ..TemplateOverrides::default()
+    };
+    let id = backend
+        .add_from_template("bug", overrides.clone())
+        .await
+        .unwrap();
+    let ticket = backend.show(id).await.unwrap().unwrap();
+    assert_eq!(ticket.title.as_ref(), "[Bug] Crash");
+    assert!(backend
+        .add_from_template("feature", overrides)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn local_backend_persists_changes() {
+    let snapshot = TempSnapshot::new();
+
+    let mut backend = Backend::local(snapshot.path()).unwrap();
+    exercise(&mut backend).await;
+    templates(&mut backend).await;
+    let before = backend.list().await.unwrap();
+
+    // Every invocation of the CLI starts from the file.
+    let mut reopened = Backend::local(snapshot.path()).unwrap();
+    assert_eq!(reopened.list().await.unwrap(), before);
+    assert_eq!(reopened.templates().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn local_backup_and_restore() {
+    let snapshot = TempSnapshot::new();
+    let backups = snapshot.dir().join("backups");
+
+    let mut backend = Backend::local(snapshot.path()).unwrap();
+    exercise(&mut backend).await;
+    let before = backend.list().await.unwrap();
+    let backup = backend.backup(&backups).unwrap();
+    assert!(backup.starts_with(&backups));
+
+    backend.add(ticket_draft()).await.unwrap();
+    assert_eq!(backend.restore(&backup).unwrap(), before.len());
+    assert_eq!(backend.list().await.unwrap(), before);
+    // The restored tickets are saved, too.
+    let mut reopened = Backend::local(snapshot.path()).unwrap();
+    assert_eq!(reopened.list().await.unwrap(), before);
+
+    assert!(backend
+        .restore(&snapshot.dir().join("missing.json"))
+        .is_err());
+    assert_eq!(backend.list().await.unwrap(), before);
+}
+
+#[tokio::test]
+async fn remote_backend() {
+    let (_client, server) = spawn_test_server().await;
+
+    let mut backend = Backend::remote(&server.addr().to_string()).await.unwrap();
+    exercise(&mut backend).await;
+    templates(&mut backend).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let err = backend.backup(dir.path()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Backups are only supported for local stores"
+    );
+}
+
+#[test]
+fn formatting() {
+    let mut store = ticket_core::TicketStore::new();
+    let id = store.add_ticket(ticket_draft());
+    let ticket = &store[id];
+    assert_eq!(
+        summary(ticket),
+        format!("#0 [ToDo] {}", ticket_title().as_ref())
+    );
+    assert!(details(ticket).ends_with(ticket_description().as_ref()));
+}
@@ -0,0 +1,76 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use ticket_cli::timeline;
+use ticket_core::{History, Ticket, TicketStore};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join(name)
+}
+
+fn tickets(store: &TicketStore) -> Vec<Ticket> {
+    store.iter().cloned().collect()
+}
+
+#[test]
+fn point_in_time_recovery() {
+    let history = History::read(&fixture("wal"), None).unwrap();
+    assert_eq!(history.last_seq(), 8);
+
+    let expected = TicketStore::load(&fixture("wal_at_6.json")).unwrap();
+    assert_eq!(tickets(&history.rebuild(6).unwrap()), tickets(&expected));
+    // Later records build on top of it.
+    let current = history.rebuild(8).unwrap();
+    assert_eq!(current.len(), 2);
+    assert_eq!(current[0.into()].assignee, None);
+    // The first records were compacted away.
+    assert!(history.rebuild(2).is_err());
+}
+
+#[test]
+fn the_timeline_describes_every_record() {
+    let history = History::read(&fixture("wal"), None).unwrap();
+    assert_eq!(
+        timeline(&history, 7),
+        [
+            "snapshot up to #3",
+            "#3 patched #0: status InProgress, assigned to alice (already in the snapshot)",
+            "#4 created #2 \"Slow search\"",
+            "#5 patched #1: status Done",
+            "#6 patched #0: title \"Crash on startup on Windows\"",
+            "#7 deleted #2",
+        ]
+    );
+    assert_eq!(
+        timeline(&history, 8).last().unwrap(),
+        "#8 patched #0: unassigned"
+    );
+}
+
+#[test]
+fn the_binary_saves_the_rebuilt_store() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("tickets.json");
+    let run = Command::new(env!("CARGO_BIN_EXE_replay"))
+        .arg(fixture("wal"))
+        .args(["--until", "6", "--output"])
+        .arg(&output)
+        .output()
+        .unwrap();
+    assert!(run.status.success());
+    let stdout = String::from_utf8(run.stdout).unwrap();
+    assert!(stdout.contains("#6 patched #0"));
+    assert!(!stdout.contains("#7"));
+    assert!(stdout.ends_with(&format!(
+        "Saved 3 tickets, as of #6, to {}\n",
+        output.display()
+    )));
+
+    let expected = TicketStore::load(&fixture("wal_at_6.json")).unwrap();
+    assert_eq!(
+        tickets(&TicketStore::load(&output).unwrap()),
+        tickets(&expected)
+    );
+}
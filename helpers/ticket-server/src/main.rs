@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Error};
+use clap::Parser;
+use tokio::net::TcpListener;
+
+use outro_08::{Server, WebhookUrl};
+use ticket_core::Config;
+
+/// Run the async ticket server.
+#[derive(Parser)]
+struct Cli {
+    /// A TOML config file. Settings it doesn't mention keep their default value.
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Load an unencrypted snapshot even though an encryption key is set, to encrypt it.
+    /// Only needed once, on the first start after setting the key.
+    #[arg(long)]
+    migrate_unencrypted: bool,
+}
+
+fn main() -> Result<(), Error> {
+    let cli = Cli::parse();
+    // Every request is logged, at the `info` level.
+    tracing_subscriber::fmt::init();
+    let config = match &cli.config {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(config.server.worker_threads)
+        .enable_all()
+        .build()
+        .context("Failed to start the async runtime")?
+        .block_on(run(config, cli.config, cli.migrate_unencrypted))
+}
+
+async fn run(
+    config: Config,
+    config_file: Option<PathBuf>,
+    migrate_unencrypted: bool,
+) -> Result<(), Error> {
+    let address = config.server.address;
+    let listener = TcpListener::bind(address)
+        .await
+        .with_context(|| format!("Failed to listen on {address}"))?;
+    let mut server = Server::new().settings(&config);
+    if let Some(path) = config_file {
+        server = server.reload_from(path);
+    }
+    for url in &config.server.webhooks {
+        let url: WebhookUrl = url.parse().context("Invalid `server.webhooks` entry")?;
+        server = server.webhook(url);
+    }
+    let key = config.server.encryption_key()?;
+    if let Some(path) = config.server.snapshot {
+        server = server
+            .persistent(path)
+            .compression(config.server.compression);
+        if let Some(key) = key {
+            server = server.encryption_key(key);
+            if migrate_unencrypted {
+                server = server.migrate_unencrypted();
+            }
+        }
+    }
+    tracing::info!(address = %listener.local_addr()?, "listening");
+    server.serve(listener).await?;
+    Ok(())
+}
@@ -0,0 +1,291 @@
+//! A terminal dashboard for the ticket store.
+//!
+//! This library holds the dashboard's state and logic, independent of any terminal:
+//! the UI itself lives in `main.rs`, behind the `tui` feature.
+//!
+//! The dashboard never reads tickets straight out of the store after startup. It keeps
+//! its own view, fed by the store's subscription API: whatever changes the store,
+//! the table reflects it on the next [`Dashboard::refresh`].
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::mpsc::Receiver;
+
+use anyhow::{anyhow, Error};
+use ticket_core::{
+    aggregate, Facet, FacetValue, Status, StoreEvent, Ticket, TicketId, TicketPatch, TicketStore,
+};
+use ticket_fields::render_chain;
+
+/// The order in which status groups are displayed.
+pub const STATUSES: [Status; 3] = [Status::ToDo, Status::InProgress, Status::Done];
+
+/// What the user can do, independently of the key it's bound to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Up,
+    Down,
+    /// Move the selected ticket to the next status: ToDo → InProgress → Done → ToDo.
+    CycleStatus,
+    /// Assign the selected ticket to the next person on the roster, then to no one.
+    CycleAssignee,
+}
+
+pub struct Dashboard {
+    path: PathBuf,
+    store: TicketStore,
+    events: Receiver<StoreEvent>,
+    view: BTreeMap<TicketId, Ticket>,
+    /// `view`, grouped by status. Rebuilt whenever `view` changes.
+    rows: Vec<Ticket>,
+    selected: usize,
+    roster: Vec<String>,
+}
+
+impl Dashboard {
+    /// Open the snapshot at `path` (or start from scratch, if there's none).
+    /// Tickets are assigned to the people in `roster`.
+    pub fn open(path: impl Into<PathBuf>, roster: Vec<String>) -> Result<Self, Error> {
+        let path = path.into();
+        let mut store =
+            TicketStore::load_or_default(&path).map_err(|e| anyhow!(render_chain(&e)))?;
+        let events = store.subscribe();
+        let view = store.iter().map(|t| (t.id, t.clone())).collect();
+        let mut dashboard = Self {
+            path,
+            store,
+            events,
+            view,
+            rows: Vec::new(),
+            selected: 0,
+            roster,
+        };
+        dashboard.regroup();
+        Ok(dashboard)
+    }
+
+    /// The tickets to display, grouped by status and ordered by id within each group.
+    pub fn rows(&self) -> &[Ticket] {
+        &self.rows
+    }
+
+    /// The index of the selected row.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected_ticket(&self) -> Option<&Ticket> {
+        self.rows.get(self.selected)
+    }
+
+    /// How many tickets are in each status group, in display order.
+    pub fn counts(&self) -> [(Status, usize); 3] {
+        let counts = aggregate::count(&self.rows, &Facet::Status.into(), |_| Vec::new());
+        STATUSES.map(|s| {
+            let count = counts.get(&vec![FacetValue::Status(s)]).copied();
+            (s, count.unwrap_or(0))
+        })
+    }
+
+    /// Direct access to the store, e.g. to add tickets.
+    /// Changes show up in the dashboard on the next [`Dashboard::refresh`], and are saved by it.
+    pub fn store_mut(&mut self) -> &mut TicketStore {
+        &mut self.store
+    }
+
+    pub fn handle(&mut self, action: Action) -> Result<(), Error> {
+        match action {
+            Action::Up => self.selected = self.selected.saturating_sub(1),
+            Action::Down => {
+                self.selected = (self.selected + 1).min(self.rows.len().saturating_sub(1))
+            }
+            Action::CycleStatus => {
+                if let Some(ticket) = self.selected_ticket() {
+                    let patch = TicketPatch {
+                        status: Some(next_status(ticket.status)),
+                        ..TicketPatch::new(ticket.id)
+                    };
+                    self.store.update(patch)?;
+                }
+            }
+            Action::CycleAssignee => {
+                if let Some(ticket) = self.selected_ticket() {
+                    let patch = TicketPatch {
+                        assignee: Some(
+                            next_assignee(&self.roster, ticket.assignee.as_deref()).map(Into::into),
+                        ),
+                        ..TicketPatch::new(ticket.id)
+                    };
+                    self.store.update(patch)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply the changes the store has reported since the last call, then save it.
+    /// Returns `false` if there was nothing new.
+    pub fn refresh(&mut self) -> Result<bool, Error> {
+        let selected_id = self.selected_ticket().map(|t| t.id);
+        let mut changed = false;
+        for event in self.events.try_iter() {
+            changed = true;
+            match event {
+                StoreEvent::Added(ticket) | StoreEvent::Updated(ticket) => {
+                    self.view.insert(ticket.id, ticket);
+                }
+                StoreEvent::Deleted(id) => {
+                    self.view.remove(&id);
+                }
+            }
+        }
+        if !changed {
+            return Ok(false);
+        }
+        self.regroup();
+        // Keep following the selected ticket when it moves to another group.
+        if let Some(position) = self.rows.iter().position(|t| Some(t.id) == selected_id) {
+            self.selected = position;
+        }
+        self.store
+            .save(&self.path)
+            .map_err(|e| anyhow!(render_chain(&e)))?;
+        Ok(true)
+    }
+
+    fn regroup(&mut self) {
+        self.rows = STATUSES
+            .iter()
+            .flat_map(|&status| self.view.values().filter(move |t| t.status == status))
+            .cloned()
+            .collect();
+        self.selected = self.selected.min(self.rows.len().saturating_sub(1));
+    }
+}
+
+pub fn next_status(status: Status) -> Status {
+    match status {
+        Status::ToDo => Status::InProgress,
+        Status::InProgress => Status::Done,
+        Status::Done => Status::ToDo,
+    }
+}
+
+/// The person after `current` on the roster, or no one after the last one.
+pub fn next_assignee(roster: &[String], current: Option<&str>) -> Option<String> {
+    let next = match current {
+        None => 0,
+        Some(current) => roster
+            .iter()
+            .position(|p| p == current)
+            .map_or(0, |i| i + 1),
+    };
+    roster.get(next).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ticket_fields::test_helpers::ticket_draft;
+
+    fn open(dir: &tempfile::TempDir) -> Dashboard {
+        let roster = vec!["Alice".to_string(), "Bob".to_string()];
+        Dashboard::open(dir.path().join("tickets.json"), roster).unwrap()
+    }
+
+    #[test]
+    fn rows_follow_store_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut dashboard = open(&dir);
+        assert!(!dashboard.refresh().unwrap());
+
+        let ids: Vec<_> = (0..3)
+            .map(|_| dashboard.store_mut().add_ticket(ticket_draft()))
+            .collect();
+        // Nothing changes until the events are processed.
+        assert!(dashboard.rows().is_empty());
+        assert!(dashboard.refresh().unwrap());
+        assert_eq!(dashboard.rows().len(), 3);
+
+        dashboard.store_mut().delete(ids[1]);
+        dashboard.refresh().unwrap();
+        let shown: Vec<_> = dashboard.rows().iter().map(|t| t.id).collect();
+        assert_eq!(shown, [ids[0], ids[2]]);
+    }
+
+    #[test]
+    fn status_changes_regroup_and_follow_the_selection() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut dashboard = open(&dir);
+        let first = dashboard.store_mut().add_ticket(ticket_draft());
+        let second = dashboard.store_mut().add_ticket(ticket_draft());
+        dashboard.refresh().unwrap();
+
+        // Moving the first ticket to `InProgress` puts it after the remaining `ToDo` one.
+        dashboard.handle(Action::CycleStatus).unwrap();
+        dashboard.refresh().unwrap();
+        let shown: Vec<_> = dashboard.rows().iter().map(|t| t.id).collect();
+        assert_eq!(shown, [second, first]);
+        assert_eq!(dashboard.selected_ticket().unwrap().id, first);
+        assert_eq!(
+            dashboard.counts(),
+            [
+                (Status::ToDo, 1),
+                (Status::InProgress, 1),
+                (Status::Done, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn assignee_cycles_through_the_roster() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut dashboard = open(&dir);
+        dashboard.store_mut().add_ticket(ticket_draft());
+        dashboard.refresh().unwrap();
+
+        let mut seen = Vec::new();
+        for _ in 0..3 {
+            dashboard.handle(Action::CycleAssignee).unwrap();
+            dashboard.refresh().unwrap();
+            seen.push(
+                dashboard
+                    .selected_ticket()
+                    .unwrap()
+                    .assignee
+                    .as_deref()
+                    .map(String::from),
+            );
+        }
+        assert_eq!(
+            seen,
+            [Some("Alice".to_string()), Some("Bob".to_string()), None]
+        );
+    }
+
+    #[test]
+    fn changes_are_saved() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut dashboard = open(&dir);
+        dashboard.store_mut().add_ticket(ticket_draft());
+        dashboard.handle(Action::Down).unwrap();
+        dashboard.refresh().unwrap();
+        dashboard.handle(Action::CycleStatus).unwrap();
+        dashboard.refresh().unwrap();
+
+        let reopened = open(&dir);
+        assert_eq!(reopened.rows()[0].status, Status::InProgress);
+    }
+
+    #[test]
+    fn selection_stays_in_bounds() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut dashboard = open(&dir);
+        dashboard.handle(Action::Down).unwrap();
+        dashboard.handle(Action::Up).unwrap();
+        assert_eq!(dashboard.selected(), 0);
+        assert!(dashboard.selected_ticket().is_none());
+        // Acting on nothing is a no-op.
+        dashboard.handle(Action::CycleStatus).unwrap();
+        assert!(!dashboard.refresh().unwrap());
+    }
+}
@@ -0,0 +1,97 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Error;
+use clap::Parser;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+
+use ticket_tui::{Action, Dashboard};
+
+/// A live dashboard of the tickets in a local snapshot file.
+#[derive(Parser)]
+struct Cli {
+    /// The snapshot file holding the tickets.
+    #[arg(long, default_value = "tickets.json")]
+    store: PathBuf,
+    /// The people tickets can be assigned to. Repeat it to add more than one.
+    #[arg(long)]
+    assignee: Vec<String>,
+}
+
+fn main() -> Result<(), Error> {
+    let cli = Cli::parse();
+    let dashboard = Dashboard::open(cli.store, cli.assignee)?;
+    let terminal = ratatui::init();
+    let result = run(terminal, dashboard);
+    ratatui::restore();
+    result
+}
+
+fn run(mut terminal: DefaultTerminal, mut dashboard: Dashboard) -> Result<(), Error> {
+    loop {
+        terminal.draw(|frame| draw(frame, &dashboard))?;
+        // Wake up regularly, even without input, to pick up store events.
+        if event::poll(Duration::from_millis(250))? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                let action = match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Up | KeyCode::Char('k') => Action::Up,
+                    KeyCode::Down | KeyCode::Char('j') => Action::Down,
+                    KeyCode::Char('s') => Action::CycleStatus,
+                    KeyCode::Char('a') => Action::CycleAssignee,
+                    _ => continue,
+                };
+                dashboard.handle(action)?;
+            }
+        }
+        dashboard.refresh()?;
+    }
+}
+
+fn draw(frame: &mut Frame, dashboard: &Dashboard) {
+    let [table_area, help_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+
+    let counts = dashboard
+        .counts()
+        .map(|(status, count)| format!("{status}: {count}"))
+        .join(" | ");
+    let rows = dashboard.rows().iter().map(|ticket| {
+        Row::new([
+            ticket.id.to_string(),
+            ticket.status.to_string(),
+            ticket.title.as_ref().to_string(),
+            ticket.assignee.clone().unwrap_or_else(|| "-".into()),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(6),
+            Constraint::Length(12),
+            Constraint::Fill(1),
+            Constraint::Length(16),
+        ],
+    )
+    .header(
+        Row::new(["ID", "Status", "Title", "Assignee"])
+            .style(Style::new().add_modifier(Modifier::BOLD)),
+    )
+    .block(Block::bordered().title(format!(" Tickets — {counts} ")))
+    .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    let mut state = TableState::new().with_selected(Some(dashboard.selected()));
+    frame.render_stateful_widget(table, table_area, &mut state);
+
+    frame.render_widget(
+        Line::from("↑/k ↓/j: move   s: next status   a: next assignee   q: quit"),
+        help_area,
+    );
+}
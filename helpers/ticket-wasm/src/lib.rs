@@ -0,0 +1,107 @@
+//! The finished ticket model, compiled to WebAssembly for a browser playground.
+//!
+//! Tickets go in and come out as JSON, encoded the same way as in the server protocol.
+//! Build it with `wasm-pack build helpers/ticket-wasm --target web`.
+use ticket_core::{Query, TicketDraft, TicketStore};
+use wasm_bindgen::prelude::*;
+
+/// A `TicketStore` that lives in the browser.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct Playground {
+    store: TicketStore,
+}
+
+#[wasm_bindgen]
+impl Playground {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a ticket from a draft, e.g. `{"title": "...", "description": "..."}`.
+    /// Returns the new ticket's id, a `BigInt` on the JavaScript side.
+    pub fn insert_json(&mut self, draft: &str) -> Result<u64, JsError> {
+        Ok(self.insert(draft)?)
+    }
+
+    /// The ticket with id `id`, or `undefined` if there's none.
+    pub fn get_json(&self, id: u64) -> Option<String> {
+        let ticket = self.store.get(id.into())?;
+        Some(serde_json::to_string(ticket).expect("Tickets can always be serialized"))
+    }
+
+    /// A JSON array of the tickets matching `query`, e.g. `{"status": "ToDo", "text": "crash"}`.
+    /// See `ticket_core::Query` for the available criteria.
+    pub fn query_json(&self, query: &str) -> Result<String, JsError> {
+        Ok(self.query(query)?)
+    }
+}
+
+// `JsError`s can only be created on `wasm32`: the logic lives in plain functions, so it can be
+// tested natively.
+impl Playground {
+    fn insert(&mut self, draft: &str) -> Result<u64, serde_json::Error> {
+        let draft: TicketDraft = serde_json::from_str(draft)?;
+        Ok(self.store.add_ticket(draft).value())
+    }
+
+    fn query(&self, query: &str) -> Result<String, serde_json::Error> {
+        let query: Query = serde_json::from_str(query)?;
+        let matching: Vec<_> = self.store.query(&query).collect();
+        serde_json::to_string(&matching)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use ticket_core::Ticket;
+
+    fn playground() -> Playground {
+        let mut playground = Playground::new();
+        for (title, description) in [
+            ("Crash on startup", "It panics"),
+            ("Dark mode", "Add a dark theme"),
+        ] {
+            let draft = serde_json::json!({"title": title, "description": description});
+            playground.insert(&draft.to_string()).unwrap();
+        }
+        playground
+    }
+
+    #[test]
+    fn insert_and_get() {
+        let playground = playground();
+        let ticket: Value = serde_json::from_str(&playground.get_json(1).unwrap()).unwrap();
+        assert_eq!(ticket["title"], "Dark mode");
+        assert_eq!(ticket["status"], "ToDo");
+        assert_eq!(playground.get_json(2), None);
+    }
+
+    #[test]
+    fn invalid_drafts_are_rejected() {
+        let mut playground = Playground::new();
+        let err = playground
+            .insert(r#"{"title": "", "description": "Something"}"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("The title cannot be empty"));
+        assert!(playground.insert("not json").is_err());
+    }
+
+    #[test]
+    fn queries() {
+        let playground = playground();
+        let titles = |query: &str| -> Vec<String> {
+            let tickets: Vec<Ticket> =
+                serde_json::from_str(&playground.query(query).unwrap()).unwrap();
+            tickets.into_iter().map(|t| t.title.into()).collect()
+        };
+        assert_eq!(titles("{}").len(), 2);
+        assert_eq!(titles(r#"{"text": "PANIC"}"#), ["Crash on startup"]);
+        assert!(titles(r#"{"status": "Done"}"#).is_empty());
+        assert!(titles(r#"{"assignee": "alice"}"#).is_empty());
+        assert!(playground.query(r#"{"colour": "red"}"#).is_err());
+    }
+}
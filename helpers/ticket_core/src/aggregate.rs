@@ -0,0 +1,255 @@
+//! Counting tickets by facet: how many there are in each status, for each assignee, with
+//! each tag... or for each combination of those, e.g. each status × assignee pair.
+//!
+//! A [`GroupBy`] lists the facets to group tickets by. Each group is identified by a [`Key`],
+//! holding one [`FacetValue`] per facet, in the same order. Tickets can be in several groups
+//! at once: one with two tags counts towards both when grouping by tag. Groups that no
+//! ticket falls into are left out.
+//!
+//! Tickets don't have a priority of their own: it's a custom field like any other, see
+//! [`Facet::Field`].
+use std::collections::HashMap;
+
+use crate::custom_fields::FieldValue;
+use crate::data::{Assignee, Status, Ticket, TicketId};
+use crate::store::TicketStore;
+
+/// Something to group tickets by.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Facet {
+    Status,
+    Assignee,
+    Tag,
+    /// The named custom field, e.g. `Facet::Field("priority".into())`.
+    Field(String),
+}
+
+/// Which group a ticket falls into, for one facet.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FacetValue {
+    Status(Status),
+    /// `None` for unassigned tickets.
+    Assignee(Option<Assignee>),
+    /// `None` for untagged tickets.
+    Tag(Option<String>),
+    /// `None` for tickets that don't set the field.
+    Field(Option<FieldValue>),
+}
+
+/// One value per facet of the `GroupBy`, in the same order.
+pub type Key = Vec<FacetValue>;
+
+/// The facets to group tickets by, for [`TicketStore::aggregate`]. See the [module docs](self).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GroupBy {
+    facets: Vec<Facet>,
+}
+
+impl GroupBy {
+    pub fn new(facet: Facet) -> Self {
+        Self {
+            facets: vec![facet],
+        }
+    }
+
+    /// Split every group further, by `facet`.
+    pub fn and(mut self, facet: Facet) -> Self {
+        self.facets.push(facet);
+        self
+    }
+
+    pub fn facets(&self) -> &[Facet] {
+        &self.facets
+    }
+}
+
+impl From<Facet> for GroupBy {
+    fn from(facet: Facet) -> Self {
+        Self::new(facet)
+    }
+}
+
+/// Like [`TicketStore::aggregate`], for tickets that aren't read straight out of a store,
+/// e.g. a view of one that's kept up to date with its events.
+///
+/// `tags` gives the tags of a ticket. It's only called when grouping by tag.
+pub fn count<'a, 't>(
+    tickets: impl IntoIterator<Item = &'a Ticket>,
+    group_by: &GroupBy,
+    tags: impl Fn(TicketId) -> Vec<&'t str>,
+) -> HashMap<Key, usize> {
+    let mut counts = HashMap::new();
+    for ticket in tickets {
+        let mut keys: Vec<Key> = vec![Vec::with_capacity(group_by.facets.len())];
+        for facet in &group_by.facets {
+            let values = match facet {
+                Facet::Status => vec![FacetValue::Status(ticket.status)],
+                Facet::Assignee => vec![FacetValue::Assignee(ticket.assignee.clone())],
+                Facet::Tag => {
+                    let tags = tags(ticket.id);
+                    if tags.is_empty() {
+                        vec![FacetValue::Tag(None)]
+                    } else {
+                        tags.into_iter()
+                            .map(|tag| FacetValue::Tag(Some(tag.to_owned())))
+                            .collect()
+                    }
+                }
+                Facet::Field(name) => {
+                    vec![FacetValue::Field(ticket.custom_fields.get(name).cloned())]
+                }
+            };
+            keys = keys
+                .iter()
+                .flat_map(|key| {
+                    values.iter().map(move |value| {
+                        let mut key = key.clone();
+                        key.push(value.clone());
+                        key
+                    })
+                })
+                .collect();
+        }
+        for key in keys {
+            *counts.entry(key).or_default() += 1;
+        }
+    }
+    counts
+}
+
+impl TicketStore {
+    /// How many tickets fall into each group. See the [module docs](crate::aggregate).
+    pub fn aggregate(&self, group_by: &GroupBy) -> HashMap<Key, usize> {
+        count(self.iter(), group_by, |id| self.tags(id).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::custom_fields::{CustomFields, FieldSchema, FieldType};
+    use crate::data::{TicketDraft, TicketPatch};
+
+    /// Four tickets, in every status, two of them assigned, three of them tagged,
+    /// and two with a priority.
+    fn store() -> TicketStore {
+        let mut store = TicketStore::new();
+        let schema = FieldSchema::new().with("priority", FieldType::Integer);
+        store.set_schema(schema).unwrap();
+        let tickets = [
+            (Status::ToDo, None, &["bug"][..], Some(1)),
+            (Status::InProgress, Some("alice"), &["bug", "ui"], Some(2)),
+            (Status::InProgress, Some("alice"), &[], None),
+            (Status::Done, Some("bob"), &["ui"], Some(1)),
+        ];
+        for (status, assignee, tags, priority) in tickets {
+            let draft = TicketDraft::new("A title".into(), "A description".into()).unwrap();
+            let fields: CustomFields = priority.map(|p| ("priority", p)).into_iter().collect();
+            let id = store.add_ticket_with_fields(draft, fields).unwrap();
+            store
+                .update(TicketPatch {
+                    status: Some(status),
+                    assignee: Some(assignee.map(Assignee::from)),
+                    ..TicketPatch::new(id)
+                })
+                .unwrap();
+            for tag in tags {
+                store.add_tag(id, tag).unwrap();
+            }
+        }
+        store
+    }
+
+    fn status(status: Status) -> FacetValue {
+        FacetValue::Status(status)
+    }
+
+    fn assignee(name: Option<&str>) -> FacetValue {
+        FacetValue::Assignee(name.map(Assignee::from))
+    }
+
+    fn tag(tag: Option<&str>) -> FacetValue {
+        FacetValue::Tag(tag.map(String::from))
+    }
+
+    #[test]
+    fn grouping_by_a_single_facet() {
+        let store = store();
+        let by_status = store.aggregate(&Facet::Status.into());
+        assert_eq!(
+            by_status,
+            HashMap::from([
+                (vec![status(Status::ToDo)], 1),
+                (vec![status(Status::InProgress)], 2),
+                (vec![status(Status::Done)], 1),
+            ])
+        );
+
+        let by_assignee = store.aggregate(&Facet::Assignee.into());
+        assert_eq!(by_assignee[&vec![assignee(None)]], 1);
+        assert_eq!(by_assignee[&vec![assignee(Some("alice"))]], 2);
+        assert_eq!(by_assignee[&vec![assignee(Some("bob"))]], 1);
+
+        let by_priority = store.aggregate(&Facet::Field("priority".into()).into());
+        let priority = |p: Option<i64>| vec![FacetValue::Field(p.map(FieldValue::from))];
+        assert_eq!(by_priority[&priority(Some(1))], 2);
+        assert_eq!(by_priority[&priority(Some(2))], 1);
+        assert_eq!(by_priority[&priority(None)], 1);
+    }
+
+    #[test]
+    fn tickets_count_towards_each_of_their_tags() {
+        let by_tag = store().aggregate(&Facet::Tag.into());
+        assert_eq!(
+            by_tag,
+            HashMap::from([
+                (vec![tag(Some("bug"))], 2),
+                (vec![tag(Some("ui"))], 2),
+                (vec![tag(None)], 1),
+            ])
+        );
+        // More than there are tickets.
+        assert_eq!(by_tag.values().sum::<usize>(), 5);
+    }
+
+    #[test]
+    fn combined_facets() {
+        let store = store();
+        let by_status_and_assignee =
+            store.aggregate(&GroupBy::new(Facet::Status).and(Facet::Assignee));
+        assert_eq!(
+            by_status_and_assignee,
+            HashMap::from([
+                (vec![status(Status::ToDo), assignee(None)], 1),
+                (vec![status(Status::InProgress), assignee(Some("alice"))], 2),
+                (vec![status(Status::Done), assignee(Some("bob"))], 1),
+            ])
+        );
+
+        let by_assignee_and_tag = store.aggregate(&GroupBy::new(Facet::Assignee).and(Facet::Tag));
+        assert_eq!(by_assignee_and_tag.len(), 5);
+        assert_eq!(
+            by_assignee_and_tag[&vec![assignee(Some("alice")), tag(Some("bug"))]],
+            1
+        );
+        assert_eq!(
+            by_assignee_and_tag[&vec![assignee(Some("alice")), tag(None)]],
+            1
+        );
+        assert_eq!(
+            by_assignee_and_tag[&vec![assignee(Some("bob")), tag(Some("ui"))]],
+            1
+        );
+    }
+
+    #[test]
+    fn counting_tickets_outside_a_store() {
+        let store = store();
+        let done: Vec<_> = store.with_status(Status::Done).collect();
+        let counts = count(done, &Facet::Tag.into(), |_| Vec::new());
+        assert_eq!(counts, HashMap::from([(vec![tag(None)], 1)]));
+        assert!(TicketStore::new()
+            .aggregate(&Facet::Status.into())
+            .is_empty());
+    }
+}
@@ -0,0 +1,130 @@
+//! Users, roles and tokens, shared by the ticket servers.
+//!
+//! A server can be given a set of [`Users`], each identified by a secret token.
+//! Every request is then checked against the role of the user whose token it carries:
+//! readers can only look, writers can change tickets, admins can do everything.
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Roles are ordered: each one can do everything the previous one can.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Can read tickets.
+    Reader,
+    /// Can also create, update and delete tickets.
+    Writer,
+    /// Can also manage the server itself.
+    Admin,
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Role::Reader => "reader",
+            Role::Writer => "writer",
+            Role::Admin => "admin",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct User {
+    pub name: String,
+    pub role: Role,
+}
+
+impl User {
+    /// Succeeds if this user's role is at least `required`.
+    pub fn authorize(&self, required: Role) -> Result<(), AuthError> {
+        if self.role >= required {
+            Ok(())
+        } else {
+            Err(AuthError::Forbidden {
+                user: self.name.clone(),
+                role: self.role,
+                required,
+            })
+        }
+    }
+}
+
+/// The users known to a server, by token.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Users(HashMap<String, User>);
+
+impl Users {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `user`, who will authenticate with `token`.
+    /// Replaces whoever had that token before.
+    pub fn insert(&mut self, token: impl Into<String>, user: User) {
+        self.0.insert(token.into(), user);
+    }
+
+    pub fn authenticate(&self, token: &str) -> Result<&User, AuthError> {
+        self.0.get(token).ok_or(AuthError::InvalidToken)
+    }
+}
+
+/// Why a request was refused.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum AuthError {
+    #[error("Authenticate before sending any other command")]
+    Unauthenticated,
+    #[error("The token doesn't belong to any user")]
+    InvalidToken,
+    #[error("This command requires the {required} role, but {user} is a {role}")]
+    Forbidden {
+        user: String,
+        role: Role,
+        required: Role,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(role: Role) -> User {
+        User {
+            name: "alice".into(),
+            role,
+        }
+    }
+
+    #[test]
+    fn roles_are_ordered() {
+        assert!(Role::Reader < Role::Writer);
+        assert!(Role::Writer < Role::Admin);
+    }
+
+    #[test]
+    fn role_boundaries() {
+        assert!(user(Role::Reader).authorize(Role::Reader).is_ok());
+        assert_eq!(
+            user(Role::Reader).authorize(Role::Writer),
+            Err(AuthError::Forbidden {
+                user: "alice".into(),
+                role: Role::Reader,
+                required: Role::Writer
+            })
+        );
+        assert!(user(Role::Writer).authorize(Role::Writer).is_ok());
+        assert!(user(Role::Writer).authorize(Role::Admin).is_err());
+        assert!(user(Role::Admin).authorize(Role::Admin).is_ok());
+    }
+
+    #[test]
+    fn tokens() {
+        let mut users = Users::new();
+        users.insert("s3cret", user(Role::Writer));
+        assert_eq!(users.authenticate("s3cret"), Ok(&user(Role::Writer)));
+        assert_eq!(users.authenticate("nope"), Err(AuthError::InvalidToken));
+    }
+}
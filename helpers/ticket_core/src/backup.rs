@@ -0,0 +1,323 @@
+//! Point-in-time backups of a `TicketStore`.
+//!
+//! Unlike a snapshot, which is overwritten on every save, every backup is a new file in the
+//! backup directory: `backup-<milliseconds since the Unix epoch>.json`, as told by the store's
+//! clock, or the system's if it has none. Besides the snapshot
+//! itself, it records some metadata—most importantly the version of the backup format, so that
+//! restoring a backup written by a newer version of this crate fails cleanly instead of
+//! misreading it.
+//!
+//! Backups are compressed and encrypted like snapshots, according to an [`Encoding`]. Reading
+//! them back detects how they were, provided the key is right.
+use crate::clock::{Clock, SystemClock};
+use crate::snapshot::{parse, read_json_if_exists, write_atomically, Encoding, Snapshot};
+use crate::store::TicketStore;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use ticket_fields::{Context, ContextError};
+
+/// The version of the backup format written by [`TicketStore::backup_to`].
+/// Restoring accepts it and every older one.
+pub const BACKUP_VERSION: u32 = 1;
+
+const PREFIX: &str = "backup-";
+const EXTENSION: &str = "json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupMetadata {
+    pub version: u32,
+    /// When the backup was taken, in milliseconds since the Unix epoch.
+    pub created_at_ms: u64,
+    /// How many tickets the backup holds.
+    pub tickets: usize,
+}
+
+impl BackupMetadata {
+    pub fn created_at(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.created_at_ms)
+    }
+}
+
+/// A backup found by [`list_backups`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupInfo {
+    pub path: PathBuf,
+    pub metadata: BackupMetadata,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Archive {
+    #[serde(flatten)]
+    metadata: BackupMetadata,
+    snapshot: Snapshot,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RestoreError {
+    #[error("Version {found} of the backup format is not supported (only up to {BACKUP_VERSION})")]
+    UnsupportedVersion { found: u32 },
+    #[error("The backup should hold {expected} tickets, but it holds {found}")]
+    Inconsistent { expected: usize, found: usize },
+}
+
+impl TicketStore {
+    /// Write a backup of the whole store to a new file in `dir`, creating `dir` if needed.
+    /// Returns the path of the backup.
+    pub fn backup_to(&self, dir: &Path, encoding: &Encoding) -> Result<PathBuf, ContextError> {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        let now = self.now().unwrap_or_else(|| SystemClock.now());
+        // Two backups taken within the same millisecond still get a file each.
+        let mut created_at_ms = now.as_millis();
+        let path = loop {
+            let path = dir.join(format!("{PREFIX}{created_at_ms:013}.{EXTENSION}"));
+            if !path.exists() {
+                break path;
+            }
+            created_at_ms += 1;
+        };
+        let archive = Archive {
+            metadata: BackupMetadata {
+                version: BACKUP_VERSION,
+                created_at_ms,
+                tickets: self.len(),
+            },
+            snapshot: Snapshot::of(self),
+        };
+        write_atomically(&path, &archive, encoding)?;
+        Ok(path)
+    }
+
+    /// Rebuild a store from a backup written by [`TicketStore::backup_to`], with the same
+    /// key if it was encrypted.
+    pub fn restore_from(path: &Path, encoding: &Encoding) -> Result<Self, ContextError> {
+        let invalid = || format!("Can't restore the backup at {}", path.display());
+        let json = read(path, encoding)?;
+        // Check the version before trying to make sense of the rest.
+        let metadata: BackupMetadata = parse(path, &json)?;
+        if metadata.version > BACKUP_VERSION {
+            return Err(RestoreError::UnsupportedVersion {
+                found: metadata.version,
+            })
+            .with_context(invalid);
+        }
+        let archive: Archive = parse(path, &json)?;
+        let store = archive.snapshot.into_store();
+        if store.len() != archive.metadata.tickets {
+            return Err(RestoreError::Inconsistent {
+                expected: archive.metadata.tickets,
+                found: store.len(),
+            })
+            .with_context(invalid);
+        }
+        Ok(store)
+    }
+}
+
+/// Every backup in `dir`, oldest first. Other files are ignored.
+/// A directory that doesn't exist has no backups.
+///
+/// Backups that can't be read, or decoded with `encoding`, are skipped with a warning: one
+/// corrupt file doesn't hide the others.
+pub fn list_backups(dir: &Path, encoding: &Encoding) -> Result<Vec<BackupInfo>, ContextError> {
+    let entries = match fs::read_dir(dir) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        entries => entries.with_context(|| format!("Failed to list {}", dir.display()))?,
+    };
+    let mut backups = Vec::new();
+    for entry in entries {
+        let path = entry
+            .with_context(|| format!("Failed to list {}", dir.display()))?
+            .path();
+        let is_backup = path.extension().is_some_and(|e| e == EXTENSION)
+            && path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(PREFIX));
+        if !is_backup {
+            continue;
+        }
+        match read(&path, encoding).and_then(|json| parse(&path, &json)) {
+            Ok(metadata) => backups.push(BackupInfo { path, metadata }),
+            Err(error) => {
+                tracing::warn!(%error, "skipped an unreadable backup");
+            }
+        }
+    }
+    backups.sort_by(|a, b| {
+        (a.metadata.created_at_ms, &a.path).cmp(&(b.metadata.created_at_ms, &b.path))
+    });
+    Ok(backups)
+}
+
+/// The backup at `path`, as plain JSON.
+fn read(path: &Path, encoding: &Encoding) -> Result<Vec<u8>, ContextError> {
+    read_json_if_exists(path, encoding)?
+        .ok_or_else(|| std::io::Error::from(ErrorKind::NotFound))
+        .with_context(|| format!("Failed to read the backup at {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{TestClock, Timestamp};
+    use crate::data::{Status, TicketPatch};
+    use crate::repository::conformance::draft;
+
+    fn plain() -> Encoding {
+        Encoding::default()
+    }
+
+    fn store(tickets: usize) -> TicketStore {
+        let mut store = TicketStore::new();
+        for _ in 0..tickets {
+            store.add_ticket(draft());
+        }
+        store
+    }
+
+    #[test]
+    fn backup_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = store(3);
+        store
+            .update(TicketPatch {
+                status: Some(Status::Done),
+                ..TicketPatch::new(0.into())
+            })
+            .unwrap();
+        store.add_tag(1.into(), "bug").unwrap();
+        store.delete(2.into());
+
+        let path = store
+            .backup_to(&dir.path().join("backups"), &plain())
+            .unwrap();
+        let mut restored = TicketStore::restore_from(&path, &plain()).unwrap();
+        assert_eq!(
+            restored.iter().collect::<Vec<_>>(),
+            store.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(restored.tags(1.into()).collect::<Vec<_>>(), ["bug"]);
+        assert_eq!(restored.add_ticket(draft()), 3.into());
+    }
+
+    #[test]
+    fn listing_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(list_backups(&dir.path().join("missing"), &plain())
+            .unwrap()
+            .is_empty());
+
+        // Backups are named after the time on the store's clock, which doesn't move here.
+        let clock = TestClock::starting_at(Timestamp::from_millis(1_700_000_000_000));
+        let paths: Vec<_> = (0..3)
+            .map(|tickets| {
+                let mut store = store(tickets);
+                store.set_clock(clock.clone());
+                store.backup_to(dir.path(), &plain()).unwrap()
+            })
+            .collect();
+        fs::write(dir.path().join("notes.json"), "{}").unwrap();
+        fs::write(dir.path().join("backup-notes.txt"), "").unwrap();
+
+        let backups = list_backups(dir.path(), &plain()).unwrap();
+        assert_eq!(
+            backups.iter().map(|b| &b.path).collect::<Vec<_>>(),
+            paths.iter().collect::<Vec<_>>()
+        );
+        for (tickets, backup) in backups.iter().enumerate() {
+            assert_eq!(backup.metadata.tickets, tickets);
+            assert_eq!(backup.metadata.version, BACKUP_VERSION);
+        }
+        // Even when they're taken within the same millisecond, backups get distinct,
+        // increasing timestamps.
+        let names: Vec<_> = paths.iter().map(|p| p.file_name().unwrap()).collect();
+        assert_eq!(
+            names,
+            [
+                "backup-1700000000000.json",
+                "backup-1700000000001.json",
+                "backup-1700000000002.json"
+            ]
+        );
+        assert_eq!(backups[2].metadata.created_at_ms, 1_700_000_000_002);
+        assert_eq!(
+            backups[0].metadata.created_at(),
+            UNIX_EPOCH + Duration::from_millis(1_700_000_000_000)
+        );
+    }
+
+    #[test]
+    fn unreadable_backups_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = store(1).backup_to(dir.path(), &plain()).unwrap();
+        fs::write(dir.path().join("backup-0000000000000.json"), "{").unwrap();
+        let truncated = dir.path().join("backup-0000000000001.json");
+        fs::write(&truncated, "{\"version\":1").unwrap();
+
+        let backups = list_backups(dir.path(), &plain()).unwrap();
+        assert_eq!(backups.iter().map(|b| &b.path).collect::<Vec<_>>(), [&path]);
+        assert!(TicketStore::restore_from(&truncated, &plain()).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "gzip")]
+    fn compressed_backups() {
+        use crate::compression::Compression;
+        let dir = tempfile::tempdir().unwrap();
+        let gzipped = Encoding {
+            compression: Compression::Gzip,
+            ..Encoding::default()
+        };
+        let path = store(3).backup_to(dir.path(), &gzipped).unwrap();
+        assert!(crate::compression::is_compressed(&fs::read(&path).unwrap()));
+
+        // Compression is detected: the encoding only matters for its key.
+        let restored = TicketStore::restore_from(&path, &plain()).unwrap();
+        assert_eq!(restored.len(), 3);
+        let backups = list_backups(dir.path(), &plain()).unwrap();
+        assert_eq!(backups[0].metadata.tickets, 3);
+    }
+
+    #[test]
+    fn newer_versions_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = store(1).backup_to(dir.path(), &plain()).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        // The snapshot of a future version may not even look like one.
+        let future = content
+            .replacen(r#""version":1"#, r#""version":2"#, 1)
+            .replacen(r#""tickets":["#, r#""tickets_v2":["#, 1);
+        fs::write(&path, future).unwrap();
+
+        let err = TicketStore::restore_from(&path, &plain()).unwrap_err();
+        assert!(err.context().starts_with("Can't restore the backup"));
+        let source = std::error::Error::source(&err).unwrap();
+        assert!(matches!(
+            source.downcast_ref(),
+            Some(RestoreError::UnsupportedVersion { found: 2 })
+        ));
+    }
+
+    #[test]
+    fn inconsistent_backups_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = store(2).backup_to(dir.path(), &plain()).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        fs::write(
+            &path,
+            content.replacen(r#""tickets":2"#, r#""tickets":3"#, 1),
+        )
+        .unwrap();
+
+        let err = TicketStore::restore_from(&path, &plain()).unwrap_err();
+        let source = std::error::Error::source(&err).unwrap();
+        assert_eq!(
+            source.to_string(),
+            "The backup should hold 3 tickets, but it holds 2"
+        );
+        assert!(TicketStore::restore_from(&dir.path().join("missing.json"), &plain()).is_err());
+    }
+}
@@ -0,0 +1,622 @@
+//! The channel-based ticket server built throughout the threads chapter:
+//! a single thread owns the `TicketStore`, clients talk to it over a bounded channel.
+//!
+//! A server started with [`launch_authenticated`] only serves clients holding a valid token
+//! (see [`TicketStoreClient::with_token`]), and checks every command against their role.
+//! One started with [`launch_logged`] reports every command it handles to a
+//! [`CommandLogger`](crate::command_log::CommandLogger).
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::time::Instant;
+
+use parallel::IntoChunks;
+use tracing::Span;
+
+use crate::auth::{AuthError, Role, Users};
+use crate::clock::{Clock, SystemClock, Timestamp};
+use crate::command_log::{CommandLogger, CommandRecord, Outcome};
+use crate::data::{Ticket, TicketDraft, TicketPatch};
+use crate::store::{TicketId, TicketNotFound, TicketStore};
+
+/// The most drafts [`TicketStoreClient::insert_batch`] sends in a single command.
+pub const MAX_BATCH_LEN: usize = 1000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("The store is overloaded")]
+    Overloaded,
+    #[error("The store has shut down")]
+    Disconnected,
+    #[error(transparent)]
+    NotFound(#[from] TicketNotFound),
+    #[error(transparent)]
+    Denied(#[from] AuthError),
+}
+
+#[derive(Clone)]
+pub struct TicketStoreClient {
+    sender: SyncSender<Request>,
+    token: Option<Arc<str>>,
+}
+
+impl TicketStoreClient {
+    /// A client that presents `token` with every command.
+    pub fn with_token(&self, token: impl Into<Arc<str>>) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            token: Some(token.into()),
+        }
+    }
+
+    pub fn insert(&self, draft: TicketDraft) -> Result<TicketId, ClientError> {
+        self.request(|response_channel| Command::Insert {
+            draft,
+            response_channel,
+        })
+    }
+
+    /// Insert the drafts [`MAX_BATCH_LEN`] at a time, instead of one round-trip per ticket:
+    /// a huge batch doesn't keep the server from answering other clients in the meantime.
+    /// The ids are in the same order as `drafts`.
+    ///
+    /// If a command fails, the drafts sent with the previous ones are still inserted.
+    pub fn insert_batch(&self, drafts: Vec<TicketDraft>) -> Result<Vec<TicketId>, ClientError> {
+        let mut ids = Vec::with_capacity(drafts.len());
+        for drafts in IntoChunks::new(drafts, MAX_BATCH_LEN) {
+            ids.extend(self.request(|response_channel| Command::InsertBatch {
+                drafts,
+                response_channel,
+            })?);
+        }
+        Ok(ids)
+    }
+
+    pub fn get(&self, id: TicketId) -> Result<Option<Ticket>, ClientError> {
+        self.request(|response_channel| Command::Get {
+            id,
+            response_channel,
+        })
+    }
+
+    pub fn update(&self, patch: TicketPatch) -> Result<(), ClientError> {
+        self.request(|response_channel| Command::Update {
+            patch,
+            response_channel,
+        })?
+        .map_err(ClientError::from)
+    }
+
+    /// Apply `patch` only if the ticket's `updated_at` is still `read`'s, returning whether it
+    /// was: see [`TicketStore::update_if_unchanged`].
+    pub fn update_if_unchanged(
+        &self,
+        patch: TicketPatch,
+        read: Option<Timestamp>,
+    ) -> Result<bool, ClientError> {
+        self.request(|response_channel| Command::UpdateIfUnchanged {
+            patch,
+            read,
+            response_channel,
+        })?
+        .map_err(ClientError::from)
+    }
+
+    pub fn list(&self) -> Result<Vec<Ticket>, ClientError> {
+        self.request(|response_channel| Command::List { response_channel })
+    }
+
+    fn request<T>(&self, command: impl FnOnce(Reply<T>) -> Command) -> Result<T, ClientError> {
+        let (response_sender, response_receiver) = sync_channel(1);
+        let request = Request {
+            token: self.token.clone(),
+            command: command(response_sender),
+        };
+        self.sender.try_send(request).map_err(|e| match e {
+            TrySendError::Full(_) => ClientError::Overloaded,
+            TrySendError::Disconnected(_) => ClientError::Disconnected,
+        })?;
+        Ok(response_receiver
+            .recv()
+            .map_err(|_| ClientError::Disconnected)??)
+    }
+}
+
+/// Spawn the server thread, returning a client to talk to it.
+/// `capacity` is the number of commands that can be queued before clients
+/// start getting `ClientError::Overloaded`.
+pub fn launch(capacity: usize) -> TicketStoreClient {
+    spawn(capacity, None, None, Arc::new(SystemClock))
+}
+
+/// Like [`launch`], but the server tells the time with `clock`: it's when tickets are
+/// recorded as created, see [`TicketStore::with_clock`].
+pub fn launch_with_clock(capacity: usize, clock: impl Clock + 'static) -> TicketStoreClient {
+    spawn(capacity, None, None, Arc::new(clock))
+}
+
+/// Like [`launch`], but commands are only accepted from `users`, within the limits of their role.
+/// The returned client has no token: use [`TicketStoreClient::with_token`] to get one that does.
+pub fn launch_authenticated(capacity: usize, users: Users) -> TicketStoreClient {
+    spawn(capacity, Some(users), None, Arc::new(SystemClock))
+}
+
+/// Like [`launch`], or [`launch_authenticated`] if there are `users`, but every command
+/// is reported to `logger` once it's been answered—rejected ones included.
+pub fn launch_logged(
+    capacity: usize,
+    users: Option<Users>,
+    logger: impl CommandLogger + 'static,
+) -> TicketStoreClient {
+    spawn(
+        capacity,
+        users,
+        Some(Box::new(logger)),
+        Arc::new(SystemClock),
+    )
+}
+
+fn spawn(
+    capacity: usize,
+    users: Option<Users>,
+    logger: Option<Box<dyn CommandLogger>>,
+    clock: Arc<dyn Clock>,
+) -> TicketStoreClient {
+    let (sender, receiver) = sync_channel(capacity);
+    let store = TicketStore::with_clock(clock);
+    std::thread::spawn(move || server(receiver, store, users, logger));
+    TicketStoreClient {
+        sender,
+        token: None,
+    }
+}
+
+/// Commands are answered with their result, or with the reason they were refused.
+type Reply<T> = SyncSender<Result<T, AuthError>>;
+
+struct Request {
+    token: Option<Arc<str>>,
+    command: Command,
+}
+
+enum Command {
+    Insert {
+        draft: TicketDraft,
+        response_channel: Reply<TicketId>,
+    },
+    InsertBatch {
+        drafts: Vec<TicketDraft>,
+        response_channel: Reply<Vec<TicketId>>,
+    },
+    Get {
+        id: TicketId,
+        response_channel: Reply<Option<Ticket>>,
+    },
+    Update {
+        patch: TicketPatch,
+        response_channel: Reply<Result<(), TicketNotFound>>,
+    },
+    UpdateIfUnchanged {
+        patch: TicketPatch,
+        read: Option<Timestamp>,
+        response_channel: Reply<Result<bool, TicketNotFound>>,
+    },
+    List {
+        response_channel: Reply<Vec<Ticket>>,
+    },
+}
+
+impl Command {
+    /// The least privileged role allowed to send this command.
+    fn required_role(&self) -> Role {
+        match self {
+            Command::Get { .. } | Command::List { .. } => Role::Reader,
+            Command::Insert { .. }
+            | Command::InsertBatch { .. }
+            | Command::Update { .. }
+            | Command::UpdateIfUnchanged { .. } => Role::Writer,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Command::Insert { .. } => "insert",
+            Command::InsertBatch { .. } => "insert_batch",
+            Command::Get { .. } => "get",
+            Command::Update { .. } => "update",
+            Command::UpdateIfUnchanged { .. } => "update_if_unchanged",
+            Command::List { .. } => "list",
+        }
+    }
+
+    /// The span that every event about this command is recorded in.
+    fn span(&self) -> Span {
+        let ticket_id = match self {
+            // The id of an inserted ticket is recorded once it's known.
+            Command::Insert { .. } | Command::InsertBatch { .. } | Command::List { .. } => None,
+            Command::Get { id, .. } => Some(id.value()),
+            Command::Update { patch, .. } | Command::UpdateIfUnchanged { patch, .. } => {
+                Some(patch.id.value())
+            }
+        };
+        tracing::info_span!("command", command = self.name(), ticket_id)
+    }
+
+    /// Answer with `error` instead of executing the command.
+    /// Like every other answer, it's lost if the client has gone away in the meantime.
+    fn reject(self, error: AuthError) {
+        match self {
+            Command::Insert {
+                response_channel, ..
+            } => {
+                let _ = response_channel.send(Err(error));
+            }
+            Command::InsertBatch {
+                response_channel, ..
+            } => {
+                let _ = response_channel.send(Err(error));
+            }
+            Command::Get {
+                response_channel, ..
+            } => {
+                let _ = response_channel.send(Err(error));
+            }
+            Command::Update {
+                response_channel, ..
+            } => {
+                let _ = response_channel.send(Err(error));
+            }
+            Command::UpdateIfUnchanged {
+                response_channel, ..
+            } => {
+                let _ = response_channel.send(Err(error));
+            }
+            Command::List { response_channel } => {
+                let _ = response_channel.send(Err(error));
+            }
+        }
+    }
+}
+
+/// Check that `token` belongs to a user allowed to send `command`.
+/// Without users, every command is allowed.
+fn authorize(
+    users: Option<&Users>,
+    token: Option<&str>,
+    command: &Command,
+) -> Result<(), AuthError> {
+    let Some(users) = users else {
+        return Ok(());
+    };
+    let token = token.ok_or(AuthError::Unauthenticated)?;
+    users
+        .authenticate(token)?
+        .authorize(command.required_role())
+}
+
+fn server(
+    receiver: Receiver<Request>,
+    mut store: TicketStore,
+    users: Option<Users>,
+    mut logger: Option<Box<dyn CommandLogger>>,
+) {
+    // The loop ends when all clients have been dropped.
+    while let Ok(Request { token, command }) = receiver.recv() {
+        let span = command.span();
+        let _entered = span.enter();
+        let started = Instant::now();
+        let name = command.name();
+        let outcome = match authorize(users.as_ref(), token.as_deref(), &command) {
+            Ok(()) => execute(&mut store, command),
+            Err(error) => {
+                tracing::warn!(%error, "command rejected");
+                command.reject(error);
+                Outcome::Denied
+            }
+        };
+        let latency = started.elapsed();
+        if outcome != Outcome::Denied {
+            tracing::info!(latency_us = latency.as_micros() as u64, "command handled");
+        }
+        if let Some(logger) = &mut logger {
+            logger.log(&CommandRecord {
+                command: name,
+                latency,
+                outcome,
+            });
+        }
+    }
+}
+
+/// Execute `command` against `store`, and answer it.
+fn execute(store: &mut TicketStore, command: Command) -> Outcome {
+    match command {
+        Command::Insert {
+            draft,
+            response_channel,
+        } => {
+            let id = store.add_ticket(draft);
+            Span::current().record("ticket_id", id.value());
+            let _ = response_channel.send(Ok(id));
+            Outcome::Ok
+        }
+        Command::InsertBatch {
+            drafts,
+            response_channel,
+        } => {
+            let ids = drafts
+                .into_iter()
+                .map(|draft| store.add_ticket(draft))
+                .collect();
+            let _ = response_channel.send(Ok(ids));
+            Outcome::Ok
+        }
+        Command::Get {
+            id,
+            response_channel,
+        } => {
+            let ticket = store.get(id).cloned();
+            let outcome = found(ticket.is_some());
+            let _ = response_channel.send(Ok(ticket));
+            outcome
+        }
+        Command::Update {
+            patch,
+            response_channel,
+        } => {
+            let updated = store.update(patch);
+            let outcome = found(updated.is_ok());
+            let _ = response_channel.send(Ok(updated));
+            outcome
+        }
+        Command::UpdateIfUnchanged {
+            patch,
+            read,
+            response_channel,
+        } => {
+            let updated = store.update_if_unchanged(patch, read);
+            let outcome = found(updated.is_ok());
+            let _ = response_channel.send(Ok(updated));
+            outcome
+        }
+        Command::List { response_channel } => {
+            let _ = response_channel.send(Ok(store.iter().cloned().collect()));
+            Outcome::Ok
+        }
+    }
+}
+
+fn found(found: bool) -> Outcome {
+    if found {
+        Outcome::Ok
+    } else {
+        Outcome::NotFound
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::User;
+    use crate::command_log::MemoryLogger;
+    use crate::data::Status;
+    use crate::repository::conformance::draft;
+    use std::time::Duration;
+
+    #[test]
+    fn test_round_trip() {
+        let client = launch(5);
+        let id = client.insert(draft()).unwrap();
+
+        let ticket = client.get(id).unwrap().unwrap();
+        assert_eq!(ticket.status, Status::ToDo);
+
+        client
+            .update(TicketPatch {
+                status: Some(Status::InProgress),
+                ..TicketPatch::new(id)
+            })
+            .unwrap();
+        let tickets = client.list().unwrap();
+        assert_eq!(tickets.len(), 1);
+        assert_eq!(tickets[0].status, Status::InProgress);
+    }
+
+    #[test]
+    fn test_creation_times_come_from_the_clock() {
+        let clock = crate::clock::TestClock::new();
+        let client = launch_with_clock(5, clock.clone());
+        clock.advance(Duration::from_secs(3));
+        let id = client.insert(draft()).unwrap();
+        let created_at = client.get(id).unwrap().unwrap().created_at;
+        assert_eq!(created_at, Some(clock.now()));
+    }
+
+    #[test]
+    fn test_updates_conditional_on_the_last_change() {
+        let clock = crate::clock::TestClock::new();
+        let client = launch_with_clock(5, clock.clone());
+        let id = client.insert(draft()).unwrap();
+        let read = client.get(id).unwrap().unwrap().updated_at;
+
+        // Someone else changes the ticket after it was read.
+        clock.advance(Duration::from_secs(1));
+        let assign = TicketPatch {
+            assignee: Some(Some("Alice".into())),
+            ..TicketPatch::new(id)
+        };
+        client.update(assign).unwrap();
+        let close = TicketPatch {
+            status: Some(Status::Done),
+            ..TicketPatch::new(id)
+        };
+        assert!(!client.update_if_unchanged(close.clone(), read).unwrap());
+        assert_eq!(client.get(id).unwrap().unwrap().status, Status::ToDo);
+
+        let read = client.get(id).unwrap().unwrap().updated_at;
+        assert!(client.update_if_unchanged(close, read).unwrap());
+        assert_eq!(client.get(id).unwrap().unwrap().status, Status::Done);
+        assert!(matches!(
+            client.update_if_unchanged(TicketPatch::new(TicketId::from(7)), read),
+            Err(ClientError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_update_missing_ticket() {
+        let client = launch(5);
+        let err = client
+            .update(TicketPatch::new(TicketId::from(7)))
+            .unwrap_err();
+        assert!(
+            matches!(err, ClientError::NotFound(TicketNotFound(id)) if id == TicketId::from(7))
+        );
+    }
+
+    #[test]
+    fn test_insert_batch() {
+        let client = launch(1);
+        let first = client.insert(draft()).unwrap();
+        let ids = client.insert_batch(vec![draft(); 3]).unwrap();
+        assert_eq!(ids.len(), 3);
+        assert!(ids.iter().all(|&id| id > first));
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(client.list().unwrap().len(), 4);
+
+        assert!(client.insert_batch(Vec::new()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_large_batches_are_split() {
+        let logger = MemoryLogger::new();
+        let client = launch_logged(1, None, logger.clone());
+        let ids = client
+            .insert_batch(vec![draft(); 2 * MAX_BATCH_LEN + 1])
+            .unwrap();
+        assert_eq!(ids.len(), 2 * MAX_BATCH_LEN + 1);
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        // Make sure the last batch has been logged.
+        client.list().unwrap();
+        client.list().unwrap();
+        let batches = logger
+            .records()
+            .iter()
+            .filter(|r| r.command == "insert_batch")
+            .count();
+        assert_eq!(batches, 3);
+    }
+
+    #[test]
+    fn test_clients_share_the_store() {
+        let client = launch(5);
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let client = client.clone();
+                std::thread::spawn(move || client.insert(draft()).unwrap())
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(client.list().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_every_command_is_logged() {
+        let logger = MemoryLogger::new();
+        let client = launch_logged(5, Some(users()), logger.clone());
+        let writer = client.with_token("writer");
+        let id = writer.insert(draft()).unwrap();
+        writer.get(id).unwrap();
+        writer.get(TicketId::from(7)).unwrap();
+        writer
+            .update(TicketPatch::new(TicketId::from(7)))
+            .unwrap_err();
+        client.with_token("reader").insert(draft()).unwrap_err();
+        writer.list().unwrap();
+        // Commands are logged after they're answered, but before the next one is picked up.
+        writer.list().unwrap();
+
+        let records = logger.records();
+        let logged: Vec<_> = records.iter().map(|r| (r.command, r.outcome)).collect();
+        assert_eq!(
+            logged[..6],
+            [
+                ("insert", Outcome::Ok),
+                ("get", Outcome::Ok),
+                ("get", Outcome::NotFound),
+                ("update", Outcome::NotFound),
+                ("insert", Outcome::Denied),
+                ("list", Outcome::Ok),
+            ]
+        );
+        assert!(records.iter().all(|r| r.latency < Duration::from_secs(1)));
+    }
+
+    fn users() -> Users {
+        let mut users = Users::new();
+        for (token, role) in [
+            ("reader", Role::Reader),
+            ("writer", Role::Writer),
+            ("admin", Role::Admin),
+        ] {
+            let name = token.to_string();
+            users.insert(token, User { name, role });
+        }
+        users
+    }
+
+    #[test]
+    fn test_missing_or_invalid_token() {
+        let client = launch_authenticated(5, users());
+        assert!(matches!(
+            client.list(),
+            Err(ClientError::Denied(AuthError::Unauthenticated))
+        ));
+        assert!(matches!(
+            client.with_token("guessed").list(),
+            Err(ClientError::Denied(AuthError::InvalidToken))
+        ));
+    }
+
+    #[test]
+    fn test_readers_cannot_mutate() {
+        let client = launch_authenticated(5, users());
+        let id = client.with_token("writer").insert(draft()).unwrap();
+
+        let reader = client.with_token("reader");
+        assert!(reader.get(id).unwrap().is_some());
+        assert_eq!(reader.list().unwrap().len(), 1);
+        let denied = |result: Result<_, ClientError>| {
+            matches!(
+                result,
+                Err(ClientError::Denied(AuthError::Forbidden {
+                    required: Role::Writer,
+                    ..
+                }))
+            )
+        };
+        assert!(denied(reader.insert(draft()).map(|_| ())));
+        assert!(denied(reader.insert_batch(vec![draft()]).map(|_| ())));
+        assert!(denied(reader.update(TicketPatch {
+            status: Some(Status::Done),
+            ..TicketPatch::new(id)
+        })));
+        assert_eq!(reader.get(id).unwrap().unwrap().status, Status::ToDo);
+    }
+
+    #[test]
+    fn test_writers_and_admins_can_mutate() {
+        let client = launch_authenticated(5, users());
+        for token in ["writer", "admin"] {
+            let client = client.with_token(token);
+            let id = client.insert(draft()).unwrap();
+            client
+                .update(TicketPatch {
+                    status: Some(Status::Done),
+                    ..TicketPatch::new(id)
+                })
+                .unwrap();
+        }
+        assert_eq!(client.with_token("reader").list().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,168 @@
+//! Telling the time.
+//!
+//! Everything that depends on the time—when tickets were created, whether they're overdue,
+//! rate limits, closing stale tickets—reads it from a [`Clock`], as a [`Timestamp`].
+//! [`SystemClock`] tells the actual time, while a [`TestClock`] only moves when a test tells it
+//! to: tests never have to sleep, and they get the same results on every run.
+//!
+//! [`Timestamp`] itself doesn't need `std`: tickets carry them.
+use core::ops::Add;
+use core::time::Duration;
+use serde::{Deserialize, Serialize};
+
+/// A point in time, to the millisecond. In JSON, it's the number of milliseconds since the
+/// Unix epoch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Timestamp(u64);
+
+impl Timestamp {
+    pub const UNIX_EPOCH: Self = Self(0);
+
+    /// The time `millis` milliseconds after the Unix epoch.
+    pub const fn from_millis(millis: u64) -> Self {
+        Self(millis)
+    }
+
+    pub const fn as_millis(self) -> u64 {
+        self.0
+    }
+
+    /// How long after `earlier` this is, or zero if it's not after it.
+    pub fn saturating_duration_since(self, earlier: Timestamp) -> Duration {
+        Duration::from_millis(self.0.saturating_sub(earlier.0))
+    }
+
+    /// Sub-millisecond parts of `duration` are dropped.
+    pub fn checked_add(self, duration: Duration) -> Option<Self> {
+        let millis = u64::try_from(duration.as_millis()).ok()?;
+        self.0.checked_add(millis).map(Self)
+    }
+}
+
+impl Add<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    /// # Panics
+    ///
+    /// If the result is too far in the future to be represented.
+    fn add(self, duration: Duration) -> Timestamp {
+        self.checked_add(duration)
+            .expect("Overflow when adding a duration to a timestamp")
+    }
+}
+
+#[cfg(feature = "std")]
+pub use clocks::{Clock, SystemClock, TestClock};
+
+#[cfg(feature = "std")]
+mod clocks {
+    use super::Timestamp;
+    use std::fmt::Debug;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    /// Where time-dependent code gets the current time from.
+    pub trait Clock: Debug + Send + Sync {
+        fn now(&self) -> Timestamp;
+    }
+
+    /// Clocks can be shared.
+    impl<C: Clock + ?Sized> Clock for Arc<C> {
+        fn now(&self) -> Timestamp {
+            C::now(self)
+        }
+    }
+
+    /// The actual time, as told by the operating system.
+    ///
+    /// Asking for it panics on some targets, like `wasm32-unknown-unknown`.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct SystemClock;
+
+    impl Clock for SystemClock {
+        fn now(&self) -> Timestamp {
+            // A system clock set before 1970 is treated as the epoch.
+            let since_epoch = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default();
+            Timestamp::from_millis(since_epoch.as_millis() as u64)
+        }
+    }
+
+    /// A clock that only moves forward when told to, for tests.
+    /// Clones share the same time: advancing one advances them all.
+    #[derive(Debug, Clone)]
+    pub struct TestClock {
+        millis: Arc<AtomicU64>,
+    }
+
+    impl TestClock {
+        /// A clock that reads the same time on every run: 2023-11-14, 22:13:20 UTC.
+        pub fn new() -> Self {
+            Self::starting_at(Timestamp::from_millis(1_700_000_000_000))
+        }
+
+        pub fn starting_at(start: Timestamp) -> Self {
+            Self {
+                millis: Arc::new(AtomicU64::new(start.as_millis())),
+            }
+        }
+
+        /// Sub-millisecond parts of `by` are dropped, like [`Timestamp::checked_add`] does.
+        pub fn advance(&self, by: Duration) {
+            let by = u64::try_from(by.as_millis()).expect("Tests don't run for centuries");
+            self.millis.fetch_add(by, Ordering::SeqCst);
+        }
+    }
+
+    impl Default for TestClock {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Clock for TestClock {
+        fn now(&self) -> Timestamp {
+            Timestamp::from_millis(self.millis.load(Ordering::SeqCst))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamp_arithmetic() {
+        let start = Timestamp::from_millis(1_000);
+        let later = start + Duration::from_millis(1_500);
+        assert_eq!(later.as_millis(), 2_500);
+        assert_eq!(
+            later.saturating_duration_since(start),
+            Duration::from_millis(1_500)
+        );
+        assert_eq!(start.saturating_duration_since(later), Duration::ZERO);
+        assert_eq!(
+            start.checked_add(Duration::from_micros(1_999)),
+            Some(start + Duration::from_millis(1))
+        );
+        assert_eq!(
+            Timestamp::from_millis(u64::MAX).checked_add(Duration::from_millis(1)),
+            None
+        );
+        assert_eq!(serde_json::to_string(&later).unwrap(), "2500");
+    }
+
+    #[test]
+    fn test_clocks_only_move_when_told_to() {
+        let clock = TestClock::new();
+        let shared = clock.clone();
+        let start = clock.now();
+        assert_eq!(start, TestClock::new().now());
+        clock.advance(Duration::from_secs(90));
+        assert_eq!(shared.now(), start + Duration::from_secs(90));
+        assert!(SystemClock.now() > Timestamp::UNIX_EPOCH);
+    }
+}
@@ -0,0 +1,121 @@
+//! A record of every command handled by the channel-based server (see [`crate::client`]).
+//!
+//! The server hands a [`CommandRecord`] to its [`CommandLogger`] once each command is
+//! answered. [`JsonLinesLogger`] writes them out as JSON, one per line—to stderr, by
+//! default—while [`MemoryLogger`] keeps them around for tests to look at.
+use std::io::{Stderr, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// What happened to a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Ok,
+    /// The command was about a ticket that doesn't exist.
+    NotFound,
+    /// The client wasn't allowed to send the command, so it wasn't executed.
+    Denied,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CommandRecord {
+    /// `insert`, `insert_batch`, `get`, `update`, `update_if_unchanged` or `list`.
+    pub command: &'static str,
+    /// From the moment the server picked the command up, to the moment it answered.
+    #[serde(rename = "latency_us", serialize_with = "micros")]
+    pub latency: Duration,
+    pub outcome: Outcome,
+}
+
+fn micros<S: serde::Serializer>(latency: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(latency.as_micros() as u64)
+}
+
+/// Where the server sends a record of every command it handles.
+///
+/// It's called on the server thread, between two commands: slow loggers slow the server down.
+pub trait CommandLogger: Send {
+    fn log(&mut self, record: &CommandRecord);
+}
+
+/// Writes every record to `W` as a line of JSON, like
+/// `{"command":"get","latency_us":3,"outcome":"not_found"}`.
+#[derive(Debug)]
+pub struct JsonLinesLogger<W> {
+    writer: W,
+}
+
+impl<W: Write + Send> JsonLinesLogger<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Logs to stderr.
+impl Default for JsonLinesLogger<Stderr> {
+    fn default() -> Self {
+        Self::new(std::io::stderr())
+    }
+}
+
+impl<W: Write + Send> CommandLogger for JsonLinesLogger<W> {
+    fn log(&mut self, record: &CommandRecord) {
+        let mut line = serde_json::to_vec(record).expect("Records are always serializable");
+        line.push(b'\n');
+        // Losing a log line is better than bringing the server down.
+        let _ = self.writer.write_all(&line);
+    }
+}
+
+/// Keeps every record in memory. Clones share the same records, so a test can keep one
+/// and hand the other to the server.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryLogger {
+    records: Arc<Mutex<Vec<CommandRecord>>>,
+}
+
+impl MemoryLogger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every record logged so far, oldest first.
+    pub fn records(&self) -> Vec<CommandRecord> {
+        self.records.lock().unwrap().clone()
+    }
+}
+
+impl CommandLogger for MemoryLogger {
+    fn log(&mut self, record: &CommandRecord) {
+        self.records.lock().unwrap().push(*record);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_are_written_as_json_lines() {
+        let mut logger = JsonLinesLogger::new(Vec::new());
+        for (command, outcome) in [("get", Outcome::NotFound), ("insert", Outcome::Denied)] {
+            logger.log(&CommandRecord {
+                command,
+                latency: Duration::from_micros(1500),
+                outcome,
+            });
+        }
+        assert_eq!(
+            String::from_utf8(logger.into_inner()).unwrap(),
+            "{\"command\":\"get\",\"latency_us\":1500,\"outcome\":\"not_found\"}\n\
+             {\"command\":\"insert\",\"latency_us\":1500,\"outcome\":\"denied\"}\n"
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! Optional compression for the files `ticket_core` writes: snapshots and the write-ahead log.
+//!
+//! Reading doesn't need to be told which compression was used: a gzip stream starts with
+//! two magic bytes that can't start a JSON document. Files written before compression
+//! was turned on (or after it was turned off) keep opening as before.
+//!
+//! Gzip needs the `gzip` feature. Without it, `Config::parse` rejects `compression = "gzip"`,
+//! and compressed files can't be read.
+use serde::Deserialize;
+#[cfg(feature = "fs")]
+use std::io;
+
+/// Every gzip stream starts with these two bytes.
+#[cfg(feature = "fs")]
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    /// Plain JSON.
+    #[default]
+    None,
+    Gzip,
+}
+
+impl Compression {
+    /// Whether this build of `ticket_core` can write (and read) files compressed this way.
+    pub fn is_supported(self) -> bool {
+        match self {
+            Self::None => true,
+            Self::Gzip => cfg!(feature = "gzip"),
+        }
+    }
+
+    /// Compress `bytes` into a single, self-delimiting gzip member.
+    #[cfg(feature = "fs")]
+    pub(crate) fn compress(self, bytes: Vec<u8>) -> io::Result<Vec<u8>> {
+        match self {
+            Self::None => Ok(bytes),
+            #[cfg(feature = "gzip")]
+            Self::Gzip => {
+                use std::io::Write;
+
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(&bytes)?;
+                encoder.finish()
+            }
+            #[cfg(not(feature = "gzip"))]
+            Self::Gzip => Err(unsupported()),
+        }
+    }
+}
+
+#[cfg(feature = "fs")]
+pub(crate) fn is_compressed(bytes: &[u8]) -> bool {
+    bytes.starts_with(&GZIP_MAGIC)
+}
+
+/// Undo [`Compression::compress`], whichever compression was used.
+#[cfg(feature = "fs")]
+pub(crate) fn decompress(bytes: Vec<u8>) -> io::Result<Vec<u8>> {
+    if !is_compressed(&bytes) {
+        return Ok(bytes);
+    }
+    let (decompressed, len) = read_member(&bytes)?;
+    if len < bytes.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "unexpected bytes after the end of the gzip stream",
+        ));
+    }
+    Ok(decompressed)
+}
+
+/// Decompress the gzip member at the start of `bytes`.
+/// Also returns how many bytes it took up, so that members can be read back to back.
+///
+/// A member that was cut short fails with `ErrorKind::UnexpectedEof`.
+#[cfg(feature = "gzip")]
+pub(crate) fn read_member(bytes: &[u8]) -> io::Result<(Vec<u8>, usize)> {
+    use std::io::Read;
+
+    let mut decoder = flate2::bufread::GzDecoder::new(bytes);
+    let mut decompressed = Vec::new();
+    decoder.read_to_end(&mut decompressed)?;
+    Ok((decompressed, bytes.len() - decoder.into_inner().len()))
+}
+
+#[cfg(all(feature = "fs", not(feature = "gzip")))]
+pub(crate) fn read_member(_bytes: &[u8]) -> io::Result<(Vec<u8>, usize)> {
+    Err(unsupported())
+}
+
+#[cfg(all(feature = "fs", not(feature = "gzip")))]
+fn unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "gzip compression requires the `gzip` feature of `ticket_core`",
+    )
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uncompressed_bytes_are_left_alone() {
+        let json = br#"{"next_id": 0, "tickets": []}"#.to_vec();
+        assert_eq!(Compression::None.compress(json.clone()).unwrap(), json);
+        assert!(!is_compressed(&json));
+        assert_eq!(decompress(json.clone()).unwrap(), json);
+    }
+
+    #[test]
+    #[cfg(feature = "gzip")]
+    fn gzip_round_trip() {
+        let json = br#"{"next_id": 0, "tickets": []}"#.to_vec();
+        let compressed = Compression::Gzip.compress(json.clone()).unwrap();
+        assert!(is_compressed(&compressed));
+        assert_eq!(decompress(compressed.clone()).unwrap(), json);
+
+        // Members can be read back to back...
+        let mut two = compressed.clone();
+        two.extend(Compression::Gzip.compress(b"second".to_vec()).unwrap());
+        assert_eq!(read_member(&two).unwrap(), (json, compressed.len()));
+        assert_eq!(read_member(&two[compressed.len()..]).unwrap().0, b"second");
+        // ...but a whole file is a single one.
+        assert!(decompress(two).is_err());
+
+        let torn = read_member(&compressed[..compressed.len() - 3]).unwrap_err();
+        assert_eq!(torn.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    #[cfg(not(feature = "gzip"))]
+    fn gzip_is_unsupported() {
+        assert!(!Compression::Gzip.is_supported());
+        let err = Compression::Gzip.compress(Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        assert!(decompress(vec![0x1f, 0x8b, 0]).is_err());
+    }
+}
@@ -0,0 +1,452 @@
+//! Configuration for the ticket servers, loaded from a TOML file.
+//!
+//! Every setting has a default, so a config file only needs to mention what it changes;
+//! an empty file is a valid config. Unknown keys are rejected, to catch typos early.
+//!
+//! ```toml
+//! [limits]
+//! max_title_len = 40
+//!
+//! [server]
+//! address = "0.0.0.0:4000"
+//! worker_threads = 2
+//! snapshot = "tickets.json"
+//! compression = "gzip"
+//! encryption = { key_env = "TICKETS_KEY" }
+//! max_connections = 100
+//! max_requests_per_second = 50
+//! webhooks = ["http://127.0.0.1:8080/tickets"]
+//!
+//! [channel]
+//! capacity = 64
+//! ```
+use std::net::SocketAddr;
+#[cfg(feature = "fs")]
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use ticket_fields::{TicketDescriptionError, TicketDraftError, TicketTitleError, Violation};
+
+use crate::compression::Compression;
+use crate::data::{TicketDraft, TicketPatch};
+use crate::encryption::EncryptionKey;
+
+/// The hard limits enforced by `TicketTitle` and `TicketDescription`.
+/// A config can tighten them, but not relax them.
+const MAX_TITLE_LEN: usize = 50;
+const MAX_DESCRIPTION_LEN: usize = 500;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub limits: Limits,
+    pub server: ServerConfig,
+    pub channel: ChannelConfig,
+}
+
+/// Validation limits, in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Limits {
+    pub max_title_len: usize,
+    pub max_description_len: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_title_len: MAX_TITLE_LEN,
+            max_description_len: MAX_DESCRIPTION_LEN,
+        }
+    }
+}
+
+impl Limits {
+    pub fn check_draft(&self, draft: &TicketDraft) -> Result<(), TicketDraftError> {
+        self.check_title(draft.title.as_ref())?;
+        self.check_description(draft.description.as_ref())
+    }
+
+    /// Only the fields that the patch changes are checked.
+    pub fn check_patch(&self, patch: &TicketPatch) -> Result<(), TicketDraftError> {
+        if let Some(title) = &patch.title {
+            self.check_title(title.as_ref())?;
+        }
+        if let Some(description) = &patch.description {
+            self.check_description(description.as_ref())?;
+        }
+        Ok(())
+    }
+
+    fn check_title(&self, title: &str) -> Result<(), TicketDraftError> {
+        Violation::check(title, self.max_title_len)
+            .map_err(|v| TicketDraftError::InvalidTitle(TicketTitleError::from(v)))
+    }
+
+    fn check_description(&self, description: &str) -> Result<(), TicketDraftError> {
+        Violation::check(description, self.max_description_len)
+            .map_err(|v| TicketDraftError::InvalidDescription(TicketDescriptionError::from(v)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub address: SocketAddr,
+    /// How many threads the async runtime runs requests on.
+    pub worker_threads: usize,
+    /// Where to persist tickets. Without it, they're kept in memory.
+    pub snapshot: Option<PathBuf>,
+    /// How to compress the snapshot: `"none"` or `"gzip"`.
+    pub compression: Compression,
+    /// Whether to encrypt the snapshot, and with which key.
+    pub encryption: Option<KeySource>,
+    /// How many clients can be connected at once. Without it, there's no limit.
+    pub max_connections: Option<usize>,
+    /// How many requests each connection can send per second. Without it, there's no limit.
+    pub max_requests_per_second: Option<u32>,
+    /// Where to POST an event whenever a ticket is created or updated.
+    /// They're parsed by the server, which only supports `http://` URLs.
+    pub webhooks: Vec<String>,
+}
+
+/// Where to find an encryption key.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum KeySource {
+    /// Inline, as 64 hexadecimal digits: `{ key = "..." }`.
+    Key(EncryptionKey),
+    /// In an environment variable, to keep it out of the config file: `{ key_env = "VAR" }`.
+    KeyEnv(String),
+}
+
+impl ServerConfig {
+    /// The key to encrypt the snapshot with, if any.
+    /// Environment variables are only read here, not when the config is parsed.
+    pub fn encryption_key(&self) -> Result<Option<EncryptionKey>, ConfigError> {
+        match &self.encryption {
+            None => Ok(None),
+            Some(KeySource::Key(key)) => Ok(Some(key.clone())),
+            Some(KeySource::KeyEnv(var)) => {
+                let invalid = |reason| ConfigError::Invalid {
+                    key: "server.encryption.key_env",
+                    reason,
+                };
+                let value = std::env::var(var)
+                    .map_err(|e| invalid(format!("names `{var}`, but it can't be read: {e}")))?;
+                let key = value
+                    .parse()
+                    .map_err(|e| invalid(format!("names `{var}`, but it's invalid: {e}")))?;
+                Ok(Some(key))
+            }
+        }
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            address: SocketAddr::from(([127, 0, 0, 1], 4000)),
+            worker_threads: 4,
+            snapshot: None,
+            compression: Compression::None,
+            encryption: None,
+            max_connections: None,
+            max_requests_per_second: None,
+            webhooks: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChannelConfig {
+    /// How many commands can be queued before clients get `ClientError::Overloaded`.
+    pub capacity: usize,
+}
+
+impl Default for ChannelConfig {
+    fn default() -> Self {
+        Self { capacity: 16 }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[cfg(feature = "fs")]
+    #[error("Failed to read the config file at {}", path.display())]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    // `toml`'s messages already point at the offending line, so we show them in full.
+    #[error("Invalid config: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("Invalid config: `{key}` {reason}")]
+    Invalid { key: &'static str, reason: String },
+}
+
+impl Config {
+    pub fn parse(toml: &str) -> Result<Self, ConfigError> {
+        let config: Config = toml::from_str(toml)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    #[cfg(feature = "fs")]
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let toml = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+            path: path.to_owned(),
+            source,
+        })?;
+        Self::parse(&toml)
+    }
+
+    /// Check the constraints that the types alone can't express.
+    fn validate(&self) -> Result<(), ConfigError> {
+        let within = |key, value: usize, max: usize| {
+            if value == 0 || value > max {
+                Err(ConfigError::Invalid {
+                    key,
+                    reason: format!("must be between 1 and {max}, but it's {value}"),
+                })
+            } else {
+                Ok(())
+            }
+        };
+        within(
+            "limits.max_title_len",
+            self.limits.max_title_len,
+            MAX_TITLE_LEN,
+        )?;
+        within(
+            "limits.max_description_len",
+            self.limits.max_description_len,
+            MAX_DESCRIPTION_LEN,
+        )?;
+        within(
+            "server.worker_threads",
+            self.server.worker_threads,
+            usize::MAX,
+        )?;
+        within("channel.capacity", self.channel.capacity, usize::MAX)?;
+        if let Some(max) = self.server.max_connections {
+            within("server.max_connections", max, usize::MAX)?;
+        }
+        if let Some(max) = self.server.max_requests_per_second {
+            within(
+                "server.max_requests_per_second",
+                max as usize,
+                u32::MAX as usize,
+            )?;
+        }
+        if !self.server.compression.is_supported() {
+            return Err(ConfigError::Invalid {
+                key: "server.compression",
+                reason: "requires the `gzip` feature of `ticket_core`".into(),
+            });
+        }
+        if self.server.encryption.is_some() && !cfg!(feature = "encryption") {
+            return Err(ConfigError::Invalid {
+                key: "server.encryption",
+                reason: "requires the `encryption` feature of `ticket_core`".into(),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use ticket_fields::test_helpers::ticket_description;
+
+    #[test]
+    fn empty_config_is_all_defaults() {
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn partial_config() {
+        let config = Config::parse(
+            r#"
+            [limits]
+            max_title_len = 20
+
+            [server]
+            snapshot = "data/tickets.json"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.limits.max_title_len, 20);
+        assert_eq!(config.limits.max_description_len, MAX_DESCRIPTION_LEN);
+        assert_eq!(
+            config.server.snapshot.as_deref(),
+            Some(Path::new("data/tickets.json"))
+        );
+        assert_eq!(config.server.address, ServerConfig::default().address);
+        assert_eq!(config.channel, ChannelConfig::default());
+    }
+
+    #[test]
+    fn full_config() {
+        let config = Config::parse(
+            r#"
+            limits = { max_title_len = 10, max_description_len = 100 }
+            server = { address = "0.0.0.0:9000", worker_threads = 1 }
+            channel = { capacity = 2 }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.server.address.port(), 9000);
+        assert_eq!(config.server.worker_threads, 1);
+        assert_eq!(config.channel.capacity, 2);
+    }
+
+    #[test]
+    fn limits_cannot_be_relaxed() {
+        let err = Config::parse("[limits]\nmax_title_len = 51").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid config: `limits.max_title_len` must be between 1 and 50, but it's 51"
+        );
+        assert!(matches!(
+            Config::parse("[channel]\ncapacity = 0"),
+            Err(ConfigError::Invalid {
+                key: "channel.capacity",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn connection_and_rate_limits() {
+        let config = Config::parse("[server]\nmax_connections = 10").unwrap();
+        assert_eq!(config.server.max_connections, Some(10));
+        assert_eq!(config.server.max_requests_per_second, None);
+        assert!(matches!(
+            Config::parse("[server]\nmax_requests_per_second = 0"),
+            Err(ConfigError::Invalid {
+                key: "server.max_requests_per_second",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn compression() {
+        let config = Config::parse("[server]\ncompression = \"none\"").unwrap();
+        assert_eq!(config.server.compression, Compression::None);
+        let gzip = Config::parse("[server]\ncompression = \"gzip\"");
+        if cfg!(feature = "gzip") {
+            assert_eq!(gzip.unwrap().server.compression, Compression::Gzip);
+        } else {
+            assert!(matches!(
+                gzip,
+                Err(ConfigError::Invalid {
+                    key: "server.compression",
+                    ..
+                })
+            ));
+        }
+        let err = Config::parse("[server]\ncompression = \"zip\"").unwrap_err();
+        assert!(err.to_string().contains("unknown variant `zip`"));
+    }
+
+    #[test]
+    #[cfg(feature = "encryption")]
+    fn encryption_keys() {
+        let key = "00".repeat(32);
+        let config =
+            Config::parse(&format!("[server]\nencryption = {{ key = \"{key}\" }}")).unwrap();
+        assert_eq!(
+            config.server.encryption_key().unwrap(),
+            Some(key.parse().unwrap())
+        );
+        // Keys never show up in the config's debug output.
+        assert!(!format!("{config:?}").contains(&key));
+
+        let err = Config::parse("[server]\nencryption = { key = \"00\" }").unwrap_err();
+        assert!(err.to_string().contains("64 hexadecimal digits"));
+        assert!(Config::parse("[server]\nencryption = { password = \"hunter2\" }").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "encryption")]
+    fn encryption_keys_from_the_environment() {
+        // Variables are process-wide: this test is the only one to use this one.
+        let var = "TICKET_CORE_TEST_ENCRYPTION_KEY";
+        let config =
+            Config::parse(&format!("[server]\nencryption = {{ key_env = \"{var}\" }}")).unwrap();
+        let err = config.server.encryption_key().unwrap_err();
+        assert!(err.to_string().contains("can't be read"));
+
+        std::env::set_var(var, "not a key");
+        let err = config.server.encryption_key().unwrap_err();
+        assert!(err.to_string().contains("it's invalid"));
+
+        std::env::set_var(var, "ab".repeat(32));
+        assert!(config.server.encryption_key().unwrap().is_some());
+    }
+
+    #[test]
+    #[cfg(not(feature = "encryption"))]
+    fn encryption_needs_its_feature() {
+        let key = "00".repeat(32);
+        let err =
+            Config::parse(&format!("[server]\nencryption = {{ key = \"{key}\" }}")).unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::Invalid {
+                key: "server.encryption",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn parse_errors_are_descriptive() {
+        let message = |toml: &str| Config::parse(toml).unwrap_err().to_string();
+        assert!(message("[server]\naddress = \"nowhere\"").contains("invalid socket address"));
+        assert!(message("[server]\nworker_threads = -1").contains("line 2"));
+        assert!(message("[limits]\nmax_titel_len = 3").contains("unknown field `max_titel_len`"));
+        assert!(message("[database]").contains("unknown field `database`"));
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn missing_file() {
+        let err = Config::load(Path::new("/definitely/not/here.toml")).unwrap_err();
+        assert!(matches!(err, ConfigError::Read { .. }));
+    }
+
+    #[test]
+    fn limits_check_drafts_and_patches() {
+        let limits = Limits {
+            max_title_len: 5,
+            ..Limits::default()
+        };
+        let draft = TicketDraft {
+            title: "Too long".try_into().unwrap(),
+            description: ticket_description(),
+        };
+        let err = limits.check_draft(&draft).unwrap_err();
+        assert!(matches!(
+            err,
+            TicketDraftError::InvalidTitle(TicketTitleError::TooLong {
+                limit: 5,
+                actual: 8
+            })
+        ));
+
+        let patch = TicketPatch {
+            title: Some("Short".try_into().unwrap()),
+            ..TicketPatch::new(0.into())
+        };
+        assert!(limits.check_patch(&patch).is_ok());
+        assert!(limits.check_patch(&TicketPatch::new(0.into())).is_ok());
+    }
+}
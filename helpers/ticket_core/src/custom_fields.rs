@@ -0,0 +1,331 @@
+//! Custom fields: typed values that tickets carry beyond the fields every ticket has,
+//! such as a `severity` or an `estimate`.
+//!
+//! Which fields exist, and what they hold, is up to each store: its [`FieldSchema`] maps every
+//! field name to a [`FieldType`]. The store checks tickets' [`CustomFields`] against it when
+//! they're added or changed (see [`TicketStore::add_ticket_with_fields`] and
+//! [`TicketStore::set_fields`]), rejecting unknown fields and values of the wrong type.
+//! Tickets don't have to set every field.
+//!
+//! [`TicketStore::add_ticket_with_fields`]: crate::TicketStore::add_ticket_with_fields
+//! [`TicketStore::set_fields`]: crate::TicketStore::set_fields
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::data::TicketNotFound;
+
+/// The value of a custom field. In JSON, it's a plain boolean, integer or string.
+///
+/// Values of the same kind are ordered the usual way, strings byte-wise. Across kinds,
+/// booleans come before integers, and integers before strings.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FieldValue {
+    Bool(bool),
+    Integer(i64),
+    /// Also the value of `FieldType::Enum` fields.
+    String(String),
+}
+
+impl FieldValue {
+    fn kind(&self) -> &'static str {
+        match self {
+            FieldValue::Bool(_) => "a boolean",
+            FieldValue::Integer(_) => "an integer",
+            FieldValue::String(_) => "a string",
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn heap_bytes(&self) -> usize {
+        match self {
+            FieldValue::String(s) => s.capacity(),
+            FieldValue::Bool(_) | FieldValue::Integer(_) => 0,
+        }
+    }
+}
+
+impl From<bool> for FieldValue {
+    fn from(value: bool) -> Self {
+        FieldValue::Bool(value)
+    }
+}
+
+impl From<i64> for FieldValue {
+    fn from(value: i64) -> Self {
+        FieldValue::Integer(value)
+    }
+}
+
+impl From<&str> for FieldValue {
+    fn from(value: &str) -> Self {
+        FieldValue::String(value.into())
+    }
+}
+
+impl From<String> for FieldValue {
+    fn from(value: String) -> Self {
+        FieldValue::String(value)
+    }
+}
+
+/// A ticket's custom field values, by field name.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CustomFields(BTreeMap<String, FieldValue>);
+
+impl CustomFields {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, field: &str) -> Option<&FieldValue> {
+        self.0.get(field)
+    }
+
+    /// Set `field`, returning its previous value, if any.
+    pub fn insert(
+        &mut self,
+        field: impl Into<String>,
+        value: impl Into<FieldValue>,
+    ) -> Option<FieldValue> {
+        self.0.insert(field.into(), value.into())
+    }
+
+    pub fn remove(&mut self, field: &str) -> Option<FieldValue> {
+        self.0.remove(field)
+    }
+
+    /// Every field that's set, by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &FieldValue)> {
+        self.0.iter().map(|(field, value)| (field.as_str(), value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Roughly how many bytes the fields hold on the heap. B-tree nodes have some overhead on
+    /// top of their entries, which is left out.
+    #[cfg(feature = "std")]
+    pub(crate) fn approx_memory_bytes(&self) -> usize {
+        self.0
+            .iter()
+            .map(|(field, value)| {
+                size_of::<(String, FieldValue)>() + field.capacity() + value.heap_bytes()
+            })
+            .sum()
+    }
+}
+
+impl<K: Into<String>, V: Into<FieldValue>> FromIterator<(K, V)> for CustomFields {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self(
+            iter.into_iter()
+                .map(|(field, value)| (field.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+/// What a custom field holds.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FieldType {
+    String,
+    Integer,
+    Bool,
+    /// One of a fixed set of strings.
+    Enum {
+        values: Vec<String>,
+    },
+}
+
+/// The custom fields a store's tickets can have. See the [module docs](self).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FieldSchema(BTreeMap<String, FieldType>);
+
+#[derive(Debug, thiserror::Error)]
+pub enum CustomFieldError {
+    #[error("`{0}` is not a custom field of this store")]
+    UnknownField(String),
+    #[error("`{field}` holds {expected}, not {found}")]
+    TypeMismatch {
+        field: String,
+        expected: &'static str,
+        found: &'static str,
+    },
+    #[error("`{value}` is not a valid `{field}`. Use one of: {}", .values.join(", "))]
+    NotAnOption {
+        field: String,
+        value: String,
+        values: Vec<String>,
+    },
+    #[error(transparent)]
+    NotFound(#[from] TicketNotFound),
+}
+
+impl FieldSchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Define (or redefine) `field`.
+    pub fn with(mut self, field: impl Into<String>, kind: FieldType) -> Self {
+        self.0.insert(field.into(), kind);
+        self
+    }
+
+    pub fn get(&self, field: &str) -> Option<&FieldType> {
+        self.0.get(field)
+    }
+
+    /// Every field, by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &FieldType)> {
+        self.0.iter().map(|(field, kind)| (field.as_str(), kind))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Like [`CustomFields::approx_memory_bytes`].
+    #[cfg(feature = "std")]
+    pub(crate) fn approx_memory_bytes(&self) -> usize {
+        self.0
+            .iter()
+            .map(|(field, kind)| {
+                let values = match kind {
+                    FieldType::Enum { values } => {
+                        values.capacity() * size_of::<String>()
+                            + values.iter().map(String::capacity).sum::<usize>()
+                    }
+                    FieldType::String | FieldType::Integer | FieldType::Bool => 0,
+                };
+                size_of::<(String, FieldType)>() + field.capacity() + values
+            })
+            .sum()
+    }
+
+    /// Check that every field in `fields` is defined, and holds a value of the right type.
+    pub fn validate(&self, fields: &CustomFields) -> Result<(), CustomFieldError> {
+        for (field, value) in fields.iter() {
+            let kind = self
+                .get(field)
+                .ok_or_else(|| CustomFieldError::UnknownField(field.into()))?;
+            let mismatch = |expected| CustomFieldError::TypeMismatch {
+                field: field.into(),
+                expected,
+                found: value.kind(),
+            };
+            match (kind, value) {
+                (FieldType::String, FieldValue::String(_))
+                | (FieldType::Integer, FieldValue::Integer(_))
+                | (FieldType::Bool, FieldValue::Bool(_)) => {}
+                (FieldType::Enum { values }, FieldValue::String(value)) => {
+                    if !values.contains(value) {
+                        return Err(CustomFieldError::NotAnOption {
+                            field: field.into(),
+                            value: value.clone(),
+                            values: values.clone(),
+                        });
+                    }
+                }
+                (FieldType::String | FieldType::Enum { .. }, _) => {
+                    return Err(mismatch("a string"))
+                }
+                (FieldType::Integer, _) => return Err(mismatch("an integer")),
+                (FieldType::Bool, _) => return Err(mismatch("a boolean")),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> FieldSchema {
+        FieldSchema::new()
+            .with("estimate", FieldType::Integer)
+            .with("blocking", FieldType::Bool)
+            .with("component", FieldType::String)
+            .with(
+                "severity",
+                FieldType::Enum {
+                    values: ["low", "high"].map(String::from).to_vec(),
+                },
+            )
+    }
+
+    #[test]
+    fn valid_fields() {
+        let fields: CustomFields = [
+            ("estimate", FieldValue::from(3)),
+            ("blocking", false.into()),
+            ("component", "parser".into()),
+            ("severity", "high".into()),
+        ]
+        .into_iter()
+        .collect();
+        schema().validate(&fields).unwrap();
+        // Fields can be left out.
+        schema().validate(&CustomFields::new()).unwrap();
+    }
+
+    #[test]
+    fn type_mismatches_are_rejected() {
+        let fields: CustomFields = [("estimate", "three")].into_iter().collect();
+        let err = schema().validate(&fields).unwrap_err();
+        assert_eq!(err.to_string(), "`estimate` holds an integer, not a string");
+
+        let fields: CustomFields = [("severity", 2)].into_iter().collect();
+        let err = schema().validate(&fields).unwrap_err();
+        assert_eq!(err.to_string(), "`severity` holds a string, not an integer");
+
+        let fields: CustomFields = [("severity", "medium")].into_iter().collect();
+        let err = schema().validate(&fields).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`medium` is not a valid `severity`. Use one of: low, high"
+        );
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        let fields: CustomFields = [("priority", 1)].into_iter().collect();
+        assert!(matches!(
+            schema().validate(&fields),
+            Err(CustomFieldError::UnknownField(field)) if field == "priority"
+        ));
+        assert!(FieldSchema::new().validate(&fields).is_err());
+    }
+
+    #[test]
+    fn json_encoding() {
+        let fields: CustomFields = [("estimate", FieldValue::from(3)), ("blocking", true.into())]
+            .into_iter()
+            .collect();
+        let encoded = serde_json::to_string(&fields).unwrap();
+        assert_eq!(encoded, r#"{"blocking":true,"estimate":3}"#);
+        assert_eq!(
+            serde_json::from_str::<CustomFields>(&encoded).unwrap(),
+            fields
+        );
+
+        let encoded = serde_json::to_string(&schema()).unwrap();
+        assert!(encoded.contains(r#""severity":{"type":"enum","values":["low","high"]}"#));
+        assert_eq!(
+            serde_json::from_str::<FieldSchema>(&encoded).unwrap(),
+            schema()
+        );
+    }
+}
@@ -0,0 +1,321 @@
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use core::cmp::Ordering;
+use core::fmt;
+use core::ops::Deref;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use ticket_fields::{TicketDescription, TicketTitle};
+
+use crate::clock::Timestamp;
+use crate::custom_fields::CustomFields;
+
+pub use ticket_fields::TicketDraft;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TicketId(u64);
+
+impl TicketId {
+    pub fn value(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for TicketId {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl fmt::Display for TicketId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("There is no ticket with id {0}")]
+pub struct TicketNotFound(pub TicketId);
+
+/// The name of whoever a ticket is assigned to. In JSON, it's a plain string.
+///
+/// A store interns the names of its assignees (see [`crate::intern`]): the tickets assigned
+/// to the same person share one copy of the name, and cloning it doesn't copy it either.
+#[derive(Clone)]
+pub struct Assignee(Arc<str>);
+
+impl Assignee {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether `self` and `other` share the same copy of the name, rather than being equal.
+    pub fn shares_name_with(&self, other: &Assignee) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl From<Arc<str>> for Assignee {
+    fn from(name: Arc<str>) -> Self {
+        Self(name)
+    }
+}
+
+impl From<&str> for Assignee {
+    fn from(name: &str) -> Self {
+        Self(name.into())
+    }
+}
+
+impl From<String> for Assignee {
+    fn from(name: String) -> Self {
+        Self(name.into())
+    }
+}
+
+impl Deref for Assignee {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Assignee {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq for Assignee {
+    fn eq(&self, other: &Self) -> bool {
+        self.shares_name_with(other) || self.0 == other.0
+    }
+}
+
+impl Eq for Assignee {}
+
+impl PartialEq<str> for Assignee {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Assignee {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialOrd for Assignee {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Interned names are compared without looking at them.
+impl Ord for Assignee {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self.shares_name_with(other) {
+            Ordering::Equal
+        } else {
+            self.0.cmp(&other.0)
+        }
+    }
+}
+
+impl core::hash::Hash for Assignee {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl fmt::Debug for Assignee {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Display for Assignee {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Serialize for Assignee {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Assignee {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ticket {
+    pub id: TicketId,
+    pub title: TicketTitle,
+    pub description: TicketDescription,
+    pub status: Status,
+    /// Who's working on the ticket, if anyone.
+    #[serde(default)]
+    pub assignee: Option<Assignee>,
+    /// Checked against the store's schema, see [`crate::custom_fields`].
+    #[serde(default, skip_serializing_if = "CustomFields::is_empty")]
+    pub custom_fields: CustomFields,
+    /// When the ticket was created, if the store it was added to had a clock.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<Timestamp>,
+    /// When the ticket last changed, if the store it's in has a clock: when it was created,
+    /// to begin with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<Timestamp>,
+    /// When the ticket should be done by, if anyone said.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<Timestamp>,
+}
+
+/// A partial update: `None` fields are left untouched.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TicketPatch {
+    pub id: TicketId,
+    #[serde(default)]
+    pub title: Option<TicketTitle>,
+    #[serde(default)]
+    pub description: Option<TicketDescription>,
+    #[serde(default)]
+    pub status: Option<Status>,
+    /// `Some(None)` (`null`, in JSON) unassigns the ticket.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub assignee: Option<Option<Assignee>>,
+}
+
+impl TicketPatch {
+    /// A patch that leaves every field of ticket `id` untouched.
+    pub fn new(id: TicketId) -> Self {
+        Self {
+            id,
+            title: None,
+            description: None,
+            status: None,
+            assignee: None,
+        }
+    }
+
+    /// Overwrite the fields of `ticket` that the patch sets. Its id isn't checked.
+    pub fn apply_to(self, ticket: &mut Ticket) {
+        if let Some(title) = self.title {
+            ticket.title = title;
+        }
+        if let Some(description) = self.description {
+            ticket.description = description;
+        }
+        if let Some(status) = self.status {
+            ticket.status = status;
+        }
+        if let Some(assignee) = self.assignee {
+            ticket.assignee = assignee;
+        }
+    }
+}
+
+/// By default, serde deserializes `null` as `None` for `Option<Option<T>>`:
+/// we need `Some(None)` to tell "unassign" apart from "leave as is".
+fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Status {
+    ToDo,
+    InProgress,
+    Done,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("`{invalid_status}` is not a valid status. Use one of: ToDo, InProgress, Done")]
+pub struct ParseStatusError {
+    invalid_status: String,
+}
+
+impl TryFrom<&str> for Status {
+    type Error = ParseStatusError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "todo" => Ok(Status::ToDo),
+            "inprogress" => Ok(Status::InProgress),
+            "done" => Ok(Status::Done),
+            _ => Err(ParseStatusError {
+                invalid_status: value.to_string(),
+            }),
+        }
+    }
+}
+
+impl TryFrom<String> for Status {
+    type Error = ParseStatusError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.as_str().try_into()
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            Status::ToDo => "ToDo",
+            Status::InProgress => "InProgress",
+            Status::Done => "Done",
+        };
+        f.write_str(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_round_trip() {
+        for status in [Status::ToDo, Status::InProgress, Status::Done] {
+            assert_eq!(Status::try_from(status.to_string()).unwrap(), status);
+        }
+        assert_eq!(Status::try_from("inproGress").unwrap(), Status::InProgress);
+        assert!(Status::try_from("Blocked").is_err());
+    }
+
+    #[test]
+    fn test_patch_fields_default_to_none() {
+        let patch: TicketPatch = serde_json::from_str(r#"{"id": 3, "status": "Done"}"#).unwrap();
+        assert_eq!(patch.title, None);
+        assert_eq!(patch.description, None);
+        assert_eq!(patch.status, Some(Status::Done));
+        assert_eq!(patch.assignee, None);
+    }
+
+    #[test]
+    fn test_patch_null_assignee_unassigns() {
+        let patch: TicketPatch = serde_json::from_str(r#"{"id": 3, "assignee": null}"#).unwrap();
+        assert_eq!(patch.assignee, Some(None));
+        let encoded = serde_json::to_string(&patch).unwrap();
+        assert_eq!(
+            serde_json::from_str::<TicketPatch>(&encoded).unwrap(),
+            patch
+        );
+        // Untouched fields are left out entirely.
+        let encoded = serde_json::to_string(&TicketPatch::new(3.into())).unwrap();
+        assert!(!encoded.contains("assignee"));
+    }
+}
@@ -0,0 +1,794 @@
+//! A crash-safe store: snapshots plus a write-ahead log.
+//!
+//! Saving a full snapshot after every change (like the async server does) gets expensive as the
+//! store grows. [`DurableStore`] only appends the change itself, as a [`TicketEvent`], to a
+//! write-ahead log (WAL) and flushes it to disk before acknowledging the write.
+//! Every `compact_every` writes, it saves a snapshot and truncates the log.
+//!
+//! [`DurableStore::open`] recovers the state by loading the snapshot and replaying the log on top.
+//!
+//! Crashes are handled at two points:
+//! - A crash in the middle of an append leaves a torn record at the end of the log, with no
+//!   trailing newline. It was never acknowledged, so recovery discards it.
+//! - A crash between saving a snapshot and truncating the log leaves records in the log that the
+//!   snapshot already includes. Each record carries a sequence number, and the snapshot stores
+//!   the last one it includes: recovery skips the records it has already seen.
+//!
+//! [`DurableStore::open_with`] takes an [`Encoding`] for the snapshot and each record.
+//! A compressed record is its own gzip member, rather than a line; an encrypted one is
+//! its own sealed blob. Both are self-delimiting, so a torn one is still detected—it ends
+//! too early. Records are read back whichever way they were written, so a directory can
+//! switch compression on or off between restarts. With a key, unencrypted records and
+//! snapshots are refused, unless [`Encoding::migrate_unencrypted`] says otherwise: that's
+//! how encryption is switched on for an existing directory. Switching it off takes the key,
+//! to read what was encrypted.
+//!
+//! [`History::read`] reads a store's directory without opening it, to inspect the WAL or to
+//! rebuild the state as of an earlier record: see [`History::rebuild`].
+use crate::clock::Clock;
+use crate::compression::{decompress, is_compressed, read_member};
+use crate::data::{Ticket, TicketDraft, TicketPatch};
+use crate::encryption::{self, is_encrypted, is_torn_magic, EncryptionError, EncryptionKey};
+use crate::event_sourced::{apply, OutOfOrder, TicketEvent};
+use crate::repository::{RepositoryError, TicketRepository};
+use crate::snapshot::{
+    parse, read_json_if_exists, write_atomically, Encoding, Snapshot, TrustedSnapshot,
+};
+use crate::store::{TicketId, TicketNotFound, TicketStore};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use ticket_fields::{Context, ContextError};
+
+const SNAPSHOT: &str = "snapshot.json";
+const WAL: &str = "wal.jsonl";
+
+#[derive(Serialize)]
+struct Checkpoint {
+    /// The sequence number of the last WAL record included in the snapshot.
+    seq: u64,
+    #[serde(flatten)]
+    snapshot: Snapshot,
+}
+
+/// A [`Checkpoint`], as it's read back: the store wrote it, so it isn't validated again.
+#[derive(Deserialize)]
+struct TrustedCheckpoint<'a> {
+    seq: u64,
+    #[serde(flatten, borrow)]
+    snapshot: TrustedSnapshot<'a>,
+}
+
+/// Load the checkpoint in `dir`: the sequence number of the last record it includes, and the
+/// state as of that record. Without one, that's 0 and an empty store.
+fn read_checkpoint(dir: &Path, encoding: &Encoding) -> Result<(u64, TicketStore), ContextError> {
+    let path = dir.join(SNAPSHOT);
+    let Some(json) = read_json_if_exists(&path, encoding)? else {
+        return Ok((0, TicketStore::new()));
+    };
+    let checkpoint: TrustedCheckpoint = parse(&path, &json)?;
+    let snapshot = Snapshot::trusted(&path, checkpoint.snapshot)?;
+    Ok((checkpoint.seq, snapshot.into_store()))
+}
+
+/// A record of the write-ahead log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalRecord {
+    pub seq: u64,
+    pub event: TicketEvent,
+}
+
+pub struct DurableStore {
+    dir: PathBuf,
+    state: TicketStore,
+    wal: File,
+    /// The sequence number of the last record written (or recovered).
+    seq: u64,
+    /// How many records the WAL holds.
+    wal_records: usize,
+    compact_every: usize,
+    encoding: Encoding,
+}
+
+impl DurableStore {
+    /// Open the store in `dir`, creating it if needed, and recover its state.
+    /// The WAL is compacted after `compact_every` writes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `compact_every` is zero.
+    pub fn open(dir: impl Into<PathBuf>, compact_every: usize) -> Result<Self, ContextError> {
+        Self::open_with(dir, compact_every, Encoding::default())
+    }
+
+    /// Like [`DurableStore::open`], writing the snapshot and the records with `encoding`.
+    pub fn open_with(
+        dir: impl Into<PathBuf>,
+        compact_every: usize,
+        encoding: Encoding,
+    ) -> Result<Self, ContextError> {
+        assert!(compact_every > 0, "`compact_every` must be positive");
+        let dir = dir.into();
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        let (mut seq, mut state) = read_checkpoint(&dir, &encoding)?;
+
+        let wal_path = dir.join(WAL);
+        let (records, valid_len) = read_wal(&wal_path, &encoding)?;
+        let wal_records = records.len();
+        for record in records {
+            if record.seq > seq {
+                apply(&mut state, &record.event).with_context(|| {
+                    format!(
+                        "Record #{} in {} is out of order",
+                        record.seq,
+                        wal_path.display()
+                    )
+                })?;
+                seq = record.seq;
+            }
+        }
+
+        let wal = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&wal_path)
+            .with_context(|| format!("Failed to open {}", wal_path.display()))?;
+        // Drop the torn record, if any, so that the next append starts on a clean line.
+        wal.set_len(valid_len)
+            .with_context(|| format!("Failed to truncate {}", wal_path.display()))?;
+
+        Ok(Self {
+            dir,
+            state,
+            wal,
+            seq,
+            wal_records,
+            compact_every,
+            encoding,
+        })
+    }
+
+    pub fn state(&self) -> &TicketStore {
+        &self.state
+    }
+
+    /// Stamp the tickets created from now on with the time `clock` tells.
+    /// Their creation time is logged with them, so recovery restores it.
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.state.set_clock(clock);
+    }
+
+    /// How many records the WAL currently holds.
+    pub fn wal_records(&self) -> usize {
+        self.wal_records
+    }
+
+    /// Save a snapshot of the current state and empty the WAL.
+    /// The WAL is only truncated once the snapshot is safely on disk.
+    pub fn compact(&mut self) -> Result<(), ContextError> {
+        let checkpoint = Checkpoint {
+            seq: self.seq,
+            snapshot: Snapshot::of(&self.state),
+        };
+        write_atomically(&self.dir.join(SNAPSHOT), &checkpoint, &self.encoding)?;
+        self.wal
+            .set_len(0)
+            .context("Failed to truncate the write-ahead log")?;
+        self.wal_records = 0;
+        Ok(())
+    }
+
+    /// Make `event` durable, then apply it.
+    ///
+    /// Once the record is in the WAL, the write has happened: failing to compact afterwards
+    /// doesn't undo it, so it's logged rather than returned. The WAL keeps growing, and the
+    /// next write tries to compact it again.
+    fn record(&mut self, event: TicketEvent) -> Result<(), ContextError> {
+        let record = WalRecord {
+            seq: self.seq + 1,
+            event,
+        };
+        let mut line = serde_json::to_vec(&record).context("Failed to serialize a WAL record")?;
+        line.push(b'\n');
+        let encoded = self.encoding.encode(line, "a WAL record")?;
+        self.wal
+            .write_all(&encoded)
+            .and_then(|()| self.wal.sync_data())
+            .context("Failed to append to the write-ahead log")?;
+
+        self.seq = record.seq;
+        apply(&mut self.state, &record.event).expect("Ids are handed out in order");
+        self.wal_records += 1;
+        if self.wal_records >= self.compact_every {
+            if let Err(error) = self.compact() {
+                tracing::warn!(%error, "failed to compact the write-ahead log");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parse every complete record in the WAL at `path`.
+/// Also returns the length of the valid prefix of the file: everything after it is a torn write.
+fn read_wal(path: &Path, encoding: &Encoding) -> Result<(Vec<WalRecord>, u64), ContextError> {
+    let content = match fs::read(path) {
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok((Vec::new(), 0)),
+        read => read.with_context(|| format!("Failed to read {}", path.display()))?,
+    };
+    let corrupted = |records: &Vec<WalRecord>| {
+        format!(
+            "Corrupted record #{} in {}",
+            records.len() + 1,
+            path.display()
+        )
+    };
+
+    let mut records = Vec::new();
+    let mut valid_len = 0;
+    while valid_len < content.len() {
+        let rest = &content[valid_len..];
+        let refused = encoding.key.is_some() && !encoding.migrate_unencrypted;
+        if refused && !is_encrypted(rest) {
+            if is_torn_magic(rest) {
+                break;
+            }
+            return Err(EncryptionError::Unencrypted).with_context(|| corrupted(&records));
+        }
+        let (line, len) = if is_encrypted(rest) {
+            let opened = encryption::open(encoding.key.as_ref(), rest)
+                .with_context(|| corrupted(&records))?;
+            // Only complete blobs were fully written.
+            let Some((sealed, len)) = opened else {
+                break;
+            };
+            let line = decompress(sealed).with_context(|| corrupted(&records))?;
+            (Cow::Owned(line), len)
+        } else if is_compressed(rest) {
+            match read_member(rest) {
+                Ok((line, len)) => (Cow::Owned(line), len),
+                // Only complete members were fully written.
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e).with_context(|| corrupted(&records)),
+            }
+        } else {
+            // Only newline-terminated records were fully written.
+            let Some(end) = rest.iter().position(|&b| b == b'\n') else {
+                break;
+            };
+            (Cow::Borrowed(&rest[..end]), end + 1)
+        };
+        valid_len += len;
+        if !line.is_empty() {
+            let record = serde_json::from_slice(&line).with_context(|| corrupted(&records))?;
+            records.push(record);
+        }
+    }
+    Ok((records, valid_len as u64))
+}
+
+/// What a [`DurableStore`]'s directory holds: its last snapshot, and the records logged since.
+#[derive(Debug)]
+pub struct History {
+    /// The sequence number of the last record the snapshot includes, or 0 without a snapshot.
+    pub snapshot_seq: u64,
+    snapshot: TicketStore,
+    /// Every record in the WAL, in order. Those the snapshot already includes are kept, though
+    /// they're skipped when rebuilding.
+    pub records: Vec<WalRecord>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum HistoryError {
+    #[error(
+        "The snapshot already includes every change up to #{snapshot_seq}, \
+         so the state as of #{requested} is gone"
+    )]
+    Compacted { requested: u64, snapshot_seq: u64 },
+    #[error("Record #{seq} is out of order")]
+    OutOfOrder {
+        seq: u64,
+        #[source]
+        source: OutOfOrder,
+    },
+}
+
+impl History {
+    /// Read the snapshot and the WAL in `dir`, leaving them as they are.
+    /// A torn record at the end of the WAL is ignored, like [`DurableStore::open`] does.
+    pub fn read(dir: &Path, key: Option<EncryptionKey>) -> Result<Self, ContextError> {
+        let encoding = Encoding {
+            key,
+            ..Encoding::default()
+        };
+        let (snapshot_seq, snapshot) = read_checkpoint(dir, &encoding)?;
+        let (records, _) = read_wal(&dir.join(WAL), &encoding)?;
+        Ok(Self {
+            snapshot_seq,
+            snapshot,
+            records,
+        })
+    }
+
+    /// The sequence number of the last change, whether it's in the WAL or in the snapshot.
+    pub fn last_seq(&self) -> u64 {
+        let logged = self.records.last().map_or(0, |r| r.seq);
+        logged.max(self.snapshot_seq)
+    }
+
+    /// The state right after record `seq` was applied: the snapshot, with the records up to
+    /// `seq` replayed on top. Past the last record, that's the current state.
+    ///
+    /// Compacting throws away the records the snapshot includes, so it's too late to go back
+    /// to any of them but the last.
+    pub fn rebuild(&self, seq: u64) -> Result<TicketStore, HistoryError> {
+        if seq < self.snapshot_seq {
+            return Err(HistoryError::Compacted {
+                requested: seq,
+                snapshot_seq: self.snapshot_seq,
+            });
+        }
+        let mut state = self.snapshot.clone();
+        let mut applied = self.snapshot_seq;
+        for record in &self.records {
+            if record.seq > seq {
+                break;
+            }
+            if record.seq > applied {
+                apply(&mut state, &record.event).map_err(|source| HistoryError::OutOfOrder {
+                    seq: record.seq,
+                    source,
+                })?;
+                applied = record.seq;
+            }
+        }
+        Ok(state)
+    }
+}
+
+impl TicketRepository for DurableStore {
+    fn insert(&mut self, draft: TicketDraft) -> Result<TicketId, RepositoryError> {
+        let id = TicketId::from(self.state.next_id());
+        let created_at = self.state.now();
+        self.record(TicketEvent::Created {
+            id,
+            draft,
+            created_at,
+        })
+        .map_err(RepositoryError::backend)?;
+        Ok(id)
+    }
+
+    fn get(&self, id: TicketId) -> Result<Option<Ticket>, RepositoryError> {
+        Ok(self.state.get(id).cloned())
+    }
+
+    fn update(&mut self, patch: TicketPatch) -> Result<(), RepositoryError> {
+        if self.state.get(patch.id).is_none() {
+            return Err(TicketNotFound(patch.id).into());
+        }
+        let updated_at = self.state.now();
+        self.record(TicketEvent::Patched { patch, updated_at })
+            .map_err(RepositoryError::backend)
+    }
+
+    fn delete(&mut self, id: TicketId) -> Result<Option<Ticket>, RepositoryError> {
+        let Some(ticket) = self.state.get(id).cloned() else {
+            return Ok(None);
+        };
+        self.record(TicketEvent::Deleted { id })
+            .map_err(RepositoryError::backend)?;
+        Ok(Some(ticket))
+    }
+
+    fn list(&self) -> Result<Vec<Ticket>, RepositoryError> {
+        self.state.list()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::TestClock;
+    #[cfg(feature = "gzip")]
+    use crate::compression::Compression;
+    use crate::data::Status;
+    #[cfg(feature = "encryption")]
+    use crate::encryption::{test_key, EncryptionError};
+    use crate::repository::conformance;
+    use std::time::Duration;
+
+    fn done(id: TicketId) -> TicketPatch {
+        TicketPatch {
+            status: Some(Status::Done),
+            ..TicketPatch::new(id)
+        }
+    }
+
+    fn tickets(store: &DurableStore) -> Vec<Ticket> {
+        store.list().unwrap()
+    }
+
+    #[test]
+    fn conformance() {
+        let dir = tempfile::tempdir().unwrap();
+        conformance::check(&mut DurableStore::open(dir.path(), 3).unwrap());
+    }
+
+    #[test]
+    fn recovers_from_the_log_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = DurableStore::open(dir.path(), 100).unwrap();
+        let id = store.insert(conformance::draft()).unwrap();
+        store.update(done(id)).unwrap();
+        store.insert(conformance::draft()).unwrap();
+        let before = tickets(&store);
+        drop(store);
+
+        let reopened = DurableStore::open(dir.path(), 100).unwrap();
+        assert!(!dir.path().join(SNAPSHOT).exists());
+        assert_eq!(reopened.wal_records(), 3);
+        assert_eq!(tickets(&reopened), before);
+    }
+
+    #[test]
+    fn recovery_restores_creation_times() {
+        let dir = tempfile::tempdir().unwrap();
+        let clock = TestClock::new();
+        let mut store = DurableStore::open(dir.path(), 100).unwrap();
+        store.set_clock(clock.clone());
+        let id = store.insert(conformance::draft()).unwrap();
+        assert_eq!(store.state()[id].created_at, Some(clock.now()));
+        let before = tickets(&store);
+        drop(store);
+
+        clock.advance(Duration::from_secs(60));
+        let mut reopened = DurableStore::open(dir.path(), 100).unwrap();
+        reopened.set_clock(clock);
+        assert_eq!(tickets(&reopened), before);
+    }
+
+    #[test]
+    fn compaction_truncates_the_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = DurableStore::open(dir.path(), 2).unwrap();
+        for _ in 0..5 {
+            store.insert(conformance::draft()).unwrap();
+        }
+        assert_eq!(store.wal_records(), 1);
+        let before = tickets(&store);
+        drop(store);
+
+        let mut reopened = DurableStore::open(dir.path(), 2).unwrap();
+        assert_eq!(tickets(&reopened), before);
+        assert_eq!(
+            reopened.insert(conformance::draft()).unwrap(),
+            TicketId::from(5)
+        );
+    }
+
+    #[test]
+    fn failed_compactions_do_not_fail_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = DurableStore::open(dir.path(), 2).unwrap();
+        // A directory in the way of the snapshot makes every compaction fail.
+        let obstacle = dir.path().join(SNAPSHOT);
+        fs::create_dir_all(obstacle.join("in-the-way")).unwrap();
+        let first = store.insert(conformance::draft()).unwrap();
+        store.update(done(first)).unwrap();
+        assert_eq!(store.wal_records(), 2);
+        assert!(store.compact().is_err());
+
+        // The next write compacts, once it's possible again.
+        fs::remove_dir_all(&obstacle).unwrap();
+        store.insert(conformance::draft()).unwrap();
+        assert_eq!(store.wal_records(), 0);
+        let before = tickets(&store);
+        drop(store);
+        assert_eq!(tickets(&DurableStore::open(dir.path(), 2).unwrap()), before);
+    }
+
+    #[test]
+    fn torn_last_record_is_discarded() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = DurableStore::open(dir.path(), 100).unwrap();
+        let id = store.insert(conformance::draft()).unwrap();
+        store.update(done(id)).unwrap();
+        drop(store);
+
+        // Simulate a crash halfway through appending a third record.
+        let wal = dir.path().join(WAL);
+        let mut content = fs::read(&wal).unwrap();
+        let last_line_start = content[..content.len() - 1]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .unwrap()
+            + 1;
+        let torn = content[last_line_start..content.len() - 5].to_vec();
+        content.extend_from_slice(&torn);
+        fs::write(&wal, &content).unwrap();
+
+        let mut recovered = DurableStore::open(dir.path(), 100).unwrap();
+        assert_eq!(recovered.wal_records(), 2);
+        assert_eq!(recovered.get(id).unwrap().unwrap().status, Status::Done);
+
+        // The log is usable again: new records don't get glued to the torn one.
+        let second = recovered.insert(conformance::draft()).unwrap();
+        drop(recovered);
+        let reopened = DurableStore::open(dir.path(), 100).unwrap();
+        assert_eq!(reopened.wal_records(), 3);
+        assert!(reopened.get(second).unwrap().is_some());
+    }
+
+    #[test]
+    fn crash_between_snapshot_and_truncation() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = DurableStore::open(dir.path(), 100).unwrap();
+        let first = store.insert(conformance::draft()).unwrap();
+        store.delete(first).unwrap();
+        store.insert(conformance::draft()).unwrap();
+        let wal = fs::read(dir.path().join(WAL)).unwrap();
+        store.compact().unwrap();
+        let before = tickets(&store);
+        drop(store);
+
+        // Put the log back, as if we crashed right after writing the snapshot.
+        fs::write(dir.path().join(WAL), wal).unwrap();
+        let recovered = DurableStore::open(dir.path(), 100).unwrap();
+        assert_eq!(tickets(&recovered), before);
+    }
+
+    #[test]
+    fn rebuilding_an_earlier_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = DurableStore::open(dir.path(), 3).unwrap();
+        let first = store.insert(conformance::draft()).unwrap();
+        store.insert(conformance::draft()).unwrap();
+        store.update(done(first)).unwrap();
+        // Compacted: the snapshot takes over the first three records.
+        let second = store.insert(conformance::draft()).unwrap();
+        store.delete(first).unwrap();
+        let current = tickets(&store);
+        drop(store);
+
+        let history = History::read(dir.path(), None).unwrap();
+        assert_eq!(history.snapshot_seq, 3);
+        assert_eq!(
+            history.records.iter().map(|r| r.seq).collect::<Vec<_>>(),
+            [4, 5]
+        );
+        assert_eq!(history.last_seq(), 5);
+        assert_eq!(history.rebuild(5).unwrap().list().unwrap(), current);
+        assert_eq!(history.rebuild(99).unwrap().list().unwrap(), current);
+
+        let before_delete = history.rebuild(4).unwrap();
+        assert_eq!(before_delete.len(), 3);
+        assert_eq!(before_delete[first].status, Status::Done);
+        assert_eq!(history.rebuild(3).unwrap().get(second), None);
+        assert!(matches!(
+            history.rebuild(2),
+            Err(HistoryError::Compacted {
+                requested: 2,
+                snapshot_seq: 3
+            })
+        ));
+        // Reading the history doesn't change a thing.
+        assert_eq!(
+            tickets(&DurableStore::open(dir.path(), 3).unwrap()),
+            current
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "gzip")]
+    fn compressed_records_and_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let gzip = Encoding {
+            compression: Compression::Gzip,
+            ..Encoding::default()
+        };
+        // Start with a plain log, then switch to compression after a restart.
+        let mut store = DurableStore::open(dir.path(), 4).unwrap();
+        store.insert(conformance::draft()).unwrap();
+        drop(store);
+        let mut store = DurableStore::open_with(dir.path(), 4, gzip.clone()).unwrap();
+        let id = store.insert(conformance::draft()).unwrap();
+        store.update(done(id)).unwrap();
+        let before = tickets(&store);
+        drop(store);
+
+        let wal = fs::read(dir.path().join(WAL)).unwrap();
+        assert!(!is_compressed(&wal));
+        assert!(wal.windows(2).any(is_compressed));
+        let mut reopened = DurableStore::open_with(dir.path(), 4, gzip).unwrap();
+        assert_eq!(reopened.wal_records(), 3);
+        assert_eq!(tickets(&reopened), before);
+
+        // The fourth write triggers a compaction, into a compressed snapshot.
+        reopened.insert(conformance::draft()).unwrap();
+        assert_eq!(reopened.wal_records(), 0);
+        assert!(is_compressed(&fs::read(dir.path().join(SNAPSHOT)).unwrap()));
+        let before = tickets(&reopened);
+        drop(reopened);
+        assert_eq!(tickets(&DurableStore::open(dir.path(), 4).unwrap()), before);
+    }
+
+    #[test]
+    #[cfg(feature = "gzip")]
+    fn torn_compressed_record_is_discarded() {
+        let encoding = Encoding {
+            compression: Compression::Gzip,
+            ..Encoding::default()
+        };
+        check_torn_records_are_discarded(encoding);
+    }
+
+    #[test]
+    #[cfg(feature = "encryption")]
+    fn torn_encrypted_record_is_discarded() {
+        let encoding = Encoding {
+            key: Some(test_key()),
+            ..Encoding::default()
+        };
+        check_torn_records_are_discarded(encoding);
+    }
+
+    /// Cut the second of two records anywhere: its header, its body or its trailer.
+    #[cfg(any(feature = "gzip", feature = "encryption"))]
+    fn check_torn_records_are_discarded(encoding: Encoding) {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = DurableStore::open_with(dir.path(), 100, encoding.clone()).unwrap();
+        store.insert(conformance::draft()).unwrap();
+        let complete = fs::read(dir.path().join(WAL)).unwrap();
+        store.insert(conformance::draft()).unwrap();
+        let content = fs::read(dir.path().join(WAL)).unwrap();
+        drop(store);
+
+        for cut in complete.len() + 1..content.len() {
+            fs::write(dir.path().join(WAL), &content[..cut]).unwrap();
+            let recovered = DurableStore::open_with(dir.path(), 100, encoding.clone()).unwrap();
+            assert_eq!(recovered.wal_records(), 1, "cut at byte {cut}");
+            drop(recovered);
+            assert_eq!(fs::read(dir.path().join(WAL)).unwrap(), complete);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "encryption")]
+    fn encrypted_records_and_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let encrypted = Encoding {
+            key: Some(test_key()),
+            ..Encoding::default()
+        };
+        let mut store = DurableStore::open_with(dir.path(), 3, encrypted.clone()).unwrap();
+        for _ in 0..4 {
+            store.insert(conformance::draft()).unwrap();
+        }
+        let before = tickets(&store);
+        drop(store);
+
+        // Neither the snapshot nor the log give the titles away.
+        let title = conformance::draft().title;
+        for file in [SNAPSHOT, WAL] {
+            let content = fs::read(dir.path().join(file)).unwrap();
+            assert!(is_encrypted(&content), "{file}");
+            let leaks = content
+                .windows(title.as_ref().len())
+                .any(|w| w == title.as_ref().as_bytes());
+            assert!(!leaks, "{file}");
+        }
+
+        let reopened = DurableStore::open_with(dir.path(), 3, encrypted).unwrap();
+        assert_eq!(tickets(&reopened), before);
+        drop(reopened);
+        let err = DurableStore::open(dir.path(), 3).err().unwrap();
+        assert!(err.context().starts_with("Failed to decrypt the snapshot"));
+        assert_eq!(
+            error_source::<EncryptionError>(&err),
+            Some(&EncryptionError::MissingKey)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "encryption")]
+    fn plain_records_are_refused_under_a_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = DurableStore::open(dir.path(), 100).unwrap();
+        store.insert(conformance::draft()).unwrap();
+        drop(store);
+
+        let mut encrypted = Encoding {
+            key: Some(test_key()),
+            ..Encoding::default()
+        };
+        let err = DurableStore::open_with(dir.path(), 100, encrypted.clone())
+            .err()
+            .unwrap();
+        assert_eq!(
+            error_source::<EncryptionError>(&err),
+            Some(&EncryptionError::Unencrypted)
+        );
+
+        // Migrating reads them, and encrypts everything from the next compaction on.
+        encrypted.migrate_unencrypted = true;
+        let mut migrated = DurableStore::open_with(dir.path(), 100, encrypted.clone()).unwrap();
+        migrated.insert(conformance::draft()).unwrap();
+        migrated.compact().unwrap();
+        drop(migrated);
+        encrypted.migrate_unencrypted = false;
+        let reopened = DurableStore::open_with(dir.path(), 100, encrypted).unwrap();
+        assert_eq!(tickets(&reopened).len(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "encryption")]
+    fn tampered_record_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let encrypted = Encoding {
+            key: Some(test_key()),
+            ..Encoding::default()
+        };
+        let mut store = DurableStore::open_with(dir.path(), 100, encrypted.clone()).unwrap();
+        store.insert(conformance::draft()).unwrap();
+        store.insert(conformance::draft()).unwrap();
+        drop(store);
+
+        // Flip a byte in the middle of the first record's ciphertext.
+        let wal = dir.path().join(WAL);
+        let mut content = fs::read(&wal).unwrap();
+        content[30] ^= 0x01;
+        fs::write(&wal, content).unwrap();
+
+        let err = DurableStore::open_with(dir.path(), 100, encrypted)
+            .err()
+            .unwrap();
+        assert!(err.context().starts_with("Corrupted record #1"));
+        assert_eq!(
+            error_source::<EncryptionError>(&err),
+            Some(&EncryptionError::DecryptionFailed)
+        );
+    }
+
+    #[cfg(feature = "encryption")]
+    fn error_source<E: std::error::Error + 'static>(err: &ContextError) -> Option<&E> {
+        std::error::Error::source(err)?.downcast_ref()
+    }
+
+    #[test]
+    fn corruption_in_the_middle_of_the_log_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = DurableStore::open(dir.path(), 100).unwrap();
+        store.insert(conformance::draft()).unwrap();
+        drop(store);
+
+        let wal = dir.path().join(WAL);
+        let mut content = b"garbage\n".to_vec();
+        content.extend(fs::read(&wal).unwrap());
+        fs::write(&wal, content).unwrap();
+
+        let err = DurableStore::open(dir.path(), 100).err().unwrap();
+        assert!(err.context().starts_with("Corrupted record #1"));
+    }
+
+    #[test]
+    fn out_of_order_records_are_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = DurableStore::open(dir.path(), 100).unwrap();
+        store.insert(conformance::draft()).unwrap();
+        store.insert(conformance::draft()).unwrap();
+        drop(store);
+
+        // Drop the first creation: the second one comes too early.
+        let wal = dir.path().join(WAL);
+        let content = fs::read_to_string(&wal).unwrap();
+        let (_, rest) = content.split_once('\n').unwrap();
+        fs::write(&wal, rest).unwrap();
+
+        let err = DurableStore::open(dir.path(), 100).err().unwrap();
+        assert!(err.context().starts_with("Record #2 in"));
+        assert!(std::error::Error::source(&err).unwrap().is::<OutOfOrder>());
+    }
+}
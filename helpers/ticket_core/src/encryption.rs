@@ -0,0 +1,333 @@
+//! Optional encryption at rest for snapshots and the write-ahead log.
+//!
+//! Data is sealed with ChaCha20-Poly1305, an authenticated cipher: opening data that was
+//! tampered with (or using the wrong key) fails with [`EncryptionError::DecryptionFailed`],
+//! rather than returning garbage. Like compressed files, encrypted ones are recognized by
+//! their first bytes: loading them only needs the key.
+//!
+//! With a key, unencrypted data is refused with [`EncryptionError::Unencrypted`]: otherwise,
+//! anyone able to write the file could replace it with plain, unauthenticated data. Reading
+//! it anyway, to encrypt it, is a one-time migration the caller has to opt into, see
+//! `Encoding::migrate_unencrypted`.
+//!
+//! A sealed blob is laid out as:
+//! - the magic bytes `TKE1`;
+//! - the length of the ciphertext, as a 4-byte big-endian integer;
+//! - a random 12-byte nonce;
+//! - the ciphertext, followed by its 16-byte authentication tag.
+//!
+//! The magic bytes and the length are authenticated too. The length makes blobs self-delimiting,
+//! so WAL records can be sealed one by one: a torn one is detected because it ends too early.
+//! Data is compressed before it's encrypted, if at all—ciphertexts don't compress.
+//!
+//! Encrypting and decrypting need the `encryption` feature.
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+
+const KEY_LEN: usize = 32;
+#[cfg(feature = "fs")]
+const MAGIC: [u8; 4] = *b"TKE1";
+/// The magic bytes and the length, which are authenticated as associated data.
+#[cfg(feature = "fs")]
+const AAD_LEN: usize = 8;
+#[cfg(feature = "fs")]
+const NONCE_LEN: usize = 12;
+#[cfg(feature = "fs")]
+const HEADER_LEN: usize = AAD_LEN + NONCE_LEN;
+
+/// A 256-bit key, written as 64 hexadecimal digits.
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct EncryptionKey([u8; KEY_LEN]);
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Keys end up in `Config`, which gets printed: don't leak them.
+        f.write_str("EncryptionKey(..)")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("An encryption key must be 64 hexadecimal digits")]
+pub struct InvalidKey;
+
+impl FromStr for EncryptionKey {
+    type Err = InvalidKey;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != 2 * KEY_LEN || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(InvalidKey);
+        }
+        let mut key = [0; KEY_LEN];
+        for (byte, digits) in key.iter_mut().zip(s.as_bytes().chunks(2)) {
+            let digits = std::str::from_utf8(digits).expect("Hexadecimal digits are ASCII");
+            *byte = u8::from_str_radix(digits, 16).expect("Hexadecimal digits were checked");
+        }
+        Ok(Self(key))
+    }
+}
+
+impl TryFrom<String> for EncryptionKey {
+    type Error = InvalidKey;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum EncryptionError {
+    #[error("The data is encrypted, but no key was provided")]
+    MissingKey,
+    /// An authenticated cipher can't tell a wrong key from tampered (or corrupted) data.
+    #[error("Decryption failed: the key is wrong, or the data was tampered with")]
+    DecryptionFailed,
+    #[error("The encrypted data is truncated")]
+    Truncated,
+    #[error("The data isn't encrypted, although a key was provided")]
+    Unencrypted,
+    #[error("Encryption requires the `encryption` feature of `ticket_core`")]
+    Unsupported,
+}
+
+#[cfg(feature = "fs")]
+pub(crate) fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(&MAGIC)
+}
+
+/// Whether `bytes` could be a blob cut short before the end of its magic bytes.
+#[cfg(feature = "fs")]
+pub(crate) fn is_torn_magic(bytes: &[u8]) -> bool {
+    bytes.len() < MAGIC.len() && MAGIC.starts_with(bytes)
+}
+
+/// Seal `plaintext` into a single blob.
+#[cfg(feature = "encryption")]
+pub(crate) fn seal(key: &EncryptionKey, plaintext: Vec<u8>) -> Result<Vec<u8>, EncryptionError> {
+    use ring::aead::{Aad, Nonce};
+    use ring::rand::{SecureRandom, SystemRandom};
+
+    let mut nonce = [0; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .expect("The system's random number generator failed");
+    let len = plaintext.len() + ring::aead::CHACHA20_POLY1305.tag_len();
+    let len = u32::try_from(len).expect("Blobs are smaller than 4 GiB");
+
+    let mut blob = Vec::with_capacity(HEADER_LEN + len as usize);
+    blob.extend_from_slice(&MAGIC);
+    blob.extend_from_slice(&len.to_be_bytes());
+    blob.extend_from_slice(&nonce);
+    let mut ciphertext = plaintext;
+    cipher(key)
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(&blob[..AAD_LEN]),
+            &mut ciphertext,
+        )
+        .expect("The plaintext is small enough");
+    blob.extend(ciphertext);
+    Ok(blob)
+}
+
+#[cfg(all(feature = "fs", not(feature = "encryption")))]
+pub(crate) fn seal(_key: &EncryptionKey, _plaintext: Vec<u8>) -> Result<Vec<u8>, EncryptionError> {
+    Err(EncryptionError::Unsupported)
+}
+
+/// Open the blob at the start of `bytes`.
+/// Also returns how many bytes it took up, so that blobs can be read back to back.
+/// Returns `None` if the blob was cut short.
+#[cfg(feature = "fs")]
+pub(crate) fn open(
+    key: Option<&EncryptionKey>,
+    bytes: &[u8],
+) -> Result<Option<(Vec<u8>, usize)>, EncryptionError> {
+    let key = key.ok_or(EncryptionError::MissingKey)?;
+    let Some(header) = bytes.get(..HEADER_LEN) else {
+        return Ok(None);
+    };
+    let len = u32::from_be_bytes(header[MAGIC.len()..AAD_LEN].try_into().unwrap()) as usize;
+    let Some(ciphertext) = bytes.get(HEADER_LEN..HEADER_LEN + len) else {
+        return Ok(None);
+    };
+    let plaintext = decrypt(key, header, ciphertext.to_vec())?;
+    Ok(Some((plaintext, HEADER_LEN + len)))
+}
+
+#[cfg(feature = "encryption")]
+fn decrypt(
+    key: &EncryptionKey,
+    header: &[u8],
+    mut ciphertext: Vec<u8>,
+) -> Result<Vec<u8>, EncryptionError> {
+    use ring::aead::{Aad, Nonce};
+
+    let nonce = Nonce::try_assume_unique_for_key(&header[AAD_LEN..]).unwrap();
+    let plaintext_len = cipher(key)
+        .open_in_place(nonce, Aad::from(&header[..AAD_LEN]), &mut ciphertext)
+        .map_err(|_| EncryptionError::DecryptionFailed)?
+        .len();
+    ciphertext.truncate(plaintext_len);
+    Ok(ciphertext)
+}
+
+#[cfg(all(feature = "fs", not(feature = "encryption")))]
+fn decrypt(
+    _key: &EncryptionKey,
+    _header: &[u8],
+    _ciphertext: Vec<u8>,
+) -> Result<Vec<u8>, EncryptionError> {
+    Err(EncryptionError::Unsupported)
+}
+
+#[cfg(feature = "encryption")]
+fn cipher(key: &EncryptionKey) -> ring::aead::LessSafeKey {
+    let key = ring::aead::UnboundKey::new(&ring::aead::CHACHA20_POLY1305, &key.0)
+        .expect("Keys are 32 bytes long");
+    ring::aead::LessSafeKey::new(key)
+}
+
+/// Undo [`seal`]: `bytes` must be a single blob.
+///
+/// Unencrypted data is returned as is without a key. With one, only if `accept_unencrypted`.
+#[cfg(feature = "fs")]
+pub(crate) fn unseal(
+    key: Option<&EncryptionKey>,
+    bytes: Vec<u8>,
+    accept_unencrypted: bool,
+) -> Result<Vec<u8>, EncryptionError> {
+    if !is_encrypted(&bytes) {
+        return match key {
+            Some(_) if !accept_unencrypted => Err(EncryptionError::Unencrypted),
+            _ => Ok(bytes),
+        };
+    }
+    match open(key, &bytes)? {
+        Some((plaintext, len)) if len == bytes.len() => Ok(plaintext),
+        // Whatever was appended after the blob wasn't sealed with it.
+        Some(_) => Err(EncryptionError::DecryptionFailed),
+        None => Err(EncryptionError::Truncated),
+    }
+}
+
+#[cfg(test)]
+const TEST_KEY: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+/// A fixed key, for tests.
+#[cfg(test)]
+pub(crate) fn test_key() -> EncryptionKey {
+    TEST_KEY.parse().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_hexadecimal() {
+        assert_eq!(test_key().0[31], 0x1f);
+        assert_eq!(TEST_KEY.to_uppercase().parse(), Ok(test_key()));
+        assert_eq!(TEST_KEY[2..].parse::<EncryptionKey>(), Err(InvalidKey));
+        assert_eq!(
+            format!("{TEST_KEY}00").parse::<EncryptionKey>(),
+            Err(InvalidKey)
+        );
+        // `from_str_radix` alone would accept a sign.
+        let signed = format!("+f{}", &TEST_KEY[2..]);
+        assert_eq!(signed.parse::<EncryptionKey>(), Err(InvalidKey));
+        assert_eq!(format!("{:?}", test_key()), "EncryptionKey(..)");
+    }
+
+    #[test]
+    #[cfg(feature = "encryption")]
+    fn round_trip() {
+        let plaintext = br#"{"next_id": 0, "tickets": []}"#.to_vec();
+        let blob = seal(&test_key(), plaintext.clone()).unwrap();
+        assert!(is_encrypted(&blob));
+        assert_eq!(blob.len(), HEADER_LEN + plaintext.len() + 16);
+        assert_eq!(
+            unseal(Some(&test_key()), blob.clone(), false).unwrap(),
+            plaintext
+        );
+
+        // Nonces are random: the same plaintext never encrypts to the same blob.
+        assert_ne!(seal(&test_key(), plaintext.clone()).unwrap(), blob);
+        // Unencrypted data is only passed through without a key, or when asked to.
+        assert_eq!(unseal(None, plaintext.clone(), false).unwrap(), plaintext);
+        assert_eq!(
+            unseal(Some(&test_key()), plaintext.clone(), false),
+            Err(EncryptionError::Unencrypted)
+        );
+        assert_eq!(
+            unseal(Some(&test_key()), plaintext.clone(), true).unwrap(),
+            plaintext
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "encryption")]
+    fn tampering_is_detected() {
+        let blob = seal(&test_key(), b"secret".to_vec()).unwrap();
+        // Flip every byte but the magic ones, in turn: the length, the nonce,
+        // the ciphertext and the tag are all covered.
+        for i in MAGIC.len()..blob.len() {
+            let mut tampered = blob.clone();
+            tampered[i] ^= 0x01;
+            let err = unseal(Some(&test_key()), tampered, false).unwrap_err();
+            assert!(
+                matches!(
+                    err,
+                    EncryptionError::DecryptionFailed | EncryptionError::Truncated
+                ),
+                "byte {i}: {err:?}"
+            );
+        }
+        let mut appended = blob.clone();
+        appended.push(b'!');
+        assert_eq!(
+            unseal(Some(&test_key()), appended, false),
+            Err(EncryptionError::DecryptionFailed)
+        );
+
+        let mut other_key = test_key();
+        other_key.0[0] ^= 0x01;
+        assert_eq!(
+            unseal(Some(&other_key), blob.clone(), false),
+            Err(EncryptionError::DecryptionFailed)
+        );
+        assert_eq!(unseal(None, blob, false), Err(EncryptionError::MissingKey));
+    }
+
+    #[test]
+    #[cfg(feature = "encryption")]
+    fn blobs_are_self_delimiting() {
+        let mut blobs = seal(&test_key(), b"first".to_vec()).unwrap();
+        let first_len = blobs.len();
+        blobs.extend(seal(&test_key(), b"second".to_vec()).unwrap());
+
+        let (first, len) = open(Some(&test_key()), &blobs).unwrap().unwrap();
+        assert_eq!((first.as_slice(), len), (&b"first"[..], first_len));
+        let (second, _) = open(Some(&test_key()), &blobs[len..]).unwrap().unwrap();
+        assert_eq!(second, b"second");
+        for cut in first_len..blobs.len() {
+            assert_eq!(open(Some(&test_key()), &blobs[first_len..cut]), Ok(None));
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "fs", not(feature = "encryption")))]
+    fn encryption_is_unsupported() {
+        assert_eq!(
+            seal(&test_key(), Vec::new()),
+            Err(EncryptionError::Unsupported)
+        );
+        let mut blob = MAGIC.to_vec();
+        blob.extend([0; HEADER_LEN + 16]);
+        assert_eq!(
+            unseal(Some(&test_key()), blob, false),
+            Err(EncryptionError::Unsupported)
+        );
+    }
+}
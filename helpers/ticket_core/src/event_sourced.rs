@@ -0,0 +1,333 @@
+//! An event-sourced variant of `TicketStore`.
+//!
+//! Instead of mutating tickets in place, [`EventSourcedStore`] records every change as a
+//! [`TicketEvent`] in an append-only log. The current state is whatever you get by folding
+//! the log with [`apply`], starting from an empty store.
+//! Replaying a long log gets slow, so the store also takes a snapshot of the state every
+//! `N` events: [`EventSourcedStore::rebuild`] only needs to replay what came after the last one.
+//!
+//! A ticket's creation time is part of its `Created` event, and the time of each change part
+//! of its `Patched` event, so replaying the log restores them: the store doesn't stamp
+//! replayed tickets with the time of the replay.
+use crate::clock::{Clock, Timestamp};
+use crate::data::{Ticket, TicketDraft, TicketPatch};
+use crate::store::{TicketId, TicketNotFound, TicketStore};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TicketEvent {
+    Created {
+        id: TicketId,
+        draft: TicketDraft,
+        /// When the ticket was created, if the store had a clock.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        created_at: Option<Timestamp>,
+    },
+    Patched {
+        patch: TicketPatch,
+        /// When the ticket was changed, if the store had a clock.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        updated_at: Option<Timestamp>,
+    },
+    Deleted {
+        id: TicketId,
+    },
+}
+
+/// A `Created` event for an id other than the one the store hands out next: the events
+/// aren't being applied in the order they were recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("Ticket #{id} was created out of order: the next ticket would be #{expected}")]
+pub struct OutOfOrder {
+    pub id: TicketId,
+    pub expected: TicketId,
+}
+
+/// Fold a single event into `store`.
+///
+/// Events are facts: they were validated before being recorded, so there's little to reject here.
+/// A patch or deletion for a ticket that doesn't exist can only come from a corrupted log,
+/// and it's ignored. A ticket created out of order would get another id than the one the
+/// log knows it by, though, and every later event would go to the wrong ticket: it's an error,
+/// and `store` is left as it was.
+pub fn apply(store: &mut TicketStore, event: &TicketEvent) -> Result<(), OutOfOrder> {
+    match event {
+        TicketEvent::Created {
+            id,
+            draft,
+            created_at,
+        } => {
+            let expected = TicketId::from(store.next_id());
+            if *id != expected {
+                return Err(OutOfOrder { id: *id, expected });
+            }
+            store.restore_ticket(draft.clone(), *created_at);
+        }
+        TicketEvent::Patched { patch, updated_at } => {
+            let _ = store.restore_update(patch.clone(), *updated_at);
+        }
+        TicketEvent::Deleted { id } => {
+            store.delete(*id);
+        }
+    }
+    Ok(())
+}
+
+/// Fold `events` into `store`, in order.
+pub fn replay<'a>(
+    mut store: TicketStore,
+    events: impl IntoIterator<Item = &'a TicketEvent>,
+) -> Result<TicketStore, OutOfOrder> {
+    for event in events {
+        apply(&mut store, event)?;
+    }
+    Ok(store)
+}
+
+pub struct EventSourcedStore {
+    events: Vec<TicketEvent>,
+    /// Always equal to `replay(TicketStore::new(), &events)`, kept up to date as events are recorded.
+    state: TicketStore,
+    /// The state after the first `n` events, taken when `n` was a multiple of `snapshot_every`.
+    snapshot: Option<(usize, TicketStore)>,
+    snapshot_every: usize,
+}
+
+impl EventSourcedStore {
+    /// Take a snapshot every `snapshot_every` events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `snapshot_every` is zero.
+    pub fn new(snapshot_every: usize) -> Self {
+        assert!(snapshot_every > 0, "`snapshot_every` must be positive");
+        Self {
+            events: Vec::new(),
+            state: TicketStore::new(),
+            snapshot: None,
+            snapshot_every,
+        }
+    }
+
+    /// Stamp the tickets created from now on with the time `clock` tells.
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.state.set_clock(clock);
+    }
+
+    pub fn add_ticket(&mut self, draft: TicketDraft) -> TicketId {
+        let id = TicketId::from(self.state.next_id());
+        let created_at = self.state.now();
+        self.record(TicketEvent::Created {
+            id,
+            draft,
+            created_at,
+        });
+        id
+    }
+
+    pub fn update(&mut self, patch: TicketPatch) -> Result<(), TicketNotFound> {
+        if self.state.get(patch.id).is_none() {
+            return Err(TicketNotFound(patch.id));
+        }
+        let updated_at = self.state.now();
+        self.record(TicketEvent::Patched { patch, updated_at });
+        Ok(())
+    }
+
+    pub fn delete(&mut self, id: TicketId) -> Option<Ticket> {
+        let ticket = self.state.get(id)?.clone();
+        self.record(TicketEvent::Deleted { id });
+        Some(ticket)
+    }
+
+    /// The current state.
+    pub fn state(&self) -> &TicketStore {
+        &self.state
+    }
+
+    /// Every event recorded so far, oldest first.
+    pub fn events(&self) -> &[TicketEvent] {
+        &self.events
+    }
+
+    /// How many events have been recorded when the latest snapshot was taken.
+    pub fn snapshot_len(&self) -> usize {
+        self.snapshot.as_ref().map_or(0, |(n, _)| *n)
+    }
+
+    /// Recompute the state from the latest snapshot and the events recorded after it.
+    pub fn rebuild(&self) -> TicketStore {
+        let (start, base) = match &self.snapshot {
+            Some((n, snapshot)) => (*n, snapshot.clone()),
+            None => (0, TicketStore::new()),
+        };
+        replay(base, &self.events[start..]).expect("The log was recorded in order")
+    }
+
+    /// Append an event to the log and fold it into the current state.
+    fn record(&mut self, event: TicketEvent) {
+        apply(&mut self.state, &event).expect("Ids are handed out in order");
+        self.events.push(event);
+        if self.events.len().is_multiple_of(self.snapshot_every) {
+            self.snapshot = Some((self.events.len(), self.state.clone()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::TestClock;
+    use crate::data::Status;
+    use crate::repository::conformance::draft;
+    use proptest::prelude::*;
+    use std::time::Duration;
+    use ticket_fields::TicketTitle;
+
+    #[test]
+    fn events_are_recorded_only_for_valid_commands() {
+        let mut store = EventSourcedStore::new(10);
+        let id = store.add_ticket(draft());
+        assert!(store.update(TicketPatch::new(TicketId::from(9))).is_err());
+        assert!(store.delete(TicketId::from(9)).is_none());
+        assert_eq!(store.delete(id).unwrap().id, id);
+
+        assert_eq!(
+            store.events(),
+            [
+                TicketEvent::Created {
+                    id,
+                    draft: draft(),
+                    created_at: None
+                },
+                TicketEvent::Deleted { id }
+            ]
+        );
+    }
+
+    #[test]
+    fn replaying_restores_timestamps() {
+        let clock = TestClock::new();
+        let mut store = EventSourcedStore::new(10);
+        store.set_clock(clock.clone());
+        let id = store.add_ticket(draft());
+        let created_at = store.state()[id].created_at;
+        assert_eq!(created_at, Some(clock.now()));
+        clock.advance(Duration::from_secs(60));
+        store.update(TicketPatch::new(id)).unwrap();
+        let updated_at = store.state()[id].updated_at;
+        assert_eq!(updated_at, Some(clock.now()));
+
+        clock.advance(Duration::from_secs(60));
+        let replayed = replay(TicketStore::with_clock(clock), store.events()).unwrap();
+        assert_eq!(replayed[id].created_at, created_at);
+        assert_eq!(replayed[id].updated_at, updated_at);
+    }
+
+    #[test]
+    fn out_of_order_creations_are_rejected() {
+        let mut store = TicketStore::new();
+        let skipped = TicketEvent::Created {
+            id: TicketId::from(1),
+            draft: draft(),
+            created_at: None,
+        };
+        assert_eq!(
+            apply(&mut store, &skipped),
+            Err(OutOfOrder {
+                id: TicketId::from(1),
+                expected: TicketId::from(0)
+            })
+        );
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn snapshots_are_taken_every_n_events() {
+        let mut store = EventSourcedStore::new(3);
+        for _ in 0..7 {
+            store.add_ticket(draft());
+        }
+        assert_eq!(store.snapshot_len(), 6);
+        let rebuilt = store.rebuild();
+        assert_eq!(rebuilt.len(), 7);
+        // The ids keep flowing from where the log left off.
+        let mut rebuilt = rebuilt;
+        assert_eq!(rebuilt.add_ticket(draft()), TicketId::from(7));
+    }
+
+    #[derive(Clone, Debug)]
+    enum Command {
+        Add,
+        /// Indexes are taken modulo the number of ids handed out so far (plus one,
+        /// to hit missing tickets too).
+        SetStatus(usize, Status),
+        Rename(usize, String),
+        Delete(usize),
+    }
+
+    fn command() -> impl Strategy<Value = Command> {
+        let status = prop_oneof![
+            Just(Status::ToDo),
+            Just(Status::InProgress),
+            Just(Status::Done)
+        ];
+        prop_oneof![
+            Just(Command::Add),
+            (any::<usize>(), status).prop_map(|(i, s)| Command::SetStatus(i, s)),
+            (any::<usize>(), "[a-z]{1,10}").prop_map(|(i, t)| Command::Rename(i, t)),
+            any::<usize>().prop_map(Command::Delete),
+        ]
+    }
+
+    proptest! {
+        /// Whatever the command sequence, the event-sourced store ends up in the same state
+        /// as the mutable one—and so does rebuilding from its snapshot and log.
+        #[test]
+        fn matches_the_mutable_store(
+            commands in prop::collection::vec(command(), 0..50),
+            snapshot_every in 1..10usize,
+        ) {
+            let mut mutable = TicketStore::new();
+            let mut sourced = EventSourcedStore::new(snapshot_every);
+            let mut issued = 0u64;
+
+            for command in commands {
+                let pick = |i: usize| TicketId::from(i as u64 % (issued + 1));
+                match command {
+                    Command::Add => {
+                        prop_assert_eq!(mutable.add_ticket(draft()), sourced.add_ticket(draft()));
+                        issued += 1;
+                    }
+                    Command::SetStatus(i, status) => {
+                        let patch = TicketPatch { status: Some(status), ..TicketPatch::new(pick(i)) };
+                        prop_assert_eq!(
+                            mutable.update(patch.clone()).is_ok(),
+                            sourced.update(patch).is_ok()
+                        );
+                    }
+                    Command::Rename(i, title) => {
+                        let title = TicketTitle::try_from(title).unwrap();
+                        let patch = TicketPatch { title: Some(title), ..TicketPatch::new(pick(i)) };
+                        prop_assert_eq!(
+                            mutable.update(patch.clone()).is_ok(),
+                            sourced.update(patch).is_ok()
+                        );
+                    }
+                    Command::Delete(i) => {
+                        prop_assert_eq!(mutable.delete(pick(i)), sourced.delete(pick(i)));
+                    }
+                }
+            }
+
+            let expected: Vec<_> = mutable.iter().collect();
+            prop_assert_eq!(&sourced.state().iter().collect::<Vec<_>>(), &expected);
+            let rebuilt = sourced.rebuild();
+            prop_assert_eq!(&rebuilt.iter().collect::<Vec<_>>(), &expected);
+            let from_scratch = replay(TicketStore::new(), sourced.events()).unwrap();
+            prop_assert_eq!(&from_scratch.iter().collect::<Vec<_>>(), &expected);
+            prop_assert_eq!(rebuilt.next_id(), mutable.next_id());
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! Change notifications for a `TicketStore`.
+//!
+//! Call [`TicketStore::subscribe`](crate::TicketStore::subscribe) to get a receiver:
+//! every change made through the store's methods is then sent to it, in order.
+use crate::data::Ticket;
+use crate::store::TicketId;
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StoreEvent {
+    Added(Ticket),
+    /// The ticket, as it is after the update.
+    Updated(Ticket),
+    Deleted(TicketId),
+}
+
+/// The senders of every live subscription.
+#[derive(Debug, Default)]
+pub(crate) struct Subscribers(Vec<Sender<StoreEvent>>);
+
+/// Subscriptions are tied to a specific store: a clone starts with none.
+impl Clone for Subscribers {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl Subscribers {
+    pub(crate) fn subscribe(&mut self) -> Receiver<StoreEvent> {
+        let (sender, receiver) = channel();
+        self.0.push(sender);
+        receiver
+    }
+
+    /// The senders' slots. What's queued in each channel isn't included.
+    pub(crate) fn approx_memory_bytes(&self) -> usize {
+        self.0.capacity() * size_of::<Sender<StoreEvent>>()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Send `event` to all subscribers, forgetting the ones whose receiver has been dropped.
+    pub(crate) fn notify(&mut self, event: StoreEvent) {
+        self.0.retain(|s| s.send(event.clone()).is_ok());
+    }
+}
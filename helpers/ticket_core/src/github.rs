@@ -0,0 +1,222 @@
+//! Importing GitHub issues as tickets.
+//!
+//! [`parse_issues`] maps the JSON returned by GitHub's
+//! [list repository issues](https://docs.github.com/en/rest/issues/issues#list-repository-issues)
+//! endpoint to [`ImportedIssue`]s. It works offline, on any saved response.
+//! Fetching the issues over HTTP, with `import_github`, requires the `github` feature.
+//!
+//! GitHub is more lenient than we are, so issues are adjusted to fit:
+//! - titles and bodies that are too long are cut short, ending with `…`;
+//! - issues without a body get a placeholder description.
+use serde::Deserialize;
+use ticket_fields::TicketDraftError;
+
+use crate::data::{Status, TicketDraft};
+
+const MAX_TITLE_LEN: usize = 50;
+const MAX_DESCRIPTION_LEN: usize = 500;
+const NO_DESCRIPTION: &str = "No description provided.";
+
+/// A GitHub issue, ready to be added to a store.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedIssue {
+    /// The issue number on GitHub, e.g. `42` for `owner/repo#42`.
+    pub number: u64,
+    pub draft: TicketDraft,
+    /// `ToDo` for open issues, `Done` for closed ones.
+    pub status: Status,
+    /// The names of the issue's labels.
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("Failed to parse the issues returned by GitHub")]
+    Json(#[from] serde_json::Error),
+    #[error("Issue #{number} can't be turned into a ticket")]
+    InvalidIssue {
+        number: u64,
+        #[source]
+        source: TicketDraftError,
+    },
+    #[cfg(feature = "github")]
+    #[error("Failed to fetch issues from GitHub")]
+    Http(#[source] Box<ureq::Error>),
+    #[cfg(feature = "github")]
+    #[error("Failed to read the response from GitHub")]
+    Io(#[from] std::io::Error),
+}
+
+/// The subset of an issue that we care about.
+#[derive(Deserialize)]
+struct Issue {
+    number: u64,
+    title: String,
+    body: Option<String>,
+    state: IssueState,
+    #[serde(default)]
+    labels: Vec<Label>,
+    /// Pull requests are issues too, as far as the API is concerned: this is how to tell them apart.
+    pull_request: Option<serde::de::IgnoredAny>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum IssueState {
+    Open,
+    Closed,
+}
+
+#[derive(Deserialize)]
+struct Label {
+    name: String,
+}
+
+/// Parse a page of issues, as returned by the GitHub API.
+/// Pull requests are skipped.
+pub fn parse_issues(json: &str) -> Result<Vec<ImportedIssue>, ImportError> {
+    let issues: Vec<Issue> = serde_json::from_str(json)?;
+    issues
+        .into_iter()
+        .filter(|issue| issue.pull_request.is_none())
+        .map(convert)
+        .collect()
+}
+
+fn convert(issue: Issue) -> Result<ImportedIssue, ImportError> {
+    let description = match issue.body.as_deref().map(str::trim) {
+        None | Some("") => NO_DESCRIPTION.to_string(),
+        Some(body) => truncate(body, MAX_DESCRIPTION_LEN),
+    };
+    let draft = TicketDraft::new(truncate(issue.title.trim(), MAX_TITLE_LEN), description)
+        .map_err(|source| ImportError::InvalidIssue {
+            number: issue.number,
+            source,
+        })?;
+    Ok(ImportedIssue {
+        number: issue.number,
+        draft,
+        status: match issue.state {
+            IssueState::Open => Status::ToDo,
+            IssueState::Closed => Status::Done,
+        },
+        tags: issue.labels.into_iter().map(|label| label.name).collect(),
+    })
+}
+
+/// Cut `s` down to at most `max_len` bytes, marking the cut with `…`.
+fn truncate(s: &str, max_len: usize) -> String {
+    if s.len() <= max_len {
+        return s.to_string();
+    }
+    const ELLIPSIS: char = '…';
+    let mut end = max_len - ELLIPSIS.len_utf8();
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    let mut truncated = s[..end].trim_end().to_string();
+    truncated.push(ELLIPSIS);
+    truncated
+}
+
+/// Fetch every issue (open or closed) of `owner/repo` from the GitHub API.
+///
+/// Anonymous requests are heavily rate-limited: set `GITHUB_TOKEN` to authenticate.
+#[cfg(feature = "github")]
+pub fn import_github(owner: &str, repo: &str) -> Result<Vec<ImportedIssue>, ImportError> {
+    const PER_PAGE: usize = 100;
+    let url = format!("https://api.github.com/repos/{owner}/{repo}/issues");
+    let token = std::env::var("GITHUB_TOKEN").ok();
+
+    let mut imported = Vec::new();
+    for page in 1.. {
+        let mut request = ureq::get(&url)
+            .query("state", "all")
+            .query("per_page", &PER_PAGE.to_string())
+            .query("page", &page.to_string())
+            .set("Accept", "application/vnd.github+json")
+            .set("User-Agent", "ticket_core");
+        if let Some(token) = &token {
+            request = request.set("Authorization", &format!("Bearer {token}"));
+        }
+        let body = request
+            .call()
+            .map_err(|e| ImportError::Http(Box::new(e)))?
+            .into_string()?;
+        // The page size counts pull requests too, so check it before they're filtered out.
+        let fetched = serde_json::from_str::<Vec<serde::de::IgnoredAny>>(&body)?.len();
+        imported.extend(parse_issues(&body)?);
+        if fetched < PER_PAGE {
+            break;
+        }
+    }
+    Ok(imported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../fixtures/github_issues.json");
+
+    #[test]
+    fn parses_the_fixture() {
+        let issues = parse_issues(FIXTURE).unwrap();
+        let numbers: Vec<_> = issues.iter().map(|i| i.number).collect();
+        // #3 is a pull request.
+        assert_eq!(numbers, [1, 2, 4]);
+
+        let first = &issues[0];
+        assert_eq!(first.draft.title.as_ref(), "Crash when the store is empty");
+        assert_eq!(
+            first.draft.description.as_ref(),
+            "Running `list` on a fresh store panics."
+        );
+        assert_eq!(first.status, Status::ToDo);
+        assert_eq!(first.tags, ["bug", "good first issue"]);
+
+        let second = &issues[1];
+        assert_eq!(second.status, Status::Done);
+        assert_eq!(second.draft.description.as_ref(), NO_DESCRIPTION);
+        assert!(second.tags.is_empty());
+    }
+
+    #[test]
+    fn oversized_fields_are_truncated() {
+        let issues = parse_issues(FIXTURE).unwrap();
+        let long = &issues[2];
+        let title = long.draft.title.as_ref();
+        assert!(title.len() <= MAX_TITLE_LEN);
+        assert!(title.ends_with('…'));
+        let description = long.draft.description.as_ref();
+        assert!(description.len() <= MAX_DESCRIPTION_LEN);
+        assert!(description.ends_with('…'));
+    }
+
+    #[test]
+    fn truncation_respects_char_boundaries() {
+        assert_eq!(truncate("short", 10), "short");
+        // `é` is two bytes long: we can't cut through it.
+        assert_eq!(truncate("ééééé", 7), "éé…");
+    }
+
+    #[test]
+    fn blank_titles_are_rejected() {
+        let json = r#"[{"number": 9, "title": "  ", "body": null, "state": "open"}]"#;
+        assert!(matches!(
+            parse_issues(json),
+            Err(ImportError::InvalidIssue {
+                number: 9,
+                source: TicketDraftError::InvalidTitle(_)
+            })
+        ));
+    }
+
+    #[test]
+    fn malformed_json() {
+        assert!(matches!(
+            parse_issues(r#"{"message": "Not Found"}"#),
+            Err(ImportError::Json(_))
+        ));
+    }
+}
@@ -0,0 +1,121 @@
+//! Storing short, often repeated strings—tag names, for instance—only once.
+//!
+//! An [`Interner`] hands out a [`Symbol`] for each distinct string it's given.
+//! Symbols are plain integers: copying, hashing and comparing them doesn't touch the
+//! string they stand for, which can be looked up again with [`Interner::resolve`].
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::store::hash_map_bytes;
+
+/// A string stored in an [`Interner`].
+/// Symbols are only meaningful to the interner that created them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(u32);
+
+#[derive(Clone, Debug, Default)]
+pub struct Interner {
+    symbols: HashMap<Arc<str>, Symbol>,
+    /// Indexed by symbol. The strings are shared with `symbols`, not copied.
+    strings: Vec<Arc<str>>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The symbol for `s`, storing `s` if it's the first time the interner sees it.
+    pub fn intern(&mut self, s: &str) -> Symbol {
+        if let Some(&symbol) = self.symbols.get(s) {
+            return symbol;
+        }
+        let symbol = Symbol(
+            u32::try_from(self.strings.len())
+                .expect("An interner can't hold more than 2^32 strings"),
+        );
+        let s: Arc<str> = Arc::from(s);
+        self.strings.push(Arc::clone(&s));
+        self.symbols.insert(s, symbol);
+        symbol
+    }
+
+    /// The symbol for `s`, if it has been interned already.
+    pub fn get(&self, s: &str) -> Option<Symbol> {
+        self.symbols.get(s).copied()
+    }
+
+    /// The string that `symbol` stands for.
+    ///
+    /// # Panics
+    ///
+    /// If `symbol` was created by another interner, and this one has fewer strings.
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        &self.strings[symbol.0 as usize]
+    }
+
+    /// Like [`Interner::resolve`], but shares the interner's copy of the string.
+    pub fn share(&self, symbol: Symbol) -> Arc<str> {
+        Arc::clone(&self.strings[symbol.0 as usize])
+    }
+
+    /// How many distinct strings have been interned.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Roughly how many bytes the interner holds on the heap.
+    pub fn approx_memory_bytes(&self) -> usize {
+        // Each string is allocated once, behind its two reference counts.
+        let strings: usize = self
+            .strings
+            .iter()
+            .map(|s| 2 * size_of::<usize>() + s.len())
+            .sum();
+        strings + self.strings.capacity() * size_of::<Arc<str>>() + hash_map_bytes(&self.symbols)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equal_strings_share_a_symbol() {
+        let mut interner = Interner::new();
+        let bug = interner.intern("bug");
+        let feature = interner.intern("feature");
+        assert_ne!(bug, feature);
+        assert_eq!(interner.intern(&String::from("bug")), bug);
+        assert_eq!(interner.len(), 2);
+
+        assert_eq!(interner.resolve(bug), "bug");
+        assert_eq!(interner.resolve(feature), "feature");
+        assert_eq!(interner.get("feature"), Some(feature));
+        assert_eq!(interner.get("Bug"), None);
+    }
+
+    #[test]
+    fn strings_are_stored_once() {
+        let mut interner = Interner::new();
+        let symbol = interner.intern("good first issue");
+        interner.intern("good first issue");
+        // One reference from `symbols`, one from `strings`.
+        assert_eq!(Arc::strong_count(&interner.strings[symbol.0 as usize]), 2);
+    }
+
+    #[test]
+    fn memory_grows_with_distinct_strings_only() {
+        let mut interner = Interner::new();
+        assert_eq!(interner.approx_memory_bytes(), 0);
+        interner.intern("bug");
+        let one = interner.approx_memory_bytes();
+        assert!(one >= "bug".len());
+        interner.intern("bug");
+        assert_eq!(interner.approx_memory_bytes(), one);
+    }
+}
@@ -0,0 +1,123 @@
+//! A minimal key-value storage interface, and two backends for it.
+//!
+//! [`KvStore`] knows nothing about tickets: keys and values are plain bytes.
+//! `repository::KvRepository` maps tickets onto it, so that any key-value store can hold them:
+//! - [`MemoryKv`], an in-memory `BTreeMap`;
+//! - [`FileKv`], with the `fs` feature, an append-only file.
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::error::Error;
+
+#[cfg(feature = "fs")]
+mod file;
+
+#[cfg(feature = "fs")]
+pub use file::FileKv;
+
+/// A key and its value.
+pub type Entry = (Vec<u8>, Vec<u8>);
+
+pub trait KvStore {
+    type Error: Error + Send + Sync + 'static;
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error>;
+
+    /// Set the value of `key`, replacing the previous one, if any.
+    fn put(&mut self, key: &[u8], value: &[u8]) -> Result<(), Self::Error>;
+
+    /// Returns whether `key` had a value.
+    fn delete(&mut self, key: &[u8]) -> Result<bool, Self::Error>;
+
+    /// Every entry whose key starts with `prefix`, ordered by key.
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<Vec<Entry>, Self::Error>;
+}
+
+/// The in-memory backend: it never fails.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryKv {
+    entries: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl MemoryKv {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Every entry, ordered by key.
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], &[u8])> {
+        self.entries
+            .iter()
+            .map(|(k, v)| (k.as_slice(), v.as_slice()))
+    }
+}
+
+impl KvStore for MemoryKv {
+    type Error = Infallible;
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        Ok(self.entries.get(key).cloned())
+    }
+
+    fn put(&mut self, key: &[u8], value: &[u8]) -> Result<(), Self::Error> {
+        self.entries.insert(key.to_vec(), value.to_vec());
+        Ok(())
+    }
+
+    fn delete(&mut self, key: &[u8]) -> Result<bool, Self::Error> {
+        Ok(self.entries.remove(key).is_some())
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<Vec<Entry>, Self::Error> {
+        // Keys are sorted: the ones starting with `prefix` all come right after it.
+        Ok(self
+            .entries
+            .range(prefix.to_vec()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_put_delete() {
+        let mut kv = MemoryKv::new();
+        assert_eq!(kv.get(b"a").unwrap(), None);
+        kv.put(b"a", b"1").unwrap();
+        kv.put(b"a", b"2").unwrap();
+        assert_eq!(kv.get(b"a").unwrap().as_deref(), Some(&b"2"[..]));
+        assert!(kv.delete(b"a").unwrap());
+        assert!(!kv.delete(b"a").unwrap());
+        assert!(kv.is_empty());
+    }
+
+    #[test]
+    fn scan_prefix() {
+        let mut kv = MemoryKv::new();
+        for key in ["a", "ab", "abc", "ac", "b", ""] {
+            kv.put(key.as_bytes(), b"").unwrap();
+        }
+        let keys = |prefix: &str| -> Vec<String> {
+            kv.scan_prefix(prefix.as_bytes())
+                .unwrap()
+                .into_iter()
+                .map(|(key, _)| String::from_utf8(key).unwrap())
+                .collect()
+        };
+        assert_eq!(keys("ab"), ["ab", "abc"]);
+        assert_eq!(keys("a"), ["a", "ab", "abc", "ac"]);
+        assert!(keys("c").is_empty());
+        assert_eq!(keys("").len(), 6);
+    }
+}
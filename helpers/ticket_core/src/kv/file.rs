@@ -0,0 +1,230 @@
+use super::{Entry, KvStore, MemoryKv};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+const PUT: u8 = 0;
+const DELETE: u8 = 1;
+/// The operation byte, then the key and value lengths as little-endian `u32`s.
+const HEADER_LEN: usize = 1 + 4 + 4;
+
+/// A key-value store persisted to an append-only file.
+///
+/// Every write appends a record to the file, and is flushed to disk before returning:
+/// `[op: u8][key length: u32][value length: u32][key][value]`. Opening the file replays the
+/// records into an in-memory index, which serves every read.
+///
+/// A crash mid-append leaves a torn record at the end of the file: it was never acknowledged,
+/// so [`FileKv::open`] drops it. Overwritten and deleted values keep taking up space until
+/// [`FileKv::compact`] is called.
+#[derive(Debug)]
+pub struct FileKv {
+    path: PathBuf,
+    file: File,
+    index: MemoryKv,
+}
+
+impl FileKv {
+    /// Open the store at `path`, creating the file if needed.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let (index, valid_len) = replay(&path)?;
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        // Drop the torn record, if any, so that the next append starts on a record boundary.
+        file.set_len(valid_len)?;
+        Ok(Self { path, file, index })
+    }
+
+    /// Rewrite the file with the live entries only, dropping overwritten and deleted values.
+    pub fn compact(&mut self) -> io::Result<()> {
+        let tmp = self.path.with_extension("tmp");
+        let mut compacted = Vec::new();
+        for (key, value) in self.index.iter() {
+            encode(&mut compacted, PUT, key, value);
+        }
+        fs::write(&tmp, compacted)?;
+        File::open(&tmp)?.sync_all()?;
+        fs::rename(&tmp, &self.path)?;
+        self.file = OpenOptions::new().append(true).open(&self.path)?;
+        Ok(())
+    }
+
+    fn append(&mut self, op: u8, key: &[u8], value: &[u8]) -> io::Result<()> {
+        let mut record = Vec::with_capacity(HEADER_LEN + key.len() + value.len());
+        encode(&mut record, op, key, value);
+        self.file.write_all(&record)?;
+        self.file.sync_data()
+    }
+}
+
+impl KvStore for FileKv {
+    type Error = io::Error;
+
+    // The index can't fail: its errors are `Infallible`, so `let Ok(..)` always matches.
+
+    fn get(&self, key: &[u8]) -> io::Result<Option<Vec<u8>>> {
+        let Ok(value) = self.index.get(key);
+        Ok(value)
+    }
+
+    fn put(&mut self, key: &[u8], value: &[u8]) -> io::Result<()> {
+        self.append(PUT, key, value)?;
+        let Ok(()) = self.index.put(key, value);
+        Ok(())
+    }
+
+    fn delete(&mut self, key: &[u8]) -> io::Result<bool> {
+        let Ok(Some(_)) = self.index.get(key) else {
+            return Ok(false);
+        };
+        self.append(DELETE, key, &[])?;
+        let Ok(deleted) = self.index.delete(key);
+        Ok(deleted)
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> io::Result<Vec<Entry>> {
+        let Ok(entries) = self.index.scan_prefix(prefix);
+        Ok(entries)
+    }
+}
+
+fn encode(buffer: &mut Vec<u8>, op: u8, key: &[u8], value: &[u8]) {
+    let len = |bytes: &[u8]| u32::try_from(bytes.len()).expect("Keys and values are below 4 GiB");
+    buffer.push(op);
+    buffer.extend(len(key).to_le_bytes());
+    buffer.extend(len(value).to_le_bytes());
+    buffer.extend(key);
+    buffer.extend(value);
+}
+
+/// Rebuild the index from the file at `path`.
+/// Also returns the length of the valid prefix of the file: everything after it is a torn write.
+fn replay(path: &Path) -> io::Result<(MemoryKv, u64)> {
+    let content = match fs::read(path) {
+        Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+        read => read?,
+    };
+    let mut index = MemoryKv::new();
+    let mut rest = content.as_slice();
+    while let Some((Record { op, key, value }, remainder)) = decode(rest) {
+        match op {
+            PUT => {
+                let Ok(()) = index.put(key, value);
+            }
+            DELETE => {
+                let Ok(_) = index.delete(key);
+            }
+            _ => {
+                let offset = content.len() - rest.len();
+                return Err(io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "Unknown operation {op} at byte {offset} of {}",
+                        path.display()
+                    ),
+                ));
+            }
+        }
+        rest = remainder;
+    }
+    Ok((index, (content.len() - rest.len()) as u64))
+}
+
+struct Record<'a> {
+    op: u8,
+    key: &'a [u8],
+    value: &'a [u8],
+}
+
+/// Split the first complete record off `bytes`, if there is one.
+fn decode(bytes: &[u8]) -> Option<(Record<'_>, &[u8])> {
+    let (header, rest) = bytes.split_first_chunk::<HEADER_LEN>()?;
+    let len = |range: std::ops::Range<usize>| {
+        u32::from_le_bytes(header[range].try_into().unwrap()) as usize
+    };
+    let (key_len, value_len) = (len(1..5), len(5..9));
+    if rest.len() < key_len + value_len {
+        return None;
+    }
+    let (key, rest) = rest.split_at(key_len);
+    let (value, rest) = rest.split_at(value_len);
+    let op = header[0];
+    Some((Record { op, key, value }, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn survives_a_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.kv");
+        let mut kv = FileKv::open(&path).unwrap();
+        kv.put(b"a", b"1").unwrap();
+        kv.put(b"b", b"2").unwrap();
+        kv.put(b"a", b"3").unwrap();
+        assert!(kv.delete(b"b").unwrap());
+        drop(kv);
+
+        let kv = FileKv::open(&path).unwrap();
+        assert_eq!(kv.get(b"a").unwrap().as_deref(), Some(&b"3"[..]));
+        assert_eq!(kv.get(b"b").unwrap(), None);
+    }
+
+    #[test]
+    fn torn_records_are_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.kv");
+        let mut kv = FileKv::open(&path).unwrap();
+        kv.put(b"kept", b"value").unwrap();
+        kv.put(b"torn", b"value").unwrap();
+        drop(kv);
+        // Simulate a crash halfway through the second append.
+        let len = fs::metadata(&path).unwrap().len();
+        OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(len - 3)
+            .unwrap();
+
+        let mut kv = FileKv::open(&path).unwrap();
+        assert_eq!(kv.get(b"torn").unwrap(), None);
+        // The next append starts where the torn record used to.
+        kv.put(b"after", b"crash").unwrap();
+        drop(kv);
+        let kv = FileKv::open(&path).unwrap();
+        assert_eq!(kv.scan_prefix(b"").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn corrupted_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.kv");
+        fs::write(&path, [7, 0, 0, 0, 0, 0, 0, 0, 0]).unwrap();
+        let err = FileKv::open(&path).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn compaction_drops_dead_values() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.kv");
+        let mut kv = FileKv::open(&path).unwrap();
+        for i in 0..10u8 {
+            kv.put(b"counter", &[i]).unwrap();
+        }
+        kv.put(b"gone", b"soon").unwrap();
+        kv.delete(b"gone").unwrap();
+        let before = fs::metadata(&path).unwrap().len();
+        kv.compact().unwrap();
+        assert!(fs::metadata(&path).unwrap().len() < before);
+
+        kv.put(b"other", b"value").unwrap();
+        drop(kv);
+        let kv = FileKv::open(&path).unwrap();
+        assert_eq!(kv.get(b"counter").unwrap().as_deref(), Some(&[9][..]));
+        assert_eq!(kv.scan_prefix(b"").unwrap().len(), 2);
+    }
+}
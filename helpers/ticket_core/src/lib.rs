@@ -0,0 +1,119 @@
+//! The canonical ticket management types, as they stand at the end of the course.
+//!
+//! The exercises build the `Ticket`/`TicketStore` model up one step at a time, each
+//! with its own local copy. Code that needs the finished model—the async server,
+//! tools, benchmarks—should depend on this crate instead of growing yet another copy.
+//!
+//! Optional features:
+//! - `std` (default): everything but the data model itself—stores, workspaces, auth, config.
+//!   Without it, the crate is `#![no_std]`: [`data`] only needs `alloc`, so tickets can be
+//!   validated and (de)serialized on embedded targets too.
+//! - `fs` (default): reading and writing files—snapshots, backups, `DurableStore`, `Config::load`.
+//! - `threads` (default): the channel-based server in `client`, which runs on its own thread.
+//! - `gzip`: gzip-compressed snapshots and write-ahead logs, see [`compression`].
+//! - `encryption`: encrypted snapshots and write-ahead logs, see [`encryption`].
+//! - `sqlite`: a SQLite-backed `TicketRepository`, `repository::SqliteTicketRepository`.
+//! - `github`: `github::import_github`, to fetch issues straight from GitHub.
+//!
+//! With `std` alone, what's left only needs memory: it compiles to
+//! `wasm32-unknown-unknown`, as used by `ticket-wasm`.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+pub mod clock;
+pub mod custom_fields;
+pub mod data;
+
+#[cfg(feature = "std")]
+pub mod aggregate;
+#[cfg(feature = "std")]
+pub mod auth;
+#[cfg(feature = "fs")]
+pub mod backup;
+#[cfg(feature = "threads")]
+pub mod client;
+#[cfg(feature = "threads")]
+pub mod command_log;
+#[cfg(feature = "std")]
+pub mod compression;
+#[cfg(feature = "std")]
+pub mod config;
+#[cfg(feature = "fs")]
+pub mod durable;
+#[cfg(feature = "std")]
+pub mod encryption;
+#[cfg(feature = "std")]
+pub mod event_sourced;
+#[cfg(feature = "std")]
+pub mod events;
+#[cfg(feature = "std")]
+pub mod github;
+#[cfg(feature = "std")]
+pub mod intern;
+#[cfg(feature = "std")]
+pub mod kv;
+#[cfg(feature = "std")]
+pub mod query;
+#[cfg(feature = "std")]
+pub mod rate;
+#[cfg(feature = "std")]
+pub mod repository;
+#[cfg(feature = "fs")]
+pub mod snapshot;
+#[cfg(feature = "std")]
+pub mod stale;
+#[cfg(feature = "std")]
+pub mod store;
+#[cfg(feature = "std")]
+mod tags;
+#[cfg(feature = "std")]
+pub mod template;
+#[cfg(feature = "std")]
+pub mod workspace;
+
+pub use clock::Timestamp;
+pub use custom_fields::{CustomFieldError, CustomFields, FieldSchema, FieldType, FieldValue};
+pub use data::{
+    Assignee, ParseStatusError, Status, Ticket, TicketDraft, TicketId, TicketNotFound, TicketPatch,
+};
+pub use ticket_fields::{TicketDescription, TicketTitle};
+
+#[cfg(feature = "std")]
+pub use aggregate::{Facet, FacetValue, GroupBy};
+#[cfg(feature = "std")]
+pub use auth::{AuthError, Role, User, Users};
+#[cfg(feature = "fs")]
+pub use backup::{list_backups, BackupInfo, BackupMetadata, RestoreError};
+#[cfg(feature = "std")]
+pub use clock::{Clock, SystemClock, TestClock};
+#[cfg(feature = "std")]
+pub use compression::Compression;
+#[cfg(feature = "std")]
+pub use config::{Config, ConfigError, KeySource};
+#[cfg(feature = "fs")]
+pub use durable::{DurableStore, History, HistoryError, WalRecord};
+#[cfg(feature = "std")]
+pub use encryption::{EncryptionError, EncryptionKey};
+#[cfg(feature = "std")]
+pub use event_sourced::{EventSourcedStore, TicketEvent};
+#[cfg(feature = "std")]
+pub use events::StoreEvent;
+#[cfg(feature = "std")]
+pub use kv::KvStore;
+#[cfg(feature = "std")]
+pub use query::{Order, Query, SortKey};
+#[cfg(feature = "std")]
+pub use rate::RateTracker;
+#[cfg(feature = "std")]
+pub use repository::{KvRepository, RepositoryError, TicketRepository};
+#[cfg(feature = "fs")]
+pub use snapshot::Encoding;
+#[cfg(feature = "std")]
+pub use stale::{AuditEntry, StaleTickets};
+#[cfg(feature = "std")]
+pub use store::{StoreStats, TicketStore, Transaction, TransactionGuard};
+#[cfg(feature = "std")]
+pub use template::{FromTemplate, TemplateError, TemplateOverrides, TicketTemplate};
+#[cfg(feature = "std")]
+pub use workspace::{ProjectName, ScopedId, Workspace, WorkspaceError};
@@ -0,0 +1,353 @@
+//! Looking tickets up by their content, rather than by id, and sorting them.
+use serde::Deserialize;
+use std::cmp::Ordering;
+
+use crate::data::{Status, Ticket, TicketId};
+use crate::store::TicketStore;
+
+/// Which tickets [`TicketStore::query`] returns.
+/// Every criterion is optional: the default query matches every ticket.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Query {
+    pub status: Option<Status>,
+    pub assignee: Option<String>,
+    /// Matched, ignoring case, against the title and the description.
+    pub text: Option<String>,
+}
+
+impl Query {
+    pub fn matches(&self, ticket: &Ticket) -> bool {
+        self.status.is_none_or(|status| ticket.status == status)
+            && self
+                .assignee
+                .as_ref()
+                .is_none_or(|assignee| ticket.assignee.as_deref() == Some(assignee.as_str()))
+            && self.text.as_ref().is_none_or(|text| {
+                let text = text.to_lowercase();
+                [ticket.title.as_ref(), ticket.description.as_ref()]
+                    .iter()
+                    .any(|field| field.to_lowercase().contains(&text))
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Ascending,
+    Descending,
+}
+
+/// A field to sort tickets by, for [`TicketStore::sorted_by`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SortKey {
+    /// Ids are handed out in sequence: this is also the order tickets were created in.
+    Id(Order),
+    /// Byte-wise, so uppercase letters come before lowercase ones.
+    Title(Order),
+    /// Following the workflow: to do, in progress, done.
+    Status(Order),
+    /// Unassigned tickets come before assigned ones.
+    Assignee(Order),
+    /// Tickets created without a clock, with no creation time, come first.
+    CreatedAt(Order),
+    /// The custom field with this name, ordered like
+    /// [`FieldValue`](crate::custom_fields::FieldValue)s are.
+    /// Tickets without it come first.
+    Field(String, Order),
+}
+
+impl SortKey {
+    pub fn compare(&self, a: &Ticket, b: &Ticket) -> Ordering {
+        let (ordering, order) = match self {
+            SortKey::Id(order) => (a.id.cmp(&b.id), order),
+            SortKey::Title(order) => (a.title.as_ref().cmp(b.title.as_ref()), order),
+            SortKey::Status(order) => (rank(a.status).cmp(&rank(b.status)), order),
+            SortKey::Assignee(order) => (a.assignee.cmp(&b.assignee), order),
+            SortKey::CreatedAt(order) => (a.created_at.cmp(&b.created_at), order),
+            SortKey::Field(field, order) => (
+                a.custom_fields.get(field).cmp(&b.custom_fields.get(field)),
+                order,
+            ),
+        };
+        match order {
+            Order::Ascending => ordering,
+            Order::Descending => ordering.reverse(),
+        }
+    }
+}
+
+/// How alike two titles are, from 0 (nothing in common) to 1 (the same title).
+///
+/// It's one minus the edit distance between the titles, over the length of the longer one,
+/// counting characters. Case and runs of whitespace are ignored: "Crash on  startup" and
+/// "crash on startup" are the same title.
+pub fn title_similarity(a: &str, b: &str) -> f64 {
+    let normalize = |title: &str| -> Vec<char> {
+        title
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+            .chars()
+            .collect()
+    };
+    let (a, b) = (normalize(a), normalize(b));
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - edit_distance(&a, &b) as f64 / longest as f64
+}
+
+/// Levenshtein distance: how many characters to insert, delete or substitute to turn `a` into `b`.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    // Only the previous row of the table is needed to compute the next one.
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+fn rank(status: Status) -> u8 {
+    match status {
+        Status::ToDo => 0,
+        Status::InProgress => 1,
+        Status::Done => 2,
+    }
+}
+
+impl TicketStore {
+    /// The tickets matching `query`, in the store's order.
+    pub fn query<'a>(&'a self, query: &'a Query) -> impl Iterator<Item = &'a Ticket> {
+        self.iter().filter(|ticket| query.matches(ticket))
+    }
+
+    /// Every ticket, sorted by the first key, then by the second one among tickets that are equal
+    /// according to the first, and so on.
+    ///
+    /// The sort is stable: tickets that are equal according to every key keep the store's order.
+    pub fn sorted_by(&self, keys: &[SortKey]) -> Vec<&Ticket> {
+        let mut tickets: Vec<_> = self.iter().collect();
+        tickets.sort_by(|a, b| {
+            keys.iter()
+                .map(|key| key.compare(a, b))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+        tickets
+    }
+
+    /// The tickets whose title is at least `threshold` similar to `title` (see
+    /// [`title_similarity`]), with their similarity, most similar first.
+    pub fn find_similar(&self, title: &str, threshold: f64) -> Vec<(TicketId, f64)> {
+        let mut similar: Vec<_> = self
+            .iter()
+            .map(|ticket| (ticket.id, title_similarity(title, ticket.title.as_ref())))
+            .filter(|&(_, similarity)| similarity >= threshold)
+            .collect();
+        // Ties keep the store's order.
+        similar.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        similar
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::Timestamp;
+    use crate::custom_fields::{CustomFields, FieldSchema, FieldType};
+    use crate::data::{Assignee, TicketDraft, TicketPatch};
+
+    fn store() -> TicketStore {
+        let mut store = TicketStore::new();
+        for (title, description) in [
+            ("Crash on startup", "It panics"),
+            ("Dark mode", "Add a dark theme"),
+        ] {
+            store.add_ticket(TicketDraft::new(title.into(), description.into()).unwrap());
+        }
+        let mut patch = TicketPatch::new(1.into());
+        patch.status = Some(Status::InProgress);
+        patch.assignee = Some(Some("alice".into()));
+        store.update(patch).unwrap();
+        store
+    }
+
+    fn titles(store: &TicketStore, query: Query) -> Vec<String> {
+        store
+            .query(&query)
+            .map(|t| t.title.clone().into())
+            .collect()
+    }
+
+    #[test]
+    fn criteria_are_combined() {
+        let store = store();
+        assert_eq!(titles(&store, Query::default()).len(), 2);
+        let query = Query {
+            status: Some(Status::InProgress),
+            assignee: Some("alice".into()),
+            ..Query::default()
+        };
+        assert_eq!(titles(&store, query), ["Dark mode"]);
+        let query = Query {
+            status: Some(Status::Done),
+            assignee: Some("alice".into()),
+            ..Query::default()
+        };
+        assert!(titles(&store, query).is_empty());
+    }
+
+    #[test]
+    fn text_is_matched_ignoring_case() {
+        let store = store();
+        let query = |text: &str| Query {
+            text: Some(text.into()),
+            ..Query::default()
+        };
+        assert_eq!(titles(&store, query("PANIC")), ["Crash on startup"]);
+        assert_eq!(titles(&store, query("dark")), ["Dark mode"]);
+    }
+
+    /// Tickets with pairwise ties on status and assignee.
+    fn store_with_ties() -> TicketStore {
+        let mut store = TicketStore::new();
+        for (title, status, assignee) in [
+            ("b", Status::Done, Some("alice")),
+            ("a", Status::ToDo, None),
+            ("c", Status::InProgress, Some("bob")),
+            ("a", Status::Done, Some("alice")),
+            ("b", Status::ToDo, Some("bob")),
+            ("a", Status::Done, None),
+        ] {
+            let id = store.add_ticket(TicketDraft::new(title.into(), "-".into()).unwrap());
+            let mut patch = TicketPatch::new(id);
+            patch.status = Some(status);
+            patch.assignee = Some(assignee.map(Assignee::from));
+            store.update(patch).unwrap();
+        }
+        store
+    }
+
+    fn ids(tickets: Vec<&Ticket>) -> Vec<u64> {
+        tickets.iter().map(|t| t.id.value()).collect()
+    }
+
+    #[test]
+    fn later_keys_break_ties() {
+        let store = store_with_ties();
+        let by_status = [SortKey::Status(Order::Descending)];
+        assert_eq!(ids(store.sorted_by(&by_status)), [0, 3, 5, 2, 1, 4]);
+
+        let by_status_then_title = [
+            SortKey::Status(Order::Descending),
+            SortKey::Title(Order::Ascending),
+        ];
+        assert_eq!(
+            ids(store.sorted_by(&by_status_then_title)),
+            [3, 5, 0, 2, 1, 4]
+        );
+
+        let by_status_title_then_newest = [
+            SortKey::Status(Order::Descending),
+            SortKey::Title(Order::Ascending),
+            SortKey::Id(Order::Descending),
+        ];
+        assert_eq!(
+            ids(store.sorted_by(&by_status_title_then_newest)),
+            [5, 3, 0, 2, 1, 4]
+        );
+    }
+
+    #[test]
+    fn sorting_by_a_field_then_by_creation_time() {
+        let mut store = TicketStore::new();
+        store
+            .set_schema(FieldSchema::new().with("priority", FieldType::Integer))
+            .unwrap();
+        for (priority, created_at) in [
+            (Some(1), 20),
+            (Some(3), 30),
+            (None, 10),
+            (Some(1), 10),
+            (Some(3), 10),
+            (Some(3), 30),
+        ] {
+            let draft = TicketDraft::new("A title".into(), "-".into()).unwrap();
+            let fields = CustomFields::from_iter(priority.map(|p| ("priority", p)));
+            let id = store.add_ticket_with_fields(draft, fields).unwrap();
+            // Backdated directly: the store's clock can only tell the time it is now.
+            store.get_mut(id).unwrap().created_at = Some(Timestamp::from_millis(created_at));
+        }
+
+        let keys = [
+            SortKey::Field("priority".into(), Order::Descending),
+            SortKey::CreatedAt(Order::Ascending),
+            SortKey::Id(Order::Ascending),
+        ];
+        assert_eq!(ids(store.sorted_by(&keys)), [4, 1, 5, 3, 0, 2]);
+    }
+
+    #[test]
+    fn ties_keep_the_store_order() {
+        let mut store = store_with_ties();
+        assert_eq!(ids(store.sorted_by(&[])), [0, 1, 2, 3, 4, 5]);
+
+        let by_assignee = [SortKey::Assignee(Order::Ascending)];
+        assert_eq!(ids(store.sorted_by(&by_assignee)), [1, 5, 0, 3, 2, 4]);
+        // Deleting moves the last ticket into the gap, and ties follow the new order.
+        store.delete(0.into());
+        assert_eq!(ids(store.sorted_by(&by_assignee)), [5, 1, 3, 2, 4]);
+    }
+
+    #[test]
+    fn title_similarity_scores() {
+        assert_eq!(
+            title_similarity("Crash on startup", "Crash on startup"),
+            1.0
+        );
+        // Case and spacing don't count.
+        assert_eq!(
+            title_similarity("Crash on  startup", "CRASH ON STARTUP"),
+            1.0
+        );
+        // One substitution in 16 characters.
+        assert_eq!(
+            title_similarity("Crash on startup", "Crash in startup"),
+            0.9375
+        );
+        assert!(title_similarity("Crash on startup", "Dark mode") < 0.3);
+        assert_eq!(title_similarity("", ""), 1.0);
+        assert_eq!(title_similarity("abc", ""), 0.0);
+    }
+
+    #[test]
+    fn finding_similar_titles() {
+        let mut store = store();
+        for title in ["Crashes on startup", "Crash at startup", "Startup is slow"] {
+            store.add_ticket(TicketDraft::new(title.into(), "A description".into()).unwrap());
+        }
+        // Exact first, then the near duplicates, most similar first.
+        let similar = store.find_similar("crash on startup", 0.8);
+        let ids: Vec<_> = similar.iter().map(|&(id, _)| id.value()).collect();
+        assert_eq!(ids, [0, 2, 3]);
+        assert_eq!(similar[0].1, 1.0);
+        assert!(similar[1].1 > similar[2].1);
+
+        // Dissimilar titles don't make the cut.
+        assert!(store.find_similar("Add a dark theme", 0.8).is_empty());
+        // Every ticket is at least 0 similar.
+        assert_eq!(
+            store.find_similar("Add a dark theme", 0.0).len(),
+            store.len()
+        );
+    }
+}
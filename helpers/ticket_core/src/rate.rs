@@ -0,0 +1,203 @@
+//! Counting events over a sliding window of time: how many tickets were created in the
+//! last five minutes, in the last hour, and so on.
+//!
+//! Time comes from a [`Clock`], so that tests can move it forward by hand with a
+//! [`TestClock`](crate::clock::TestClock) instead of sleeping.
+use crate::clock::{Clock, SystemClock, Timestamp};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Bucket {
+    /// The tick this bucket counts events for, in `resolution`s since the tracker's origin.
+    tick: u64,
+    count: u64,
+}
+
+/// Counts events over a sliding window of time.
+///
+/// Time is cut into ticks, `resolution` long, and the window's ticks are kept in a ring of
+/// buckets. Recording an event only touches the current tick's bucket—emptying it first if
+/// it still holds a tick from an earlier trip around the ring—so it takes constant time,
+/// however many events were recorded. Counting sums up at most every bucket.
+///
+/// Counts are accurate to a tick: the current one is always counted in full, so
+/// [`RateTracker::count_last`] may include events up to one `resolution` older than asked for.
+#[derive(Debug, Clone)]
+pub struct RateTracker {
+    clock: Arc<dyn Clock>,
+    origin: Timestamp,
+    resolution: Duration,
+    len: usize,
+    /// Only allocated with the first event: most trackers never see one.
+    buckets: Vec<Bucket>,
+}
+
+impl RateTracker {
+    /// Count the events of the last `window`, `resolution` by `resolution`.
+    ///
+    /// # Panics
+    ///
+    /// If `resolution` is zero, or longer than `window`.
+    pub fn new(window: Duration, resolution: Duration) -> Self {
+        Self::with_clock(window, resolution, SystemClock)
+    }
+
+    /// Like [`RateTracker::new`], reading the time from `clock` rather than the system's.
+    /// The system clock is never asked.
+    ///
+    /// # Panics
+    ///
+    /// If `resolution` is zero, or longer than `window`.
+    pub fn with_clock(window: Duration, resolution: Duration, clock: impl Clock + 'static) -> Self {
+        assert!(!resolution.is_zero(), "The resolution can't be zero");
+        assert!(
+            resolution <= window,
+            "The resolution can't be longer than the window"
+        );
+        let len = window.as_nanos().div_ceil(resolution.as_nanos());
+        Self {
+            origin: clock.now(),
+            clock: Arc::new(clock),
+            resolution,
+            len: usize::try_from(len).expect("Too many buckets"),
+            buckets: Vec::new(),
+        }
+    }
+
+    /// The last hour, ten seconds at a time, by `clock`.
+    pub fn last_hour(clock: impl Clock + 'static) -> Self {
+        Self::with_clock(Duration::from_secs(60 * 60), Duration::from_secs(10), clock)
+    }
+
+    /// The time, as told by the tracker's clock.
+    pub fn now(&self) -> Timestamp {
+        self.clock.now()
+    }
+
+    /// Roughly how many bytes the tracker holds on the heap, its clock aside: clones share it.
+    pub(crate) fn approx_memory_bytes(&self) -> usize {
+        self.buckets.capacity() * size_of::<Bucket>()
+    }
+
+    /// How far back events are counted.
+    pub fn window(&self) -> Duration {
+        self.resolution * self.len as u32
+    }
+
+    /// Record an event, now.
+    pub fn record(&mut self) {
+        let tick = self.tick();
+        if self.buckets.is_empty() {
+            self.buckets = vec![Bucket::default(); self.len];
+        }
+        let bucket = &mut self.buckets[(tick % self.len as u64) as usize];
+        if bucket.tick != tick {
+            *bucket = Bucket { tick, count: 0 };
+        }
+        bucket.count += 1;
+    }
+
+    /// How many events were recorded during the last `span`, rounded up to a whole number
+    /// of ticks. Spans longer than the window are cut down to it.
+    pub fn count_last(&self, span: Duration) -> u64 {
+        let now = self.tick();
+        let ticks = span.as_nanos().div_ceil(self.resolution.as_nanos());
+        let ticks = ticks.min(self.len as u128) as u64;
+        self.buckets
+            .iter()
+            .filter(|b| b.tick <= now && now - b.tick < ticks)
+            .map(|b| b.count)
+            .sum()
+    }
+
+    /// The current tick.
+    fn tick(&self) -> u64 {
+        let elapsed = self.clock.now().saturating_duration_since(self.origin);
+        (elapsed.as_nanos() / self.resolution.as_nanos()) as u64
+    }
+}
+
+/// The last hour, ten seconds at a time, by the system's clock.
+impl Default for RateTracker {
+    fn default() -> Self {
+        Self::last_hour(SystemClock)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::TestClock;
+
+    const SECOND: Duration = Duration::from_secs(1);
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    fn tracker(clock: &TestClock) -> RateTracker {
+        RateTracker::with_clock(10 * MINUTE, MINUTE, clock.clone())
+    }
+
+    #[test]
+    fn counts_slide_with_time() {
+        let clock = TestClock::new();
+        let mut tracker = tracker(&clock);
+        assert_eq!(tracker.window(), 10 * MINUTE);
+        assert_eq!(tracker.count_last(10 * MINUTE), 0);
+
+        tracker.record();
+        tracker.record();
+        clock.advance(MINUTE);
+        tracker.record();
+        assert_eq!(tracker.count_last(MINUTE), 1);
+        assert_eq!(tracker.count_last(2 * MINUTE), 3);
+        // Partial ticks are rounded up.
+        assert_eq!(tracker.count_last(61 * SECOND), 3);
+        assert_eq!(tracker.count_last(Duration::ZERO), 0);
+
+        clock.advance(9 * MINUTE);
+        assert_eq!(tracker.count_last(10 * MINUTE), 1);
+        assert_eq!(tracker.count_last(60 * MINUTE), 1);
+        clock.advance(MINUTE);
+        assert_eq!(tracker.count_last(10 * MINUTE), 0);
+    }
+
+    #[test]
+    fn buckets_are_reused_around_the_ring() {
+        let clock = TestClock::new();
+        let mut tracker = tracker(&clock);
+        for _ in 0..5 {
+            tracker.record();
+        }
+        // Same bucket, one trip around the ring later.
+        clock.advance(10 * MINUTE);
+        tracker.record();
+        assert_eq!(tracker.count_last(10 * MINUTE), 1);
+        assert_eq!(tracker.buckets.len(), 10);
+    }
+
+    #[test]
+    fn ticks_count_from_the_clocks_time() {
+        // Far behind the system's: ticks counted from the system's time would never pass.
+        let clock = TestClock::starting_at(Timestamp::from_millis(0));
+        let mut tracker = tracker(&clock);
+        tracker.record();
+        clock.advance(10 * MINUTE);
+        assert_eq!(tracker.count_last(10 * MINUTE), 0);
+    }
+
+    #[test]
+    fn clones_share_their_clock() {
+        let clock = TestClock::new();
+        let mut tracker = tracker(&clock);
+        tracker.record();
+        let copy = tracker.clone();
+        clock.advance(10 * MINUTE);
+        assert_eq!(copy.count_last(10 * MINUTE), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "The resolution can't be longer than the window")]
+    fn resolution_fits_in_the_window() {
+        RateTracker::new(SECOND, MINUTE);
+    }
+}
@@ -0,0 +1,153 @@
+//! Storage backends for tickets.
+//!
+//! [`TicketRepository`] is the interface shared by every backend: the in-memory
+//! [`TicketStore`], [`KvRepository`] on top of any [`KvStore`](crate::kv::KvStore) and,
+//! behind the `sqlite` feature, `SqliteTicketRepository`.
+//! Code that only needs to read and write tickets should be generic over it.
+//!
+//! [`CachedRepository`] can be layered on top of any backend to cut down on reads.
+use crate::data::{Ticket, TicketDraft, TicketPatch};
+use crate::store::{TicketId, TicketNotFound, TicketStore};
+use std::error::Error;
+
+mod cached;
+mod kv;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+pub use cached::{CacheStats, CachedRepository};
+pub use kv::KvRepository;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteTicketRepository;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RepositoryError {
+    #[error(transparent)]
+    NotFound(#[from] TicketNotFound),
+    /// The backend itself failed, e.g. because the database couldn't be reached.
+    #[error("The storage backend failed")]
+    Backend(#[source] Box<dyn Error + Send + Sync + 'static>),
+}
+
+impl RepositoryError {
+    pub fn backend(e: impl Error + Send + Sync + 'static) -> Self {
+        Self::Backend(Box::new(e))
+    }
+}
+
+pub trait TicketRepository {
+    fn insert(&mut self, draft: TicketDraft) -> Result<TicketId, RepositoryError>;
+
+    fn get(&self, id: TicketId) -> Result<Option<Ticket>, RepositoryError>;
+
+    /// Fails with `RepositoryError::NotFound` if there's no ticket with the patch's id.
+    fn update(&mut self, patch: TicketPatch) -> Result<(), RepositoryError>;
+
+    /// Returns the deleted ticket, or `None` if there was no ticket with that id.
+    fn delete(&mut self, id: TicketId) -> Result<Option<Ticket>, RepositoryError>;
+
+    /// All tickets, ordered by id.
+    fn list(&self) -> Result<Vec<Ticket>, RepositoryError>;
+}
+
+/// The in-memory backend: it never fails.
+impl TicketRepository for TicketStore {
+    fn insert(&mut self, draft: TicketDraft) -> Result<TicketId, RepositoryError> {
+        Ok(self.add_ticket(draft))
+    }
+
+    fn get(&self, id: TicketId) -> Result<Option<Ticket>, RepositoryError> {
+        Ok(TicketStore::get(self, id).cloned())
+    }
+
+    fn update(&mut self, patch: TicketPatch) -> Result<(), RepositoryError> {
+        Ok(TicketStore::update(self, patch)?)
+    }
+
+    fn delete(&mut self, id: TicketId) -> Result<Option<Ticket>, RepositoryError> {
+        Ok(TicketStore::delete(self, id))
+    }
+
+    fn list(&self) -> Result<Vec<Ticket>, RepositoryError> {
+        // The store's own order drifts from the ids' order once tickets are deleted.
+        let mut tickets: Vec<_> = self.iter().cloned().collect();
+        tickets.sort_unstable_by_key(|t| t.id);
+        Ok(tickets)
+    }
+}
+
+/// A test suite that every backend must pass.
+#[cfg(test)]
+pub(crate) mod conformance {
+    use super::*;
+    use crate::data::Status;
+    pub(crate) use ticket_fields::test_helpers::ticket_draft as draft;
+    use ticket_fields::test_helpers::{ticket_description, ticket_title};
+
+    pub(crate) fn check(repository: &mut impl TicketRepository) {
+        assert!(repository.list().unwrap().is_empty());
+
+        let first = repository.insert(draft()).unwrap();
+        let second = repository.insert(draft()).unwrap();
+        assert_ne!(first, second);
+
+        let ticket = repository.get(first).unwrap().unwrap();
+        assert_eq!(ticket.id, first);
+        assert_eq!(ticket.title, ticket_title());
+        assert_eq!(ticket.description, ticket_description());
+        assert_eq!(ticket.status, Status::ToDo);
+        assert_eq!(ticket.assignee, None);
+
+        repository
+            .update(TicketPatch {
+                status: Some(Status::Done),
+                assignee: Some(Some("Alice".into())),
+                ..TicketPatch::new(second)
+            })
+            .unwrap();
+        let updated = repository.get(second).unwrap().unwrap();
+        assert_eq!(updated.status, Status::Done);
+        assert_eq!(updated.assignee.as_deref(), Some("Alice"));
+        assert_eq!(updated.title, ticket_title());
+
+        repository
+            .update(TicketPatch {
+                assignee: Some(None),
+                ..TicketPatch::new(second)
+            })
+            .unwrap();
+        assert_eq!(repository.get(second).unwrap().unwrap().assignee, None);
+
+        let missing = TicketId::from(1_000);
+        assert!(matches!(
+            repository.update(TicketPatch::new(missing)),
+            Err(RepositoryError::NotFound(TicketNotFound(id))) if id == missing
+        ));
+        assert!(repository.get(missing).unwrap().is_none());
+
+        assert_eq!(repository.delete(first).unwrap().unwrap().id, first);
+        assert!(repository.delete(first).unwrap().is_none());
+        let ids: Vec<_> = repository.list().unwrap().iter().map(|t| t.id).collect();
+        assert_eq!(ids, [second]);
+
+        // Ids are never reused, even after a deletion.
+        let third = repository.insert(draft()).unwrap();
+        assert!(third > second);
+
+        // Listing stays ordered by id after deleting from the front.
+        let fourth = repository.insert(draft()).unwrap();
+        repository.delete(second).unwrap();
+        let ids: Vec<_> = repository.list().unwrap().iter().map(|t| t.id).collect();
+        assert_eq!(ids, [third, fourth]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_memory_store() {
+        conformance::check(&mut TicketStore::new());
+    }
+}
@@ -0,0 +1,259 @@
+use super::{RepositoryError, TicketRepository};
+use crate::data::{Ticket, TicketDraft, TicketPatch};
+use crate::store::TicketId;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
+
+/// How well the cache is doing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// Wraps a repository with an LRU cache for `get`.
+///
+/// Writes go straight to the inner repository and evict the ticket they touch from the cache,
+/// so reads never observe stale data—as long as nobody writes to the inner repository directly.
+/// `list` is not cached.
+///
+/// `get` takes `&self`, but a cache lookup needs to update the recency order: the cache lives
+/// in a `RefCell`, which makes `CachedRepository` `!Sync`. Put it behind a lock to share it.
+pub struct CachedRepository<R> {
+    inner: R,
+    capacity: usize,
+    cache: RefCell<Lru>,
+    stats: Cell<CacheStats>,
+}
+
+impl<R: TicketRepository> CachedRepository<R> {
+    /// Cache up to `capacity` tickets. A capacity of zero disables caching.
+    pub fn new(inner: R, capacity: usize) -> Self {
+        Self {
+            inner,
+            capacity,
+            cache: RefCell::new(Lru::default()),
+            stats: Cell::new(CacheStats::default()),
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats.get()
+    }
+
+    /// How many tickets are currently cached.
+    pub fn cached(&self) -> usize {
+        self.cache.borrow().entries.len()
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn record(&self, hit: bool) {
+        let mut stats = self.stats.get();
+        if hit {
+            stats.hits += 1;
+        } else {
+            stats.misses += 1;
+        }
+        self.stats.set(stats);
+    }
+}
+
+impl<R: TicketRepository> TicketRepository for CachedRepository<R> {
+    fn insert(&mut self, draft: TicketDraft) -> Result<TicketId, RepositoryError> {
+        self.inner.insert(draft)
+    }
+
+    fn get(&self, id: TicketId) -> Result<Option<Ticket>, RepositoryError> {
+        if let Some(ticket) = self.cache.borrow_mut().get(id) {
+            self.record(true);
+            return Ok(Some(ticket));
+        }
+        self.record(false);
+        // Only tickets that exist are cached: lookups for missing ids always reach the backend.
+        let ticket = self.inner.get(id)?;
+        if let Some(ticket) = &ticket {
+            self.cache.borrow_mut().put(ticket.clone(), self.capacity);
+        }
+        Ok(ticket)
+    }
+
+    fn update(&mut self, patch: TicketPatch) -> Result<(), RepositoryError> {
+        self.cache.get_mut().remove(patch.id);
+        self.inner.update(patch)
+    }
+
+    fn delete(&mut self, id: TicketId) -> Result<Option<Ticket>, RepositoryError> {
+        self.cache.get_mut().remove(id);
+        self.inner.delete(id)
+    }
+
+    fn list(&self) -> Result<Vec<Ticket>, RepositoryError> {
+        self.inner.list()
+    }
+}
+
+/// A least-recently-used map. Every access gets a new, increasing tick:
+/// the entry with the lowest tick is the one to evict.
+#[derive(Default)]
+struct Lru {
+    entries: HashMap<TicketId, (Ticket, u64)>,
+    by_tick: BTreeMap<u64, TicketId>,
+    next_tick: u64,
+}
+
+impl Lru {
+    fn get(&mut self, id: TicketId) -> Option<Ticket> {
+        let tick = self.tick();
+        let (ticket, last_used) = self.entries.get_mut(&id)?;
+        self.by_tick.remove(last_used);
+        self.by_tick.insert(tick, id);
+        *last_used = tick;
+        Some(ticket.clone())
+    }
+
+    fn put(&mut self, ticket: Ticket, capacity: usize) {
+        if capacity == 0 {
+            return;
+        }
+        self.remove(ticket.id);
+        while self.entries.len() >= capacity {
+            let Some((_, oldest)) = self.by_tick.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+        let tick = self.tick();
+        self.by_tick.insert(tick, ticket.id);
+        self.entries.insert(ticket.id, (ticket, tick));
+    }
+
+    fn remove(&mut self, id: TicketId) {
+        if let Some((_, tick)) = self.entries.remove(&id) {
+            self.by_tick.remove(&tick);
+        }
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.next_tick += 1;
+        self.next_tick
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Status;
+    use crate::repository::conformance;
+    use crate::store::TicketStore;
+
+    /// An in-memory backend that counts how many times it's read from.
+    #[derive(Default)]
+    struct CountingBackend {
+        store: TicketStore,
+        reads: Cell<usize>,
+    }
+
+    impl TicketRepository for CountingBackend {
+        fn insert(&mut self, draft: TicketDraft) -> Result<TicketId, RepositoryError> {
+            self.store.insert(draft)
+        }
+
+        fn get(&self, id: TicketId) -> Result<Option<Ticket>, RepositoryError> {
+            self.reads.set(self.reads.get() + 1);
+            TicketRepository::get(&self.store, id)
+        }
+
+        fn update(&mut self, patch: TicketPatch) -> Result<(), RepositoryError> {
+            TicketRepository::update(&mut self.store, patch)
+        }
+
+        fn delete(&mut self, id: TicketId) -> Result<Option<Ticket>, RepositoryError> {
+            TicketRepository::delete(&mut self.store, id)
+        }
+
+        fn list(&self) -> Result<Vec<Ticket>, RepositoryError> {
+            self.store.list()
+        }
+    }
+
+    fn cached(capacity: usize) -> CachedRepository<CountingBackend> {
+        CachedRepository::new(CountingBackend::default(), capacity)
+    }
+
+    fn reads(repository: &CachedRepository<CountingBackend>) -> usize {
+        repository.inner.reads.get()
+    }
+
+    #[test]
+    fn conformance() {
+        conformance::check(&mut cached(2));
+    }
+
+    #[test]
+    fn repeated_gets_hit_the_cache() {
+        let mut repository = cached(10);
+        let id = repository.insert(conformance::draft()).unwrap();
+
+        for _ in 0..3 {
+            assert!(repository.get(id).unwrap().is_some());
+        }
+        assert_eq!(reads(&repository), 1);
+        assert_eq!(repository.stats(), CacheStats { hits: 2, misses: 1 });
+    }
+
+    #[test]
+    fn writes_invalidate() {
+        let mut repository = cached(10);
+        let id = repository.insert(conformance::draft()).unwrap();
+        repository.get(id).unwrap();
+
+        repository
+            .update(TicketPatch {
+                status: Some(Status::Done),
+                ..TicketPatch::new(id)
+            })
+            .unwrap();
+        // The stale copy is gone: we go back to the backend and see the update.
+        assert_eq!(repository.get(id).unwrap().unwrap().status, Status::Done);
+        assert_eq!(reads(&repository), 2);
+
+        repository.delete(id).unwrap();
+        assert!(repository.get(id).unwrap().is_none());
+        assert_eq!(reads(&repository), 3);
+        assert_eq!(repository.cached(), 0);
+    }
+
+    #[test]
+    fn least_recently_used_is_evicted() {
+        let mut repository = cached(2);
+        let ids: Vec<_> = (0..3)
+            .map(|_| repository.insert(conformance::draft()).unwrap())
+            .collect();
+
+        repository.get(ids[0]).unwrap();
+        repository.get(ids[1]).unwrap();
+        // Touch the first ticket again, so that the second one becomes the oldest.
+        repository.get(ids[0]).unwrap();
+        repository.get(ids[2]).unwrap();
+        assert_eq!(repository.cached(), 2);
+        assert_eq!(reads(&repository), 3);
+
+        repository.get(ids[0]).unwrap();
+        assert_eq!(reads(&repository), 3);
+        repository.get(ids[1]).unwrap();
+        assert_eq!(reads(&repository), 4);
+    }
+
+    #[test]
+    fn zero_capacity_disables_caching() {
+        let mut repository = cached(0);
+        let id = repository.insert(conformance::draft()).unwrap();
+        repository.get(id).unwrap();
+        repository.get(id).unwrap();
+        assert_eq!(reads(&repository), 2);
+        assert_eq!(repository.stats(), CacheStats { hits: 0, misses: 2 });
+    }
+}
@@ -0,0 +1,261 @@
+use super::{RepositoryError, TicketRepository};
+use crate::custom_fields::CustomFields;
+use crate::data::{Assignee, Status, Ticket, TicketDraft, TicketPatch};
+use crate::store::{TicketId, TicketNotFound};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::path::Path;
+use ticket_fields::{TicketDescription, TicketTitle};
+
+/// Schema migrations, in order. `MIGRATIONS[i]` brings the schema from version `i` to `i + 1`.
+///
+/// The current version is stored in the database itself (`PRAGMA user_version`):
+/// opening a database only runs the migrations it's missing.
+/// Never edit a migration that has already shipped—append a new one instead.
+const MIGRATIONS: &[&str] = &[
+    // `AUTOINCREMENT` guarantees that the ids of deleted tickets are never reused.
+    "CREATE TABLE tickets (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        title TEXT NOT NULL,
+        description TEXT NOT NULL,
+        status TEXT NOT NULL
+    );",
+    "ALTER TABLE tickets ADD COLUMN assignee TEXT;",
+];
+
+/// A ticket repository backed by a SQLite database.
+pub struct SqliteTicketRepository {
+    connection: Connection,
+}
+
+impl SqliteTicketRepository {
+    /// Open (or create) the database at `path`, bringing its schema up to date.
+    pub fn open(path: &Path) -> Result<Self, RepositoryError> {
+        Self::from_connection(Connection::open(path).map_err(RepositoryError::backend)?)
+    }
+
+    /// A fresh database that only lives as long as the repository.
+    pub fn in_memory() -> Result<Self, RepositoryError> {
+        Self::from_connection(Connection::open_in_memory().map_err(RepositoryError::backend)?)
+    }
+
+    fn from_connection(mut connection: Connection) -> Result<Self, RepositoryError> {
+        migrate(&mut connection).map_err(RepositoryError::backend)?;
+        Ok(Self { connection })
+    }
+
+    /// The version of the database schema, i.e. the number of migrations applied to it.
+    pub fn schema_version(&self) -> Result<usize, RepositoryError> {
+        schema_version(&self.connection).map_err(RepositoryError::backend)
+    }
+}
+
+fn schema_version(connection: &Connection) -> rusqlite::Result<usize> {
+    connection.pragma_query_value(None, "user_version", |row| row.get(0))
+}
+
+fn migrate(connection: &mut Connection) -> rusqlite::Result<()> {
+    let current = schema_version(connection)?;
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(current) {
+        // Each migration is applied atomically, together with the version bump.
+        let transaction = connection.transaction()?;
+        transaction.execute_batch(migration)?;
+        transaction.pragma_update(None, "user_version", version + 1)?;
+        transaction.commit()?;
+    }
+    Ok(())
+}
+
+const COLUMNS: &str = "id, title, description, status, assignee";
+
+/// A row of the `tickets` table, as stored.
+struct StoredTicket {
+    id: i64,
+    title: String,
+    description: String,
+    status: String,
+    assignee: Option<String>,
+}
+
+impl StoredTicket {
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            description: row.get(2)?,
+            status: row.get(3)?,
+            assignee: row.get(4)?,
+        })
+    }
+
+    /// The database might have been written by someone else: validate what we read.
+    fn validate(self) -> Result<Ticket, RepositoryError> {
+        Ok(Ticket {
+            id: TicketId::from(self.id as u64),
+            title: TicketTitle::try_from(self.title).map_err(RepositoryError::backend)?,
+            description: TicketDescription::try_from(self.description)
+                .map_err(RepositoryError::backend)?,
+            status: Status::try_from(self.status).map_err(RepositoryError::backend)?,
+            assignee: self.assignee.map(Assignee::from),
+            // Custom fields and timestamps are `TicketStore` features:
+            // the table has no column for them.
+            custom_fields: CustomFields::new(),
+            created_at: None,
+            updated_at: None,
+            due: None,
+        })
+    }
+}
+
+impl TicketRepository for SqliteTicketRepository {
+    fn insert(&mut self, draft: TicketDraft) -> Result<TicketId, RepositoryError> {
+        self.connection
+            .execute(
+                "INSERT INTO tickets (title, description, status) VALUES (?1, ?2, ?3)",
+                params![
+                    draft.title.as_ref(),
+                    draft.description.as_ref(),
+                    Status::ToDo.to_string()
+                ],
+            )
+            .map_err(RepositoryError::backend)?;
+        Ok(TicketId::from(self.connection.last_insert_rowid() as u64))
+    }
+
+    fn get(&self, id: TicketId) -> Result<Option<Ticket>, RepositoryError> {
+        self.connection
+            .query_row(
+                &format!("SELECT {COLUMNS} FROM tickets WHERE id = ?1"),
+                [id.value() as i64],
+                StoredTicket::from_row,
+            )
+            .optional()
+            .map_err(RepositoryError::backend)?
+            .map(StoredTicket::validate)
+            .transpose()
+    }
+
+    fn update(&mut self, patch: TicketPatch) -> Result<(), RepositoryError> {
+        // `COALESCE` keeps the current value when the patch leaves a field untouched.
+        // The assignee needs a flag instead, since `NULL` is a legitimate new value for it.
+        let (set_assignee, assignee) = match &patch.assignee {
+            Some(assignee) => (true, assignee.as_deref()),
+            None => (false, None),
+        };
+        let updated = self
+            .connection
+            .execute(
+                "UPDATE tickets SET
+                    title = COALESCE(?2, title),
+                    description = COALESCE(?3, description),
+                    status = COALESCE(?4, status),
+                    assignee = CASE WHEN ?5 THEN ?6 ELSE assignee END
+                 WHERE id = ?1",
+                params![
+                    patch.id.value() as i64,
+                    patch.title.as_ref().map(|t| t.as_ref()),
+                    patch.description.as_ref().map(|d| d.as_ref()),
+                    patch.status.map(|s| s.to_string()),
+                    set_assignee,
+                    assignee,
+                ],
+            )
+            .map_err(RepositoryError::backend)?;
+        if updated == 0 {
+            return Err(TicketNotFound(patch.id).into());
+        }
+        Ok(())
+    }
+
+    fn delete(&mut self, id: TicketId) -> Result<Option<Ticket>, RepositoryError> {
+        let deleted = self.get(id)?;
+        if deleted.is_some() {
+            self.connection
+                .execute("DELETE FROM tickets WHERE id = ?1", [id.value() as i64])
+                .map_err(RepositoryError::backend)?;
+        }
+        Ok(deleted)
+    }
+
+    fn list(&self) -> Result<Vec<Ticket>, RepositoryError> {
+        let mut statement = self
+            .connection
+            .prepare(&format!("SELECT {COLUMNS} FROM tickets ORDER BY id"))
+            .map_err(RepositoryError::backend)?;
+        let rows = statement
+            .query_map([], StoredTicket::from_row)
+            .map_err(RepositoryError::backend)?;
+        rows.map(|row| row.map_err(RepositoryError::backend)?.validate())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::conformance;
+
+    #[test]
+    fn conformance() {
+        conformance::check(&mut SqliteTicketRepository::in_memory().unwrap());
+    }
+
+    #[test]
+    fn tickets_survive_reopening() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tickets.db");
+
+        let mut repository = SqliteTicketRepository::open(&path).unwrap();
+        let id = repository.insert(conformance::draft()).unwrap();
+        let deleted = repository.insert(conformance::draft()).unwrap();
+        repository.delete(deleted).unwrap();
+        drop(repository);
+
+        let mut reopened = SqliteTicketRepository::open(&path).unwrap();
+        assert_eq!(reopened.schema_version().unwrap(), MIGRATIONS.len());
+        assert_eq!(reopened.list().unwrap().len(), 1);
+        assert!(reopened.get(id).unwrap().is_some());
+        // The deleted ticket's id isn't handed out again after a restart.
+        assert!(reopened.insert(conformance::draft()).unwrap().value() > id.value() + 1);
+    }
+
+    #[test]
+    fn older_schemas_are_migrated_on_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tickets.db");
+
+        // A database created before assignees were introduced.
+        let connection = Connection::open(&path).unwrap();
+        connection.execute_batch(MIGRATIONS[0]).unwrap();
+        connection.pragma_update(None, "user_version", 1).unwrap();
+        connection
+            .execute(
+                "INSERT INTO tickets (title, description, status) VALUES ('Old', 'An old ticket', 'Done')",
+                [],
+            )
+            .unwrap();
+        drop(connection);
+
+        let repository = SqliteTicketRepository::open(&path).unwrap();
+        assert_eq!(repository.schema_version().unwrap(), MIGRATIONS.len());
+        let tickets = repository.list().unwrap();
+        assert_eq!(tickets.len(), 1);
+        assert_eq!(tickets[0].status, Status::Done);
+        assert_eq!(tickets[0].assignee, None);
+    }
+
+    #[test]
+    fn invalid_rows_are_reported() {
+        let repository = SqliteTicketRepository::in_memory().unwrap();
+        repository
+            .connection
+            .execute(
+                "INSERT INTO tickets (title, description, status) VALUES ('', 'Empty title', 'ToDo')",
+                [],
+            )
+            .unwrap();
+        assert!(matches!(
+            repository.list(),
+            Err(RepositoryError::Backend(_))
+        ));
+    }
+}
@@ -0,0 +1,749 @@
+//! Persisting a `TicketStore` to disk, so that it survives a restart.
+//!
+//! A snapshot is a JSON file holding every ticket, their tags, the store's templates and
+//! custom field schema, and the id of the next ticket.
+//! Snapshots are written to a temporary file first and then renamed over the
+//! previous one: a crash mid-write leaves the old snapshot untouched.
+//!
+//! They can be compressed and encrypted (see [`crate::compression`] and [`crate::encryption`]):
+//! loading detects both on its own, provided it's given the key.
+use crate::clock::Timestamp;
+use crate::compression::{decompress, Compression};
+use crate::custom_fields::{CustomFields, FieldSchema};
+use crate::data::{Assignee, Status, Ticket, TicketId};
+use crate::encryption::{seal, unseal, EncryptionKey};
+use crate::store::TicketStore;
+use crate::template::TicketTemplate;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+use ticket_fields::{Context, ContextError, TicketDescription, TicketTitle, TicketTitleError};
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Snapshot {
+    next_id: u64,
+    tickets: Vec<Ticket>,
+    /// Snapshots written before tags were introduced don't have any.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    tags: BTreeMap<TicketId, Vec<String>>,
+    /// Nor templates.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    templates: Vec<TicketTemplate>,
+    /// Nor custom fields.
+    #[serde(default, skip_serializing_if = "FieldSchema::is_empty")]
+    schema: FieldSchema,
+}
+
+impl Snapshot {
+    pub(crate) fn of(store: &TicketStore) -> Self {
+        Self {
+            next_id: store.next_id(),
+            tickets: store.iter().cloned().collect(),
+            tags: store
+                .all_tags()
+                .map(|(id, tags)| (id, tags.map(String::from).collect()))
+                .collect(),
+            templates: store.templates().cloned().collect(),
+            schema: store.schema().clone(),
+        }
+    }
+
+    /// Convert a snapshot read from `path` without validation.
+    pub(crate) fn trusted(path: &Path, snapshot: TrustedSnapshot) -> Result<Self, ContextError> {
+        Self::try_from(snapshot).with_context(|| format!("Invalid snapshot at {}", path.display()))
+    }
+
+    pub(crate) fn into_store(self) -> TicketStore {
+        // Never hand out an id that's already taken, even if the snapshot was edited by hand.
+        let next_id = self
+            .tickets
+            .iter()
+            .map(|t| t.id.value() + 1)
+            .max()
+            .unwrap_or(0)
+            .max(self.next_id);
+        let mut store = TicketStore::from_parts(self.tickets, next_id);
+        for (id, tags) in self.tags {
+            for tag in tags {
+                // Tags of tickets that aren't in the snapshot are dropped.
+                let _ = store.add_tag(id, &tag);
+            }
+        }
+        for template in self.templates {
+            store.register_template(template);
+        }
+        // Like the tickets themselves, their custom fields were valid when they were saved.
+        store.schema = self.schema;
+        store
+    }
+}
+
+/// A snapshot read back without validating its titles and descriptions,
+/// which were valid when the snapshot was written. See [`TicketStore::load_trusted`].
+#[derive(Deserialize)]
+pub(crate) struct TrustedSnapshot<'a> {
+    next_id: u64,
+    #[serde(borrow)]
+    tickets: Vec<TrustedTicket<'a>>,
+    #[serde(default)]
+    tags: BTreeMap<TicketId, Vec<String>>,
+    #[serde(default)]
+    templates: Vec<TicketTemplate>,
+    #[serde(default)]
+    schema: FieldSchema,
+}
+
+/// A `Ticket`, with its fields as they're found in the file.
+#[derive(Deserialize)]
+struct TrustedTicket<'a> {
+    id: TicketId,
+    /// Titles are copied inline: they can be borrowed from the file, unless they're escaped.
+    #[serde(borrow)]
+    title: Cow<'a, str>,
+    description: String,
+    status: Status,
+    #[serde(default)]
+    assignee: Option<String>,
+    #[serde(default)]
+    custom_fields: CustomFields,
+    #[serde(default)]
+    created_at: Option<Timestamp>,
+    #[serde(default)]
+    updated_at: Option<Timestamp>,
+    #[serde(default)]
+    due: Option<Timestamp>,
+}
+
+/// Titles are still checked for length, in every build: one that's too long doesn't fit
+/// inline, however much the file is trusted.
+impl TryFrom<TrustedSnapshot<'_>> for Snapshot {
+    type Error = TicketTitleError;
+
+    fn try_from(value: TrustedSnapshot<'_>) -> Result<Self, Self::Error> {
+        let tickets = value
+            .tickets
+            .into_iter()
+            .map(|t| {
+                let title = if t.title.len() > TicketTitle::MAX_LEN {
+                    TicketTitle::try_from(t.title.as_ref())?
+                } else {
+                    TicketTitle::new_unchecked(&t.title)
+                };
+                Ok(Ticket {
+                    id: t.id,
+                    title,
+                    description: TicketDescription::new_unchecked(t.description),
+                    status: t.status,
+                    assignee: t.assignee.map(Assignee::from),
+                    custom_fields: t.custom_fields,
+                    created_at: t.created_at,
+                    updated_at: t.updated_at,
+                    due: t.due,
+                })
+            })
+            .collect::<Result<_, Self::Error>>()?;
+        Ok(Self {
+            next_id: value.next_id,
+            tickets,
+            tags: value.tags,
+            templates: value.templates,
+            schema: value.schema,
+        })
+    }
+}
+
+/// How snapshots are written to disk: plain JSON, unless told otherwise.
+/// `DurableStore` writes its log records the same way.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Encoding {
+    pub compression: Compression,
+    /// Encrypt with this key. It's also needed to read encrypted snapshots back.
+    pub key: Option<EncryptionKey>,
+    /// With a key, read unencrypted data too, instead of refusing it. Only meant for a one-time
+    /// migration, to encrypt what was written before there was a key: it's encrypted on the
+    /// next write. Without a key, unencrypted data is always read.
+    pub migrate_unencrypted: bool,
+}
+
+impl Encoding {
+    /// Compress, then encrypt `json`. `what` names it, for error messages.
+    pub(crate) fn encode(&self, json: Vec<u8>, what: &str) -> Result<Vec<u8>, ContextError> {
+        let compressed = self
+            .compression
+            .compress(json)
+            .with_context(|| format!("Failed to compress {what}"))?;
+        match &self.key {
+            Some(key) => seal(key, compressed).with_context(|| format!("Failed to encrypt {what}")),
+            None => Ok(compressed),
+        }
+    }
+
+    /// Undo [`Encoding::encode`]: compression and encryption are detected,
+    /// only the key has to be right.
+    pub(crate) fn decode(&self, bytes: Vec<u8>, what: &str) -> Result<Vec<u8>, ContextError> {
+        let compressed = unseal(self.key.as_ref(), bytes, self.migrate_unencrypted)
+            .with_context(|| format!("Failed to decrypt {what}"))?;
+        decompress(compressed).with_context(|| format!("Failed to decompress {what}"))
+    }
+}
+
+/// Serialize `value` to `path` as JSON, going through a temporary file.
+///
+/// Once it returns, the new snapshot is on disk for good: the temporary file is flushed
+/// before it's renamed, and the directory after, so that the rename survives a crash too.
+/// Only then is it safe to throw away what the snapshot replaces, like a write-ahead log.
+pub(crate) fn write_atomically(
+    path: &Path,
+    value: &impl Serialize,
+    encoding: &Encoding,
+) -> Result<(), ContextError> {
+    let encoded = serde_json::to_vec(value).context("Failed to serialize the ticket store")?;
+    let encoded = encoding.encode(encoded, "the snapshot")?;
+    let tmp = path.with_extension("tmp");
+    File::create(&tmp)
+        .and_then(|mut file| {
+            file.write_all(&encoded)?;
+            file.sync_all()
+        })
+        .with_context(|| format!("Failed to write the snapshot to {}", tmp.display()))?;
+    fs::rename(&tmp, path)
+        .with_context(|| format!("Failed to move the snapshot to {}", path.display()))?;
+    sync_parent(path)
+        .with_context(|| format!("Failed to flush the directory of {}", path.display()))
+}
+
+/// Flush the directory entry of `path` to disk.
+#[cfg(unix)]
+fn sync_parent(path: &Path) -> std::io::Result<()> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(parent)?.sync_all()
+}
+
+/// Directories can't be opened, let alone flushed, on other platforms: renames are as
+/// durable as the file system makes them.
+#[cfg(not(unix))]
+fn sync_parent(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Read the snapshot at `path`, as plain JSON.
+fn read(path: &Path, encoding: &Encoding) -> Result<Vec<u8>, ContextError> {
+    let encoded = fs::read(path)
+        .with_context(|| format!("Failed to read the snapshot at {}", path.display()))?;
+    encoding.decode(encoded, &format!("the snapshot at {}", path.display()))
+}
+
+/// Read the file at `path` as plain JSON, however it was encoded.
+/// Returns `None` if it doesn't exist.
+pub(crate) fn read_json_if_exists(
+    path: &Path,
+    encoding: &Encoding,
+) -> Result<Option<Vec<u8>>, ContextError> {
+    let encoded = match fs::read(path) {
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        read => {
+            read.with_context(|| format!("Failed to read the snapshot at {}", path.display()))?
+        }
+    };
+    encoding
+        .decode(encoded, &format!("the snapshot at {}", path.display()))
+        .map(Some)
+}
+
+/// Deserialize the JSON file at `path`, however it was encoded.
+/// Returns `None` if it doesn't exist.
+pub(crate) fn read_if_exists<T: DeserializeOwned>(
+    path: &Path,
+    encoding: &Encoding,
+) -> Result<Option<T>, ContextError> {
+    read_json_if_exists(path, encoding)?
+        .map(|json| parse(path, &json))
+        .transpose()
+}
+
+/// Deserialize `json`, read from `path`.
+pub(crate) fn parse<'a, T: Deserialize<'a>>(
+    path: &Path,
+    json: &'a [u8],
+) -> Result<T, ContextError> {
+    serde_json::from_slice(json).with_context(|| format!("Invalid snapshot at {}", path.display()))
+}
+
+impl TicketStore {
+    /// Write the whole store to `path`, replacing any previous snapshot.
+    pub fn save(&self, path: &Path) -> Result<(), ContextError> {
+        self.save_with(path, &Encoding::default())
+    }
+
+    /// Like [`TicketStore::save`], compressing and/or encrypting the snapshot.
+    pub fn save_with(&self, path: &Path, encoding: &Encoding) -> Result<(), ContextError> {
+        write_atomically(path, &Snapshot::of(self), encoding)
+    }
+
+    /// Load a store from a snapshot written by [`TicketStore::save`].
+    pub fn load(path: &Path) -> Result<Self, ContextError> {
+        Self::load_with(path, &Encoding::default())
+    }
+
+    /// Load a store from a snapshot written by [`TicketStore::save_with`].
+    pub fn load_with(path: &Path, encoding: &Encoding) -> Result<Self, ContextError> {
+        let encoded = read(path, encoding)?;
+        let snapshot: Snapshot = parse(path, &encoded)?;
+        Ok(snapshot.into_store())
+    }
+
+    /// Like [`TicketStore::load_with`], but titles and descriptions aren't validated again:
+    /// only use it on snapshots written by [`TicketStore::save_with`] that nobody has edited
+    /// since. Debug builds still validate them, and panic if they're invalid. A title too long
+    /// to be stored is an error in every build.
+    ///
+    /// Workspaces and durable stores load their own snapshots this way.
+    pub fn load_trusted(path: &Path, encoding: &Encoding) -> Result<Self, ContextError> {
+        let encoded = read(path, encoding)?;
+        let snapshot: TrustedSnapshot = parse(path, &encoded)?;
+        Ok(Snapshot::trusted(path, snapshot)?.into_store())
+    }
+
+    /// Like [`TicketStore::load`], but starts from an empty store if there's no snapshot yet.
+    pub fn load_or_default(path: &Path) -> Result<Self, ContextError> {
+        Self::load_or_default_with(path, &Encoding::default())
+    }
+
+    /// Like [`TicketStore::load_with`], but starts from an empty store if there's no snapshot yet.
+    pub fn load_or_default_with(path: &Path, encoding: &Encoding) -> Result<Self, ContextError> {
+        Ok(
+            read_if_exists::<Snapshot>(path, encoding)?
+                .map_or_else(Self::new, Snapshot::into_store),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{Status, TicketDraft, TicketPatch};
+    use crate::repository::conformance::draft;
+    use crate::store::TicketId;
+    use ticket_fields::test_helpers::ticket_description;
+
+    fn gzipped() -> Encoding {
+        Encoding {
+            compression: Compression::Gzip,
+            ..Encoding::default()
+        }
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+
+        let mut store = TicketStore::new();
+        let first = store.add_ticket(draft());
+        let second = store.add_ticket(draft());
+        store
+            .update(TicketPatch {
+                status: Some(Status::Done),
+                ..TicketPatch::new(second)
+            })
+            .unwrap();
+        store.add_tag(first, "duplicate").unwrap();
+        store.add_tag(second, "bug").unwrap();
+        store.delete(first);
+        store.save(&path).unwrap();
+
+        let mut loaded = TicketStore::load(&path).unwrap();
+        assert_eq!(
+            loaded.iter().collect::<Vec<_>>(),
+            store.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(loaded.tags(second).collect::<Vec<_>>(), ["bug"]);
+        assert_eq!(loaded.with_tag("duplicate").count(), 0);
+        // Ids of deleted tickets are not reused after a reload either.
+        assert_eq!(loaded.add_ticket(draft()), TicketId::from(2));
+    }
+
+    #[test]
+    fn snapshot_without_tags() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        fs::write(&path, r#"{"next_id": 3, "tickets": []}"#).unwrap();
+        let mut loaded = TicketStore::load(&path).unwrap();
+        assert_eq!(loaded.add_ticket(draft()), TicketId::from(3));
+
+        // Untagged stores are saved in the same format.
+        loaded.delete(TicketId::from(3));
+        loaded.save(&path).unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("tags"));
+    }
+
+    #[test]
+    fn templates_are_saved_too() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        let template = TicketTemplate {
+            name: "bug".into(),
+            title_prefix: "[Bug] ".into(),
+            description: ticket_description(),
+            tags: vec!["bug".into()],
+            custom_fields: [("priority", 2)].into_iter().collect(),
+        };
+        let mut store = TicketStore::new();
+        store.register_template(template.clone());
+        store.save(&path).unwrap();
+
+        let loaded = TicketStore::load(&path).unwrap();
+        assert_eq!(loaded.templates().collect::<Vec<_>>(), [&template]);
+        let trusted = TicketStore::load_trusted(&path, &Encoding::default()).unwrap();
+        assert_eq!(trusted.template("bug"), Some(&template));
+    }
+
+    #[test]
+    fn custom_fields_are_saved_with_their_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        let schema = FieldSchema::new().with("estimate", crate::FieldType::Integer);
+        let mut store = TicketStore::new();
+        store.set_schema(schema.clone()).unwrap();
+        let fields: CustomFields = [("estimate", 5)].into_iter().collect();
+        let id = store
+            .add_ticket_with_fields(draft(), fields.clone())
+            .unwrap();
+        store.save(&path).unwrap();
+
+        let json = fs::read_to_string(&path).unwrap();
+        assert!(json.contains(r#""custom_fields":{"estimate":5}"#));
+        for loaded in [
+            TicketStore::load(&path).unwrap(),
+            TicketStore::load_trusted(&path, &Encoding::default()).unwrap(),
+        ] {
+            assert_eq!(loaded[id].custom_fields, fields);
+            assert_eq!(loaded.schema(), &schema);
+        }
+    }
+
+    #[test]
+    fn timestamps_are_saved() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        use crate::clock::{Clock, TestClock};
+
+        let clock = TestClock::new();
+        let mut store = TicketStore::with_clock(clock.clone());
+        let id = store.add_ticket(draft());
+        let created_at = clock.now();
+        clock.advance(std::time::Duration::from_secs(1));
+        let due = clock.now() + std::time::Duration::from_secs(60);
+        store.set_due(id, Some(due)).unwrap();
+        let undated = store.add_ticket(draft());
+        store.save(&path).unwrap();
+
+        for loaded in [
+            TicketStore::load(&path).unwrap(),
+            TicketStore::load_trusted(&path, &Encoding::default()).unwrap(),
+        ] {
+            assert_eq!(loaded[id].created_at, Some(created_at));
+            assert_eq!(loaded[id].updated_at, Some(clock.now()));
+            assert_eq!(loaded[id].due, Some(due));
+            assert_eq!(loaded[undated].due, None);
+        }
+    }
+
+    #[test]
+    fn trusted_load_matches_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+
+        let mut store = TicketStore::new();
+        let id = store.add_ticket(TicketDraft {
+            // Escaped characters can't be borrowed from the file.
+            title: "A \"quoted\" title".try_into().unwrap(),
+            description: ticket_description(),
+        });
+        store
+            .update(TicketPatch {
+                assignee: Some(Some("Alice".into())),
+                ..TicketPatch::new(id)
+            })
+            .unwrap();
+        store.add_ticket(draft());
+        store.add_tag(id, "bug").unwrap();
+        store.save(&path).unwrap();
+
+        let mut trusted = TicketStore::load_trusted(&path, &Encoding::default()).unwrap();
+        let mut loaded = TicketStore::load(&path).unwrap();
+        assert_eq!(
+            trusted.iter().collect::<Vec<_>>(),
+            loaded.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(trusted.tags(id).collect::<Vec<_>>(), ["bug"]);
+        assert_eq!(trusted.add_ticket(draft()), loaded.add_ticket(draft()));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Invalid title")]
+    fn trusted_load_still_validates_in_debug_builds() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        let ticket = r#"{"id": 0, "title": "", "description": "edited", "status": "ToDo"}"#;
+        fs::write(&path, format!(r#"{{"next_id": 1, "tickets": [{ticket}]}}"#)).unwrap();
+        let _ = TicketStore::load_trusted(&path, &Encoding::default());
+    }
+
+    #[test]
+    fn trusted_load_rejects_titles_too_long_to_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        let title = "A".repeat(TicketTitle::MAX_LEN + 1);
+        let ticket = format!(
+            r#"{{"id": 0, "title": "{title}", "description": "edited", "status": "ToDo"}}"#
+        );
+        fs::write(&path, format!(r#"{{"next_id": 1, "tickets": [{ticket}]}}"#)).unwrap();
+
+        let err = TicketStore::load_trusted(&path, &Encoding::default()).unwrap_err();
+        assert!(err.context().starts_with("Invalid snapshot"));
+        assert_eq!(
+            std::error::Error::source(&err).unwrap().to_string(),
+            "The title cannot be longer than 50 bytes"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "encryption")]
+    fn trusted_load_reads_encrypted_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        let encrypted = Encoding {
+            key: Some(crate::encryption::test_key()),
+            ..Encoding::default()
+        };
+        let mut store = TicketStore::new();
+        store.add_ticket(draft());
+        store.save_with(&path, &encrypted).unwrap();
+        let loaded = TicketStore::load_trusted(&path, &encrypted).unwrap();
+        assert_eq!(
+            loaded.iter().collect::<Vec<_>>(),
+            store.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "gzip")]
+    fn compressed_snapshots_are_much_smaller() {
+        let dir = tempfile::tempdir().unwrap();
+        let (plain, gzip) = (dir.path().join("plain.json"), dir.path().join("gzip.json"));
+
+        let mut store = TicketStore::new();
+        for i in 0..1_000 {
+            let id = store.add_ticket(TicketDraft {
+                title: format!("Ticket #{i}").try_into().unwrap(),
+                description: ticket_description(),
+            });
+            store
+                .add_tag(id, if i % 2 == 0 { "even" } else { "odd" })
+                .unwrap();
+        }
+        store.save(&plain).unwrap();
+        store.save_with(&gzip, &gzipped()).unwrap();
+
+        let size = |path: &Path| fs::metadata(path).unwrap().len();
+        assert!(
+            size(&gzip) * 10 < size(&plain),
+            "{} bytes compressed, {} bytes plain",
+            size(&gzip),
+            size(&plain)
+        );
+
+        // Both load the same, with either loader, without being told which is which.
+        let tickets = |store: &TicketStore| store.iter().cloned().collect::<Vec<_>>();
+        for loaded in [
+            TicketStore::load(&gzip).unwrap(),
+            TicketStore::load_trusted(&gzip, &Encoding::default()).unwrap(),
+            TicketStore::load_or_default(&gzip).unwrap(),
+        ] {
+            assert_eq!(tickets(&loaded), tickets(&store));
+            assert_eq!(loaded.with_tag("odd").count(), 500);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "gzip")]
+    fn switching_compression_back_and_forth() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        let mut store = TicketStore::new();
+        store.add_ticket(draft());
+        store.save(&path).unwrap();
+
+        let mut loaded = TicketStore::load(&path).unwrap();
+        loaded.add_ticket(draft());
+        loaded.save_with(&path, &gzipped()).unwrap();
+        assert!(crate::compression::is_compressed(&fs::read(&path).unwrap()));
+
+        let loaded = TicketStore::load(&path).unwrap();
+        assert_eq!(loaded.len(), 2);
+        loaded.save(&path).unwrap();
+        assert_eq!(TicketStore::load(&path).unwrap().len(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "gzip")]
+    fn truncated_compressed_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        TicketStore::new().save_with(&path, &gzipped()).unwrap();
+        let compressed = fs::read(&path).unwrap();
+        fs::write(&path, &compressed[..compressed.len() / 2]).unwrap();
+        let err = TicketStore::load(&path).unwrap_err();
+        assert!(err
+            .context()
+            .starts_with("Failed to decompress the snapshot"));
+    }
+
+    #[test]
+    #[cfg(not(feature = "gzip"))]
+    fn compressed_snapshots_need_the_gzip_feature() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        let err = TicketStore::new().save_with(&path, &gzipped()).unwrap_err();
+        assert_eq!(err.context(), "Failed to compress the snapshot");
+
+        fs::write(&path, [0x1f, 0x8b, 0x08, 0x00]).unwrap();
+        let err = TicketStore::load_or_default(&path).unwrap_err();
+        assert!(err
+            .context()
+            .starts_with("Failed to decompress the snapshot"));
+    }
+
+    #[test]
+    #[cfg(feature = "encryption")]
+    fn encrypted_snapshot_round_trip() {
+        use crate::encryption::{is_encrypted, test_key};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        let encrypted = Encoding {
+            key: Some(test_key()),
+            ..Encoding::default()
+        };
+        let mut store = TicketStore::new();
+        let id = store.add_ticket(draft());
+        store.add_tag(id, "secret").unwrap();
+        store.save_with(&path, &encrypted).unwrap();
+
+        let content = fs::read(&path).unwrap();
+        assert!(is_encrypted(&content));
+        assert!(!content.windows(6).any(|w| w == b"secret"));
+        let loaded = TicketStore::load_with(&path, &encrypted).unwrap();
+        assert_eq!(loaded.get(id), store.get(id));
+        assert_eq!(loaded.tags(id).collect::<Vec<_>>(), ["secret"]);
+    }
+
+    #[test]
+    #[cfg(feature = "encryption")]
+    fn plain_snapshots_are_refused_under_a_key() {
+        use crate::encryption::{test_key, EncryptionError};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        let mut encrypted = Encoding {
+            key: Some(test_key()),
+            ..Encoding::default()
+        };
+        let mut store = TicketStore::new();
+        store.add_ticket(draft());
+        // As if someone swapped the encrypted snapshot for one of their own.
+        store.save(&path).unwrap();
+
+        let err = TicketStore::load_or_default_with(&path, &encrypted).unwrap_err();
+        assert!(err.context().starts_with("Failed to decrypt the snapshot"));
+        let source = std::error::Error::source(&err).unwrap();
+        assert_eq!(
+            source.downcast_ref::<EncryptionError>(),
+            Some(&EncryptionError::Unencrypted)
+        );
+        assert!(TicketStore::load_with(&path, &encrypted).is_err());
+
+        // Unless it's a migration.
+        encrypted.migrate_unencrypted = true;
+        let loaded = TicketStore::load_with(&path, &encrypted).unwrap();
+        assert_eq!(loaded.len(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "encryption")]
+    fn tampered_snapshot_fails_to_decrypt() {
+        use crate::encryption::{test_key, EncryptionError};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        let encrypted = Encoding {
+            key: Some(test_key()),
+            ..Encoding::default()
+        };
+        let mut store = TicketStore::new();
+        store.add_ticket(draft());
+        store.save_with(&path, &encrypted).unwrap();
+
+        let error = |encoding: &Encoding| {
+            let err = TicketStore::load_with(&path, encoding).unwrap_err();
+            assert!(err.context().starts_with("Failed to decrypt the snapshot"));
+            let source = std::error::Error::source(&err).unwrap();
+            source.downcast_ref::<EncryptionError>().unwrap().clone()
+        };
+        assert_eq!(error(&Encoding::default()), EncryptionError::MissingKey);
+
+        let mut content = fs::read(&path).unwrap();
+        let middle = content.len() / 2;
+        content[middle] ^= 0x01;
+        fs::write(&path, content).unwrap();
+        assert_eq!(error(&encrypted), EncryptionError::DecryptionFailed);
+    }
+
+    #[test]
+    #[cfg(all(feature = "gzip", feature = "encryption"))]
+    fn compressed_and_encrypted_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        let encoding = Encoding {
+            compression: Compression::Gzip,
+            key: Some(crate::encryption::test_key()),
+            ..Encoding::default()
+        };
+        let mut store = TicketStore::new();
+        for _ in 0..100 {
+            store.add_ticket(draft());
+        }
+        store.save(&path).unwrap();
+        let plain = fs::metadata(&path).unwrap().len();
+        store.save_with(&path, &encoding).unwrap();
+        // Compression happens before encryption, or it wouldn't shrink anything.
+        assert!(fs::metadata(&path).unwrap().len() * 10 < plain);
+        assert_eq!(TicketStore::load_with(&path, &encoding).unwrap().len(), 100);
+    }
+
+    #[test]
+    fn missing_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        assert!(TicketStore::load(&path).is_err());
+        assert!(TicketStore::load_or_default(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn corrupted_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        fs::write(&path, "not json").unwrap();
+        let err = TicketStore::load_or_default(&path).unwrap_err();
+        assert!(err.context().starts_with("Invalid snapshot"));
+    }
+}
@@ -0,0 +1,331 @@
+//! Closing tickets that have been left `InProgress` for too long.
+//!
+//! A store with a clock stamps each ticket's `updated_at` whenever it changes, and
+//! [`StaleTickets`] compares it against the time of its own clock: give it the store's.
+//! Tickets without an `updated_at`, from a store without a clock, are never stale.
+//! Tickets are closed at the first scan after they've gone `after` without changing, so how
+//! late they can be closed depends on how often they're scanned.
+//!
+//! With the `threads` feature, [`spawn_auto_close`] scans the tickets of the channel-based
+//! server (see [`crate::client`]) on a thread of its own.
+use crate::clock::{Clock, SystemClock, Timestamp};
+use crate::data::{Status, Ticket, TicketId, TicketPatch};
+use crate::store::TicketStore;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// What happened to a ticket that was closed for being stale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuditEntry {
+    pub ticket: TicketId,
+    /// How long the ticket had gone without changing, as of the scan that closed it.
+    pub idle_for: Duration,
+}
+
+/// Finds the `InProgress` tickets that haven't changed for a while. See the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct StaleTickets {
+    after: Duration,
+    clock: Arc<dyn Clock>,
+}
+
+impl StaleTickets {
+    /// Tickets are stale once they've been `InProgress`, unchanged, for `after`.
+    pub fn new(after: Duration) -> Self {
+        Self {
+            after,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Read the time from `clock`, rather than the system's.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Look at every ticket, and return the stale ones.
+    pub fn scan<'a>(&self, tickets: impl IntoIterator<Item = &'a Ticket>) -> Vec<AuditEntry> {
+        self.stale(tickets)
+            .into_iter()
+            .map(|(entry, _)| entry)
+            .collect()
+    }
+
+    /// The stale tickets, with the last time they changed.
+    fn stale<'a>(
+        &self,
+        tickets: impl IntoIterator<Item = &'a Ticket>,
+    ) -> Vec<(AuditEntry, Timestamp)> {
+        let now = self.clock.now();
+        tickets
+            .into_iter()
+            .filter(|ticket| ticket.status == Status::InProgress)
+            .filter_map(|ticket| {
+                let updated_at = ticket.updated_at?;
+                let idle_for = now.saturating_duration_since(updated_at);
+                let entry = AuditEntry {
+                    ticket: ticket.id,
+                    idle_for,
+                };
+                (idle_for >= self.after).then_some((entry, updated_at))
+            })
+            .collect()
+    }
+
+    /// Scan the tickets of `store`, and mark the stale ones as `Done`.
+    pub fn close(&self, store: &mut TicketStore) -> Vec<AuditEntry> {
+        let stale = self.scan(store.iter());
+        for entry in &stale {
+            store
+                .update(done(entry.ticket))
+                .expect("The ticket was just scanned");
+            log(entry);
+        }
+        stale
+    }
+}
+
+fn done(id: TicketId) -> TicketPatch {
+    TicketPatch {
+        status: Some(Status::Done),
+        ..TicketPatch::new(id)
+    }
+}
+
+fn log(entry: &AuditEntry) {
+    tracing::info!(
+        ticket_id = entry.ticket.value(),
+        idle_secs = entry.idle_for.as_secs(),
+        "closed a stale ticket"
+    );
+}
+
+#[cfg(feature = "threads")]
+pub use scheduler::{every, spawn_auto_close};
+
+#[cfg(feature = "threads")]
+mod scheduler {
+    use super::*;
+    use crate::client::{ClientError, TicketStoreClient};
+    use std::sync::mpsc::{channel, sync_channel, Receiver};
+
+    /// A tick every `interval`, until the receiver is dropped.
+    pub fn every(interval: Duration) -> Receiver<()> {
+        let (sender, receiver) = sync_channel(0);
+        std::thread::spawn(move || loop {
+            std::thread::sleep(interval);
+            if sender.send(()).is_err() {
+                return;
+            }
+        });
+        receiver
+    }
+
+    /// Spawn a thread that closes the stale tickets of the server behind `client`, once per tick.
+    ///
+    /// After every scan, what it closed is sent to the returned receiver—the audit log—even
+    /// if that's nothing. Tickets that other clients change between the scan and their closing
+    /// are left alone: they aren't stale anymore. The thread stops when `ticks` is disconnected, or the server is.
+    /// The client needs a writer's token if the server requires authentication.
+    pub fn spawn_auto_close(
+        client: TicketStoreClient,
+        stale: StaleTickets,
+        ticks: Receiver<()>,
+    ) -> Receiver<Vec<AuditEntry>> {
+        let (audit, audit_log) = channel();
+        std::thread::spawn(move || {
+            for () in ticks {
+                let closed = match close(&client, &stale) {
+                    Ok(closed) => closed,
+                    Err(ClientError::Disconnected) => return,
+                    Err(error) => {
+                        // Overloaded, most likely: try again at the next tick.
+                        tracing::warn!(%error, "failed to close stale tickets");
+                        continue;
+                    }
+                };
+                let _ = audit.send(closed);
+            }
+        });
+        audit_log
+    }
+
+    fn close(
+        client: &TicketStoreClient,
+        stale: &StaleTickets,
+    ) -> Result<Vec<AuditEntry>, ClientError> {
+        let mut closed = Vec::new();
+        let tickets = client.list()?;
+        for (entry, updated_at) in stale.stale(&tickets) {
+            match client.update_if_unchanged(done(entry.ticket), Some(updated_at)) {
+                Ok(true) => {
+                    log(&entry);
+                    closed.push(entry);
+                }
+                Ok(false) => {}
+                // Other clients can't delete tickets, but be lenient anyway.
+                Err(ClientError::NotFound(_)) => {}
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(closed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::TestClock;
+    use crate::repository::conformance::draft;
+
+    const HOUR: Duration = Duration::from_secs(60 * 60);
+
+    fn start(store: &mut TicketStore, id: TicketId) {
+        store
+            .update(TicketPatch {
+                status: Some(Status::InProgress),
+                ..TicketPatch::new(id)
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn only_idle_tickets_in_progress_are_closed() {
+        let clock = TestClock::new();
+        let stale = StaleTickets::new(2 * HOUR).with_clock(clock.clone());
+        let mut store = TicketStore::with_clock(clock.clone());
+        let [idle, busy, todo] = [(); 3].map(|()| store.add_ticket(draft()));
+        start(&mut store, idle);
+        start(&mut store, busy);
+
+        assert!(stale.close(&mut store).is_empty());
+        clock.advance(HOUR);
+        store
+            .update(TicketPatch {
+                assignee: Some(Some("Alice".into())),
+                ..TicketPatch::new(busy)
+            })
+            .unwrap();
+        assert!(stale.close(&mut store).is_empty());
+
+        clock.advance(HOUR);
+        assert_eq!(
+            stale.close(&mut store),
+            [AuditEntry {
+                ticket: idle,
+                idle_for: 2 * HOUR
+            }]
+        );
+        assert_eq!(store[idle].status, Status::Done);
+        assert_eq!(store[busy].status, Status::InProgress);
+        assert_eq!(store[todo].status, Status::ToDo);
+
+        clock.advance(HOUR);
+        let closed = stale.close(&mut store);
+        assert_eq!(closed.iter().map(|e| e.ticket).collect::<Vec<_>>(), [busy]);
+    }
+
+    #[test]
+    fn tickets_idle_before_the_first_scan_are_closed() {
+        let clock = TestClock::new();
+        let stale = StaleTickets::new(HOUR).with_clock(clock.clone());
+        let mut store = TicketStore::with_clock(clock.clone());
+        let id = store.add_ticket(draft());
+        start(&mut store, id);
+        clock.advance(10 * HOUR);
+
+        assert_eq!(
+            stale.close(&mut store),
+            [AuditEntry {
+                ticket: id,
+                idle_for: 10 * HOUR
+            }]
+        );
+    }
+
+    #[test]
+    fn changes_count_even_when_undone() {
+        let clock = TestClock::new();
+        let stale = StaleTickets::new(HOUR).with_clock(clock.clone());
+        let mut store = TicketStore::with_clock(clock.clone());
+        let id = store.add_ticket(draft());
+        start(&mut store, id);
+
+        clock.advance(HOUR / 2);
+        for assignee in [Some("Alice".into()), None] {
+            store
+                .update(TicketPatch {
+                    assignee: Some(assignee),
+                    ..TicketPatch::new(id)
+                })
+                .unwrap();
+        }
+        clock.advance(HOUR / 2);
+        assert!(stale.close(&mut store).is_empty());
+        clock.advance(HOUR / 2);
+        assert_eq!(stale.close(&mut store).len(), 1);
+    }
+
+    #[test]
+    fn reopened_tickets_start_over() {
+        let clock = TestClock::new();
+        let stale = StaleTickets::new(HOUR).with_clock(clock.clone());
+        let mut store = TicketStore::with_clock(clock.clone());
+        let id = store.add_ticket(draft());
+        start(&mut store, id);
+
+        clock.advance(HOUR / 2);
+        store.update(done(id)).unwrap();
+        start(&mut store, id);
+        clock.advance(HOUR / 2);
+        assert!(stale.close(&mut store).is_empty());
+        clock.advance(HOUR / 2);
+        assert_eq!(stale.close(&mut store).len(), 1);
+    }
+
+    #[test]
+    fn tickets_of_stores_without_a_clock_are_never_stale() {
+        let clock = TestClock::new();
+        let stale = StaleTickets::new(HOUR).with_clock(clock.clone());
+        let mut store = TicketStore::new();
+        let id = store.add_ticket(draft());
+        start(&mut store, id);
+        clock.advance(10 * HOUR);
+        assert!(stale.close(&mut store).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "threads")]
+    fn the_scheduler_closes_stale_tickets() {
+        let clock = TestClock::new();
+        let client = crate::client::launch_with_clock(4, clock.clone());
+        let id = client.insert(draft()).unwrap();
+        client
+            .update(TicketPatch {
+                status: Some(Status::InProgress),
+                ..TicketPatch::new(id)
+            })
+            .unwrap();
+
+        let (tick, ticks) = std::sync::mpsc::channel();
+        let stale = StaleTickets::new(HOUR).with_clock(clock.clone());
+        let audit_log = spawn_auto_close(client.clone(), stale, ticks);
+        tick.send(()).unwrap();
+        assert!(audit_log.recv().unwrap().is_empty());
+
+        clock.advance(HOUR);
+        tick.send(()).unwrap();
+        assert_eq!(
+            audit_log.recv().unwrap(),
+            [AuditEntry {
+                ticket: id,
+                idle_for: HOUR
+            }]
+        );
+        assert_eq!(client.get(id).unwrap().unwrap().status, Status::Done);
+
+        drop(tick);
+        assert!(audit_log.recv().is_err());
+    }
+}
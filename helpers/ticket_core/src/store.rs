@@ -0,0 +1,841 @@
+pub use crate::data::{TicketId, TicketNotFound};
+
+use crate::clock::{Clock, Timestamp};
+use crate::custom_fields::{CustomFieldError, CustomFields, FieldSchema, FieldValue};
+use crate::data::{Status, Ticket, TicketDraft, TicketPatch};
+use crate::events::{StoreEvent, Subscribers};
+use crate::intern::Interner;
+use crate::rate::RateTracker;
+use crate::tags::Tags;
+use crate::template::TicketTemplate;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Index;
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+mod transaction;
+
+pub use transaction::{Transaction, TransactionGuard};
+
+/// A snapshot of a store's size, as returned by [`TicketStore::stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StoreStats {
+    pub tickets: usize,
+    /// Distinct tags on at least one ticket.
+    pub tags: usize,
+    /// See [`TicketStore::approx_memory_bytes`].
+    pub approx_memory_bytes: usize,
+    /// Tickets added in the last minute and in the last hour, if the store counts them:
+    /// see [`TicketStore::created_in_last`].
+    pub created_last_minute: Option<u64>,
+    pub created_last_hour: Option<u64>,
+}
+
+/// Tickets are kept in a `Vec`, so iterating over them walks contiguous memory in a
+/// deterministic order, with a side index from id to position for O(1) lookups.
+///
+/// Tickets are iterated in the order they were added, with one exception: deleting a
+/// ticket moves the last one into the freed slot, to avoid shifting everything after it.
+#[derive(Clone, Debug, Default)]
+pub struct TicketStore {
+    tickets: Vec<Ticket>,
+    /// Where each ticket is in `tickets`.
+    positions: HashMap<TicketId, usize>,
+    tags: Tags,
+    /// The names tickets are assigned to. Tickets share the interned copy of their assignee's
+    /// name, rather than each holding its own.
+    assignees: Interner,
+    /// By name: see [`TicketStore::register_template`].
+    pub(crate) templates: BTreeMap<String, TicketTemplate>,
+    /// What tickets' custom fields are checked against.
+    pub(crate) schema: FieldSchema,
+    counter: u64,
+    subscribers: Subscribers,
+    /// Only stores with a clock count them, and tell the time from it.
+    /// Boxed, to keep the others small.
+    creations: Option<Box<RateTracker>>,
+}
+
+impl TicketStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A store that tells the time with `clock`: it records when tickets are created,
+    /// and counts the recently created ones.
+    ///
+    /// Stores don't have one by default: asking the system for the time panics on some
+    /// targets, like `wasm32-unknown-unknown`. Pass `SystemClock` to use it anyway.
+    pub fn with_clock(clock: impl Clock + 'static) -> Self {
+        let mut store = Self::default();
+        store.set_clock(clock);
+        store
+    }
+
+    /// Tell the time with `clock` from now on, e.g. for a store loaded from a snapshot.
+    /// Tickets already in the store keep their creation time, but the count of recently
+    /// created ones starts over.
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.creations = Some(Box::new(RateTracker::last_hour(clock)));
+    }
+
+    /// The time, as told by the store's clock, if it has one.
+    pub fn now(&self) -> Option<Timestamp> {
+        self.creations.as_ref().map(|creations| creations.now())
+    }
+
+    pub fn add_ticket(&mut self, ticket: TicketDraft) -> TicketId {
+        self.add(ticket, CustomFields::new(), self.now())
+    }
+
+    /// Add a ticket created at `created_at`, rather than now: when replaying its creation.
+    pub(crate) fn restore_ticket(
+        &mut self,
+        ticket: TicketDraft,
+        created_at: Option<Timestamp>,
+    ) -> TicketId {
+        self.add(ticket, CustomFields::new(), created_at)
+    }
+
+    /// Like [`TicketStore::add_ticket`], if `fields` fit the store's schema.
+    pub fn add_ticket_with_fields(
+        &mut self,
+        ticket: TicketDraft,
+        fields: CustomFields,
+    ) -> Result<TicketId, CustomFieldError> {
+        self.schema.validate(&fields)?;
+        Ok(self.add(ticket, fields, self.now()))
+    }
+
+    fn add(
+        &mut self,
+        ticket: TicketDraft,
+        custom_fields: CustomFields,
+        created_at: Option<Timestamp>,
+    ) -> TicketId {
+        let id = TicketId::from(self.counter);
+        self.counter += 1;
+        let ticket = Ticket {
+            id,
+            title: ticket.title,
+            description: ticket.description,
+            status: Status::ToDo,
+            assignee: None,
+            custom_fields,
+            created_at,
+            updated_at: created_at,
+            due: None,
+        };
+        self.subscribers.notify(StoreEvent::Added(ticket.clone()));
+        self.push(ticket);
+        if let Some(creations) = &mut self.creations {
+            creations.record();
+        }
+        id
+    }
+
+    fn push(&mut self, ticket: Ticket) {
+        self.positions.insert(ticket.id, self.tickets.len());
+        self.tickets.push(ticket);
+    }
+
+    pub fn get(&self, id: TicketId) -> Option<&Ticket> {
+        self.positions.get(&id).map(|&i| &self.tickets[i])
+    }
+
+    /// Changes made through the returned reference are neither reported to subscribers nor
+    /// stamped in `updated_at`: the caller has to take care of both. Hence it's not public.
+    pub(crate) fn get_mut(&mut self, id: TicketId) -> Option<&mut Ticket> {
+        self.positions.get(&id).map(|&i| &mut self.tickets[i])
+    }
+
+    /// Changing a ticket, this way or any other the store offers, stamps its `updated_at` with
+    /// the store's clock.
+    pub fn update(&mut self, patch: TicketPatch) -> Result<(), TicketNotFound> {
+        let now = self.now();
+        self.restore_update(patch, now)
+    }
+
+    /// Like [`TicketStore::update`], provided the ticket hasn't changed since it was read: its
+    /// `updated_at` is still `read`'s. Returns whether the patch was applied.
+    ///
+    /// For changes decided on from an earlier read, which shouldn't overwrite the ones made in
+    /// the meantime. Changes made at the same time, as the store's clock tells it, can't be
+    /// told apart.
+    pub fn update_if_unchanged(
+        &mut self,
+        patch: TicketPatch,
+        read: Option<Timestamp>,
+    ) -> Result<bool, TicketNotFound> {
+        let ticket = self.get(patch.id).ok_or(TicketNotFound(patch.id))?;
+        if ticket.updated_at != read {
+            return Ok(false);
+        }
+        self.update(patch)?;
+        Ok(true)
+    }
+
+    /// Apply `patch` as if it was applied at `updated_at`, rather than now: when replaying it.
+    pub(crate) fn restore_update(
+        &mut self,
+        patch: TicketPatch,
+        updated_at: Option<Timestamp>,
+    ) -> Result<(), TicketNotFound> {
+        let position = *self
+            .positions
+            .get(&patch.id)
+            .ok_or(TicketNotFound(patch.id))?;
+        let ticket = &mut self.tickets[position];
+        patch.apply_to(ticket);
+        intern_assignee(&mut self.assignees, ticket);
+        ticket.updated_at = updated_at;
+        self.subscribers.notify(StoreEvent::Updated(ticket.clone()));
+        Ok(())
+    }
+
+    /// Set (or, with `None`, clear) the time the ticket is due by.
+    pub fn set_due(&mut self, id: TicketId, due: Option<Timestamp>) -> Result<(), TicketNotFound> {
+        let now = self.now();
+        let ticket = self.get_mut(id).ok_or(TicketNotFound(id))?;
+        ticket.due = due;
+        ticket.updated_at = now;
+        let ticket = ticket.clone();
+        self.subscribers.notify(StoreEvent::Updated(ticket));
+        Ok(())
+    }
+
+    /// The tickets that aren't `Done` yet, although they were due before `now`.
+    /// Pass the store's own [`TicketStore::now`], unless you're asking about another time.
+    pub fn overdue(&self, now: Timestamp) -> impl Iterator<Item = &Ticket> {
+        self.tickets.iter().filter(move |ticket| {
+            ticket.status != Status::Done && ticket.due.is_some_and(|due| due < now)
+        })
+    }
+
+    /// Set the ticket's custom fields that are in `fields`, if they fit the store's schema.
+    /// Its other fields are left untouched.
+    pub fn set_fields(
+        &mut self,
+        id: TicketId,
+        fields: CustomFields,
+    ) -> Result<(), CustomFieldError> {
+        let position = *self.positions.get(&id).ok_or(TicketNotFound(id))?;
+        self.schema.validate(&fields)?;
+        let now = self.now();
+        let ticket = &mut self.tickets[position];
+        for (field, value) in fields.iter() {
+            ticket.custom_fields.insert(field, value.clone());
+        }
+        ticket.updated_at = now;
+        self.subscribers.notify(StoreEvent::Updated(ticket.clone()));
+        Ok(())
+    }
+
+    /// Unset one of the ticket's custom fields, returning its value.
+    pub fn remove_field(
+        &mut self,
+        id: TicketId,
+        field: &str,
+    ) -> Result<Option<FieldValue>, TicketNotFound> {
+        let position = *self.positions.get(&id).ok_or(TicketNotFound(id))?;
+        let now = self.now();
+        let ticket = &mut self.tickets[position];
+        let removed = ticket.custom_fields.remove(field);
+        if removed.is_some() {
+            ticket.updated_at = now;
+            self.subscribers.notify(StoreEvent::Updated(ticket.clone()));
+        }
+        Ok(removed)
+    }
+
+    pub fn schema(&self) -> &FieldSchema {
+        &self.schema
+    }
+
+    /// Replace the store's schema, provided every ticket's custom fields fit it.
+    pub fn set_schema(&mut self, schema: FieldSchema) -> Result<(), CustomFieldError> {
+        for ticket in &self.tickets {
+            schema.validate(&ticket.custom_fields)?;
+        }
+        self.schema = schema;
+        Ok(())
+    }
+
+    /// The last ticket takes the deleted one's place in the iteration order.
+    pub fn delete(&mut self, id: TicketId) -> Option<Ticket> {
+        let position = self.positions.remove(&id)?;
+        let deleted = self.tickets.swap_remove(position);
+        if let Some(moved) = self.tickets.get(position) {
+            self.positions.insert(moved.id, position);
+        }
+        self.tags.forget(id);
+        self.subscribers.notify(StoreEvent::Deleted(id));
+        Some(deleted)
+    }
+
+    /// Get notified of every change made to the store from now on.
+    /// The subscription ends when the receiver is dropped.
+    pub fn subscribe(&mut self) -> Receiver<StoreEvent> {
+        self.subscribers.subscribe()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tickets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tickets.is_empty()
+    }
+
+    /// All tickets, in the store's order: see [`TicketStore`].
+    pub fn iter(&self) -> impl Iterator<Item = &Ticket> {
+        self.tickets.iter()
+    }
+
+    /// Roughly how many bytes the store holds on the heap: its tickets, with their
+    /// descriptions and custom fields, its assignees' names (once each), its tags and its
+    /// indexes, its templates and schema, and what it keeps to count creations and notify
+    /// subscribers. Titles are stored inline, so they're counted as part of each ticket.
+    ///
+    /// Strings and vectors count for their capacity, spare room included. Allocator and
+    /// B-tree node overhead isn't accounted for, so the actual footprint is somewhat
+    /// higher—but it grows in proportion to this.
+    pub fn approx_memory_bytes(&self) -> usize {
+        let per_ticket: usize = self
+            .tickets
+            .iter()
+            .map(|t| t.description.capacity() + t.custom_fields.approx_memory_bytes())
+            .sum();
+        let templates: usize = self
+            .templates
+            .iter()
+            .map(|(name, template)| {
+                size_of::<(String, TicketTemplate)>()
+                    + name.capacity()
+                    + template.approx_memory_bytes()
+            })
+            .sum();
+        let creations = self.creations.as_ref().map_or(0, |creations| {
+            size_of::<RateTracker>() + creations.approx_memory_bytes()
+        });
+        self.tickets.capacity() * size_of::<Ticket>()
+            + per_ticket
+            + hash_map_bytes(&self.positions)
+            + self.tags.approx_memory_bytes()
+            + self.assignees.approx_memory_bytes()
+            + templates
+            + self.schema.approx_memory_bytes()
+            + creations
+            + self.subscribers.approx_memory_bytes()
+    }
+
+    /// How many tickets were added during the last `span`, up to an hour.
+    /// Returns `None` if the store wasn't given a clock, see [`TicketStore::with_clock`].
+    ///
+    /// Only tickets added with [`TicketStore::add_ticket`] since the store was created count:
+    /// loading a snapshot doesn't tell when its tickets were added. Counts are rounded to
+    /// ten seconds, see [`RateTracker::count_last`].
+    pub fn created_in_last(&self, span: Duration) -> Option<u64> {
+        self.creations.as_ref().map(|c| c.count_last(span))
+    }
+
+    /// A summary of what the store holds.
+    pub fn stats(&self) -> StoreStats {
+        StoreStats {
+            tickets: self.len(),
+            tags: self.tags.len(),
+            approx_memory_bytes: self.approx_memory_bytes(),
+            created_last_minute: self.created_in_last(Duration::from_secs(60)),
+            created_last_hour: self.created_in_last(Duration::from_secs(60 * 60)),
+        }
+    }
+
+    pub fn with_status(&self, status: Status) -> impl Iterator<Item = &Ticket> {
+        self.iter().filter(move |t| t.status == status)
+    }
+
+    /// Tag a ticket. Returns `false` if it already had that tag.
+    ///
+    /// Tags aren't part of `Ticket`, so tagging isn't reported to subscribers.
+    pub fn add_tag(&mut self, id: TicketId, tag: &str) -> Result<bool, TicketNotFound> {
+        if !self.positions.contains_key(&id) {
+            return Err(TicketNotFound(id));
+        }
+        Ok(self.tags.add(id, tag))
+    }
+
+    /// Returns `false` if the ticket didn't have that tag.
+    pub fn remove_tag(&mut self, id: TicketId, tag: &str) -> Result<bool, TicketNotFound> {
+        if !self.positions.contains_key(&id) {
+            return Err(TicketNotFound(id));
+        }
+        Ok(self.tags.remove(id, tag))
+    }
+
+    /// The ticket's tags, in the order they were added.
+    /// There are none for tickets that don't exist.
+    pub fn tags(&self, id: TicketId) -> impl Iterator<Item = &str> {
+        self.tags.of(id)
+    }
+
+    /// The tickets with `tag`, ordered by id.
+    pub fn with_tag<'a>(&'a self, tag: &str) -> impl Iterator<Item = &'a Ticket> + 'a {
+        self.tags
+            .tickets_with(tag)
+            .map(|id| &self.tickets[self.positions[&id]])
+    }
+
+    /// Every tagged ticket's id, with its tags.
+    #[cfg(feature = "fs")]
+    pub(crate) fn all_tags(&self) -> impl Iterator<Item = (TicketId, impl Iterator<Item = &str>)> {
+        self.tags.iter()
+    }
+
+    /// The id that will be assigned to the next ticket.
+    pub(crate) fn next_id(&self) -> u64 {
+        self.counter
+    }
+
+    /// Rebuild a store from its tickets and the id of the next ticket.
+    /// The tickets keep the order they're given in.
+    #[cfg(feature = "fs")]
+    pub(crate) fn from_parts(mut tickets: Vec<Ticket>, next_id: u64) -> Self {
+        // The tickets stay where they are: only the index has to be built, in one go.
+        let positions = tickets.iter().enumerate().map(|(i, t)| (t.id, i)).collect();
+        let mut assignees = Interner::new();
+        for ticket in &mut tickets {
+            intern_assignee(&mut assignees, ticket);
+        }
+        Self {
+            tickets,
+            positions,
+            assignees,
+            counter: next_id,
+            ..Self::default()
+        }
+    }
+}
+
+/// Make `ticket` share the interned copy of its assignee's name.
+fn intern_assignee(assignees: &mut Interner, ticket: &mut Ticket) {
+    if let Some(assignee) = &mut ticket.assignee {
+        let symbol = assignees.intern(assignee);
+        *assignee = assignees.share(symbol).into();
+    }
+}
+
+/// The heap footprint of a hash map's table: every slot it has room for, plus one
+/// control byte each. Whatever the keys and values point to isn't included.
+pub(crate) fn hash_map_bytes<K, V>(map: &HashMap<K, V>) -> usize {
+    map.capacity() * (size_of::<(K, V)>() + 1)
+}
+
+impl<'a> IntoIterator for &'a TicketStore {
+    type Item = &'a Ticket;
+    type IntoIter = std::slice::Iter<'a, Ticket>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tickets.iter()
+    }
+}
+
+impl Index<TicketId> for TicketStore {
+    type Output = Ticket;
+
+    fn index(&self, index: TicketId) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::TestClock;
+    use crate::repository::conformance::draft;
+    use ticket_fields::test_helpers::{ticket_description, ticket_title};
+
+    #[test]
+    fn test_add_and_get() {
+        let mut store = TicketStore::new();
+        let id = store.add_ticket(draft());
+        let ticket = store.get(id).unwrap();
+        assert_eq!(ticket.id, id);
+        assert_eq!(ticket.title, ticket_title());
+        assert_eq!(ticket.status, Status::ToDo);
+        assert!(store.get(TicketId::from(42)).is_none());
+    }
+
+    #[test]
+    fn test_update() {
+        let mut store = TicketStore::new();
+        let id = store.add_ticket(draft());
+        store
+            .update(TicketPatch {
+                status: Some(Status::Done),
+                ..TicketPatch::new(id)
+            })
+            .unwrap();
+        assert_eq!(store[id].status, Status::Done);
+        assert_eq!(store[id].title, ticket_title());
+
+        let missing = TicketPatch::new(TicketId::from(42));
+        assert_eq!(store.update(missing).unwrap_err().0, TicketId::from(42));
+    }
+
+    #[test]
+    fn test_delete_does_not_reuse_ids() {
+        let mut store = TicketStore::new();
+        let first = store.add_ticket(draft());
+        assert!(store.delete(first).is_some());
+        assert!(store.delete(first).is_none());
+        let second = store.add_ticket(draft());
+        assert_ne!(first, second);
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn test_subscribers_see_every_change() {
+        let mut store = TicketStore::new();
+        let events = store.subscribe();
+        let id = store.add_ticket(draft());
+        store
+            .update(TicketPatch {
+                assignee: Some(Some("Alice".into())),
+                ..TicketPatch::new(id)
+            })
+            .unwrap();
+        store.delete(id);
+        // Failed operations don't produce events.
+        assert!(store.update(TicketPatch::new(id)).is_err());
+        assert!(store.delete(id).is_none());
+
+        let events: Vec<_> = events.try_iter().collect();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(&events[0], StoreEvent::Added(t) if t.id == id));
+        assert!(
+            matches!(&events[1], StoreEvent::Updated(t) if t.assignee.as_deref() == Some("Alice"))
+        );
+        assert_eq!(events[2], StoreEvent::Deleted(id));
+    }
+
+    #[test]
+    fn test_dropped_subscriptions_are_forgotten() {
+        let mut store = TicketStore::new();
+        drop(store.subscribe());
+        store.add_ticket(draft());
+        assert!(store.subscribers.is_empty());
+    }
+
+    #[test]
+    fn test_deleting_moves_the_last_ticket_into_the_gap() {
+        let mut store = TicketStore::new();
+        let ids: Vec<_> = (0..5).map(|_| store.add_ticket(draft())).collect();
+
+        assert_eq!(store.delete(ids[1]).unwrap().id, ids[1]);
+        let iterated: Vec<_> = store.iter().map(|t| t.id).collect();
+        assert_eq!(iterated, [ids[0], ids[4], ids[2], ids[3]]);
+
+        // Deleting the last ticket doesn't move anything.
+        store.delete(ids[3]);
+        let fresh = store.add_ticket(draft());
+        let iterated: Vec<_> = (&store).into_iter().map(|t| t.id).collect();
+        assert_eq!(iterated, [ids[0], ids[4], ids[2], fresh]);
+
+        // Lookups follow the tickets that moved.
+        for id in iterated {
+            assert_eq!(store[id].id, id);
+        }
+        store
+            .update(TicketPatch {
+                status: Some(Status::Done),
+                ..TicketPatch::new(ids[4])
+            })
+            .unwrap();
+        assert_eq!(store.get(ids[4]).unwrap().status, Status::Done);
+        assert!(store.get(ids[1]).is_none());
+        assert_eq!(store.len(), 4);
+    }
+
+    #[test]
+    fn test_tags() {
+        let mut store = TicketStore::new();
+        let ids: Vec<_> = (0..4).map(|_| store.add_ticket(draft())).collect();
+        for &id in &ids[1..] {
+            assert!(store.add_tag(id, "bug").unwrap());
+        }
+        assert!(store.add_tag(ids[2], "urgent").unwrap());
+        assert!(!store.add_tag(ids[2], "bug").unwrap());
+        assert_eq!(store.tags(ids[2]).collect::<Vec<_>>(), ["bug", "urgent"]);
+        assert_eq!(store.tags(ids[0]).count(), 0);
+
+        let with_bug: Vec<_> = store.with_tag("bug").map(|t| t.id).collect();
+        assert_eq!(with_bug, &ids[1..]);
+        assert_eq!(store.with_tag("feature").count(), 0);
+
+        assert!(store.remove_tag(ids[1], "bug").unwrap());
+        assert!(!store.remove_tag(ids[1], "bug").unwrap());
+        // Deleting a ticket drops its tags, and the store's reordering doesn't affect them.
+        store.delete(ids[2]);
+        let with_bug: Vec<_> = store.with_tag("bug").map(|t| t.id).collect();
+        assert_eq!(with_bug, [ids[3]]);
+        assert_eq!(store.with_tag("urgent").count(), 0);
+        assert_eq!(store.tags(ids[3]).collect::<Vec<_>>(), ["bug"]);
+
+        let missing = TicketId::from(42);
+        assert_eq!(store.add_tag(missing, "bug").unwrap_err().0, missing);
+        assert!(store.remove_tag(missing, "bug").is_err());
+    }
+
+    #[test]
+    fn test_custom_fields() {
+        use crate::custom_fields::FieldType;
+
+        let mut store = TicketStore::new();
+        let schema = FieldSchema::new()
+            .with("estimate", FieldType::Integer)
+            .with("blocking", FieldType::Bool);
+        store.set_schema(schema).unwrap();
+        let fields: CustomFields = [("estimate", 3)].into_iter().collect();
+        let id = store.add_ticket_with_fields(draft(), fields).unwrap();
+        assert_eq!(store[id].custom_fields.get("estimate"), Some(&3.into()));
+
+        let wrong: CustomFields = [("blocking", "yes")].into_iter().collect();
+        assert!(matches!(
+            store.add_ticket_with_fields(draft(), wrong.clone()),
+            Err(CustomFieldError::TypeMismatch { .. })
+        ));
+        assert!(matches!(
+            store.set_fields(id, wrong),
+            Err(CustomFieldError::TypeMismatch { .. })
+        ));
+        let unknown: CustomFields = [("priority", 1)].into_iter().collect();
+        assert!(matches!(
+            store.set_fields(id, unknown),
+            Err(CustomFieldError::UnknownField(_))
+        ));
+        // Rejected changes leave the ticket alone.
+        assert_eq!(store.len(), 1);
+        assert_eq!(store[id].custom_fields.len(), 1);
+
+        let blocking: CustomFields = [("blocking", true)].into_iter().collect();
+        store.set_fields(id, blocking.clone()).unwrap();
+        assert_eq!(store[id].custom_fields.len(), 2);
+        assert_eq!(store.remove_field(id, "estimate").unwrap(), Some(3.into()));
+        assert_eq!(store[id].custom_fields, blocking);
+
+        let missing = TicketId::from(42);
+        assert!(matches!(
+            store.set_fields(missing, CustomFields::new()),
+            Err(CustomFieldError::NotFound(_))
+        ));
+        // A schema that doesn't fit the existing tickets is refused.
+        assert!(store.set_schema(FieldSchema::new()).is_err());
+        assert!(store.schema().get("blocking").is_some());
+    }
+
+    #[test]
+    fn test_memory_accounting() {
+        let mut store = TicketStore::new();
+        assert_eq!(store.approx_memory_bytes(), 0);
+
+        let id = store.add_ticket(TicketDraft {
+            title: ticket_title(),
+            description: "1234567890".try_into().unwrap(),
+        });
+        let one = store.approx_memory_bytes();
+        assert!(one >= size_of::<Ticket>() + 10);
+
+        // With the tables' capacity unchanged, the difference is exactly the new text, and
+        // the interned assignee.
+        store
+            .update(TicketPatch {
+                description: Some("12345678901234567890".try_into().unwrap()),
+                assignee: Some(Some("Alice".into())),
+                ..TicketPatch::new(id)
+            })
+            .unwrap();
+        assert_eq!(
+            store.approx_memory_bytes(),
+            one + 10 + store.assignees.approx_memory_bytes()
+        );
+
+        store.add_tag(id, "bug").unwrap();
+        assert!(store.approx_memory_bytes() > one + 10 + "Alice".len() + "bug".len());
+        assert_eq!(
+            store.stats(),
+            StoreStats {
+                tickets: 1,
+                tags: 1,
+                approx_memory_bytes: store.approx_memory_bytes(),
+                created_last_minute: None,
+                created_last_hour: None,
+            }
+        );
+
+        // So do custom fields, the schema they're checked against, and templates.
+        use crate::custom_fields::FieldType;
+        let tagged = store.approx_memory_bytes();
+        let schema = FieldSchema::new().with("estimate", FieldType::Integer);
+        store.set_schema(schema).unwrap();
+        let with_schema = store.approx_memory_bytes();
+        assert!(with_schema > tagged + "estimate".len());
+        let fields = CustomFields::from_iter([("estimate", 3)]);
+        store.set_fields(id, fields).unwrap();
+        let with_fields = store.approx_memory_bytes();
+        assert!(with_fields > with_schema + "estimate".len());
+        store.register_template(TicketTemplate {
+            name: "bug".into(),
+            title_prefix: "[Bug] ".into(),
+            description: "Steps:".try_into().unwrap(),
+            tags: Vec::new(),
+            custom_fields: CustomFields::new(),
+        });
+        assert!(store.approx_memory_bytes() > with_fields + "bug[Bug] Steps:".len());
+    }
+
+    #[test]
+    fn test_creation_rate() {
+        let clock = TestClock::new();
+        let mut store = TicketStore::with_clock(clock.clone());
+        store.add_ticket(draft());
+        clock.advance(Duration::from_secs(30 * 60));
+        store.add_ticket(draft());
+        store.add_ticket(draft());
+        assert_eq!(store.created_in_last(Duration::from_secs(60)), Some(2));
+        assert_eq!(store.stats().created_last_hour, Some(3));
+
+        clock.advance(Duration::from_secs(31 * 60));
+        let stats = store.stats();
+        assert_eq!(
+            (stats.created_last_minute, stats.created_last_hour),
+            (Some(0), Some(2))
+        );
+        assert_eq!(stats.tickets, 3);
+    }
+
+    #[test]
+    fn test_creation_time() {
+        let mut store = TicketStore::new();
+        let id = store.add_ticket(draft());
+        assert_eq!(store[id].created_at, None);
+
+        let clock = TestClock::new();
+        let start = clock.now();
+        store.set_clock(clock.clone());
+        assert_eq!(store.now(), Some(start));
+        clock.advance(Duration::from_secs(5));
+        let later = store.add_ticket(draft());
+        assert_eq!(
+            store[later].created_at,
+            Some(start + Duration::from_secs(5))
+        );
+        assert_eq!(store[id].created_at, None);
+    }
+
+    #[test]
+    fn test_due_dates() {
+        let clock = TestClock::new();
+        let mut store = TicketStore::with_clock(clock.clone());
+        let events = store.subscribe();
+        let [late, on_time, done, undated] = [(); 4].map(|()| store.add_ticket(draft()));
+        let deadline = clock.now() + Duration::from_secs(60 * 60);
+        for id in [late, on_time, done] {
+            store.set_due(id, Some(deadline)).unwrap();
+        }
+        store
+            .set_due(on_time, Some(deadline + Duration::from_secs(60)))
+            .unwrap();
+        assert!(
+            matches!(events.try_iter().last(), Some(StoreEvent::Updated(t)) if t.id == on_time)
+        );
+        store
+            .update(TicketPatch {
+                status: Some(Status::Done),
+                ..TicketPatch::new(done)
+            })
+            .unwrap();
+
+        let overdue = |store: &TicketStore| {
+            store
+                .overdue(store.now().unwrap())
+                .map(|t| t.id)
+                .collect::<Vec<_>>()
+        };
+        assert!(overdue(&store).is_empty());
+        clock.advance(Duration::from_secs(60 * 60));
+        // Due *before* now.
+        assert!(overdue(&store).is_empty());
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(overdue(&store), [late]);
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(overdue(&store), [late, on_time]);
+
+        store.set_due(late, None).unwrap();
+        assert_eq!(overdue(&store), [on_time]);
+        assert_eq!(store[undated].due, None);
+        assert!(store.set_due(TicketId::from(42), Some(deadline)).is_err());
+    }
+
+    #[test]
+    fn test_repeated_assignees_share_their_name() {
+        let mut store = TicketStore::new();
+        let ids: Vec<_> = (0..3).map(|_| store.add_ticket(draft())).collect();
+        for (&id, name) in ids.iter().zip(["Alice", "Bob", "Alice"]) {
+            let patch = TicketPatch {
+                assignee: Some(Some(name.into())),
+                ..TicketPatch::new(id)
+            };
+            store.update(patch).unwrap();
+        }
+        let assignee = |store: &TicketStore, id| store[id].assignee.clone().unwrap();
+        assert!(assignee(&store, ids[0]).shares_name_with(&assignee(&store, ids[2])));
+        assert!(!assignee(&store, ids[0]).shares_name_with(&assignee(&store, ids[1])));
+        assert_eq!(store.assignees.len(), 2);
+
+        // Adding another Alice doesn't store her name again.
+        let before = store.approx_memory_bytes();
+        let patch = TicketPatch {
+            assignee: Some(Some("Alice".into())),
+            ..TicketPatch::new(ids[1])
+        };
+        store.update(patch).unwrap();
+        assert_eq!(store.approx_memory_bytes(), before);
+        assert!(assignee(&store, ids[1]).shares_name_with(&assignee(&store, ids[0])));
+    }
+
+    #[test]
+    fn test_memory_grows_with_the_tickets() {
+        let mut store = TicketStore::new();
+        for _ in 0..100 {
+            store.add_ticket(draft());
+        }
+        let hundred = store.approx_memory_bytes();
+        for _ in 0..900 {
+            store.add_ticket(draft());
+        }
+        let thousand = store.approx_memory_bytes();
+        let per_ticket = size_of::<Ticket>() + ticket_description().as_ref().len();
+        assert!(thousand >= 1000 * per_ticket);
+        assert!(thousand > 5 * hundred);
+    }
+
+    #[test]
+    fn test_iteration_follows_insertion_order() {
+        let mut store = TicketStore::new();
+        let ids: Vec<_> = (0..5).map(|_| store.add_ticket(draft())).collect();
+        store
+            .update(TicketPatch {
+                status: Some(Status::InProgress),
+                ..TicketPatch::new(ids[3])
+            })
+            .unwrap();
+
+        let iterated: Vec<_> = store.iter().map(|t| t.id).collect();
+        assert_eq!(iterated, ids);
+        let in_progress: Vec<_> = store
+            .with_status(Status::InProgress)
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(in_progress, [ids[3]]);
+    }
+}
@@ -0,0 +1,464 @@
+//! Grouping changes to a [`TicketStore`], so that they're kept all together or not at all.
+//!
+//! There are two ways to go about it:
+//! - [`TicketStore::begin`] returns a [`TransactionGuard`]: changes made through it are applied
+//!   to the store straight away, and undone when the guard is dropped—unless
+//!   [`TransactionGuard::commit`] was called first. Returning early with `?`, or panicking,
+//!   leaves the store as it was before the transaction started.
+//! - [`TicketStore::transaction`] hands a [`Transaction`] to a closure. Changes are only
+//!   recorded at first, and applied together once the closure returns `Ok`.
+use super::TicketStore;
+use crate::data::{Ticket, TicketDraft, TicketId, TicketNotFound, TicketPatch};
+use crate::events::{StoreEvent, Subscribers};
+use crate::rate::RateTracker;
+use std::collections::HashSet;
+use std::ops::Deref;
+
+/// Dereferences to the store, to read it as it is within the transaction.
+///
+/// Subscribers are only told about the changes once they're committed, in the order they were made.
+/// Ids handed out by a transaction that was rolled back are not reused, but the tickets it added
+/// don't count as created (see [`TicketStore::created_in_last`]).
+pub struct TransactionGuard<'a> {
+    store: &'a mut TicketStore,
+    /// How to undo each change made so far, oldest first.
+    undo: Vec<Undo>,
+    events: Vec<StoreEvent>,
+    /// The store's subscribers, set aside until the transaction is over.
+    subscribers: Subscribers,
+    /// The store's count of creations as it was before the first ticket was added, if one was.
+    creations_before: Option<Option<Box<RateTracker>>>,
+    committed: bool,
+}
+
+enum Undo {
+    Add(TicketId),
+    /// The ticket as it was before the update.
+    Update(Ticket),
+    Delete {
+        ticket: Ticket,
+        position: usize,
+        tags: Vec<String>,
+    },
+}
+
+/// Changes to be applied to the store, recorded by [`TicketStore::transaction`].
+///
+/// Each change is checked against the store as it would be with the changes recorded before it:
+/// a ticket inserted by the transaction can be updated by it, a deleted one can't.
+pub struct Transaction<'a> {
+    store: &'a TicketStore,
+    changes: Vec<Change>,
+    /// The id the next inserted ticket will get.
+    next_id: u64,
+    deleted: HashSet<TicketId>,
+}
+
+enum Change {
+    Insert(TicketDraft),
+    Update(TicketPatch),
+    Delete(TicketId),
+}
+
+impl Transaction<'_> {
+    /// Returns the id the ticket will have, once the transaction is applied.
+    pub fn insert(&mut self, draft: TicketDraft) -> TicketId {
+        let id = TicketId::from(self.next_id);
+        self.next_id += 1;
+        self.changes.push(Change::Insert(draft));
+        id
+    }
+
+    pub fn update(&mut self, patch: TicketPatch) -> Result<(), TicketNotFound> {
+        self.check(patch.id)?;
+        self.changes.push(Change::Update(patch));
+        Ok(())
+    }
+
+    pub fn delete(&mut self, id: TicketId) -> Result<(), TicketNotFound> {
+        self.check(id)?;
+        self.deleted.insert(id);
+        self.changes.push(Change::Delete(id));
+        Ok(())
+    }
+
+    /// Check that ticket `id` will exist when the changes recorded so far are applied.
+    fn check(&self, id: TicketId) -> Result<(), TicketNotFound> {
+        let inserted = (self.store.next_id()..self.next_id).contains(&id.value());
+        if self.deleted.contains(&id) || !(inserted || self.store.get(id).is_some()) {
+            return Err(TicketNotFound(id));
+        }
+        Ok(())
+    }
+}
+
+impl TicketStore {
+    /// Start a transaction. See [`TransactionGuard`].
+    pub fn begin(&mut self) -> TransactionGuard<'_> {
+        let subscribers = std::mem::take(&mut self.subscribers);
+        TransactionGuard {
+            store: self,
+            undo: Vec::new(),
+            events: Vec::new(),
+            subscribers,
+            creations_before: None,
+            committed: false,
+        }
+    }
+
+    /// Record changes with `f`, and apply them all if it returns `Ok`.
+    /// If it returns an error instead, the store is left untouched.
+    ///
+    /// The store can't be changed while `f` runs, so everything it checked
+    /// is still true when its changes are applied.
+    pub fn transaction<T, E>(
+        &mut self,
+        f: impl FnOnce(&mut Transaction<'_>) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let mut transaction = Transaction {
+            next_id: self.counter,
+            store: self,
+            changes: Vec::new(),
+            deleted: HashSet::new(),
+        };
+        let value = f(&mut transaction)?;
+        let changes = transaction.changes;
+
+        let mut guard = self.begin();
+        for change in changes {
+            match change {
+                Change::Insert(draft) => {
+                    guard.add_ticket(draft);
+                }
+                Change::Update(patch) => guard
+                    .update(patch)
+                    .expect("Updated tickets are checked when the update is recorded"),
+                Change::Delete(id) => {
+                    guard
+                        .delete(id)
+                        .expect("Deleted tickets are checked when the deletion is recorded");
+                }
+            }
+        }
+        guard.commit();
+        Ok(value)
+    }
+}
+
+impl TransactionGuard<'_> {
+    pub fn add_ticket(&mut self, draft: TicketDraft) -> TicketId {
+        if self.creations_before.is_none() {
+            self.creations_before = Some(self.store.creations.clone());
+        }
+        let id = self.store.add_ticket(draft);
+        self.events.push(StoreEvent::Added(self.store[id].clone()));
+        self.undo.push(Undo::Add(id));
+        id
+    }
+
+    pub fn update(&mut self, patch: TicketPatch) -> Result<(), TicketNotFound> {
+        let id = patch.id;
+        let previous = self.store.get(id).cloned().ok_or(TicketNotFound(id))?;
+        self.store.update(patch)?;
+        self.events
+            .push(StoreEvent::Updated(self.store[id].clone()));
+        self.undo.push(Undo::Update(previous));
+        Ok(())
+    }
+
+    pub fn delete(&mut self, id: TicketId) -> Option<Ticket> {
+        let position = *self.store.positions.get(&id)?;
+        let tags = self.store.tags(id).map(String::from).collect();
+        let ticket = self.store.delete(id)?;
+        self.events.push(StoreEvent::Deleted(id));
+        self.undo.push(Undo::Delete {
+            ticket: ticket.clone(),
+            position,
+            tags,
+        });
+        Some(ticket)
+    }
+
+    /// Keep the changes, and tell subscribers about them.
+    pub fn commit(mut self) {
+        self.committed = true;
+    }
+
+    /// Undo the changes in reverse order, so that each one finds the store
+    /// exactly as it left it—down to the order of the tickets.
+    fn rollback(&mut self) {
+        let store = &mut *self.store;
+        for undo in self.undo.drain(..).rev() {
+            match undo {
+                Undo::Add(id) => {
+                    store.delete(id);
+                }
+                Undo::Update(previous) => {
+                    let position = store.positions[&previous.id];
+                    store.tickets[position] = previous;
+                }
+                Undo::Delete {
+                    ticket,
+                    position,
+                    tags,
+                } => {
+                    // Deleting moved the last ticket into `position`: move it back.
+                    let id = ticket.id;
+                    store.push(ticket);
+                    let last = store.tickets.len() - 1;
+                    store.tickets.swap(position, last);
+                    store.positions.insert(id, position);
+                    store.positions.insert(store.tickets[last].id, last);
+                    for tag in tags {
+                        store.tags.add(id, &tag);
+                    }
+                }
+            }
+        }
+        // Nothing else records creations while the transaction holds the store.
+        if let Some(creations) = self.creations_before.take() {
+            store.creations = creations;
+        }
+    }
+}
+
+impl Deref for TransactionGuard<'_> {
+    type Target = TicketStore;
+
+    fn deref(&self) -> &Self::Target {
+        self.store
+    }
+}
+
+impl Drop for TransactionGuard<'_> {
+    fn drop(&mut self) {
+        if !self.committed {
+            self.rollback();
+        }
+        self.store.subscribers = std::mem::take(&mut self.subscribers);
+        if self.committed {
+            for event in self.events.drain(..) {
+                self.store.subscribers.notify(event);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Status;
+    use crate::repository::conformance::draft;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    /// A store with a few tickets, some of them tagged.
+    fn store() -> TicketStore {
+        let mut store = TicketStore::new();
+        for i in 0..4 {
+            let id = store.add_ticket(draft());
+            if i % 2 == 0 {
+                store.add_tag(id, "bug").unwrap();
+            }
+        }
+        store
+    }
+
+    /// The tickets of `store`, in order, with their tags.
+    fn contents(store: &TicketStore) -> Vec<(Ticket, Vec<String>)> {
+        store
+            .iter()
+            .map(|t| (t.clone(), store.tags(t.id).map(String::from).collect()))
+            .collect()
+    }
+
+    fn done(id: TicketId) -> TicketPatch {
+        TicketPatch {
+            status: Some(Status::Done),
+            ..TicketPatch::new(id)
+        }
+    }
+
+    #[test]
+    fn committed_changes_are_kept() {
+        let mut store = store();
+        let events = store.subscribe();
+        let mut tx = store.begin();
+        let id = tx.add_ticket(draft());
+        tx.update(done(TicketId::from(1))).unwrap();
+        tx.delete(TicketId::from(0));
+        // Changes are visible within the transaction, but not reported yet.
+        assert_eq!(tx.len(), 4);
+        assert!(events.try_recv().is_err());
+        tx.commit();
+
+        assert_eq!(store[id].status, Status::ToDo);
+        assert_eq!(store[TicketId::from(1)].status, Status::Done);
+        assert!(store.get(TicketId::from(0)).is_none());
+        let events: Vec<_> = events.try_iter().collect();
+        assert!(matches!(
+            &events[..],
+            [
+                StoreEvent::Added(_),
+                StoreEvent::Updated(_),
+                StoreEvent::Deleted(_)
+            ]
+        ));
+    }
+
+    #[test]
+    fn rolled_back_insertions_are_not_counted_as_created() {
+        use crate::clock::TestClock;
+        use std::time::Duration;
+        let minute = Duration::from_secs(60);
+        let clock = TestClock::new();
+        let mut store = TicketStore::with_clock(clock.clone());
+        store.add_ticket(draft());
+
+        let mut tx = store.begin();
+        tx.add_ticket(draft());
+        tx.add_ticket(draft());
+        assert_eq!(tx.created_in_last(minute), Some(3));
+        drop(tx);
+        assert_eq!(store.created_in_last(minute), Some(1));
+
+        // Even if time has moved on by the time they're rolled back.
+        let mut tx = store.begin();
+        tx.add_ticket(draft());
+        clock.advance(minute);
+        drop(tx);
+        assert_eq!(store.created_in_last(2 * minute), Some(1));
+
+        let mut tx = store.begin();
+        tx.add_ticket(draft());
+        tx.commit();
+        assert_eq!(store.created_in_last(minute), Some(1));
+        assert_eq!(store.created_in_last(2 * minute), Some(2));
+    }
+
+    #[test]
+    fn dropped_transactions_are_rolled_back() {
+        let mut store = store();
+        let before = contents(&store);
+        let events = store.subscribe();
+        {
+            let mut tx = store.begin();
+            tx.delete(TicketId::from(0));
+            let id = tx.add_ticket(draft());
+            tx.update(done(id)).unwrap();
+            tx.update(done(TicketId::from(3))).unwrap();
+            tx.delete(TicketId::from(2));
+            tx.delete(id);
+        }
+        assert_eq!(contents(&store), before);
+        assert_eq!(store.with_tag("bug").count(), 2);
+        // Subscribers never heard of it, but are still subscribed.
+        assert!(events.try_recv().is_err());
+        store.add_ticket(draft());
+        assert!(events.try_recv().is_ok());
+    }
+
+    #[test]
+    fn rollback_on_early_return() {
+        fn close_all(store: &mut TicketStore, ids: &[TicketId]) -> Result<(), TicketNotFound> {
+            let mut tx = store.begin();
+            for &id in ids {
+                tx.update(done(id))?;
+            }
+            tx.commit();
+            Ok(())
+        }
+
+        let mut store = store();
+        let before = contents(&store);
+        let ids = [0, 1, 42, 3].map(TicketId::from);
+        assert_eq!(close_all(&mut store, &ids).unwrap_err().0, ids[2]);
+        assert_eq!(contents(&store), before);
+
+        close_all(&mut store, &ids[..2]).unwrap();
+        assert_eq!(store.with_status(Status::Done).count(), 2);
+    }
+
+    #[test]
+    fn rollback_on_panic() {
+        let mut store = store();
+        let before = contents(&store);
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let mut tx = store.begin();
+            tx.delete(TicketId::from(1));
+            tx.add_ticket(draft());
+            panic!("Something went wrong halfway through");
+        }));
+        assert!(result.is_err());
+        assert_eq!(contents(&store), before);
+    }
+
+    #[test]
+    fn transactions_apply_every_change() {
+        let mut store = store();
+        let events = store.subscribe();
+        let id = store
+            .transaction(|tx| {
+                let id = tx.insert(draft());
+                tx.update(done(id))?;
+                tx.delete(TicketId::from(0))?;
+                tx.delete(TicketId::from(3))?;
+                // Nothing has been applied yet.
+                assert_eq!(tx.store.len(), 4);
+                Ok::<_, TicketNotFound>(id)
+            })
+            .unwrap();
+
+        assert_eq!(id, TicketId::from(4));
+        assert_eq!(store[id].status, Status::Done);
+        let ids: Vec<_> = store.iter().map(|t| t.id).collect();
+        assert_eq!(ids, [4, 1, 2].map(TicketId::from));
+        assert_eq!(events.try_iter().count(), 4);
+    }
+
+    #[test]
+    fn failed_transactions_leave_the_store_untouched() {
+        use ticket_fields::{TicketTitle, TicketTitleError};
+
+        let mut store = store();
+        let before = contents(&store);
+        let events = store.subscribe();
+        let result = store.transaction(|tx| {
+            let id = tx.insert(draft());
+            tx.delete(TicketId::from(1)).unwrap();
+            tx.update(TicketPatch {
+                title: Some(TicketTitle::try_from("")?),
+                ..TicketPatch::new(id)
+            })
+            .unwrap();
+            Ok::<_, TicketTitleError>(())
+        });
+        assert_eq!(result.unwrap_err(), TicketTitleError::Empty);
+        assert_eq!(contents(&store), before);
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn transactions_check_every_change() {
+        let mut store = store();
+        let result = store.transaction(|tx| {
+            tx.delete(TicketId::from(2))?;
+            let id = tx.insert(draft());
+            tx.update(done(id))?;
+            // Deleted within the transaction, or never there in the first place.
+            assert!(tx.update(done(TicketId::from(2))).is_err());
+            assert!(tx.delete(TicketId::from(2)).is_err());
+            assert!(tx.delete(TicketId::from(5)).is_err());
+            tx.delete(TicketId::from(42))
+        });
+        assert_eq!(result.unwrap_err().0, TicketId::from(42));
+        assert_eq!(store.len(), 4);
+    }
+
+    #[test]
+    fn rolled_back_ids_are_not_reused() {
+        let mut store = store();
+        let rolled_back = store.begin().add_ticket(draft());
+        assert!(store.get(rolled_back).is_none());
+        assert!(store.add_ticket(draft()) > rolled_back);
+    }
+}
@@ -0,0 +1,118 @@
+//! Free-form labels on tickets, e.g. `bug` or `good first issue`.
+//!
+//! Stores usually have many tickets but only a handful of distinct tags, so tag names
+//! are interned: each one is stored once, and tickets refer to it by [`Symbol`].
+//! An index from tag to tickets makes [`TicketStore::with_tag`](crate::TicketStore::with_tag)
+//! as cheap as the number of tickets it returns.
+use std::collections::{BTreeSet, HashMap};
+
+use crate::data::TicketId;
+use crate::intern::{Interner, Symbol};
+use crate::store::hash_map_bytes;
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Tags {
+    names: Interner,
+    by_ticket: HashMap<TicketId, Vec<Symbol>>,
+    by_tag: HashMap<Symbol, BTreeSet<TicketId>>,
+}
+
+impl Tags {
+    /// Returns `false` if the ticket already had that tag.
+    pub(crate) fn add(&mut self, id: TicketId, tag: &str) -> bool {
+        let tag = self.names.intern(tag);
+        let tags = self.by_ticket.entry(id).or_default();
+        if tags.contains(&tag) {
+            return false;
+        }
+        tags.push(tag);
+        self.by_tag.entry(tag).or_default().insert(id);
+        true
+    }
+
+    /// Returns `false` if the ticket didn't have that tag.
+    pub(crate) fn remove(&mut self, id: TicketId, tag: &str) -> bool {
+        let Some(tag) = self.names.get(tag) else {
+            return false;
+        };
+        let Some(tags) = self.by_ticket.get_mut(&id) else {
+            return false;
+        };
+        let Some(position) = tags.iter().position(|&t| t == tag) else {
+            return false;
+        };
+        tags.remove(position);
+        if tags.is_empty() {
+            self.by_ticket.remove(&id);
+        }
+        self.unindex(tag, id);
+        true
+    }
+
+    /// Drop every tag of a deleted ticket.
+    pub(crate) fn forget(&mut self, id: TicketId) {
+        for tag in self.by_ticket.remove(&id).unwrap_or_default() {
+            self.unindex(tag, id);
+        }
+    }
+
+    fn unindex(&mut self, tag: Symbol, id: TicketId) {
+        if let Some(ids) = self.by_tag.get_mut(&tag) {
+            ids.remove(&id);
+            // The name stays interned: it's likely to be used again.
+            if ids.is_empty() {
+                self.by_tag.remove(&tag);
+            }
+        }
+    }
+
+    /// The ticket's tags, in the order they were added.
+    pub(crate) fn of(&self, id: TicketId) -> impl Iterator<Item = &str> {
+        self.by_ticket
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .map(|&tag| self.names.resolve(tag))
+    }
+
+    /// The tickets with `tag`, ordered by id.
+    pub(crate) fn tickets_with(&self, tag: &str) -> impl Iterator<Item = TicketId> + '_ {
+        self.names
+            .get(tag)
+            .and_then(|tag| self.by_tag.get(&tag))
+            .into_iter()
+            .flatten()
+            .copied()
+    }
+
+    /// How many distinct tags are on at least one ticket.
+    pub(crate) fn len(&self) -> usize {
+        self.by_tag.len()
+    }
+
+    /// Roughly how many bytes the tags and their indexes hold on the heap.
+    pub(crate) fn approx_memory_bytes(&self) -> usize {
+        let per_ticket: usize = self
+            .by_ticket
+            .values()
+            .map(|tags| tags.capacity() * size_of::<Symbol>())
+            .sum();
+        // B-tree nodes have some overhead on top of their ids, which is left out.
+        let per_tag: usize = self
+            .by_tag
+            .values()
+            .map(|ids| ids.len() * size_of::<TicketId>())
+            .sum();
+        self.names.approx_memory_bytes()
+            + hash_map_bytes(&self.by_ticket)
+            + per_ticket
+            + hash_map_bytes(&self.by_tag)
+            + per_tag
+    }
+
+    /// Every tagged ticket, with its tags.
+    #[cfg(feature = "fs")]
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (TicketId, impl Iterator<Item = &str>)> {
+        self.by_ticket.keys().map(|&id| (id, self.of(id)))
+    }
+}
@@ -0,0 +1,277 @@
+//! Templates for the kinds of tickets that keep coming back: bug reports, feature requests...
+//!
+//! A [`TicketTemplate`] is registered on a store under its name (see
+//! [`TicketStore::register_template`]), and pre-fills the tickets created from it. Whatever
+//! the [`TemplateOverrides`] set takes precedence over the template:
+//! - The title is the template's prefix followed by the overriding title.
+//! - An overriding description replaces the template's skeleton.
+//! - Overriding tags replace the template's default tags.
+//! - Custom fields are overridden one by one: the ticket gets the template's default fields,
+//!   except for those the overrides set.
+use crate::custom_fields::{CustomFieldError, CustomFields};
+use crate::data::{TicketDraft, TicketId};
+use crate::store::TicketStore;
+use serde::{Deserialize, Serialize};
+use ticket_fields::{TicketDescription, TicketDraftError};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TicketTemplate {
+    pub name: String,
+    /// Put in front of every title, e.g. `"[Bug] "`.
+    #[serde(default)]
+    pub title_prefix: String,
+    /// The description of tickets that don't come with their own,
+    /// e.g. headings for the reporter to fill in.
+    pub description: TicketDescription,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Checked against the schema of the store the ticket is added to, not when the template
+    /// is registered.
+    #[serde(default)]
+    pub custom_fields: CustomFields,
+}
+
+/// What a ticket created from a template sets for itself. See the [module docs](self).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateOverrides {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub custom_fields: CustomFields,
+}
+
+impl TicketTemplate {
+    /// The tags of a ticket created from this template.
+    pub fn tags_with<'a>(&'a self, overrides: &'a TemplateOverrides) -> &'a [String] {
+        overrides.tags.as_deref().unwrap_or(&self.tags)
+    }
+
+    /// The custom fields of a ticket created from this template.
+    pub fn custom_fields_with(&self, overrides: &TemplateOverrides) -> CustomFields {
+        let mut fields = self.custom_fields.clone();
+        for (field, value) in overrides.custom_fields.iter() {
+            fields.insert(field, value.clone());
+        }
+        fields
+    }
+
+    /// Roughly how many bytes the template holds on the heap.
+    pub(crate) fn approx_memory_bytes(&self) -> usize {
+        self.name.capacity()
+            + self.title_prefix.capacity()
+            + self.description.capacity()
+            + self.tags.capacity() * size_of::<String>()
+            + self.tags.iter().map(String::capacity).sum::<usize>()
+            + self.custom_fields.approx_memory_bytes()
+    }
+}
+
+/// Building a draft from a template. `TicketDraft` comes from `ticket_fields`,
+/// which doesn't know about templates, hence the trait.
+pub trait FromTemplate: Sized {
+    fn from_template(
+        template: &TicketTemplate,
+        overrides: &TemplateOverrides,
+    ) -> Result<Self, TicketDraftError>;
+}
+
+impl FromTemplate for TicketDraft {
+    /// The title and the description are validated as usual: a template with no prefix
+    /// still needs an overriding title, for instance.
+    fn from_template(
+        template: &TicketTemplate,
+        overrides: &TemplateOverrides,
+    ) -> Result<Self, TicketDraftError> {
+        let title = overrides.title.as_deref().unwrap_or_default();
+        let title = format!("{}{title}", template.title_prefix);
+        let description = match &overrides.description {
+            Some(description) => description.clone(),
+            None => template.description.as_ref().to_owned(),
+        };
+        TicketDraft::new(title.trim().to_owned(), description)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TemplateError {
+    #[error("There is no template named `{0}`")]
+    UnknownTemplate(String),
+    #[error(transparent)]
+    InvalidDraft(#[from] TicketDraftError),
+    #[error(transparent)]
+    InvalidFields(#[from] CustomFieldError),
+}
+
+impl TicketStore {
+    /// Register `template` under its name, returning the template it replaces, if any.
+    pub fn register_template(&mut self, template: TicketTemplate) -> Option<TicketTemplate> {
+        self.templates.insert(template.name.clone(), template)
+    }
+
+    pub fn template(&self, name: &str) -> Option<&TicketTemplate> {
+        self.templates.get(name)
+    }
+
+    /// Every registered template, by name.
+    pub fn templates(&self) -> impl Iterator<Item = &TicketTemplate> {
+        self.templates.values()
+    }
+
+    /// Add a ticket created from the template named `name`, tagged with its tags.
+    /// Its custom fields have to fit the store's schema, like
+    /// [`TicketStore::add_ticket_with_fields`]'s.
+    pub fn add_from_template(
+        &mut self,
+        name: &str,
+        overrides: &TemplateOverrides,
+    ) -> Result<TicketId, TemplateError> {
+        let template = self
+            .template(name)
+            .ok_or_else(|| TemplateError::UnknownTemplate(name.to_owned()))?;
+        let draft = TicketDraft::from_template(template, overrides)?;
+        let tags = template.tags_with(overrides).to_vec();
+        let fields = template.custom_fields_with(overrides);
+        let id = self.add_ticket_with_fields(draft, fields)?;
+        for tag in tags {
+            self.add_tag(id, &tag).expect("The ticket was just added");
+        }
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::custom_fields::{FieldSchema, FieldType};
+
+    fn bug() -> TicketTemplate {
+        TicketTemplate {
+            name: "bug".into(),
+            title_prefix: "[Bug] ".into(),
+            description: "Steps to reproduce:\n\nExpected:\n\nActual:"
+                .try_into()
+                .unwrap(),
+            tags: vec!["bug".into(), "triage".into()],
+            custom_fields: [(PRIORITY, 3), ("estimate", 0)].into_iter().collect(),
+        }
+    }
+
+    const PRIORITY: &str = "priority";
+
+    fn store() -> TicketStore {
+        let mut store = TicketStore::new();
+        let schema = FieldSchema::new()
+            .with(PRIORITY, FieldType::Integer)
+            .with("estimate", FieldType::Integer);
+        store.set_schema(schema).unwrap();
+        store
+    }
+
+    #[test]
+    fn the_template_fills_in_what_isnt_overridden() {
+        let overrides = TemplateOverrides {
+            title: Some("Crash on startup".into()),
+            ..TemplateOverrides::default()
+        };
+        let draft = TicketDraft::from_template(&bug(), &overrides).unwrap();
+        assert_eq!(draft.title.as_ref(), "[Bug] Crash on startup");
+        assert_eq!(draft.description, bug().description);
+        assert_eq!(bug().tags_with(&overrides), ["bug", "triage"]);
+        assert_eq!(bug().custom_fields_with(&overrides), bug().custom_fields);
+    }
+
+    #[test]
+    fn overrides_take_precedence() {
+        let overrides = TemplateOverrides {
+            title: Some("Crash on startup".into()),
+            description: Some("It panics".into()),
+            tags: Some(vec!["urgent".into()]),
+            custom_fields: [(PRIORITY, 1)].into_iter().collect(),
+        };
+        let template = bug();
+        let draft = TicketDraft::from_template(&template, &overrides).unwrap();
+        // The prefix is kept: the overriding title comes after it.
+        assert_eq!(draft.title.as_ref(), "[Bug] Crash on startup");
+        assert_eq!(draft.description.as_ref(), "It panics");
+        assert_eq!(template.tags_with(&overrides), ["urgent"]);
+        // Fields are overridden one by one: the template's other defaults are kept.
+        let fields = template.custom_fields_with(&overrides);
+        assert_eq!(fields.get(PRIORITY), Some(&1.into()));
+        assert_eq!(fields.get("estimate"), Some(&0.into()));
+        // Overriding with no tags at all is different from not overriding them.
+        let untagged = TemplateOverrides {
+            tags: Some(Vec::new()),
+            ..overrides
+        };
+        assert!(template.tags_with(&untagged).is_empty());
+    }
+
+    #[test]
+    fn drafts_are_validated() {
+        let template = TicketTemplate {
+            title_prefix: String::new(),
+            ..bug()
+        };
+        let err = TicketDraft::from_template(&template, &TemplateOverrides::default());
+        assert!(matches!(err, Err(TicketDraftError::InvalidTitle(_))));
+        // The prefix alone makes a title.
+        let draft = TicketDraft::from_template(&bug(), &TemplateOverrides::default()).unwrap();
+        assert_eq!(draft.title.as_ref(), "[Bug]");
+    }
+
+    #[test]
+    fn adding_tickets_from_registered_templates() {
+        let mut store = store();
+        assert_eq!(store.register_template(bug()), None);
+        let overrides = TemplateOverrides {
+            title: Some("Crash".into()),
+            ..TemplateOverrides::default()
+        };
+        let id = store.add_from_template("bug", &overrides).unwrap();
+        assert_eq!(store[id].title.as_ref(), "[Bug] Crash");
+        assert_eq!(store.tags(id).collect::<Vec<_>>(), ["bug", "triage"]);
+        assert_eq!(store[id].custom_fields, bug().custom_fields);
+        let urgent = TemplateOverrides {
+            custom_fields: [(PRIORITY, 1)].into_iter().collect(),
+            ..overrides.clone()
+        };
+        let id = store.add_from_template("bug", &urgent).unwrap();
+        assert_eq!(store[id].custom_fields.get(PRIORITY), Some(&1.into()));
+        assert_eq!(store[id].custom_fields.get("estimate"), Some(&0.into()));
+
+        assert!(matches!(
+            store.add_from_template("feature", &overrides),
+            Err(TemplateError::UnknownTemplate(name)) if name == "feature"
+        ));
+        let replaced = store.register_template(TicketTemplate {
+            tags: Vec::new(),
+            ..bug()
+        });
+        assert_eq!(replaced, Some(bug()));
+        assert_eq!(store.templates().count(), 1);
+    }
+
+    #[test]
+    fn template_fields_are_checked_against_the_schema() {
+        let mut store = store();
+        store.register_template(bug());
+        let overrides = TemplateOverrides {
+            custom_fields: [(PRIORITY, "high")].into_iter().collect(),
+            ..TemplateOverrides::default()
+        };
+        assert!(matches!(
+            store.add_from_template("bug", &overrides),
+            Err(TemplateError::InvalidFields(_))
+        ));
+        // A store without a schema for them refuses the template's defaults too.
+        let mut unschematized = TicketStore::new();
+        unschematized.register_template(bug());
+        let err = unschematized.add_from_template("bug", &TemplateOverrides::default());
+        assert!(matches!(err, Err(TemplateError::InvalidFields(_))));
+        assert!(store.is_empty() && unschematized.is_empty());
+    }
+}
@@ -0,0 +1,428 @@
+//! Several independent ticket stores, one per project.
+//!
+//! Each project has its own `TicketStore`, and therefore its own id sequence: ticket `#0`
+//! exists in every project. A bare `TicketId` is only meaningful together with its project,
+//! which is what [`ScopedId`] captures—there's no way to address a ticket of one project
+//! through another.
+use crate::clock::Clock;
+use crate::data::{Ticket, TicketDraft, TicketPatch};
+#[cfg(feature = "fs")]
+use crate::snapshot::{
+    parse, read_json_if_exists, write_atomically, Encoding, Snapshot, TrustedSnapshot,
+};
+use crate::store::{TicketId, TicketNotFound, TicketStore};
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+#[cfg(feature = "fs")]
+use std::path::Path;
+use std::sync::Arc;
+#[cfg(feature = "fs")]
+use ticket_fields::ContextError;
+
+/// A project name: 1 to 32 lowercase ASCII letters, digits, `-` or `_`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ProjectName(String);
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProjectNameError {
+    #[error("The project name cannot be empty")]
+    Empty,
+    #[error("The project name cannot be longer than 32 characters")]
+    TooLong,
+    #[error("The project name can only contain lowercase letters, digits, `-` and `_`")]
+    InvalidCharacter,
+}
+
+impl ProjectName {
+    /// The name of the project requests go to when they don't name one.
+    pub const DEFAULT: &'static str = "default";
+
+    /// The project requests go to when they don't name one.
+    pub fn default_project() -> Self {
+        Self(Self::DEFAULT.into())
+    }
+
+    /// Check that `name` is a valid project name, without taking ownership of it.
+    pub fn validate(name: &str) -> Result<(), ProjectNameError> {
+        if name.is_empty() {
+            Err(ProjectNameError::Empty)
+        } else if name.len() > 32 {
+            Err(ProjectNameError::TooLong)
+        } else if !name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-' || b == b'_')
+        {
+            Err(ProjectNameError::InvalidCharacter)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl TryFrom<String> for ProjectName {
+    type Error = ProjectNameError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::validate(&value)?;
+        Ok(Self(value))
+    }
+}
+
+impl TryFrom<&str> for ProjectName {
+    type Error = ProjectNameError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.to_string().try_into()
+    }
+}
+
+impl From<ProjectName> for String {
+    fn from(value: ProjectName) -> Self {
+        value.0
+    }
+}
+
+impl AsRef<str> for ProjectName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Projects can be looked up by a plain `&str`, e.g. one borrowed from a request.
+impl Borrow<str> for ProjectName {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for ProjectName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A ticket id, together with the project it belongs to.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ScopedId {
+    pub project: ProjectName,
+    pub id: TicketId,
+}
+
+impl std::fmt::Display for ScopedId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}#{}", self.project, self.id)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WorkspaceError {
+    #[error("There is no project named `{0}`")]
+    UnknownProject(String),
+    #[error("A project named `{0}` already exists")]
+    ProjectExists(ProjectName),
+    #[error(transparent)]
+    TicketNotFound(#[from] TicketNotFound),
+}
+
+/// A set of named projects, each with its own `TicketStore`.
+/// It always contains the default project.
+#[derive(Clone, Debug)]
+pub struct Workspace {
+    projects: BTreeMap<ProjectName, TicketStore>,
+    /// Given to every project, see [`Workspace::set_clock`].
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl Default for Workspace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Workspace {
+    pub fn new() -> Self {
+        let mut projects = BTreeMap::new();
+        projects.insert(ProjectName::default_project(), TicketStore::new());
+        Self {
+            projects,
+            clock: None,
+        }
+    }
+
+    /// Tell the time with `clock` in every project, including the ones created later on.
+    /// See [`TicketStore::set_clock`].
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        let clock: Arc<dyn Clock> = Arc::new(clock);
+        for store in self.projects.values_mut() {
+            store.set_clock(clock.clone());
+        }
+        self.clock = Some(clock);
+    }
+
+    pub fn create_project(&mut self, name: ProjectName) -> Result<(), WorkspaceError> {
+        if self.projects.contains_key(&name) {
+            return Err(WorkspaceError::ProjectExists(name));
+        }
+        let mut store = TicketStore::new();
+        if let Some(clock) = &self.clock {
+            store.set_clock(clock.clone());
+        }
+        self.projects.insert(name, store);
+        Ok(())
+    }
+
+    /// Project names, in alphabetical order.
+    pub fn projects(&self) -> impl Iterator<Item = &ProjectName> {
+        self.projects.keys()
+    }
+
+    /// Projects can be named by a `ProjectName` or by a plain `&str`.
+    pub fn project(
+        &self,
+        name: &(impl AsRef<str> + ?Sized),
+    ) -> Result<&TicketStore, WorkspaceError> {
+        let name = name.as_ref();
+        self.projects
+            .get(name)
+            .ok_or_else(|| WorkspaceError::UnknownProject(name.to_owned()))
+    }
+
+    pub fn project_mut(
+        &mut self,
+        name: &(impl AsRef<str> + ?Sized),
+    ) -> Result<&mut TicketStore, WorkspaceError> {
+        let name = name.as_ref();
+        self.projects
+            .get_mut(name)
+            .ok_or_else(|| WorkspaceError::UnknownProject(name.to_owned()))
+    }
+
+    pub fn add_ticket(
+        &mut self,
+        project: &(impl AsRef<str> + ?Sized),
+        draft: TicketDraft,
+    ) -> Result<ScopedId, WorkspaceError> {
+        let id = self.project_mut(project)?.add_ticket(draft);
+        Ok(ScopedId {
+            // It's the name of an existing project, so it's valid.
+            project: ProjectName(project.as_ref().to_owned()),
+            id,
+        })
+    }
+
+    pub fn get(&self, id: &ScopedId) -> Result<Option<&Ticket>, WorkspaceError> {
+        Ok(self.project(&id.project)?.get(id.id))
+    }
+
+    /// Apply `patch` to the ticket with id `patch.id` in `project`.
+    pub fn update(
+        &mut self,
+        project: &(impl AsRef<str> + ?Sized),
+        patch: TicketPatch,
+    ) -> Result<(), WorkspaceError> {
+        Ok(self.project_mut(project)?.update(patch)?)
+    }
+
+    pub fn delete(&mut self, id: &ScopedId) -> Result<Option<Ticket>, WorkspaceError> {
+        Ok(self.project_mut(&id.project)?.delete(id.id))
+    }
+
+    /// Write every project to `path`, replacing any previous snapshot.
+    #[cfg(feature = "fs")]
+    pub fn save(&self, path: &Path) -> Result<(), ContextError> {
+        self.save_with(path, &Encoding::default())
+    }
+
+    /// Like [`Workspace::save`], compressing and/or encrypting the snapshot.
+    #[cfg(feature = "fs")]
+    pub fn save_with(&self, path: &Path, encoding: &Encoding) -> Result<(), ContextError> {
+        let snapshots: BTreeMap<_, _> = self
+            .projects
+            .iter()
+            .map(|(name, store)| (name.clone(), Snapshot::of(store)))
+            .collect();
+        write_atomically(path, &snapshots, encoding)
+    }
+
+    /// Load a workspace saved by [`Workspace::save`], or start from scratch if there's none.
+    #[cfg(feature = "fs")]
+    pub fn load_or_default(path: &Path) -> Result<Self, ContextError> {
+        Self::load_or_default_with(path, &Encoding::default())
+    }
+
+    /// Like [`Workspace::load_or_default`], for a workspace saved by [`Workspace::save_with`].
+    ///
+    /// The workspace wrote the file itself: like [`TicketStore::load_trusted`] does, it doesn't
+    /// validate titles and descriptions again.
+    #[cfg(feature = "fs")]
+    pub fn load_or_default_with(path: &Path, encoding: &Encoding) -> Result<Self, ContextError> {
+        let mut workspace = Self::new();
+        let Some(json) = read_json_if_exists(path, encoding)? else {
+            return Ok(workspace);
+        };
+        let snapshots: BTreeMap<ProjectName, TrustedSnapshot> = parse(path, &json)?;
+        for (name, snapshot) in snapshots {
+            let store = Snapshot::trusted(path, snapshot)?.into_store();
+            workspace.projects.insert(name, store);
+        }
+        Ok(workspace)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Status;
+    use crate::repository::conformance::draft;
+
+    fn project(name: &str) -> ProjectName {
+        ProjectName::try_from(name).unwrap()
+    }
+
+    fn workspace() -> Workspace {
+        let mut workspace = Workspace::new();
+        workspace.create_project(project("backend")).unwrap();
+        workspace.create_project(project("frontend")).unwrap();
+        workspace
+    }
+
+    #[test]
+    fn project_names_are_validated() {
+        assert!(ProjectName::try_from("my-project_2").is_ok());
+        assert!(matches!(
+            ProjectName::try_from(""),
+            Err(ProjectNameError::Empty)
+        ));
+        assert!(matches!(
+            ProjectName::try_from("a".repeat(33)),
+            Err(ProjectNameError::TooLong)
+        ));
+        assert!(matches!(
+            ProjectName::try_from("Backend"),
+            Err(ProjectNameError::InvalidCharacter)
+        ));
+    }
+
+    #[test]
+    fn projects_share_the_workspace_clock() {
+        let clock = crate::clock::TestClock::new();
+        let mut workspace = workspace();
+        workspace.set_clock(clock.clone());
+        workspace.create_project(project("ops")).unwrap();
+        for name in ["default", "backend", "ops"] {
+            let id = workspace.add_ticket(name, draft()).unwrap();
+            assert_eq!(
+                workspace.get(&id).unwrap().unwrap().created_at,
+                Some(clock.now())
+            );
+        }
+    }
+
+    #[test]
+    fn projects_are_isolated() {
+        let mut workspace = workspace();
+        let backend = workspace.add_ticket(&project("backend"), draft()).unwrap();
+        let frontend = workspace.add_ticket(&project("frontend"), draft()).unwrap();
+
+        // Same number, different tickets.
+        assert_eq!(backend.id, frontend.id);
+        assert_ne!(backend, frontend);
+
+        let patch = TicketPatch {
+            status: Some(Status::Done),
+            ..TicketPatch::new(backend.id)
+        };
+        workspace.update(&project("backend"), patch).unwrap();
+        assert_eq!(
+            workspace.get(&backend).unwrap().unwrap().status,
+            Status::Done
+        );
+        assert_eq!(
+            workspace.get(&frontend).unwrap().unwrap().status,
+            Status::ToDo
+        );
+
+        workspace.delete(&frontend).unwrap();
+        assert!(workspace.get(&backend).unwrap().is_some());
+        assert!(workspace.project(&project("default")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn ids_cannot_leak_across_projects() {
+        let mut workspace = workspace();
+        let backend = workspace.add_ticket(&project("backend"), draft()).unwrap();
+        // A scoped id always resolves within its own project: re-scoping it is an explicit act,
+        // and lands on whatever (if anything) has that number over there.
+        let rescoped = ScopedId {
+            project: project("frontend"),
+            ..backend.clone()
+        };
+        assert!(workspace.get(&rescoped).unwrap().is_none());
+        assert!(matches!(
+            workspace.update(&project("frontend"), TicketPatch::new(backend.id)),
+            Err(WorkspaceError::TicketNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn unknown_and_duplicate_projects() {
+        let mut workspace = workspace();
+        assert!(matches!(
+            workspace.add_ticket(&project("mobile"), draft()),
+            Err(WorkspaceError::UnknownProject(_))
+        ));
+        assert!(matches!(
+            workspace.create_project(project("backend")),
+            Err(WorkspaceError::ProjectExists(_))
+        ));
+        let names: Vec<_> = workspace.projects().map(|p| p.as_ref()).collect();
+        assert_eq!(names, ["backend", "default", "frontend"]);
+    }
+
+    #[test]
+    fn projects_can_be_named_by_str() {
+        let mut workspace = workspace();
+        let id = workspace.add_ticket("backend", draft()).unwrap();
+        assert_eq!(id.project, project("backend"));
+        assert_eq!(workspace.project("backend").unwrap().len(), 1);
+        assert_eq!(workspace.project(ProjectName::DEFAULT).unwrap().len(), 0);
+        // Invalid names can't name a project, so they're merely unknown.
+        let err = workspace.project("Not valid!").unwrap_err();
+        assert_eq!(err.to_string(), "There is no project named `Not valid!`");
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("workspace.json");
+        let mut workspace = workspace();
+        let id = workspace.add_ticket(&project("backend"), draft()).unwrap();
+        workspace.save(&path).unwrap();
+
+        let loaded = Workspace::load_or_default(&path).unwrap();
+        assert_eq!(loaded.projects().count(), 3);
+        assert!(loaded.get(&id).unwrap().is_some());
+        assert_eq!(id.to_string(), "backend#0");
+    }
+
+    #[test]
+    #[cfg(feature = "gzip")]
+    fn save_compressed_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("workspace.json");
+        let mut workspace = workspace();
+        let id = workspace.add_ticket(&project("backend"), draft()).unwrap();
+        let gzip = Encoding {
+            compression: crate::Compression::Gzip,
+            ..Encoding::default()
+        };
+        workspace.save_with(&path, &gzip).unwrap();
+
+        let loaded = Workspace::load_or_default(&path).unwrap();
+        assert_eq!(loaded.projects().count(), 3);
+        assert!(loaded.get(&id).unwrap().is_some());
+    }
+}
@@ -0,0 +1,72 @@
+use alloc::string::{String, ToString};
+use core::error::Error;
+
+use crate::validation::{Locale, Localize, TicketDescriptionError, TicketTitleError};
+use crate::TicketDraftError;
+
+/// Render an error together with all its sources, from the outermost
+/// to the root cause, separated by `: `.
+pub fn render_chain(error: &(dyn Error + 'static)) -> String {
+    let mut rendered = error.to_string();
+    let mut current = error.source();
+    while let Some(source) = current {
+        rendered.push_str(": ");
+        rendered.push_str(&source.to_string());
+        current = source.source();
+    }
+    rendered
+}
+
+/// Like [`render_chain`], but errors from this crate are rendered in `locale`.
+/// Other errors, and contexts, are rendered as they are.
+pub fn render_chain_localized(error: &(dyn Error + 'static), locale: Locale) -> String {
+    let localize = |error: &(dyn Error + 'static)| {
+        if let Some(e) = error.downcast_ref::<TicketTitleError>() {
+            e.localize(locale)
+        } else if let Some(e) = error.downcast_ref::<TicketDescriptionError>() {
+            e.localize(locale)
+        } else if let Some(e) = error.downcast_ref::<TicketDraftError>() {
+            e.localize(locale)
+        } else {
+            error.to_string()
+        }
+    };
+    let mut rendered = localize(error);
+    let mut current = error.source();
+    while let Some(source) = current {
+        rendered.push_str(": ");
+        rendered.push_str(&localize(source));
+        current = source.source();
+    }
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("Outer")]
+    struct Outer(#[source] Middle);
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("Middle")]
+    struct Middle(#[source] std::fmt::Error);
+
+    #[test]
+    fn test_single_error() {
+        assert_eq!(
+            render_chain(&std::fmt::Error),
+            "an error occurred when formatting an argument"
+        );
+    }
+
+    #[test]
+    fn test_nested_errors() {
+        let err = Outer(Middle(std::fmt::Error));
+        assert_eq!(
+            render_chain(&err),
+            "Outer: Middle: an error occurred when formatting an argument"
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::error::Error;
+
+/// An error annotated with a description of what was being attempted when it occurred.
+/// The original error is preserved and exposed via `Error::source`.
+#[derive(Debug, thiserror::Error)]
+#[error("{context}")]
+pub struct ContextError {
+    context: String,
+    #[source]
+    source: Box<dyn Error + Send + Sync + 'static>,
+}
+
+impl ContextError {
+    pub fn context(&self) -> &str {
+        &self.context
+    }
+}
+
+/// Attach context to the error variant of a `Result`.
+///
+/// ```
+/// use ticket_fields::{render_chain, Context};
+///
+/// let err = "forty-two"
+///     .parse::<u32>()
+///     .context("while parsing the ticket id")
+///     .unwrap_err();
+/// assert_eq!(
+///     render_chain(&err),
+///     "while parsing the ticket id: invalid digit found in string"
+/// );
+/// ```
+pub trait Context<T> {
+    fn context<C>(self, context: C) -> Result<T, ContextError>
+    where
+        C: Into<String>;
+
+    /// Like `context`, but the message is only built if an error occurred.
+    fn with_context<C, F>(self, f: F) -> Result<T, ContextError>
+    where
+        C: Into<String>,
+        F: FnOnce() -> C;
+}
+
+impl<T, E> Context<T> for Result<T, E>
+where
+    E: Error + Send + Sync + 'static,
+{
+    fn context<C>(self, context: C) -> Result<T, ContextError>
+    where
+        C: Into<String>,
+    {
+        self.map_err(|source| ContextError {
+            context: context.into(),
+            source: Box::new(source),
+        })
+    }
+
+    fn with_context<C, F>(self, f: F) -> Result<T, ContextError>
+    where
+        C: Into<String>,
+        F: FnOnce() -> C,
+    {
+        self.map_err(|source| ContextError {
+            context: f().into(),
+            source: Box::new(source),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{render_chain, TicketDraft, TicketTitleError};
+
+    #[test]
+    fn test_context_preserves_source() {
+        let err = TicketDraft::new("".into(), "A description".into())
+            .context("while loading store")
+            .unwrap_err();
+        assert_eq!(err.context(), "while loading store");
+        assert_eq!(err.to_string(), "while loading store");
+
+        let draft_error = err.source().unwrap();
+        let title_error = draft_error.source().unwrap();
+        assert!(matches!(
+            title_error.downcast_ref::<TicketTitleError>(),
+            Some(TicketTitleError::Empty)
+        ));
+    }
+
+    #[test]
+    fn test_rendered_chain() {
+        let err = TicketDraft::new("".into(), "A description".into())
+            .context("while importing ticket #3")
+            .context("while loading store")
+            .unwrap_err();
+        assert_eq!(
+            render_chain(&err),
+            "while loading store: while importing ticket #3: Invalid ticket title: The title cannot be empty"
+        );
+    }
+
+    #[test]
+    fn test_with_context_is_lazy() {
+        let ok: Result<u32, std::fmt::Error> = Ok(1);
+        let value = ok
+            .with_context(|| -> String { panic!("Context should not be built on success") })
+            .unwrap();
+        assert_eq!(value, 1);
+
+        let err: Result<u32, std::fmt::Error> = Err(std::fmt::Error);
+        let err = err
+            .with_context(|| format!("while rendering ticket {}", 7))
+            .unwrap_err();
+        assert_eq!(err.context(), "while rendering ticket 7");
+    }
+}
@@ -1,12 +1,30 @@
+use crate::validation::{TicketDescriptionError, Violation};
+use alloc::string::{String, ToString};
+
 #[derive(Debug, PartialEq, Clone, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
 pub struct TicketDescription(String);
 
-#[derive(Debug, thiserror::Error)]
-pub enum TicketDescriptionError {
-    #[error("The description cannot be empty")]
-    Empty,
-    #[error("The description cannot be longer than 500 bytes")]
-    TooLong,
+impl TicketDescription {
+    /// Wrap `description` without validating it, for values that have been validated
+    /// before—e.g. when reading back a snapshot the store wrote itself.
+    /// Debug builds still check it.
+    pub fn new_unchecked(description: String) -> Self {
+        debug_assert!(
+            validate(&description).is_ok(),
+            "Invalid description: {description:?}"
+        );
+        Self(description)
+    }
+
+    /// How many bytes the description has room for, spare ones included.
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
 }
 
 impl TryFrom<String> for TicketDescription {
@@ -27,16 +45,22 @@ impl TryFrom<&str> for TicketDescription {
     }
 }
 
-fn validate(description: &str) -> Result<(), TicketDescriptionError> {
-    if description.is_empty() {
-        Err(TicketDescriptionError::Empty)
-    } else if description.len() > 500 {
-        Err(TicketDescriptionError::TooLong)
-    } else {
-        Ok(())
+impl AsRef<str> for TicketDescription {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<TicketDescription> for String {
+    fn from(value: TicketDescription) -> Self {
+        value.0
     }
 }
 
+fn validate(description: &str) -> Result<(), TicketDescriptionError> {
+    Violation::check(description, 500).map_err(TicketDescriptionError::from)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -70,4 +94,13 @@ mod tests {
         let description = TicketDescription::try_from("A description").unwrap();
         assert_eq!(description.0, "A description");
     }
+
+    #[test]
+    fn test_new_unchecked() {
+        let description = TicketDescription::new_unchecked("A description".into());
+        assert_eq!(
+            description,
+            TicketDescription::try_from("A description").unwrap()
+        );
+    }
 }
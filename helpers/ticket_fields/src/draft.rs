@@ -0,0 +1,138 @@
+use crate::validation::{Locale, Localize, TicketDescriptionError, TicketTitleError};
+use crate::{TicketDescription, TicketTitle};
+use alloc::string::{String, ToString};
+use core::fmt;
+
+/// The validated fields required to create a new ticket.
+#[derive(Debug, PartialEq, Clone, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TicketDraft {
+    pub title: TicketTitle,
+    pub description: TicketDescription,
+}
+
+#[derive(Debug)]
+pub enum TicketDraftError {
+    InvalidTitle(TicketTitleError),
+    InvalidDescription(TicketDescriptionError),
+}
+
+impl TicketDraft {
+    pub fn new(title: String, description: String) -> Result<Self, TicketDraftError> {
+        Ok(Self {
+            title: title.try_into().map_err(TicketDraftError::InvalidTitle)?,
+            description: description
+                .try_into()
+                .map_err(TicketDraftError::InvalidDescription)?,
+        })
+    }
+}
+
+impl Localize for TicketDraftError {
+    fn localize(&self, locale: Locale) -> String {
+        match (locale, self) {
+            (Locale::English, TicketDraftError::InvalidTitle(_)) => "Invalid ticket title",
+            (Locale::English, TicketDraftError::InvalidDescription(_)) => {
+                "Invalid ticket description"
+            }
+            (Locale::French, TicketDraftError::InvalidTitle(_)) => "Titre de ticket invalide",
+            (Locale::French, TicketDraftError::InvalidDescription(_)) => {
+                "Description de ticket invalide"
+            }
+        }
+        .to_string()
+    }
+}
+
+impl fmt::Display for TicketDraftError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.localize(Locale::English))
+    }
+}
+
+impl core::error::Error for TicketDraftError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            TicketDraftError::InvalidTitle(e) => Some(e),
+            TicketDraftError::InvalidDescription(e) => Some(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{render_chain, render_chain_localized};
+    use common::{overly_long_description, valid_description, valid_title};
+    use std::error::Error;
+
+    #[test]
+    fn test_new() {
+        let draft = TicketDraft::new(valid_title(), valid_description()).unwrap();
+        assert_eq!(draft.title, TicketTitle::try_from(valid_title()).unwrap());
+        assert_eq!(
+            draft.description,
+            TicketDescription::try_from(valid_description()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid_title_source() {
+        let err = TicketDraft::new("".into(), valid_description()).unwrap_err();
+        assert!(matches!(err, TicketDraftError::InvalidTitle(_)));
+
+        let source = err.source().unwrap();
+        let root = source.downcast_ref::<TicketTitleError>().unwrap();
+        assert_eq!(root, &TicketTitleError::Empty);
+        assert!(source.source().is_none());
+    }
+
+    #[test]
+    fn test_invalid_description_source() {
+        let err = TicketDraft::new(valid_title(), overly_long_description()).unwrap_err();
+        let source = err.source().unwrap();
+        let root = source.downcast_ref::<TicketDescriptionError>().unwrap();
+        assert!(matches!(
+            root,
+            TicketDescriptionError::TooLong { limit: 500, actual } if *actual > 500
+        ));
+    }
+
+    #[test]
+    fn test_localized_chain() {
+        let err = TicketDraft::new(valid_title(), "".into()).unwrap_err();
+        assert_eq!(
+            render_chain_localized(&err, Locale::English),
+            render_chain(&err)
+        );
+        assert_eq!(
+            render_chain_localized(&err, Locale::French),
+            "Description de ticket invalide: La description ne peut pas être vide"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize_validates() {
+        let draft: TicketDraft =
+            serde_json::from_str(r#"{"title": "A title", "description": "A description"}"#)
+                .unwrap();
+        assert_eq!(
+            draft,
+            TicketDraft::new(valid_title(), valid_description()).unwrap()
+        );
+
+        let err = serde_json::from_str::<TicketDraft>(r#"{"title": "", "description": "A"}"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("The title cannot be empty"));
+    }
+
+    #[test]
+    fn test_render_chain() {
+        let err = TicketDraft::new("".into(), valid_description()).unwrap_err();
+        assert_eq!(
+            render_chain(&err),
+            "Invalid ticket title: The title cannot be empty"
+        );
+    }
+}
@@ -1,6 +1,25 @@
+//! Validated ticket fields, and the errors describing why a value was rejected.
+//!
+//! Only `alloc` is needed: without the default `std` feature, the crate is `#![no_std]`.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+mod chain;
+mod context;
 mod description;
+mod draft;
+mod markdown;
+#[cfg(feature = "std")]
 pub mod test_helpers;
 mod title;
+mod validation;
 
+pub use chain::{render_chain, render_chain_localized};
+pub use context::{Context, ContextError};
 pub use description::TicketDescription;
+pub use draft::{TicketDraft, TicketDraftError};
 pub use title::TicketTitle;
+pub use validation::{
+    Field, Locale, Localize, TicketDescriptionError, TicketTitleError, ValidationError, Violation,
+};
@@ -0,0 +1,263 @@
+//! A tiny, safe subset of Markdown, for rendering ticket descriptions as HTML.
+//!
+//! Supported:
+//! - paragraphs, separated by blank lines;
+//! - bulleted (`-`, `*`, `+`) and numbered (`1.`) lists;
+//! - `*emphasis*`, `_emphasis_`, `**strong emphasis**` and `` `code spans` ``;
+//! - backslash escapes, e.g. `\*` for a literal asterisk.
+//!
+//! Everything else is rendered as plain text. In particular, raw HTML is escaped
+//! rather than passed through: the output is safe to embed in a page as-is.
+use crate::TicketDescription;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+impl TicketDescription {
+    /// Render the description, read as Markdown, to HTML.
+    pub fn render_html(&self) -> String {
+        render_html(self.as_ref())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ListKind {
+    Bulleted,
+    Numbered,
+}
+
+impl ListKind {
+    fn tag(self) -> &'static str {
+        match self {
+            ListKind::Bulleted => "ul",
+            ListKind::Numbered => "ol",
+        }
+    }
+}
+
+enum Block<'a> {
+    Paragraph(Vec<&'a str>),
+    List(ListKind, Vec<Vec<&'a str>>),
+}
+
+/// If `line` starts a list item, its kind and the item's text.
+fn list_item(line: &str) -> Option<(ListKind, &str)> {
+    if let Some(rest) = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|marker| line.strip_prefix(marker))
+    {
+        return Some((ListKind::Bulleted, rest));
+    }
+    let digits = line.bytes().take_while(u8::is_ascii_digit).count();
+    let rest = line[digits..].strip_prefix(". ")?;
+    (digits > 0).then_some((ListKind::Numbered, rest))
+}
+
+fn blocks(markdown: &str) -> Vec<Block<'_>> {
+    let mut blocks = Vec::new();
+    // Whether the next non-blank line starts a new block, rather than continuing the last one.
+    let mut fresh = true;
+    for line in markdown.lines().map(str::trim) {
+        if line.is_empty() {
+            fresh = true;
+            continue;
+        }
+        match (list_item(line), blocks.last_mut()) {
+            (Some((kind, text)), Some(Block::List(current, items)))
+                if *current == kind && !fresh =>
+            {
+                items.push(vec![text]);
+            }
+            (Some((kind, text)), _) => blocks.push(Block::List(kind, vec![vec![text]])),
+            // A line that isn't an item continues the previous one.
+            (None, Some(Block::List(_, items))) if !fresh => items.last_mut().unwrap().push(line),
+            (None, Some(Block::Paragraph(lines))) if !fresh => lines.push(line),
+            (None, _) => blocks.push(Block::Paragraph(vec![line])),
+        }
+        fresh = false;
+    }
+    blocks
+}
+
+fn render_html(markdown: &str) -> String {
+    let mut html = String::new();
+    for block in blocks(markdown) {
+        match block {
+            Block::Paragraph(lines) => {
+                html.push_str("<p>");
+                render_inline(&lines.join("\n"), &mut html);
+                html.push_str("</p>\n");
+            }
+            Block::List(kind, items) => {
+                html.push_str(&format!("<{}>\n", kind.tag()));
+                for item in items {
+                    html.push_str("<li>");
+                    render_inline(&item.join("\n"), &mut html);
+                    html.push_str("</li>\n");
+                }
+                html.push_str(&format!("</{}>\n", kind.tag()));
+            }
+        }
+    }
+    html
+}
+
+/// Render emphasis and code spans in `text`, escaping everything else.
+fn render_inline(text: &str, html: &mut String) {
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let after = &rest[c.len_utf8()..];
+        match c {
+            '\\' if after.starts_with(|c: char| c.is_ascii_punctuation()) => {
+                let escaped = after.chars().next().unwrap();
+                escape(escaped, html);
+                rest = &after[1..];
+                continue;
+            }
+            '`' => {
+                if let Some(end) = after.find('`') {
+                    html.push_str("<code>");
+                    after[..end].chars().for_each(|c| escape(c, html));
+                    html.push_str("</code>");
+                    rest = &after[end + 1..];
+                    continue;
+                }
+            }
+            '*' if after.starts_with('*') => {
+                if let Some((inner, remainder)) = delimited(&after[1..], "**") {
+                    html.push_str("<strong>");
+                    render_inline(inner, html);
+                    html.push_str("</strong>");
+                    rest = remainder;
+                    continue;
+                }
+            }
+            '*' => {
+                if let Some((inner, remainder)) = delimited(after, "*") {
+                    html.push_str("<em>");
+                    render_inline(inner, html);
+                    html.push_str("</em>");
+                    rest = remainder;
+                    continue;
+                }
+            }
+            // Underscores only count at word boundaries, so that `snake_case` stays as it is.
+            '_' if !preceded_by_word(text, rest) => {
+                if let Some((inner, remainder)) = delimited(after, "_")
+                    .filter(|(_, remainder)| !remainder.starts_with(char::is_alphanumeric))
+                {
+                    html.push_str("<em>");
+                    render_inline(inner, html);
+                    html.push_str("</em>");
+                    rest = remainder;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        escape(c, html);
+        rest = after;
+    }
+}
+
+/// Split `s` at the first `delimiter`, if what comes before it is a valid emphasized span:
+/// non-empty, and neither starting nor ending with whitespace.
+fn delimited<'a>(s: &'a str, delimiter: &str) -> Option<(&'a str, &'a str)> {
+    let end = s.find(delimiter)?;
+    let inner = &s[..end];
+    let trimmed = !inner.is_empty() && inner.trim() == inner;
+    trimmed.then(|| (inner, &s[end + delimiter.len()..]))
+}
+
+/// Whether the character right before `rest` (a suffix of `text`) is part of a word.
+fn preceded_by_word(text: &str, rest: &str) -> bool {
+    text[..text.len() - rest.len()]
+        .chars()
+        .next_back()
+        .is_some_and(char::is_alphanumeric)
+}
+
+fn escape(c: char, html: &mut String) {
+    match c {
+        '&' => html.push_str("&amp;"),
+        '<' => html.push_str("&lt;"),
+        '>' => html.push_str("&gt;"),
+        '"' => html.push_str("&quot;"),
+        '\'' => html.push_str("&#39;"),
+        c => html.push(c),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render_html;
+
+    #[test]
+    fn paragraphs() {
+        assert_eq!(
+            render_html("First line\nsame paragraph\n\nSecond"),
+            "<p>First line\nsame paragraph</p>\n<p>Second</p>\n"
+        );
+    }
+
+    #[test]
+    fn emphasis_and_code() {
+        assert_eq!(
+            render_html("*a* _b_ **c** `d *e*`"),
+            "<p><em>a</em> <em>b</em> <strong>c</strong> <code>d *e*</code></p>\n"
+        );
+        assert_eq!(
+            render_html("**bold with *nested* emphasis**"),
+            "<p><strong>bold with <em>nested</em> emphasis</strong></p>\n"
+        );
+    }
+
+    #[test]
+    fn unmatched_delimiters_are_literal() {
+        assert_eq!(
+            render_html("2 * 3 * 4, snake_case_name, `open"),
+            "<p>2 * 3 * 4, snake_case_name, `open</p>\n"
+        );
+        assert_eq!(render_html(r"\*not emphasis\*"), "<p>*not emphasis*</p>\n");
+    }
+
+    #[test]
+    fn lists() {
+        assert_eq!(
+            render_html("Steps:\n1. Open it\n2. Close it\n\n- one\n* two\n  continued"),
+            "<p>Steps:</p>\n<ol>\n<li>Open it</li>\n<li>Close it</li>\n</ol>\n\
+             <ul>\n<li>one</li>\n<li>two\ncontinued</li>\n</ul>\n"
+        );
+    }
+
+    #[test]
+    fn html_is_escaped() {
+        assert_eq!(
+            render_html("<script>alert('hi')</script> & `<b>`"),
+            "<p>&lt;script&gt;alert(&#39;hi&#39;)&lt;/script&gt; &amp; <code>&lt;b&gt;</code></p>\n"
+        );
+        assert_eq!(
+            render_html("*<img src=x onerror=\"boom\">*"),
+            "<p><em>&lt;img src=x onerror=&quot;boom&quot;&gt;</em></p>\n"
+        );
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use super::render_html;
+    use proptest::prelude::*;
+
+    proptest! {
+        /// Whatever the input, the only markup in the output is ours.
+        #[test]
+        fn output_is_sanitized(input in "[a-z <>&*_`\\\\\n'\"=/-]{0,80}") {
+            let mut html = render_html(&input);
+            for tag in ["p", "em", "strong", "code", "ul", "ol", "li"] {
+                html = html.replace(&format!("<{tag}>"), "").replace(&format!("</{tag}>"), "");
+            }
+            prop_assert!(!html.contains(['<', '>', '"', '\'']), "{}", html);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{TicketDescription, TicketTitle};
+use crate::{TicketDescription, TicketDraft, TicketTitle};
 use common::{valid_description, valid_title};
 
 /// A function to generate a valid ticket title,
@@ -12,3 +12,12 @@ pub fn ticket_title() -> TicketTitle {
 pub fn ticket_description() -> TicketDescription {
     valid_description().try_into().unwrap()
 }
+
+/// A function to generate a valid ticket draft,
+/// for test purposes.
+pub fn ticket_draft() -> TicketDraft {
+    TicketDraft {
+        title: ticket_title(),
+        description: ticket_description(),
+    }
+}
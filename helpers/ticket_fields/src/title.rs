@@ -1,22 +1,58 @@
-use std::convert::TryFrom;
+use crate::validation::{TicketTitleError, Violation};
+use alloc::string::{String, ToString};
+use core::fmt;
 
-#[derive(Debug, PartialEq, Clone, Eq)]
-pub struct TicketTitle(String);
+/// The longest valid title, in bytes.
+const MAX_LEN: usize = 50;
 
-#[derive(Debug, thiserror::Error)]
-pub enum TicketTitleError {
-    #[error("The title cannot be empty")]
-    Empty,
-    #[error("The title cannot be longer than 50 bytes")]
-    TooLong,
+/// Titles are short by construction, so they're stored inline rather than on the heap:
+/// creating or cloning one never allocates.
+#[derive(PartialEq, Clone, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
+pub struct TicketTitle {
+    len: u8,
+    /// Only the first `len` bytes are used; the rest are zeroes, so that the derived
+    /// `PartialEq` only has to compare the bytes.
+    bytes: [u8; MAX_LEN],
+}
+
+impl TicketTitle {
+    /// The longest valid title, in bytes.
+    pub const MAX_LEN: usize = MAX_LEN;
+
+    /// `title` must be valid already.
+    fn new(title: &str) -> Self {
+        let mut bytes = [0; MAX_LEN];
+        bytes[..title.len()].copy_from_slice(title.as_bytes());
+        Self {
+            len: title.len() as u8,
+            bytes,
+        }
+    }
+
+    /// Wrap `title` without validating it, for values that have been validated before—e.g.
+    /// when reading back a snapshot the store wrote itself. Debug builds still check it.
+    ///
+    /// # Panics
+    ///
+    /// If `title` is longer than [`TicketTitle::MAX_LEN`], in every build: it wouldn't fit.
+    /// An empty title is not caught in release builds.
+    pub fn new_unchecked(title: &str) -> Self {
+        debug_assert!(validate(title).is_ok(), "Invalid title: {title:?}");
+        assert!(title.len() <= MAX_LEN, "Title too long: {title:?}");
+        Self::new(title)
+    }
 }
 
 impl TryFrom<String> for TicketTitle {
     type Error = TicketTitleError;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        validate(&value)?;
-        Ok(Self(value))
+        Self::try_from(value.as_str())
     }
 }
 
@@ -25,20 +61,34 @@ impl TryFrom<&str> for TicketTitle {
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         validate(value)?;
-        Ok(Self(value.to_string()))
+        Ok(Self::new(value))
     }
 }
 
-fn validate(title: &str) -> Result<(), TicketTitleError> {
-    if title.is_empty() {
-        Err(TicketTitleError::Empty)
-    } else if title.len() > 50 {
-        Err(TicketTitleError::TooLong)
-    } else {
-        Ok(())
+impl AsRef<str> for TicketTitle {
+    fn as_ref(&self) -> &str {
+        // SAFETY: the bytes were copied, in full, from a `&str` in `new`.
+        unsafe { core::str::from_utf8_unchecked(&self.bytes[..self.len as usize]) }
     }
 }
 
+impl From<TicketTitle> for String {
+    fn from(value: TicketTitle) -> Self {
+        value.as_ref().to_string()
+    }
+}
+
+/// Formatted like the `String` it used to wrap: `TicketTitle("A title")`.
+impl fmt::Debug for TicketTitle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TicketTitle").field(&self.as_ref()).finish()
+    }
+}
+
+fn validate(title: &str) -> Result<(), TicketTitleError> {
+    Violation::check(title, MAX_LEN).map_err(TicketTitleError::from)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -49,7 +99,7 @@ mod tests {
     fn test_try_from_string() {
         let input = valid_title();
         let title = TicketTitle::try_from(input.clone()).unwrap();
-        assert_eq!(title.0, input);
+        assert_eq!(title.as_ref(), input);
     }
 
     #[test]
@@ -67,6 +117,60 @@ mod tests {
     #[test]
     fn test_try_from_str() {
         let title = TicketTitle::try_from("A title").unwrap();
-        assert_eq!(title.0, "A title");
+        assert_eq!(title.as_ref(), "A title");
+        assert_eq!(format!("{title:?}"), r#"TicketTitle("A title")"#);
+    }
+
+    #[test]
+    fn test_stored_inline() {
+        // The bytes of the longest title, plus their length: no pointer to the heap.
+        assert_eq!(std::mem::size_of::<TicketTitle>(), MAX_LEN + 1);
+
+        let longest = "é".repeat(MAX_LEN / 2);
+        let title = TicketTitle::try_from(longest.as_str()).unwrap();
+        assert_eq!(title.as_ref(), longest);
+        assert_eq!(String::from(title.clone()), longest);
+        assert_ne!(title, TicketTitle::try_from("é").unwrap());
+    }
+
+    #[test]
+    fn test_new_unchecked() {
+        let title = TicketTitle::new_unchecked("A title");
+        assert_eq!(title, TicketTitle::try_from("A title").unwrap());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Invalid title")]
+    fn test_new_unchecked_still_checks_in_debug_builds() {
+        TicketTitle::new_unchecked("");
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        // Up to 60 characters, 1 to 4 bytes each: plenty of inputs on both sides of the limit.
+        #[test]
+        fn accepts_exactly_the_strings_within_limits(input in ".{0,60}") {
+            let within_limits = !input.is_empty() && input.len() <= 50;
+            prop_assert_eq!(TicketTitle::try_from(input.as_str()).is_ok(), within_limits);
+            prop_assert_eq!(TicketTitle::try_from(input).is_ok(), within_limits);
+        }
+
+        #[test]
+        fn ascii_boundary(len in 45usize..=55) {
+            let input = "a".repeat(len);
+            prop_assert_eq!(TicketTitle::try_from(input).is_ok(), len <= 50);
+        }
+
+        #[test]
+        fn preserves_valid_input(input in "[a-zA-Z0-9 ]{1,50}") {
+            let title = TicketTitle::try_from(input.clone()).unwrap();
+            prop_assert_eq!(title.as_ref(), input);
+        }
     }
 }
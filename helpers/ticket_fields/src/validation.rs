@@ -0,0 +1,217 @@
+//! Validation errors, kept apart from the words used to describe them.
+//!
+//! Each field has its own error type—[`TicketTitleError`], [`TicketDescriptionError`]—so that
+//! callers can tell them apart, but they only record what went wrong: which rule was broken,
+//! and the numbers involved. [`ValidationError`] erases the difference, pairing a [`Violation`]
+//! with its [`Field`]. Turning any of them into a sentence is the job of [`Localize`],
+//! in whichever [`Locale`] the user needs. `Display` falls back to English.
+use alloc::format;
+use alloc::string::String;
+use core::fmt;
+
+/// A language that error messages can be rendered in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Locale {
+    #[default]
+    English,
+    French,
+}
+
+/// Types whose message can be rendered in any [`Locale`].
+pub trait Localize {
+    fn localize(&self, locale: Locale) -> String;
+}
+
+/// The ticket field that failed validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Field {
+    Title,
+    Description,
+}
+
+/// The rule that the field's value broke.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Violation {
+    Empty,
+    /// Lengths are in bytes.
+    TooLong {
+        limit: usize,
+        actual: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ValidationError {
+    pub field: Field,
+    pub violation: Violation,
+}
+
+impl Violation {
+    /// Check that `value` is non-empty and at most `limit` bytes long.
+    pub fn check(value: &str, limit: usize) -> Result<(), Self> {
+        if value.is_empty() {
+            Err(Violation::Empty)
+        } else if value.len() > limit {
+            Err(Violation::TooLong {
+                limit,
+                actual: value.len(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Define the error type for a field: the same violations, under a type of its own.
+macro_rules! field_error {
+    ($(#[$doc:meta])* $name:ident, $field:expr) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum $name {
+            Empty,
+            /// Lengths are in bytes.
+            TooLong { limit: usize, actual: usize },
+        }
+
+        impl From<Violation> for $name {
+            fn from(value: Violation) -> Self {
+                match value {
+                    Violation::Empty => $name::Empty,
+                    Violation::TooLong { limit, actual } => $name::TooLong { limit, actual },
+                }
+            }
+        }
+
+        impl From<$name> for ValidationError {
+            fn from(value: $name) -> Self {
+                let violation = match value {
+                    $name::Empty => Violation::Empty,
+                    $name::TooLong { limit, actual } => Violation::TooLong { limit, actual },
+                };
+                ValidationError {
+                    field: $field,
+                    violation,
+                }
+            }
+        }
+
+        impl Localize for $name {
+            fn localize(&self, locale: Locale) -> String {
+                ValidationError::from(*self).localize(locale)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.localize(Locale::English))
+            }
+        }
+
+        impl core::error::Error for $name {}
+    };
+}
+
+field_error!(
+    /// The ticket title is invalid.
+    TicketTitleError,
+    Field::Title
+);
+field_error!(
+    /// The ticket description is invalid.
+    TicketDescriptionError,
+    Field::Description
+);
+
+impl Localize for ValidationError {
+    fn localize(&self, locale: Locale) -> String {
+        match (locale, self.field, self.violation) {
+            (Locale::English, field, Violation::Empty) => {
+                format!("The {} cannot be empty", english(field))
+            }
+            (Locale::English, field, Violation::TooLong { limit, .. }) => {
+                format!("The {} cannot be longer than {limit} bytes", english(field))
+            }
+            (Locale::French, field, Violation::Empty) => {
+                format!("{} ne peut pas être vide", french(field))
+            }
+            (Locale::French, field, Violation::TooLong { limit, .. }) => {
+                format!("{} ne peut pas dépasser {limit} octets", french(field))
+            }
+        }
+    }
+}
+
+fn english(field: Field) -> &'static str {
+    match field {
+        Field::Title => "title",
+        Field::Description => "description",
+    }
+}
+
+/// French nouns have a gender, so the article comes along with the name.
+fn french(field: Field) -> &'static str {
+    match field {
+        Field::Title => "Le titre",
+        Field::Description => "La description",
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.localize(Locale::English))
+    }
+}
+
+impl core::error::Error for ValidationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn structured_data() {
+        let violation = Violation::check("abcdef", 4).unwrap_err();
+        assert_eq!(
+            violation,
+            Violation::TooLong {
+                limit: 4,
+                actual: 6
+            }
+        );
+        assert!(Violation::check("abcd", 4).is_ok());
+
+        let err = ValidationError::from(TicketTitleError::from(violation));
+        assert_eq!(err.field, Field::Title);
+        assert_eq!(err.violation, violation);
+    }
+
+    #[test]
+    fn messages() {
+        let empty = ValidationError {
+            field: Field::Description,
+            violation: Violation::Empty,
+        };
+        assert_eq!(empty.to_string(), "The description cannot be empty");
+        assert_eq!(
+            empty.localize(Locale::French),
+            "La description ne peut pas être vide"
+        );
+
+        let too_long = ValidationError {
+            field: Field::Title,
+            violation: Violation::TooLong {
+                limit: 50,
+                actual: 51,
+            },
+        };
+        assert_eq!(
+            too_long.localize(Locale::English),
+            "The title cannot be longer than 50 bytes"
+        );
+        assert_eq!(
+            too_long.localize(Locale::French),
+            "Le titre ne peut pas dépasser 50 octets"
+        );
+    }
+}
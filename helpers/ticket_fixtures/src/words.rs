@@ -0,0 +1,66 @@
+//! What generated tickets are made of. Every combination fits the limits of
+//! `TicketTitle` and `TicketDescription`.
+
+pub(crate) const VERBS: &[&str] = &[
+    "Fix",
+    "Add",
+    "Remove",
+    "Speed up",
+    "Document",
+    "Refactor",
+    "Test",
+    "Translate",
+];
+
+pub(crate) const SUBJECTS: &[&str] = &[
+    "login",
+    "the search bar",
+    "CSV export",
+    "dark mode",
+    "the settings page",
+    "notifications",
+    "the API client",
+    "unicode handling",
+    "pagination",
+    "caching",
+];
+
+/// Mostly nothing: most titles are just a verb and a subject.
+pub(crate) const QUALIFIERS: &[&str] = &[
+    "",
+    "",
+    "",
+    " on mobile",
+    " for admins",
+    " in Firefox",
+    " after logout",
+    " (again)",
+    " für Deutsch",
+    " 🚀",
+];
+
+pub(crate) const SENTENCES: &[&str] = &[
+    "Steps to reproduce: open the page and click « Save ».",
+    "Works on my machine™.",
+    "Users report that the “Größe” field is truncated.",
+    "見出しが正しく表示されません。",
+    "Crashes with: thread 'main' panicked at 'index out of bounds'.",
+    "See the attached screenshot 📎.",
+    "Expected: the list is sorted. Actual: it isn't.",
+    "Likely related to the last release.",
+    "Нужно проверить на Windows.",
+    "Café, naïve, façade: accents are mangled on export.",
+];
+
+pub(crate) const TAGS: &[&str] = &[
+    "bug",
+    "feature",
+    "ui",
+    "backend",
+    "docs",
+    "performance",
+    "security",
+    "good-first-issue",
+];
+
+pub(crate) const ASSIGNEES: &[&str] = &["alice", "bob", "chloé", "dmitri", "李雷"];
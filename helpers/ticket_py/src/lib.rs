@@ -0,0 +1,145 @@
+//! Python bindings for the finished ticket model, to script it from Python.
+//!
+//! ```python
+//! import ticket_py
+//!
+//! store = ticket_py.TicketStore()
+//! id = store.insert("Crash on startup", "It panics")
+//! store.update(id, status="InProgress")
+//! print([t.title for t in store.query(status="InProgress")])
+//! ```
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use ticket_core::{Query, Status, Ticket, TicketDraft, TicketPatch, TicketStore};
+use ticket_fields::render_chain;
+
+/// A snapshot of a ticket: changing the store doesn't change it.
+#[pyclass(name = "Ticket", frozen, get_all)]
+#[derive(Debug, Clone)]
+pub struct PyTicket {
+    id: u64,
+    title: String,
+    description: String,
+    /// `"ToDo"`, `"InProgress"` or `"Done"`.
+    status: String,
+    assignee: Option<String>,
+}
+
+impl From<&Ticket> for PyTicket {
+    fn from(ticket: &Ticket) -> Self {
+        Self {
+            id: ticket.id.value(),
+            title: ticket.title.as_ref().to_string(),
+            description: ticket.description.as_ref().to_string(),
+            status: ticket.status.to_string(),
+            assignee: ticket.assignee.as_deref().map(String::from),
+        }
+    }
+}
+
+#[pymethods]
+impl PyTicket {
+    fn __repr__(&self) -> String {
+        format!(
+            "Ticket(id={}, title={:?}, status={})",
+            self.id, self.title, self.status
+        )
+    }
+}
+
+#[pyclass(name = "TicketStore")]
+#[derive(Default)]
+pub struct PyTicketStore {
+    store: TicketStore,
+}
+
+#[pymethods]
+impl PyTicketStore {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a ticket and return its id.
+    /// Raises `ValueError` if the title or the description is invalid.
+    fn insert(&mut self, title: String, description: String) -> PyResult<u64> {
+        let draft = draft(title, description)?;
+        Ok(self.store.add_ticket(draft).value())
+    }
+
+    fn get(&self, id: u64) -> Option<PyTicket> {
+        self.store.get(id.into()).map(PyTicket::from)
+    }
+
+    /// Change some of the fields of ticket `id`: the ones left to `None` are untouched.
+    #[pyo3(signature = (id, *, title=None, description=None, status=None))]
+    fn update(
+        &mut self,
+        id: u64,
+        title: Option<String>,
+        description: Option<String>,
+        status: Option<String>,
+    ) -> PyResult<()> {
+        let patch = TicketPatch {
+            title: title
+                .map(TryInto::try_into)
+                .transpose()
+                .map_err(value_error)?,
+            description: description
+                .map(TryInto::try_into)
+                .transpose()
+                .map_err(value_error)?,
+            status: status.map(parse_status).transpose()?,
+            ..TicketPatch::new(id.into())
+        };
+        self.store.update(patch).map_err(value_error)
+    }
+
+    /// The tickets matching every criterion that isn't `None`, in the store's order.
+    /// `text` is matched, ignoring case, against the title and the description.
+    #[pyo3(signature = (*, status=None, assignee=None, text=None))]
+    fn query(
+        &self,
+        status: Option<String>,
+        assignee: Option<String>,
+        text: Option<String>,
+    ) -> PyResult<Vec<PyTicket>> {
+        let query = Query {
+            status: status.map(parse_status).transpose()?,
+            assignee,
+            text,
+        };
+        Ok(self.store.query(&query).map(PyTicket::from).collect())
+    }
+
+    fn __len__(&self) -> usize {
+        self.store.len()
+    }
+}
+
+/// Check a title and a description, without adding them anywhere.
+/// Raises `ValueError` if either is invalid.
+#[pyfunction]
+fn validate_draft(title: String, description: String) -> PyResult<()> {
+    draft(title, description).map(|_| ())
+}
+
+fn draft(title: String, description: String) -> PyResult<TicketDraft> {
+    TicketDraft::new(title, description).map_err(value_error)
+}
+
+fn parse_status(status: String) -> PyResult<Status> {
+    Status::try_from(status).map_err(value_error)
+}
+
+fn value_error(error: impl std::error::Error + 'static) -> PyErr {
+    PyValueError::new_err(render_chain(&error))
+}
+
+#[pymodule]
+pub fn ticket_py(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyTicket>()?;
+    module.add_class::<PyTicketStore>()?;
+    module.add_function(wrap_pyfunction!(validate_draft, module)?)?;
+    Ok(())
+}
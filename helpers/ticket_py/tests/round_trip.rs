@@ -0,0 +1,64 @@
+use std::ffi::CString;
+
+use pyo3::prelude::*;
+
+/// Run `code` with `ticket_py` importable, as if it had been installed.
+fn run_python(code: &str) {
+    let code = CString::new(code).unwrap();
+    Python::attach(|py| {
+        let module = pyo3::wrap_pymodule!(ticket_py::ticket_py)(py);
+        py.import("sys")?
+            .getattr("modules")?
+            .set_item("ticket_py", module)?;
+        py.run(&code, None, None)
+    })
+    .unwrap_or_else(|e| panic!("Python failed: {e}"));
+}
+
+#[test]
+fn round_trip() {
+    run_python(
+        r#"
+import ticket_py
+
+store = ticket_py.TicketStore()
+first = store.insert("Crash on startup", "It panics")
+second = store.insert("Dark mode", "Add a dark theme")
+assert len(store) == 2
+
+ticket = store.get(first)
+assert (ticket.id, ticket.title, ticket.status) == (first, "Crash on startup", "ToDo")
+assert store.get(42) is None
+
+store.update(second, status="inprogress", title="Dark theme")
+assert store.get(second).title == "Dark theme"
+assert [t.id for t in store.query(status="InProgress")] == [second]
+assert [t.id for t in store.query(text="PANIC")] == [first]
+assert len(store.query()) == 2
+"#,
+    );
+}
+
+#[test]
+fn invalid_input_raises_value_error() {
+    run_python(
+        r#"
+import ticket_py
+
+def reason(f, *args, **kwargs):
+    try:
+        f(*args, **kwargs)
+    except ValueError as e:
+        return str(e)
+    raise AssertionError("no ValueError")
+
+store = ticket_py.TicketStore()
+assert "The title cannot be empty" in reason(store.insert, "", "A description")
+assert "cannot be longer than 500 bytes" in reason(ticket_py.validate_draft, "A title", "x" * 501)
+assert "not a valid status" in reason(store.query, status="Blocked")
+assert "There is no ticket with id 7" in reason(store.update, 7, title="Whatever")
+assert len(store) == 0
+ticket_py.validate_draft("A title", "A description")
+"#,
+    );
+}
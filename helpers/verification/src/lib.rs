@@ -0,0 +1,151 @@
+//! Stricter checks for some of the exercises.
+//!
+//! The exercises' own tests can only verify *what* a solution computes.
+//! The tests in this crate also look at *how* it computes it—e.g. that the threaded
+//! `sum` actually runs on other threads instead of returning `v.iter().sum()`.
+//! Run them with `cargo test -p verification`.
+//!
+//! Each check lives in its own file under `tests/`, so that each one runs in a
+//! separate process and measurements don't leak between them.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+const MAX_TRACKED_THREADS: usize = 64;
+
+/// The system allocator, instrumented to record which threads allocate or free memory,
+/// and how many bytes they allocate.
+///
+/// Spawning a thread and moving data into it inevitably goes through the allocator
+/// from the spawned thread (at the very least, to free what was moved in), which
+/// makes it a reliable place to observe thread activity without touching the code under test.
+struct ThreadTrackingAllocator;
+
+static RECORDING: AtomicBool = AtomicBool::new(false);
+static NEXT_TOKEN: AtomicU64 = AtomicU64::new(1);
+static SEEN: [AtomicU64; MAX_TRACKED_THREADS] = [const { AtomicU64::new(0) }; MAX_TRACKED_THREADS];
+static COUNTING_BYTES: AtomicBool = AtomicBool::new(false);
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+// Only one measurement can run at a time.
+static MEASURING: Mutex<()> = Mutex::new(());
+
+thread_local! {
+    // A `const`-initialized `Cell` doesn't allocate nor register a destructor,
+    // so it's safe to access from within the allocator.
+    static TOKEN: Cell<u64> = const { Cell::new(0) };
+}
+
+fn current_token() -> u64 {
+    TOKEN
+        .try_with(|token| {
+            if token.get() == 0 {
+                token.set(NEXT_TOKEN.fetch_add(1, Ordering::Relaxed));
+            }
+            token.get()
+        })
+        // The thread-local has already been destroyed: we're tearing the thread down.
+        .unwrap_or(u64::MAX)
+}
+
+fn record() {
+    if !RECORDING.load(Ordering::Relaxed) {
+        return;
+    }
+    let token = current_token();
+    for slot in &SEEN {
+        match slot.compare_exchange(0, token, Ordering::Relaxed, Ordering::Relaxed) {
+            Ok(_) => return,
+            Err(existing) if existing == token => return,
+            Err(_) => continue,
+        }
+    }
+}
+
+unsafe impl GlobalAlloc for ThreadTrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record();
+        if COUNTING_BYTES.load(Ordering::Relaxed) {
+            ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        record();
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: ThreadTrackingAllocator = ThreadTrackingAllocator;
+
+/// Run `f`, returning its output together with the number of threads—other than the
+/// calling one—that allocated or freed memory while it was running.
+pub fn count_helper_threads<F, T>(f: F) -> (T, usize)
+where
+    F: FnOnce() -> T,
+{
+    let _guard = MEASURING.lock().unwrap_or_else(|e| e.into_inner());
+    for slot in &SEEN {
+        slot.store(0, Ordering::Relaxed);
+    }
+    let caller = current_token();
+
+    RECORDING.store(true, Ordering::SeqCst);
+    let output = f();
+    RECORDING.store(false, Ordering::SeqCst);
+
+    let helpers = SEEN
+        .iter()
+        .map(|slot| slot.load(Ordering::Relaxed))
+        .filter(|&token| token != 0 && token != caller)
+        .count();
+    (output, helpers)
+}
+
+/// Run `f`, returning its output together with the number of bytes allocated—by any
+/// thread—while it was running. Memory freed in the meantime isn't subtracted.
+pub fn count_allocated_bytes<F, T>(f: F) -> (T, usize)
+where
+    F: FnOnce() -> T,
+{
+    let _guard = MEASURING.lock().unwrap_or_else(|e| e.into_inner());
+    ALLOCATED.store(0, Ordering::Relaxed);
+
+    COUNTING_BYTES.store(true, Ordering::SeqCst);
+    let output = f();
+    COUNTING_BYTES.store(false, Ordering::SeqCst);
+
+    (output, ALLOCATED.load(Ordering::Relaxed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A single test: concurrent tests would show up as helper threads of each other.
+    #[test]
+    fn counts_spawned_threads_only() {
+        let (_, helpers) = count_helper_threads(|| (0..3).collect::<Vec<i32>>().len());
+        assert_eq!(helpers, 0);
+
+        let (_, helpers) = count_helper_threads(|| {
+            let handles: Vec<_> = (0..3)
+                .map(|i| {
+                    // The box is freed by the spawned thread.
+                    let data = Box::new(i);
+                    std::thread::spawn(move || *data)
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap())
+                .sum::<usize>()
+        });
+        assert!(
+            helpers >= 3,
+            "Expected at least 3 helper threads, got {helpers}"
+        );
+    }
+}
@@ -0,0 +1,26 @@
+// 07_threads/01_threads: `sum_shared` must hand the vector to its threads without copying it.
+use std::sync::Arc;
+
+use verification::count_allocated_bytes;
+
+#[test]
+fn sum_shared_does_not_copy_the_elements() {
+    // Small values, so that the sum doesn't overflow.
+    let v: Vec<i32> = (0..100_000).map(|i| i % 100).collect();
+    let expected: i32 = v.iter().sum();
+    let data_bytes = v.len() * size_of::<i32>();
+    let shared = Arc::new(v.clone());
+
+    let (sum, copied) = count_allocated_bytes(|| threads::sum(v));
+    assert_eq!(sum, expected);
+    // The baseline: `sum` copies each half into a vector of its own.
+    assert!(copied >= data_bytes, "`sum` allocated only {copied} bytes");
+
+    // Spawning threads allocates a little bookkeeping, but nothing that grows with the input.
+    let (sum, allocated) = count_allocated_bytes(|| threads::sum_shared(shared));
+    assert_eq!(sum, expected);
+    assert!(
+        allocated < data_bytes / 10,
+        "`sum_shared` allocated {allocated} bytes for {data_bytes} bytes of elements"
+    );
+}
@@ -0,0 +1,27 @@
+// 07_threads/15_thread_pool: jobs must run on the pool's workers, not on the caller's thread.
+use std::collections::HashSet;
+use std::thread;
+
+use thread_pool::ThreadPool;
+
+#[test]
+fn jobs_run_on_a_bounded_set_of_workers() {
+    let n_workers = 3;
+    let pool = ThreadPool::new(n_workers);
+    let caller = thread::current().id();
+
+    let handles: Vec<_> = (0..50)
+        .map(|_| pool.execute(|| thread::current().id()))
+        .collect();
+    let workers: HashSet<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+    assert!(
+        !workers.contains(&caller),
+        "Jobs must not run on the caller's thread"
+    );
+    assert!(
+        workers.len() <= n_workers,
+        "Expected at most {n_workers} workers, jobs ran on {} threads",
+        workers.len()
+    );
+}
@@ -0,0 +1,17 @@
+// 07_threads/01_threads: each half of the vector must be summed in a separate thread.
+use verification::count_helper_threads;
+
+#[test]
+fn sum_spawns_one_thread_per_half() {
+    let v: Vec<i32> = (0..10_000).collect();
+    let expected: i32 = v.iter().sum();
+
+    let (sum, helpers) = count_helper_threads(|| threads::sum(v));
+
+    assert_eq!(sum, expected);
+    assert!(
+        helpers >= 2,
+        "`sum` should spawn a thread for each half of the vector, \
+         but only {helpers} other thread(s) did any work"
+    );
+}
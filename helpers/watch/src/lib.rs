@@ -0,0 +1,125 @@
+use std::path::{Component, Path, PathBuf};
+
+/// The exercise crate containing `path`, i.e. `exercises/<chapter>/<exercise>`,
+/// if `path` is a source file inside an exercise.
+///
+/// Build artifacts (anything under a `target` directory) are ignored.
+pub fn exercise_for(exercises_dir: &Path, path: &Path) -> Option<PathBuf> {
+    let relative = path.strip_prefix(exercises_dir).ok()?;
+    let mut components = relative.components();
+    let chapter = normal(components.next()?)?;
+    let exercise = normal(components.next()?)?;
+    let rest: Vec<_> = components.collect();
+    if rest.is_empty() || rest.iter().any(|c| c.as_os_str() == "target") {
+        return None;
+    }
+    let is_source = path.extension().is_some_and(|ext| ext == "rs")
+        || path.file_name().is_some_and(|name| name == "Cargo.toml");
+    if !is_source {
+        return None;
+    }
+    Some(exercises_dir.join(chapter).join(exercise))
+}
+
+fn normal(component: Component<'_>) -> Option<&std::ffi::OsStr> {
+    match component {
+        Component::Normal(name) => Some(name),
+        _ => None,
+    }
+}
+
+/// Pick the lines worth showing from a failed `cargo test` run:
+/// compiler errors and failing tests, without the build noise.
+pub fn summarize_failure(output: &str) -> Vec<&str> {
+    let interesting: Vec<_> = output
+        .lines()
+        .filter(|line| {
+            let line = line.trim_start();
+            line.starts_with("error")
+                || line.starts_with("-->")
+                || line.contains("panicked at")
+                || line.starts_with("test result")
+                || (line.starts_with("---- ") && line.ends_with(" stdout ----"))
+        })
+        .collect();
+    if interesting.is_empty() {
+        // Nothing we recognise: fall back to the last few lines.
+        let lines: Vec<_> = output.lines().collect();
+        lines[lines.len().saturating_sub(10)..].to_vec()
+    } else {
+        interesting
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_sources_to_their_exercise() {
+        let root = Path::new("/course/exercises");
+        let expected = Some(PathBuf::from("/course/exercises/07_threads/01_threads"));
+        assert_eq!(
+            exercise_for(
+                root,
+                Path::new("/course/exercises/07_threads/01_threads/src/lib.rs")
+            ),
+            expected
+        );
+        assert_eq!(
+            exercise_for(
+                root,
+                Path::new("/course/exercises/07_threads/01_threads/Cargo.toml")
+            ),
+            expected
+        );
+        assert_eq!(
+            exercise_for(
+                root,
+                Path::new("/course/exercises/07_threads/01_threads/tests/check.rs")
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    fn ignores_everything_else() {
+        let root = Path::new("/course/exercises");
+        for path in [
+            "/course/exercises/07_threads/01_threads/target/debug/build.rs",
+            "/course/exercises/07_threads/01_threads/README.md",
+            "/course/exercises/07_threads/notes.rs",
+            "/course/book/src/07_threads/01_threads.md",
+            "/course/helpers/common/src/lib.rs",
+        ] {
+            assert_eq!(exercise_for(root, Path::new(path)), None, "{path}");
+        }
+    }
+
+    #[test]
+    fn failure_summary_keeps_errors() {
+        let output = "   Compiling threads v0.1.0\n\
+            error[E0308]: mismatched types\n  \
+            --> src/lib.rs:3:5\n\
+            note: some note\n\
+            error: could not compile `threads`\n";
+        assert_eq!(
+            summarize_failure(output),
+            [
+                "error[E0308]: mismatched types",
+                "  --> src/lib.rs:3:5",
+                "error: could not compile `threads`"
+            ]
+        );
+    }
+
+    #[test]
+    fn failure_summary_falls_back_to_the_tail() {
+        let output = (0..20)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(summarize_failure(&output).len(), 10);
+        assert_eq!(summarize_failure(&output)[9], "19");
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::time::Duration;
+
+use anyhow::{Context, Error};
+use notify::{EventKind, RecursiveMode, Watcher};
+
+use watch::{exercise_for, summarize_failure};
+
+/// Editors often emit several events for a single save: wait for things
+/// to settle down before re-running the tests.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+fn main() -> Result<(), Error> {
+    let root = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../..")).canonicalize()?;
+    let exercises_dir = root.join("exercises");
+
+    let (sender, receiver) = channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    watcher
+        .watch(&exercises_dir, RecursiveMode::Recursive)
+        .with_context(|| format!("Failed to watch {}", exercises_dir.display()))?;
+    println!(
+        "👀 Watching {} for changes. Press Ctrl+C to stop.",
+        exercises_dir.display()
+    );
+
+    let mut pending = BTreeSet::new();
+    loop {
+        let event = if pending.is_empty() {
+            receiver.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        } else {
+            receiver.recv_timeout(DEBOUNCE)
+        };
+        match event {
+            Ok(Ok(event)) => {
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                    pending.extend(
+                        event
+                            .paths
+                            .iter()
+                            .filter_map(|path| exercise_for(&exercises_dir, path)),
+                    );
+                }
+            }
+            Ok(Err(e)) => eprintln!("Watch error: {e}"),
+            Err(RecvTimeoutError::Timeout) => {
+                for exercise in std::mem::take(&mut pending) {
+                    run_tests(&root, &exercise);
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        }
+    }
+}
+
+fn run_tests(root: &Path, exercise: &Path) {
+    let name = exercise.strip_prefix(root).unwrap_or(exercise).display();
+    println!("🔄 Testing {name}...");
+    let output = Command::new(env!("CARGO"))
+        .args(["test", "--quiet", "--color", "never", "--manifest-path"])
+        .arg(exercise.join("Cargo.toml"))
+        .output();
+    match output {
+        Ok(output) if output.status.success() => println!("✅ {name} passed"),
+        Ok(output) => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let combined = format!("{stderr}\n{stdout}");
+            println!("❌ {name} failed");
+            for line in summarize_failure(&combined) {
+                println!("   {line}");
+            }
+        }
+        Err(e) => println!("⚠️ Failed to run `cargo test` for {name}: {e}"),
+    }
+}
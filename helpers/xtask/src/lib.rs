@@ -0,0 +1,450 @@
+use anyhow::{bail, Context, Error};
+use std::path::{Path, PathBuf};
+
+/// Everything needed to add a new exercise to the course.
+#[derive(Debug)]
+pub struct Scaffold {
+    /// e.g. `exercises/07_threads/16_barrier`
+    pub exercise_dir: PathBuf,
+    /// e.g. `book/src/07_threads/16_barrier.md`
+    pub book_page: PathBuf,
+    pub manifest: String,
+    pub lib: String,
+    pub page: String,
+    /// The updated content of `book/src/SUMMARY.md`.
+    pub summary: String,
+    /// What has to move one number up to make room for the new exercise: the chapter's outro,
+    /// if it has one. Its exercise directory, then its book page, from the old path to the new.
+    pub renamed: Vec<(PathBuf, PathBuf)>,
+    /// The updated content of the other files that refer to the outro by its path—link maps,
+    /// crates depending on it—at the path they'll have once the outro has moved.
+    pub references: Vec<(PathBuf, String)>,
+}
+
+/// Work out where a new exercise named `name` should go in `chapter`.
+///
+/// `chapter` can either be the full directory name (`07_threads`) or its numeric prefix (`07`).
+/// The exercise is numbered after the last exercise currently in the chapter, except for its
+/// outro: the new exercise takes the outro's number, and the outro moves one number up.
+pub fn plan(root: &Path, chapter: &str, name: &str) -> Result<Scaffold, Error> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        || name.starts_with(|c: char| c.is_ascii_digit())
+    {
+        bail!("`{name}` is not a valid exercise name: use lowercase letters, digits and underscores, starting with a letter");
+    }
+    let exercises = root.join("exercises");
+    let chapter = resolve_chapter(&exercises, chapter)?;
+    let chapter_dir = exercises.join(&chapter);
+
+    let existing = subdirectories(&chapter_dir)?;
+    if existing.iter().any(|dir| strip_number(dir) == Some(name)) {
+        bail!("`{chapter}` already contains an exercise named `{name}`");
+    }
+    if package_names(&exercises)?.iter().any(|p| p == name) {
+        bail!("Another exercise crate is already named `{name}`: package names must be unique across the workspace");
+    }
+    let numbers: Vec<_> = existing
+        .iter()
+        .filter_map(|dir| Some((dir, dir.split_once('_')?.0.parse::<u32>().ok()?)))
+        .collect();
+    let outro = numbers
+        .iter()
+        .filter(|(dir, _)| dir.ends_with("_outro"))
+        .max_by_key(|&&(_, n)| n);
+    let number = match outro {
+        Some(&(_, n)) => n,
+        None => numbers.iter().map(|&(_, n)| n).max().map_or(0, |n| n + 1),
+    };
+    let dir_name = format!("{number:02}_{name}");
+
+    let summary_path = root.join("book/src/SUMMARY.md");
+    let mut summary = std::fs::read_to_string(&summary_path)
+        .with_context(|| format!("Failed to read {}", summary_path.display()))?;
+    let book_dir = root.join("book/src").join(&chapter);
+    let mut renamed = Vec::new();
+    let mut references = Vec::new();
+    if let Some(&(outro, n)) = outro {
+        let moved = format!("{:02}_outro", n + 1);
+        let (from, to) = (format!("{chapter}/{outro}"), format!("{chapter}/{moved}"));
+        renamed.push((chapter_dir.join(outro), chapter_dir.join(&moved)));
+        let page = book_dir.join(format!("{outro}.md"));
+        if page.exists() {
+            renamed.push((page, book_dir.join(format!("{moved}.md"))));
+        }
+        summary = replace_path(&summary, &from, &to);
+        for path in mentions(root, &from)? {
+            if path == summary_path {
+                continue;
+            }
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let path = renamed
+                .iter()
+                .find_map(|(old, new)| Some(new.join(path.strip_prefix(old).ok()?)))
+                .unwrap_or(path);
+            references.push((path, replace_path(&content, &from, &to)));
+        }
+    }
+    let title = title(name);
+    let summary = add_to_summary(
+        &summary,
+        &chapter,
+        &format!("  - [{title}]({chapter}/{dir_name}.md)"),
+    )?;
+
+    Ok(Scaffold {
+        exercise_dir: chapter_dir.join(&dir_name),
+        book_page: book_dir.join(format!("{dir_name}.md")),
+        manifest: format!(
+            "[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n"
+        ),
+        lib: LIB_TEMPLATE.to_owned(),
+        page: format!("# {title}\n\nTODO: explain the concepts behind this exercise.\n"),
+        summary,
+        renamed,
+        references,
+    })
+}
+
+impl Scaffold {
+    pub fn write(&self, root: &Path) -> Result<(), Error> {
+        if self.exercise_dir.exists() {
+            bail!("{} already exists", self.exercise_dir.display());
+        }
+        for (from, to) in &self.renamed {
+            std::fs::rename(from, to).with_context(|| {
+                format!("Failed to move {} to {}", from.display(), to.display())
+            })?;
+        }
+        let src = self.exercise_dir.join("src");
+        std::fs::create_dir_all(&src)
+            .with_context(|| format!("Failed to create {}", src.display()))?;
+        write(&self.exercise_dir.join("Cargo.toml"), &self.manifest)?;
+        write(&src.join("lib.rs"), &self.lib)?;
+        write(&self.book_page, &self.page)?;
+        for (path, content) in &self.references {
+            write(path, content)?;
+        }
+        write(&root.join("book/src/SUMMARY.md"), &self.summary)
+    }
+}
+
+const LIB_TEMPLATE: &str = "\
+// TODO: describe what needs to be done to solve this exercise.
+
+pub fn exercise() -> u32 {
+    todo!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn works() {
+        assert_eq!(exercise(), 42);
+    }
+}
+";
+
+fn write(path: &Path, content: &str) -> Result<(), Error> {
+    std::fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn resolve_chapter(exercises: &Path, chapter: &str) -> Result<String, Error> {
+    let chapters = subdirectories(exercises)?;
+    let matches: Vec<_> = chapters
+        .iter()
+        .filter(|dir| *dir == chapter || dir.split_once('_').map(|(n, _)| n) == Some(chapter))
+        .collect();
+    match matches.as_slice() {
+        [chapter] => Ok((*chapter).clone()),
+        [] => bail!(
+            "There is no chapter named `{chapter}`. Available chapters: {}",
+            chapters.join(", ")
+        ),
+        _ => bail!("`{chapter}` is ambiguous, use the full chapter name"),
+    }
+}
+
+fn subdirectories(dir: &Path) -> Result<Vec<String>, Error> {
+    let mut names = Vec::new();
+    for entry in
+        std::fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))?
+    {
+        let entry = entry?;
+        if entry.path().is_dir() {
+            names.push(entry.file_name().to_string_lossy().into_owned());
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+fn strip_number(dir: &str) -> Option<&str> {
+    dir.split_once('_').map(|(_, name)| name)
+}
+
+fn package_names(exercises: &Path) -> Result<Vec<String>, Error> {
+    let mut names = Vec::new();
+    for chapter in subdirectories(exercises)? {
+        for exercise in subdirectories(&exercises.join(&chapter))? {
+            let manifest = exercises.join(&chapter).join(exercise).join("Cargo.toml");
+            let Ok(manifest) = std::fs::read_to_string(manifest) else {
+                continue;
+            };
+            let name = manifest.lines().find_map(|line| {
+                let value = line.strip_prefix("name")?.trim_start().strip_prefix('=')?;
+                Some(value.trim().trim_matches('"').to_owned())
+            });
+            names.extend(name);
+        }
+    }
+    Ok(names)
+}
+
+/// `thread_pool` -> `Thread pool`
+fn title(name: &str) -> String {
+    let words = name.replace('_', " ");
+    let mut chars = words.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => words,
+    }
+}
+
+/// Insert `entry` right after the last entry of `chapter` in the book summary,
+/// or right before its outro.
+fn add_to_summary(summary: &str, chapter: &str, entry: &str) -> Result<String, Error> {
+    let marker = format!("]({chapter}/");
+    let lines: Vec<_> = summary.lines().collect();
+    let Some(mut last) = lines.iter().rposition(|line| line.contains(&marker)) else {
+        bail!("`{chapter}` doesn't appear in the book summary");
+    };
+    if lines[last].contains("_outro.md)") {
+        last -= 1;
+    }
+    let mut updated: Vec<&str> = Vec::with_capacity(lines.len() + 1);
+    updated.extend(&lines[..=last]);
+    updated.push(entry);
+    updated.extend(&lines[last + 1..]);
+    Ok(updated.join("\n") + "\n")
+}
+
+/// The files under `root` that mention `path`, e.g. `08_futures/08_outro`: manifests, sources,
+/// book pages, link maps and redirects. Build outputs and hidden directories aren't looked at.
+fn mentions(root: &Path, path: &str) -> Result<Vec<PathBuf>, Error> {
+    let mut found = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in
+            std::fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))?
+        {
+            let file = entry?.path();
+            let name = file.file_name().unwrap_or_default().to_string_lossy();
+            if file.is_dir() {
+                if !name.starts_with('.') && name != "target" {
+                    dirs.push(file);
+                }
+                continue;
+            }
+            let is_text = name == "_redirects"
+                || file
+                    .extension()
+                    .is_some_and(|ext| ["md", "rs", "toml", "json"].iter().any(|e| ext == *e));
+            if !is_text {
+                continue;
+            }
+            let Ok(content) = std::fs::read_to_string(&file) else {
+                continue;
+            };
+            if replace_path(&content, path, "") != content {
+                found.push(file);
+            }
+        }
+    }
+    found.sort();
+    Ok(found)
+}
+
+/// Replace every mention of the path `from` in `content` with `to`, leaving alone longer names
+/// that merely start like it (`08_outro` isn't replaced in `08_outro_extra`).
+fn replace_path(content: &str, from: &str, to: &str) -> String {
+    let mut replaced = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(i) = rest.find(from) {
+        let after = &rest[i + from.len()..];
+        let whole = !after.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_');
+        replaced.push_str(&rest[..i]);
+        replaced.push_str(if whole { to } else { from });
+        rest = after;
+    }
+    replaced.push_str(rest);
+    replaced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUMMARY: &str = "# Summary\n\n\
+        - [Threads](07_threads/00_intro.md)\n  - [Threads](07_threads/01_threads.md)\n\n\
+        - [Futures](08_futures/00_intro.md)\n";
+
+    fn fixture(test: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("xtask-{test}-{}", std::process::id()));
+        for dir in [
+            "07_threads/00_intro",
+            "07_threads/01_threads",
+            "08_futures/00_intro",
+        ] {
+            std::fs::create_dir_all(root.join("exercises").join(dir)).unwrap();
+        }
+        std::fs::write(
+            root.join("exercises/07_threads/01_threads/Cargo.toml"),
+            "[package]\nname = \"threads\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("book/src/07_threads")).unwrap();
+        std::fs::write(root.join("book/src/SUMMARY.md"), SUMMARY).unwrap();
+        root
+    }
+
+    #[test]
+    fn new_exercise_is_numbered_after_the_last_one() {
+        let root = fixture("numbering");
+        let scaffold = plan(&root, "07", "barrier").unwrap();
+        assert_eq!(
+            scaffold.exercise_dir,
+            root.join("exercises/07_threads/02_barrier")
+        );
+        assert_eq!(
+            scaffold.book_page,
+            root.join("book/src/07_threads/02_barrier.md")
+        );
+        assert!(scaffold.manifest.contains("name = \"barrier\""));
+        assert!(scaffold.lib.contains("todo!()"));
+        assert!(scaffold.lib.contains("#[cfg(test)]"));
+        assert_eq!(
+            scaffold.summary,
+            "# Summary\n\n\
+            - [Threads](07_threads/00_intro.md)\n  - [Threads](07_threads/01_threads.md)\n  \
+            - [Barrier](07_threads/02_barrier.md)\n\n\
+            - [Futures](08_futures/00_intro.md)\n"
+        );
+
+        scaffold.write(&root).unwrap();
+        let lib = std::fs::read_to_string(root.join("exercises/07_threads/02_barrier/src/lib.rs"));
+        let summary = std::fs::read_to_string(root.join("book/src/SUMMARY.md")).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(lib.unwrap(), LIB_TEMPLATE);
+        assert_eq!(summary, scaffold.summary);
+    }
+
+    #[test]
+    fn chapter_can_be_referred_to_by_full_name() {
+        let root = fixture("full-name");
+        let scaffold = plan(&root, "08_futures", "select");
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            scaffold.unwrap().exercise_dir,
+            root.join("exercises/08_futures/01_select")
+        );
+    }
+
+    #[test]
+    fn new_exercise_goes_before_the_outro() {
+        let root = fixture("outro");
+        let outro = root.join("exercises/08_futures/01_outro");
+        std::fs::create_dir_all(&outro).unwrap();
+        std::fs::write(outro.join("Cargo.toml"), "[package]\nname = \"outro_08\"\n").unwrap();
+        std::fs::create_dir_all(root.join("book/src/08_futures")).unwrap();
+        std::fs::write(root.join("book/src/08_futures/01_outro.md"), "# Outro\n").unwrap();
+        std::fs::write(
+            root.join("book/src/SUMMARY.md"),
+            format!("{SUMMARY}  - [Outro](08_futures/01_outro.md)\n"),
+        )
+        .unwrap();
+        let helper = root.join("helpers/server");
+        std::fs::create_dir_all(&helper).unwrap();
+        let dependencies = "[dependencies]\n\
+            outro_08 = { path = \"../../exercises/08_futures/01_outro\" }\n\
+            extra = { path = \"../../exercises/08_futures/01_outro_extra\" }\n";
+        std::fs::write(helper.join("Cargo.toml"), dependencies).unwrap();
+        std::fs::create_dir_all(root.join("site")).unwrap();
+        std::fs::write(
+            root.join("site/_redirects"),
+            "/outro /08_futures/01_outro\n/intro /08_futures/00_intro\n",
+        )
+        .unwrap();
+
+        let scaffold = plan(&root, "08", "select").unwrap();
+        assert_eq!(
+            scaffold.exercise_dir,
+            root.join("exercises/08_futures/01_select")
+        );
+        assert_eq!(
+            scaffold.summary,
+            format!(
+                "{SUMMARY}  - [Select](08_futures/01_select.md)\n  \
+                - [Outro](08_futures/02_outro.md)\n"
+            )
+        );
+        assert_eq!(
+            scaffold.renamed,
+            [
+                (outro, root.join("exercises/08_futures/02_outro")),
+                (
+                    root.join("book/src/08_futures/01_outro.md"),
+                    root.join("book/src/08_futures/02_outro.md")
+                ),
+            ]
+        );
+
+        scaffold.write(&root).unwrap();
+        let read = |path: &str| std::fs::read_to_string(root.join(path)).unwrap();
+        let moved = read("exercises/08_futures/02_outro/Cargo.toml");
+        let page = read("book/src/08_futures/02_outro.md");
+        let helper = read("helpers/server/Cargo.toml");
+        let redirects = read("site/_redirects");
+        assert!(root
+            .join("exercises/08_futures/01_select/src/lib.rs")
+            .exists());
+        std::fs::remove_dir_all(&root).unwrap();
+        assert!(moved.contains("outro_08"));
+        assert_eq!(page, "# Outro\n");
+        assert_eq!(
+            helper,
+            dependencies.replace("08_futures/01_outro\"", "08_futures/02_outro\"")
+        );
+        assert_eq!(
+            redirects,
+            "/outro /08_futures/02_outro\n/intro /08_futures/00_intro\n"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        let root = fixture("invalid");
+        let unknown_chapter = plan(&root, "09", "barrier");
+        let duplicated_package = plan(&root, "08", "threads");
+        let invalid_name = plan(&root, "07", "Thread-Pool");
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert!(unknown_chapter
+            .unwrap_err()
+            .to_string()
+            .starts_with("There is no chapter named `09`"));
+        assert!(duplicated_package.is_err());
+        assert!(invalid_name.is_err());
+    }
+
+    #[test]
+    fn titles() {
+        assert_eq!(title("thread_pool"), "Thread pool");
+        assert_eq!(title("rc"), "Rc");
+    }
+}
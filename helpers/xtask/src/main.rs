@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+
+use anyhow::Error;
+use clap::{Parser, Subcommand};
+
+/// Maintenance tasks for the course repository.
+#[derive(Parser)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a new exercise crate, with its book page and summary entry.
+    NewExercise {
+        /// The chapter, e.g. `07_threads` or just `07`.
+        chapter: String,
+        /// The exercise name, e.g. `barrier`. It's also used as the crate name.
+        name: String,
+    },
+}
+
+fn main() -> Result<(), Error> {
+    let root = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../..")).canonicalize()?;
+    match Cli::parse().command {
+        Command::NewExercise { chapter, name } => {
+            let scaffold = xtask::plan(&root, &chapter, &name)?;
+            scaffold.write(&root)?;
+            for path in [&scaffold.exercise_dir, &scaffold.book_page] {
+                let path = path.strip_prefix(&root).unwrap_or(path);
+                println!("Created {}", path.display());
+            }
+            for (from, to) in &scaffold.renamed {
+                let from = from.strip_prefix(&root).unwrap_or(from);
+                let to = to.strip_prefix(&root).unwrap_or(to);
+                println!("Moved {} to {}", from.display(), to.display());
+            }
+            for (path, _) in &scaffold.references {
+                let path = path.strip_prefix(&root).unwrap_or(path);
+                println!("Updated {}", path.display());
+            }
+            println!("Updated book/src/SUMMARY.md");
+            println!("The new crate is picked up by the `exercises/*/*` workspace glob: run `cargo test -p {name}` to try it out.");
+        }
+    }
+    Ok(())
+}